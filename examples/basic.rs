@@ -1,9 +1,6 @@
 use miette::NamedSource;
 use std::time::Instant;
-use tl::{
-    eval_untyped, object,
-    runtime::{Scope, Value},
-};
+use tl::{eval_untyped, object, runtime::Scope};
 
 fn main() -> miette::Result<()> {
     let source = NamedSource::new("examples/basic.tl", String::from(include_str!("basic.tl")));
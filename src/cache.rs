@@ -0,0 +1,133 @@
+//! Evaluation results cached on disk, keyed by the contents of the evaluated file and every file it read.
+
+use crate::{
+    Options,
+    convert::to_source,
+    eval_str,
+    parser::parse_with_options,
+    read_source,
+    runtime::{
+        Scope,
+        inspect::{ImportLog, Inspector},
+        types::Value,
+    },
+};
+use miette::Report;
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// The first line of every cache file.
+const HEADER: &str = "// tl evaluation cache";
+
+/// A directory of cached evaluation results.
+///
+/// Every entry is a tl file with the value of the evaluated file, after a comment for each file it read with the
+/// hash of its contents. An entry is only used if none of these files changed.
+/// The cache doesn't know about [`Options`] or environment variables, use a directory per configuration.
+#[derive(Debug, Clone)]
+pub struct EvalCache {
+    dir: PathBuf,
+}
+
+impl EvalCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Evaluates the file at `path`, or reads its value from the cache if neither it nor a file it read changed.
+    /// Values that can't be written as tl, like functions, are not cached.
+    /// # Errors
+    /// This function will return an error if the file can not be read, or if a parsing or evaluation error occurs.
+    /// Failing to read or write the cache is not an error, the file is evaluated instead.
+    pub fn eval_file(&self, path: impl AsRef<Path>, options: Options) -> Result<Value, Report> {
+        let path = path.as_ref();
+        let entry = self.entry(path);
+
+        if let Some(value) = read_entry(&entry) {
+            return Ok(value);
+        }
+
+        let source = read_source(path)?;
+        let ast = parse_with_options(&source, &options)?;
+
+        let log = Rc::new(ImportLog::new(options.inspector.clone()));
+        let mut scope = Scope::new(HashMap::new(), source, ast);
+        scope.set_options(Options {
+            inspector: Some(Rc::clone(&log) as Rc<dyn Inspector>),
+            ..options
+        });
+        scope.define_location(path);
+        let value = scope.eval()?;
+
+        if let Some(text) = to_source(&value) {
+            let files = std::iter::once(path.to_path_buf()).chain(log.imports());
+            let _ = write_entry(&entry, files, &text);
+        }
+
+        Ok(value)
+    }
+
+    /// Removes every cached result.
+    /// # Errors
+    /// This function will return an error if the directory exists but can not be removed.
+    pub fn clear(&self) -> std::io::Result<()> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// The cache file of the file at `path`.
+    fn entry(&self, path: &Path) -> PathBuf {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+        self.dir.join(format!("{:016x}.tl", hasher.finish()))
+    }
+}
+
+/// The value of a cache entry, if every file it was evaluated from is unchanged.
+fn read_entry(entry: &Path) -> Option<Value> {
+    let text = fs::read_to_string(entry).ok()?;
+    let mut lines = text.lines();
+    if lines.next() != Some(HEADER) {
+        return None;
+    }
+
+    for line in lines.take_while(|line| line.starts_with("// ")) {
+        let (hash, path) = line.trim_start_matches("// ").split_once(' ')?;
+        if hash_file(Path::new(path))? != hash {
+            return None;
+        }
+    }
+
+    eval_str(&text).ok()
+}
+
+fn write_entry(entry: &Path, files: impl Iterator<Item = PathBuf>, text: &str) -> Option<()> {
+    let mut entry_text = format!("{HEADER}\n");
+    for file in files {
+        // The path is read back until the end of the line
+        let path = file.to_str().filter(|path| !path.contains('\n'))?;
+        writeln!(entry_text, "// {} {path}", hash_file(&file)?).ok()?;
+    }
+    entry_text.push_str(text);
+
+    fs::create_dir_all(entry.parent()?).ok()?;
+    fs::write(entry, entry_text).ok()
+}
+
+fn hash_file(path: &Path) -> Option<String> {
+    let contents = fs::read(path).ok()?;
+
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    Some(format!("{:016x}", hasher.finish()))
+}
@@ -0,0 +1,455 @@
+use std::fmt::{self, Display};
+
+/// An error that parsing or evaluating can report, for looking up the code of a report and for documentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorInfo {
+    /// The code shown in reports, related errors can share a code.
+    pub code: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    /// Source that reports the error with the options named in the description,
+    /// `None` if the host has to cause it.
+    pub example: Option<&'static str>,
+}
+
+impl Display for ErrorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}\n\n{}", self.code, self.title, self.description)?;
+
+        if let Some(example) = self.example {
+            write!(f, "\n\nExample:")?;
+            for line in example.lines() {
+                write!(f, "\n    {line}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Every error of the lexer, the parser and the runtime, in that order.
+pub fn error_catalog() -> &'static [ErrorInfo] {
+    CATALOG
+}
+
+/// The errors reported with `code`, like `tl::runtime::call`.
+pub fn error_info(code: &str) -> Vec<ErrorInfo> {
+    CATALOG
+        .iter()
+        .filter(|info| info.code == code)
+        .copied()
+        .collect()
+}
+
+const CATALOG: &[ErrorInfo] = &[
+    // Lexer
+    ErrorInfo {
+        code: "tl::parser::lexer::unclosed_string",
+        title: "Unclosed string literal",
+        description: "A string literal has no closing `\"` before the end of the file.",
+        example: Some(r#""localhost"#),
+    },
+    ErrorInfo {
+        code: "tl::parser::lexer::unclosed_interpolation",
+        title: "Unclosed interpolation",
+        description: "A `${` in a string or path has no closing `}`.",
+        example: Some(r#""http://${host""#),
+    },
+    ErrorInfo {
+        code: "tl::parser::lexer::interpolation_too_deep",
+        title: "Interpolations nested too deep",
+        description: "Interpolations are nested inside of each other deeper than `Options::max_depth`.",
+        example: None,
+    },
+    ErrorInfo {
+        code: "tl::parser::lexer::unexpected_token",
+        title: "Unexpected token",
+        description: "A character that doesn't start any token.",
+        example: Some("1 ^ 2"),
+    },
+    ErrorInfo {
+        code: "tl::parser::lexer::invalid_int",
+        title: "Invalid integer",
+        description: "An integer literal doesn't fit into an integer of the platform.",
+        example: None,
+    },
+    ErrorInfo {
+        code: "tl::parser::lexer::invalid_float",
+        title: "Invalid float",
+        description: "A float literal can't be represented as a float.",
+        example: None,
+    },
+    ErrorInfo {
+        code: "tl::parser::lexer::io",
+        title: "IO error",
+        description: "Reading the source failed.",
+        example: None,
+    },
+    // Parser
+    ErrorInfo {
+        code: "tl::parser::ast::missing_right_side",
+        title: "Missing right side of binary operation",
+        description: "A binary operator is the last token of the expression.",
+        example: Some("1 +"),
+    },
+    ErrorInfo {
+        code: "tl::parser::ast::colon_separator",
+        title: "Unexpected ':' between object key-value pairs",
+        description: "Fields of objects are defined with `=`, not `:` like in JSON.",
+        example: Some("{ port: 80 }"),
+    },
+    ErrorInfo {
+        code: "tl::parser::ast::expected_separator",
+        title: "Expected '=' after object key",
+        description: "A key of an object is not followed by `=` and its value.",
+        example: Some("{ port 80 }"),
+    },
+    ErrorInfo {
+        code: "tl::parser::ast::expected_identifier",
+        title: "Expected identifier after dot",
+        description: "A field access has no field name after its `.`.",
+        example: Some("let server = { port = 80 } in server."),
+    },
+    ErrorInfo {
+        code: "tl::parser::ast::expected_token",
+        title: "Expected token",
+        description: "A token that the syntax requires is missing, like the `)` of a call.",
+        example: Some("max(1, 2"),
+    },
+    ErrorInfo {
+        code: "tl::parser::ast::unknown_type",
+        title: "Unknown type",
+        description: "A type annotation names a type that doesn't exist.",
+        example: Some("let port :: integer = 80 in port"),
+    },
+    ErrorInfo {
+        code: "tl::parser::ast::unexpected_token",
+        title: "Unexpected token",
+        description: "A token that can't be used at its position, like a keyword where a value is expected.",
+        example: Some("let port = in port"),
+    },
+    ErrorInfo {
+        code: "tl::parser::ast::duplicate_binding",
+        title: "Name bound twice in a pattern",
+        description: "A destructuring pattern of a `let` binding uses the same name more than once.",
+        example: Some("let [ a, a ] = [ 1 2 ] in a"),
+    },
+    ErrorInfo {
+        code: "tl::parser::ast::no_tokens_left",
+        title: "No tokens left",
+        description: "The source ends in the middle of an expression.",
+        example: Some("{ port ="),
+    },
+    ErrorInfo {
+        code: "tl::parser::ast::nesting_too_deep",
+        title: "Expression nested too deep",
+        description: "Expressions are nested inside of each other deeper than `Options::max_depth`.",
+        example: None,
+    },
+    // Runtime
+    ErrorInfo {
+        code: "tl::runtime::expr",
+        title: "Variable not in scope",
+        description: "A variable is used that is not bound by `let`, a function parameter or a builtin.",
+        example: Some("let port = 80 in prot"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::expr",
+        title: "Index out of bounds",
+        description: "An array is indexed at or past its length.",
+        example: Some("let hosts = [ \"a\" \"b\" ] in hosts[2]"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::expr",
+        title: "Mismatched types",
+        description: "A value of the wrong type is passed to a builtin or used where a specific type is needed.",
+        example: Some(r#"setPath({ a = 1 }, "a.b", 2)"#),
+    },
+    ErrorInfo {
+        code: "tl::runtime::not_callable",
+        title: "Not callable",
+        description: "A value that is not a function or a builtin is called.",
+        example: Some("let port = 8080 in port(1)"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::call",
+        title: "Mismatch in number of function arguments",
+        description: "A function is called with fewer or more arguments than it has parameters without defaults.",
+        example: Some("let add = (a, b) { a + b } in add(1)"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::call",
+        title: "Unknown argument",
+        description: "A named argument doesn't match any parameter of the function.",
+        example: Some("let f = (a) { a } in f(b = 1)"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::call",
+        title: "Argument passed more than once",
+        description: "A parameter is given a value both by position and by name, or by name twice.",
+        example: Some("let f = (a, b) { a } in f(a = 1, a = 2)"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::builtin",
+        title: "Character index out of bounds",
+        description: "A string is indexed at or past its length in unicode characters.",
+        example: Some(r#"charAt("hé", 2)"#),
+    },
+    ErrorInfo {
+        code: "tl::runtime::builtin",
+        title: "Invalid codepoint",
+        description: "A number is not a unicode scalar value.",
+        example: Some("fromCodepoint(55296)"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::builtin",
+        title: "Invalid byte",
+        description: "A number in a list of bytes is not between 0 and 255.",
+        example: Some("fromBytes([ 104 256 ])"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::builtin",
+        title: "Invalid UTF-8",
+        description: "A list of bytes is not a string encoded as UTF-8.",
+        example: Some("fromBytes([ 104 195 ])"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::builtin",
+        title: "Negative shift",
+        description: "A bit shift is given a negative amount.",
+        example: Some("shl(1, -1)"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::builtin",
+        title: "Negative precision",
+        description: "A number is rounded to a negative number of decimal places.",
+        example: Some("round(1.5, -1)"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::builtin",
+        title: "Precision too large",
+        description: "A number is rounded to more decimal places than a float has.",
+        example: Some("toFixed(70000, 1.5)"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::builtin",
+        title: "Invalid range",
+        description: "The lower bound of a range is bigger than its upper bound.",
+        example: Some("randInt(random(1), 6, 1)"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::builtin",
+        title: "Empty array",
+        description: "`mean`, `minOf` or `maxOf` is called with an empty array.",
+        example: Some("mean([ ])"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::too_large",
+        title: "Result too large",
+        description: "A range would create more items, repeating a string more bytes, or `**` a big integer with more bits \
+                      than tl allows, so evaluating can't exhaust the memory.",
+        example: Some("0..9223372036854775807"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::too_deep",
+        title: "Function calls nested too deep",
+        description: "Functions call each other deeper than `Options::max_call_depth`, usually because a recursion never ends.",
+        example: Some("let loop = (n) { loop(n) } in loop(1)"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::builtin",
+        title: "Environment variable not set",
+        description: "A string passed to `interpolateEnv` references an environment variable that is not set and has \
+                      no default. Only reported with `Options::allow_env`.",
+        example: Some(r#"interpolateEnv("$TL_UNSET_VARIABLE")"#),
+    },
+    ErrorInfo {
+        code: "tl::runtime::builtin",
+        title: "Invalid value",
+        description: "A duration or a size in bytes can't be parsed.",
+        example: Some(r#"parseDuration("h")"#),
+    },
+    #[cfg(feature = "url")]
+    ErrorInfo {
+        code: "tl::runtime::builtin",
+        title: "Invalid URL",
+        description: "A string passed to `parseUrl` is not a URL.",
+        example: Some(r#"parseUrl("http://:80")"#),
+    },
+    #[cfg(feature = "semver")]
+    ErrorInfo {
+        code: "tl::runtime::builtin",
+        title: "Invalid semantic version",
+        description: "A version or a version requirement can't be parsed.",
+        example: Some(r#"parseSemver("1.2")"#),
+    },
+    #[cfg(feature = "bigint")]
+    ErrorInfo {
+        code: "tl::runtime::builtin",
+        title: "Big integer not supported",
+        description: "A builtin that works on integers, like `gcd`, `div` or the bitwise builtins, got an integer \
+                      that doesn't fit into an `isize`.",
+        example: Some("gcd(9223372036854775808, 2)"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::integer_overflow",
+        title: "Integer overflow",
+        description: "The result of an integer operation doesn't fit into an integer. Only reported with \
+                      `Options::checked_arithmetic`, otherwise the result saturates. With the `bigint` feature \
+                      integers are promoted instead.",
+        example: if cfg!(feature = "bigint") {
+            None
+        } else {
+            Some("9223372036854775807 + 1")
+        },
+    },
+    ErrorInfo {
+        code: "tl::runtime::binary_op",
+        title: "Mismatched operand types",
+        description: "A binary operator is used on values it doesn't support. Only reported with \
+                      `Options::strict_types`, otherwise the operation evaluates to `null`.",
+        example: Some(r#"1 + "a""#),
+    },
+    ErrorInfo {
+        code: "tl::runtime::missing_field",
+        title: "Missing field",
+        description: "A field is accessed that the object doesn't have. Only reported with `Options::strict_fields`, \
+                      otherwise the access evaluates to `null`.",
+        example: Some("let server = { port = 80 } in server.host"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::contract",
+        title: "Contract violation",
+        description: "A value doesn't match its type annotation.",
+        example: Some(r#"let port :: int = "8080" in port"#),
+    },
+    ErrorInfo {
+        code: "tl::runtime::fix",
+        title: "Invalid fixpoint",
+        description: "`fix` is given something other than a function of one parameter whose body is an object literal.",
+        example: Some("fix((self) { 1 })"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::infinite_recursion",
+        title: "Infinite recursion",
+        description: "Fields of an object created by `fix` depend on each other in a cycle.",
+        example: Some("fix((self) { { a = self.b b = self.a } })"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::option",
+        title: "Unknown option type",
+        description: "The `type` of an option declared with `mkOption` doesn't exist.",
+        example: Some(r#"mkOption({ type = "integer" })"#),
+    },
+    ErrorInfo {
+        code: "tl::runtime::option",
+        title: "Unknown option",
+        description: "A definition passed to `evalOptions` has no declaration.",
+        example: Some(r#"evalOptions({ port = mkOption({ type = "int" }) }, { prot = 8080 })"#),
+    },
+    ErrorInfo {
+        code: "tl::runtime::option",
+        title: "Invalid option",
+        description: "A definition passed to `evalOptions` doesn't match the type of its declaration.",
+        example: Some(r#"evalOptions({ port = mkOption({ type = "int" }) }, { port = "8080" })"#),
+    },
+    ErrorInfo {
+        code: "tl::runtime::option",
+        title: "Missing option",
+        description: "An option without a default is not defined.",
+        example: Some(r#"evalOptions({ port = mkOption({ type = "int" }) }, { })"#),
+    },
+    ErrorInfo {
+        code: "tl::runtime::division_by_zero",
+        title: "Division by zero",
+        description: "An integer is divided by zero. With `Options::lenient_division` the division evaluates to \
+                      `null` instead.",
+        example: Some("1 / 0"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::strict_conditions",
+        title: "Condition is not a boolean",
+        description: "The condition of `if` or a comprehension is not a boolean. Only reported with \
+                      `Options::strict_conditions`, otherwise truthiness is used.",
+        example: Some("if(1, \"yes\", \"no\")"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::io",
+        title: "IO error",
+        description: "Reading or writing a file failed, like importing a file that doesn't exist.",
+        example: Some("import(./does-not-exist.tl)"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::invalid_path",
+        title: "Invalid path character",
+        description: "A control character is interpolated into a path.",
+        example: Some(r#"let name = "a\0b" in ./hosts/${name}.tl"#),
+    },
+    #[cfg(feature = "fs")]
+    ErrorInfo {
+        code: "tl::runtime::invalid_path",
+        title: "Absolute path segment",
+        description: "`join` is given an absolute path as the segment, which would replace the whole path.",
+        example: Some("join(./etc, /hosts)"),
+    },
+    ErrorInfo {
+        code: "tl::runtime::sandbox",
+        title: "Path outside of the root",
+        description: "A path resolves outside of `Options::path_root`.",
+        example: Some("import(/etc/hosts)"),
+    },
+    #[cfg(feature = "fs-write")]
+    ErrorInfo {
+        code: "tl::runtime::sandbox",
+        title: "Write not allowed",
+        description: "A file is written outside of the directories in `Options::write_allow_list`.",
+        example: Some(r#"writeFile(./out.txt, "text")"#),
+    },
+    ErrorInfo {
+        code: "tl::runtime::builtin_denied",
+        title: "Builtin denied",
+        description: "A builtin is not allowed, like `interpolateEnv` without `Options::allow_env`, \
+                      or a builtin that `Options::on_builtin_call` denies.",
+        example: Some(r#"interpolateEnv("$HOME")"#),
+    },
+    ErrorInfo {
+        code: "tl::runtime::impurity",
+        title: "Impurity not allowed",
+        description: "A builtin that depends on the environment is called with `Options::deterministic`.",
+        example: Some(r#"interpolateEnv("$HOME")"#),
+    },
+    ErrorInfo {
+        code: "tl::runtime::file_too_large",
+        title: "File too large",
+        description: "A file read by `import`, `renderTemplate` or `readFileBytes` is larger than \
+                      `Options::max_file_size`.",
+        example: None,
+    },
+    ErrorInfo {
+        code: "tl::runtime::invalid_utf8_file",
+        title: "File is not valid UTF-8",
+        description: "A file imported or rendered as a template is not UTF-8, the help guesses its encoding. \
+                      `readFileBytes` reads files of any encoding.",
+        example: None,
+    },
+    ErrorInfo {
+        code: "tl::runtime::import_cycle",
+        title: "Import cycle",
+        description: "A file is imported or rendered as a template while it is being evaluated, \
+                      because files import each other in a cycle.",
+        example: None,
+    },
+    ErrorInfo {
+        code: "tl::runtime::internal",
+        title: "Internal error",
+        description: "A state that tl never creates itself, like a builtin context of a host whose expression is not \
+                      a call.",
+        example: None,
+    },
+    ErrorInfo {
+        code: "tl::runtime::frozen",
+        title: "Frozen override",
+        description: "A field of an object frozen with `freeze` is overridden.",
+        example: Some(r#"setPath(freeze({ a = 1 }), "a", 2)"#),
+    },
+];
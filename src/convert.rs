@@ -0,0 +1,519 @@
+//! Converting JSON and TOML files to tl source, for migrating existing configuration.
+
+use crate::{
+    parser::ast::DEFAULT_MAX_DEPTH,
+    runtime::{Value, ValueKind},
+};
+use miette::{Diagnostic, NamedSource, SourceSpan};
+use std::{collections::BTreeMap, fs, iter::Peekable, path::Path, str::CharIndices};
+use thiserror::Error;
+
+/// Words that can't be used as an unquoted key.
+const KEYWORDS: &[&str] = &["null", "true", "false", "let", "in", "for", "or"];
+const INDENT: &str = "    ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    #[cfg(feature = "toml")]
+    Toml,
+}
+
+impl Format {
+    /// The format of a file by its extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            #[cfg(feature = "toml")]
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Error, Diagnostic, Debug)]
+pub enum ConvertError {
+    #[error("{message}")]
+    #[diagnostic(code(tl::convert::invalid_json))]
+    InvalidJson {
+        message: String,
+        #[source_code]
+        document: NamedSource<String>,
+        #[label("Here")]
+        at: SourceSpan,
+    },
+
+    #[cfg(feature = "toml")]
+    #[error(transparent)]
+    #[diagnostic(code(tl::convert::invalid_toml))]
+    InvalidToml(#[from] toml::de::Error),
+
+    #[error("`{path}` has no supported format")]
+    #[diagnostic(
+        code(tl::convert::unknown_format),
+        help("JSON files end with `.json`, TOML files with `.toml` and need the `toml` feature")
+    )]
+    UnknownFormat { path: String },
+
+    #[error("The document contains a value without a literal")]
+    #[diagnostic(
+        code(tl::convert::no_literal),
+        help(
+            "tl has no literal for infinite floats and NaN, and literals can't be nested deeper than `DEFAULT_MAX_DEPTH`"
+        )
+    )]
+    NoLiteral,
+
+    #[error(transparent)]
+    #[diagnostic(code(tl::convert::io))]
+    Io(#[from] std::io::Error),
+}
+
+/// Converts the file at `path` to tl source, the format is picked by its extension.
+/// # Errors
+/// This function will return an error if the file can not be read, has an unknown format or is invalid.
+pub fn convert_file(path: impl AsRef<Path>) -> Result<String, ConvertError> {
+    let path = path.as_ref();
+    let format = Format::from_path(path).ok_or_else(|| ConvertError::UnknownFormat {
+        path: path.display().to_string(),
+    })?;
+
+    let text = fs::read_to_string(path)?;
+    convert(NamedSource::new(path.display().to_string(), text), format)
+}
+
+/// Converts a JSON or TOML document to tl source that evaluates to the same value.
+/// # Errors
+/// This function will return an error if the document is invalid.
+pub fn convert(source: NamedSource<String>, format: Format) -> Result<String, ConvertError> {
+    let value = match format {
+        Format::Json => parse_json(source)?,
+        #[cfg(feature = "toml")]
+        Format::Toml => toml::from_str(source.inner())?,
+    };
+
+    to_source(&value).ok_or(ConvertError::NoLiteral)
+}
+
+/// Writes `value` as tl source that evaluates to it, if that is possible.
+/// Objects and arrays of objects or arrays are written over multiple lines, indented by four spaces.
+/// Values nested deeper than [`DEFAULT_MAX_DEPTH`] have no source, since it couldn't be parsed.
+pub fn to_source(value: &Value) -> Option<String> {
+    let mut text = String::new();
+    write_source(&mut text, value, 0)?;
+    Some(text)
+}
+
+fn write_source(text: &mut String, value: &Value, depth: usize) -> Option<()> {
+    // The items of arrays and objects are one level deeper, even if they are written on the same line
+    let has_items = match &value.kind {
+        ValueKind::Array(values) => !values.is_empty(),
+        ValueKind::Object(fields) => !fields.is_empty(),
+        _ => false,
+    };
+    if has_items && depth.saturating_add(1) >= DEFAULT_MAX_DEPTH {
+        return None;
+    }
+
+    let indent = INDENT.repeat(depth);
+    let inner_indent = INDENT.repeat(depth.saturating_add(1));
+
+    match &value.kind {
+        ValueKind::Null => text.push_str("null"),
+        ValueKind::Boolean(value) => text.push_str(&value.to_string()),
+        ValueKind::Int(value) => text.push_str(&value.to_string()),
+        #[cfg(feature = "bigint")]
+        ValueKind::BigInt(value) => text.push_str(&value.to_string()),
+        ValueKind::Float(value) => {
+            if !value.is_finite() {
+                return None;
+            }
+            // Display never uses an exponent, but leaves out the fraction of whole numbers
+            let literal = value.to_string();
+            text.push_str(&literal);
+            if !literal.contains('.') {
+                text.push_str(".0");
+            }
+        }
+        ValueKind::String(value) => text.push_str(&quote(value)),
+        ValueKind::Path(path) => {
+            let literal = path.to_str()?;
+            let is_literal = ["/", "./", "../"]
+                .iter()
+                .any(|prefix| literal.starts_with(prefix))
+                && literal
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || "/._-+~@".contains(ch));
+            if !is_literal {
+                return None;
+            }
+            text.push_str(literal);
+        }
+        ValueKind::Array(values) if values.is_empty() => text.push_str("[]"),
+        ValueKind::Array(values) => {
+            let nested = values
+                .iter()
+                .any(|value| matches!(value.kind, ValueKind::Array(_) | ValueKind::Object(_)));
+
+            if nested {
+                text.push_str("[\n");
+                for value in values {
+                    text.push_str(&inner_indent);
+                    write_source(text, value, depth.saturating_add(1))?;
+                    text.push('\n');
+                }
+                text.push_str(&indent);
+                text.push(']');
+            } else {
+                text.push('[');
+                for value in values {
+                    text.push(' ');
+                    write_source(text, value, depth)?;
+                }
+                text.push_str(" ]");
+            }
+        }
+        ValueKind::Object(fields) if fields.is_empty() => text.push_str("{}"),
+        ValueKind::Object(fields) => {
+            text.push_str("{\n");
+            for (key, value) in fields {
+                text.push_str(&inner_indent);
+                text.push_str(&key_source(key));
+                text.push_str(" = ");
+                write_source(text, value, depth.saturating_add(1))?;
+                text.push('\n');
+            }
+            text.push_str(&indent);
+            text.push('}');
+        }
+        ValueKind::Function { .. } | ValueKind::Builtin(_) | ValueKind::Lazy(_) => return None,
+    }
+
+    Some(())
+}
+
+/// An object key, quoted unless it is a valid identifier. Dots in unquoted keys would create nested objects.
+pub(crate) fn key_source(key: &str) -> String {
+    let mut chars = key.chars();
+    let is_identifier = chars
+        .next()
+        .is_some_and(|ch| ch.is_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_alphanumeric() || ch == '_')
+        && !KEYWORDS.contains(&key);
+
+    if is_identifier {
+        key.to_string()
+    } else {
+        quote(key)
+    }
+}
+
+/// A string literal of `text`, with quotes.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", escape(text))
+}
+
+/// The inside of a string literal of `text`, with quotes, backslashes, control characters and interpolations escaped.
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\0' => escaped.push_str("\\0"),
+            '$' if chars.peek() == Some(&'{') => escaped.push_str("\\$"),
+            ch => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+/// Parses a JSON document into a value.
+/// # Errors
+/// This function will return an error if the document is not valid JSON or nested deeper than [`DEFAULT_MAX_DEPTH`].
+pub fn parse_json(source: NamedSource<String>) -> Result<Value, ConvertError> {
+    let text = source.inner().clone();
+    let mut parser = JsonParser {
+        chars: text.char_indices().peekable(),
+        len: text.len(),
+        depth: 0,
+    };
+
+    let value = parser.value().and_then(|value| {
+        parser.skip_whitespace();
+        match parser.chars.next() {
+            None => Ok(value),
+            Some((offset, _)) => Err((offset, "Expected the end of the document".to_string())),
+        }
+    });
+
+    value.map_err(|(offset, message)| ConvertError::InvalidJson {
+        message,
+        document: source,
+        at: SourceSpan::new(offset.into(), 1),
+    })
+}
+
+/// An error message and the offset it happened at.
+type JsonResult<T> = Result<T, (usize, String)>;
+
+struct JsonParser<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    len: usize,
+    /// How many values the current value is inside of.
+    depth: usize,
+}
+
+impl JsonParser<'_> {
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map_or(self.len, |(offset, _)| *offset)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .chars
+            .next_if(|(_, ch)| matches!(ch, ' ' | '\t' | '\n' | '\r'))
+            .is_some()
+        {}
+    }
+
+    fn expect(&mut self, expected: char) -> JsonResult<()> {
+        self.skip_whitespace();
+        let offset = self.offset();
+        match self.chars.next() {
+            Some((_, ch)) if ch == expected => Ok(()),
+            _ => Err((offset, format!("Expected `{expected}`"))),
+        }
+    }
+
+    fn value(&mut self) -> JsonResult<Value> {
+        self.skip_whitespace();
+        let offset = self.offset();
+
+        // The values are nested as deep as the tl source they are converted to
+        if self.depth >= DEFAULT_MAX_DEPTH {
+            return Err((
+                offset,
+                format!("Values are nested more than {DEFAULT_MAX_DEPTH} levels deep"),
+            ));
+        }
+
+        self.depth = self.depth.saturating_add(1);
+        let kind = self.value_kind(offset);
+        self.depth = self.depth.saturating_sub(1);
+
+        Ok(Value::new_builtin(kind?))
+    }
+
+    fn value_kind(&mut self, offset: usize) -> JsonResult<ValueKind> {
+        let kind = match self.chars.peek().map(|(_, ch)| *ch) {
+            Some('{') => self.object()?,
+            Some('[') => self.array()?,
+            Some('"') => ValueKind::String(self.string()?),
+            Some('-' | '0'..='9') => self.number()?,
+            Some(ch) if ch.is_ascii_alphabetic() => {
+                let mut word = String::new();
+                while let Some((_, ch)) = self.chars.next_if(|(_, ch)| ch.is_ascii_alphabetic()) {
+                    word.push(ch);
+                }
+
+                match word.as_str() {
+                    "null" => ValueKind::Null,
+                    "true" => ValueKind::Boolean(true),
+                    "false" => ValueKind::Boolean(false),
+                    _ => return Err((offset, format!("Unknown literal `{word}`"))),
+                }
+            }
+            _ => return Err((offset, "Expected a value".to_string())),
+        };
+
+        Ok(kind)
+    }
+
+    fn object(&mut self) -> JsonResult<ValueKind> {
+        self.expect('{')?;
+        let mut fields = BTreeMap::new();
+
+        self.skip_whitespace();
+        if self.chars.next_if(|(_, ch)| *ch == '}').is_some() {
+            return Ok(ValueKind::Object(fields));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            fields.insert(key, self.value()?);
+
+            self.skip_whitespace();
+            let offset = self.offset();
+            match self.chars.next() {
+                Some((_, ',')) => {}
+                Some((_, '}')) => return Ok(ValueKind::Object(fields)),
+                _ => return Err((offset, "Expected `,` or `}`".to_string())),
+            }
+        }
+    }
+
+    fn array(&mut self) -> JsonResult<ValueKind> {
+        self.expect('[')?;
+        let mut values = Vec::new();
+
+        self.skip_whitespace();
+        if self.chars.next_if(|(_, ch)| *ch == ']').is_some() {
+            return Ok(ValueKind::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+
+            self.skip_whitespace();
+            let offset = self.offset();
+            match self.chars.next() {
+                Some((_, ',')) => {}
+                Some((_, ']')) => return Ok(ValueKind::Array(values)),
+                _ => return Err((offset, "Expected `,` or `]`".to_string())),
+            }
+        }
+    }
+
+    fn string(&mut self) -> JsonResult<String> {
+        self.expect('"')?;
+        let mut string = String::new();
+
+        loop {
+            let offset = self.offset();
+            match self.chars.next() {
+                None => return Err((offset, "Unterminated string".to_string())),
+                Some((_, '"')) => return Ok(string),
+                Some((_, '\\')) => {
+                    let ch = match self.chars.next().map(|(_, ch)| ch) {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape(offset)?,
+                        _ => return Err((offset, "Invalid escape".to_string())),
+                    };
+                    string.push(ch);
+                }
+                Some((_, ch)) if ch.is_control() => {
+                    return Err((offset, "Unescaped control character in string".to_string()));
+                }
+                Some((_, ch)) => string.push(ch),
+            }
+        }
+    }
+
+    /// The character of a `\u` escape, which takes a second escape for characters outside of the BMP.
+    fn unicode_escape(&mut self, offset: usize) -> JsonResult<char> {
+        let invalid = || (offset, "Invalid unicode escape".to_string());
+
+        let high = self.hex_digits().ok_or_else(invalid)?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or_else(invalid);
+        }
+
+        if self.chars.next().map(|(_, ch)| ch) != Some('\\')
+            || self.chars.next().map(|(_, ch)| ch) != Some('u')
+        {
+            return Err(invalid());
+        }
+        let low = self.hex_digits().ok_or_else(invalid)?;
+        let high = high.checked_sub(0xD800).ok_or_else(invalid)?;
+        let low = low
+            .checked_sub(0xDC00)
+            .filter(|low| *low < 0x400)
+            .ok_or_else(invalid)?;
+
+        high.checked_mul(0x400)
+            .and_then(|high| high.checked_add(low))
+            .and_then(|code| code.checked_add(0x10000))
+            .and_then(char::from_u32)
+            .ok_or_else(invalid)
+    }
+
+    fn hex_digits(&mut self) -> Option<u32> {
+        let digits: String = (0..4)
+            .map_while(|_| self.chars.next().map(|(_, ch)| ch))
+            .collect();
+        if digits.len() != 4 {
+            return None;
+        }
+        u32::from_str_radix(&digits, 16).ok()
+    }
+
+    fn number(&mut self) -> JsonResult<ValueKind> {
+        let offset = self.offset();
+        let mut number = String::new();
+        while let Some((_, ch)) = self
+            .chars
+            .next_if(|(_, ch)| ch.is_ascii_digit() || matches!(ch, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            number.push(ch);
+        }
+
+        let invalid = || (offset, format!("Invalid number `{number}`"));
+        if !is_json_number(&number) {
+            return Err(invalid());
+        }
+
+        let is_int = !number.contains(['.', 'e', 'E']);
+        if is_int && let Ok(int) = number.parse() {
+            return Ok(ValueKind::Int(int));
+        }
+        #[cfg(feature = "bigint")]
+        if is_int && let Ok(int) = number.parse() {
+            return Ok(ValueKind::BigInt(int));
+        }
+
+        // Numbers too large for an int are floats without the `bigint` feature
+        let float: f64 = number.parse().map_err(|_| invalid())?;
+        if float.is_finite() {
+            Ok(ValueKind::Float(float))
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+/// Whether `number` follows the JSON grammar, which has no leading zeros, `+` signs or digitless fractions.
+fn is_json_number(number: &str) -> bool {
+    fn digits(text: &str) -> (&str, &str) {
+        text.split_at(
+            text.find(|ch: char| !ch.is_ascii_digit())
+                .unwrap_or(text.len()),
+        )
+    }
+
+    let (int, rest) = digits(number.strip_prefix('-').unwrap_or(number));
+    if int.is_empty() || (int.len() > 1 && int.starts_with('0')) {
+        return false;
+    }
+
+    let rest = match rest.strip_prefix('.') {
+        Some(fraction) => match digits(fraction) {
+            ("", _) => return false,
+            (_, rest) => rest,
+        },
+        None => rest,
+    };
+
+    match rest.strip_prefix(['e', 'E']) {
+        Some(exponent) => {
+            let exponent = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+            !exponent.is_empty() && exponent.bytes().all(|byte| byte.is_ascii_digit())
+        }
+        None => rest.is_empty(),
+    }
+}
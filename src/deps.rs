@@ -0,0 +1,171 @@
+//! The files a tl file reads, found without evaluating it.
+
+use crate::{
+    Options,
+    parser::{
+        ast::types::{Expr, ExprKind, Literal},
+        parse_template, parse_with_options,
+    },
+    runtime::{BUILTINS_OBJECT, parent_dir, resolve_import, resolve_relative},
+};
+use miette::{NamedSource, Report};
+use std::{
+    collections::{BTreeSet, VecDeque},
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum DependencyKind {
+    /// Evaluated with `import` or `importWith`.
+    Import,
+    /// Rendered with `renderTemplate`.
+    Template,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Dependency {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub kind: DependencyKind,
+}
+
+/// A call that reads a file whose path is only known after evaluating it, like `import(./hosts/${name}.tl)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DynamicDependency {
+    pub file: PathBuf,
+    pub line: usize,
+    pub kind: DependencyKind,
+}
+
+/// Every file that is read when a file is evaluated, as far as it can be known without evaluating it.
+///
+/// With the `serde` feature it serializes to `{ files, dependencies, dynamic }`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DependencyGraph {
+    /// The root file and every file it reads transitively, breadth first.
+    pub files: Vec<PathBuf>,
+    pub dependencies: Vec<Dependency>,
+    /// Reads that could not be followed, the graph is incomplete if this is not empty.
+    pub dynamic: Vec<DynamicDependency>,
+}
+
+impl DependencyGraph {
+    /// Renders the graph in the DOT format of graphviz, templates are drawn with dashed edges.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph dependencies {\n");
+
+        for file in &self.files {
+            let _ = writeln!(out, "    {:?};", file.display().to_string());
+        }
+        for dependency in &self.dependencies {
+            let style = match dependency.kind {
+                DependencyKind::Import => "",
+                DependencyKind::Template => " [style=dashed]",
+            };
+            let _ = writeln!(
+                out,
+                "    {:?} -> {:?}{style};",
+                dependency.from.display().to_string(),
+                dependency.to.display().to_string()
+            );
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Collects the files that `root` imports and renders, and the files those read in turn.
+///
+/// Only calls of `import`, `importWith` and `renderTemplate` with a path literal are followed,
+/// the others are listed in [`DependencyGraph::dynamic`].
+/// Relative paths and directories are resolved like `import` does.
+/// # Errors
+/// This function will return an error if a file can not be read or parsed.
+pub fn dependency_graph(root: &Path, options: &Options) -> Result<DependencyGraph, Report> {
+    let mut graph = DependencyGraph::default();
+    let mut visited = BTreeSet::new();
+    let mut queue = VecDeque::from([(root.to_path_buf(), DependencyKind::Import)]);
+
+    while let Some((file, kind)) = queue.pop_front() {
+        if !visited.insert(file.clone()) {
+            continue;
+        }
+        graph.files.push(file.clone());
+
+        let text = fs::read_to_string(&file)
+            .map_err(|err| Report::msg(format!("Could not read {}: {err}", file.display())))?;
+        let source = NamedSource::new(file.display().to_string(), text);
+        let ast = match kind {
+            DependencyKind::Import => parse_with_options(&source, options)?,
+            DependencyKind::Template => parse_template(&source, options)?,
+        };
+
+        let mut reads = Vec::new();
+        collect_reads(&ast, &mut reads);
+
+        for (expr, kind) in reads {
+            let Some(ExprKind::Literal(Literal::Path(path))) = expr.map(|expr| &expr.kind) else {
+                graph.dynamic.push(DynamicDependency {
+                    file: file.clone(),
+                    line: expr.map_or(0, |expr| {
+                        crate::line_column(source.inner(), expr.span.offset()).0
+                    }),
+                    kind,
+                });
+                continue;
+            };
+
+            let path = resolve_relative(parent_dir(&file), path);
+            let to = match kind {
+                DependencyKind::Import => resolve_import(&path, options),
+                DependencyKind::Template => path,
+            };
+            graph.dependencies.push(Dependency {
+                from: file.clone(),
+                to: to.clone(),
+                kind,
+            });
+            queue.push_back((to, kind));
+        }
+    }
+
+    Ok(graph)
+}
+
+/// The path argument of every call that reads a file, `None` if the call has no arguments.
+fn collect_reads<'a>(expr: &'a Expr, reads: &mut Vec<(Option<&'a Expr>, DependencyKind)>) {
+    if let ExprKind::Call { base, args, .. } = &expr.kind {
+        let name = match &base.kind {
+            ExprKind::Identifier(name) => Some(name),
+            ExprKind::ObjectAccess { base, field, .. }
+                if base
+                    .as_ident()
+                    .is_some_and(|ident| ident == BUILTINS_OBJECT) =>
+            {
+                Some(field)
+            }
+            _ => None,
+        };
+
+        let kind = match name.map(String::as_str) {
+            Some("import" | "importWith") => Some(DependencyKind::Import),
+            Some("renderTemplate") => Some(DependencyKind::Template),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            reads.push((args.first(), kind));
+        }
+    }
+
+    for child in expr.children() {
+        collect_reads(child, reads);
+    }
+}
@@ -0,0 +1,140 @@
+//! Structural differences between two values, for reviewing changes to evaluated configs.
+
+use crate::runtime::{Value, ValueKind};
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Where a change happened, like `server.hosts[2]`. The root value has an empty path.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ValuePath(pub Vec<PathSegment>);
+
+impl ValuePath {
+    fn join(&self, segment: PathSegment) -> Self {
+        let mut path = self.clone();
+        path.0.push(segment);
+        path
+    }
+}
+
+impl Display for ValuePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, ".");
+        }
+
+        for (index, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) if index == 0 => write!(f, "{key}")?,
+                PathSegment::Key(key) => write!(f, ".{key}")?,
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A value that only exists in the new value is added, one that only exists in the old value is removed.
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub path: ValuePath,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+impl Change {
+    pub const fn kind(&self) -> ChangeKind {
+        match (&self.old, &self.new) {
+            (None, _) => ChangeKind::Added,
+            (Some(_), None) => ChangeKind::Removed,
+            (Some(_), Some(_)) => ChangeKind::Changed,
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = &self.path;
+
+        match (&self.old, &self.new) {
+            (None, None) => Ok(()),
+            (None, Some(new)) => write!(f, "+ {path} = {new}"),
+            (Some(old), None) => write!(f, "- {path} = {old}"),
+            (Some(old), Some(new)) => write!(f, "~ {path}: {old} -> {new}"),
+        }
+    }
+}
+
+/// Compares two values, descending into objects by key and into arrays by index.
+/// Values of different types, and values that are not objects or arrays, are compared with [`Value::deep_eq`].
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_at(&ValuePath::default(), old, new, &mut changes);
+    changes
+}
+
+/// Renders changes one per line, `+` for added, `-` for removed and `~` for changed values.
+pub fn render(changes: &[Change]) -> String {
+    changes.iter().map(|change| format!("{change}\n")).collect()
+}
+
+fn diff_at(path: &ValuePath, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    match (&old.kind, &new.kind) {
+        (ValueKind::Object(old), ValueKind::Object(new)) => {
+            for (key, old_value) in old {
+                let path = path.join(PathSegment::Key(key.clone()));
+                match new.get(key) {
+                    Some(new_value) => diff_at(&path, old_value, new_value, changes),
+                    None => changes.push(Change {
+                        path,
+                        old: Some(old_value.clone()),
+                        new: None,
+                    }),
+                }
+            }
+
+            for (key, new_value) in new {
+                if !old.contains_key(key) {
+                    changes.push(Change {
+                        path: path.join(PathSegment::Key(key.clone())),
+                        old: None,
+                        new: Some(new_value.clone()),
+                    });
+                }
+            }
+        }
+        (ValueKind::Array(old), ValueKind::Array(new)) => {
+            for index in 0..old.len().max(new.len()) {
+                let path = path.join(PathSegment::Index(index));
+                match (old.get(index), new.get(index)) {
+                    (Some(old_value), Some(new_value)) => {
+                        diff_at(&path, old_value, new_value, changes);
+                    }
+                    (old_value, new_value) => changes.push(Change {
+                        path,
+                        old: old_value.cloned(),
+                        new: new_value.cloned(),
+                    }),
+                }
+            }
+        }
+        _ if !old.deep_eq(new) => changes.push(Change {
+            path: path.clone(),
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
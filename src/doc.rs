@@ -0,0 +1,187 @@
+//! Documentation of the bindings a module exports, generated from its doc comments.
+
+use crate::parser::ast::types::{BinaryOperator, Expr, ExprKind, Literal, Pattern};
+use miette::NamedSource;
+use std::{collections::HashMap, fmt::Write};
+
+/// The documentation of a module.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ModuleDocs {
+    /// The parameters of the function the module evaluates to, see `importWith`.
+    pub params: Vec<DocEntry>,
+    /// The keys of the object the module evaluates to.
+    pub exports: Vec<DocEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DocEntry {
+    pub name: String,
+    pub doc: Option<String>,
+    /// The type inferred from the expression, `any` if it is only known after evaluating it.
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub type_name: &'static str,
+    /// The source of the default value of a parameter, or of an export that is a plain literal.
+    pub default: Option<String>,
+}
+
+/// Collects the documentation of a module without evaluating it.
+///
+/// Exports that refer to a `let` binding of the module use the doc comment and type of that binding.
+pub fn module_docs(source: &NamedSource<String>, ast: &Expr) -> ModuleDocs {
+    let mut docs = ModuleDocs::default();
+    let mut bindings = HashMap::new();
+    let mut expr = ast;
+
+    loop {
+        match &expr.kind {
+            ExprKind::LetIn {
+                bindings: let_bindings,
+                expr: body,
+            } => {
+                for (pattern, value) in let_bindings {
+                    if let Pattern::Identifier(name) = pattern {
+                        bindings.insert(name.as_str(), value);
+                    }
+                }
+                expr = body;
+            }
+            ExprKind::FnDecl {
+                args,
+                defaults,
+                expr: body,
+                ..
+            } if docs.params.is_empty() => {
+                docs.params = args
+                    .iter()
+                    .map(|name| {
+                        let default = defaults.get(name);
+                        DocEntry {
+                            name: name.clone(),
+                            doc: None,
+                            type_name: default.map_or("any", infer_type),
+                            default: default.and_then(|default| source_text(source, default)),
+                        }
+                    })
+                    .collect();
+                expr = body;
+            }
+            _ => break,
+        }
+    }
+
+    let ExprKind::Literal(Literal::Object(fields)) = &expr.kind else {
+        return docs;
+    };
+
+    let mut fields: Vec<_> = fields.iter().collect();
+    fields.sort_by_key(|(_, value)| value.span.offset());
+
+    docs.exports = fields
+        .into_iter()
+        .map(|(name, value)| {
+            let target = value
+                .as_ident()
+                .and_then(|ident| bindings.get(ident.as_str()).copied())
+                .unwrap_or(value);
+
+            DocEntry {
+                name: name.clone(),
+                doc: value.doc.clone().or_else(|| target.doc.clone()),
+                type_name: infer_type(target),
+                default: is_plain_literal(target)
+                    .then(|| source_text(source, target))
+                    .flatten(),
+            }
+        })
+        .collect();
+
+    docs
+}
+
+impl ModuleDocs {
+    /// Renders the documentation as markdown, with `title` as the heading.
+    pub fn to_markdown(&self, title: &str) -> String {
+        let mut out = format!("# {title}\n");
+
+        for (heading, entries) in [("Parameters", &self.params), ("Exports", &self.exports)] {
+            if entries.is_empty() {
+                continue;
+            }
+
+            let _ = write!(out, "\n## {heading}\n");
+            for entry in entries {
+                let _ = write!(out, "\n### `{}`: `{}`\n", entry.name, entry.type_name);
+                if let Some(default) = &entry.default {
+                    let _ = writeln!(out, "Default: `{default}`");
+                }
+                if let Some(doc) = &entry.doc {
+                    let _ = writeln!(out, "{doc}");
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// The type an expression evaluates to, using the names of [`ValueKind::type_of`](crate::runtime::ValueKind::type_of).
+fn infer_type(expr: &Expr) -> &'static str {
+    match &expr.kind {
+        ExprKind::Literal(literal) => match literal {
+            Literal::Null => "null",
+            Literal::Bool(_) => "boolean",
+            Literal::Int(_) => "number",
+            #[cfg(feature = "bigint")]
+            Literal::BigInt(_) => "number",
+            Literal::Float(_) => "float",
+            Literal::String(_) | Literal::InterpolatedString(_) => "string",
+            Literal::Path(_) | Literal::InterpolatedPath(_) => "path",
+            Literal::Array(_) => "array",
+            Literal::Object(_) => "object",
+        },
+        ExprKind::Not(_) => "boolean",
+        ExprKind::BinaryOp { operator, .. } => match operator {
+            BinaryOperator::Range | BinaryOperator::RangeInclusive => "array",
+            BinaryOperator::Eq
+            | BinaryOperator::NotEq
+            | BinaryOperator::Gt
+            | BinaryOperator::GtEq
+            | BinaryOperator::Lt
+            | BinaryOperator::LtEq
+            | BinaryOperator::And
+            | BinaryOperator::Or => "boolean",
+            _ => "any",
+        },
+        ExprKind::FnDecl { .. } => "function",
+        ExprKind::Comprehension { .. } => "array",
+        ExprKind::LetIn { expr, .. } => infer_type(expr),
+        ExprKind::Identifier(_)
+        | ExprKind::ArrayIndex { .. }
+        | ExprKind::ObjectAccess { .. }
+        | ExprKind::Call { .. } => "any",
+    }
+}
+
+fn is_plain_literal(expr: &Expr) -> bool {
+    matches!(
+        &expr.kind,
+        ExprKind::Literal(
+            Literal::Null
+                | Literal::Bool(_)
+                | Literal::Int(_)
+                | Literal::Float(_)
+                | Literal::String(_)
+                | Literal::Path(_)
+        )
+    )
+}
+
+fn source_text(source: &NamedSource<String>, expr: &Expr) -> Option<String> {
+    let start = expr.span.offset();
+    source
+        .inner()
+        .get(start..start.saturating_add(expr.span.len()))
+        .map(ToString::to_string)
+}
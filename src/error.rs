@@ -37,7 +37,17 @@ impl<E: Diagnostic> Diagnostic for Error<E> {
         let extra_label = LabeledSpan::new_primary_with_span(None, self.span);
 
         match self.kind.labels() {
-            Some(labels) => Some(Box::new(labels.chain(std::iter::once(extra_label)))),
+            Some(labels) => {
+                // Wrapped errors, like a tokenization error of the parser, already label the same span
+                let mut labels: Vec<_> = labels.collect();
+                if !labels
+                    .iter()
+                    .any(|label| label.inner() == extra_label.inner())
+                {
+                    labels.push(extra_label);
+                }
+                Some(Box::new(labels.into_iter()))
+            }
             None => Some(Box::new(std::iter::once(extra_label))),
         }
     }
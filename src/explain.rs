@@ -0,0 +1,99 @@
+//! Where a value of an evaluated config came from, for when imports, overlays and merges obscure it.
+
+use crate::{
+    Options,
+    diff::{PathSegment, ValuePath},
+    parser::parse_with_options,
+    read_source,
+    runtime::{
+        Scope, ValueKind,
+        inspect::{Inspector, Provenance, Step, StepKind},
+        types::Value,
+    },
+};
+use miette::Report;
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    path::Path,
+    rc::Rc,
+};
+
+/// A value of an evaluated file and the expressions it was derived from.
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub path: ValuePath,
+    pub value: Value,
+    /// In the order they were evaluated, usually starting where the value was defined.
+    pub steps: Vec<Step>,
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} = {}", self.path, self.value)?;
+
+        for step in &self.steps {
+            let action = match &step.kind {
+                StepKind::Defined => "defined".to_string(),
+                StepKind::Bound { name } => format!("bound to `{name}`"),
+                StepKind::Read => "read".to_string(),
+            };
+            writeln!(
+                f,
+                "  {action} at {}:{}:{}: {}",
+                step.file, step.line, step.column, step.text
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Evaluates the file at `path` and explains the value at `key`, like `.server.hosts.0`.
+/// Keys are separated by dots, a leading dot is optional and numbers index arrays.
+/// # Errors
+/// This function will return an error if the file can not be read, if a parsing or evaluation error occurs or if
+/// the value has no `key`.
+pub fn explain(path: impl AsRef<Path>, key: &str, options: Options) -> Result<Explanation, Report> {
+    let path = path.as_ref();
+    let source = read_source(path)?;
+    let ast = parse_with_options(&source, &options)?;
+
+    let provenance = Rc::new(Provenance::new(options.inspector.clone()));
+    let mut scope = Scope::new(HashMap::new(), source, ast);
+    scope.set_options(Options {
+        inspector: Some(Rc::clone(&provenance) as Rc<dyn Inspector>),
+        ..options
+    });
+    scope.define_location(path);
+    let mut value = scope.eval()?;
+
+    let mut value_path = ValuePath::default();
+    for segment in key
+        .trim_start_matches('.')
+        .split('.')
+        .filter(|segment| !segment.is_empty())
+    {
+        let (next, segment) = match (&value.kind, segment.parse::<usize>()) {
+            (ValueKind::Array(values), Ok(index)) => {
+                (values.get(index).cloned(), PathSegment::Index(index))
+            }
+            (ValueKind::Object(fields), _) => (
+                fields.get(segment).cloned(),
+                PathSegment::Key(segment.to_string()),
+            ),
+            _ => (None, PathSegment::Key(segment.to_string())),
+        };
+
+        value_path.0.push(segment);
+        value = next.ok_or_else(|| {
+            Report::msg(format!("{} has no value at `{value_path}`", path.display()))
+        })?;
+    }
+
+    Ok(Explanation {
+        steps: provenance.steps(&value),
+        path: value_path,
+        value,
+    })
+}
@@ -19,12 +19,50 @@ mod tests;
 mod error;
 pub use error::Error;
 
+mod catalog;
+pub use catalog::{ErrorInfo, error_catalog, error_info};
+
+mod options;
+pub use options::{BuiltinHook, Options};
+
 // Parsers
 pub mod parser;
 
 // Runtime
 pub mod runtime;
 
+// Documentation
+pub mod doc;
+
+// Linting
+pub mod lint;
+
+// Diffing
+pub mod diff;
+
+// Dependencies
+pub mod deps;
+
+// Caching
+pub mod cache;
+
+// Conversion
+pub mod convert;
+pub mod nix;
+
+// Templates
+pub mod template;
+
+// Provenance
+pub mod explain;
+
+// Cross references
+pub mod rename;
+pub mod xref;
+
+// Reports
+pub mod report;
+
 // Utils
 mod utils;
 pub use utils::*;
@@ -0,0 +1,478 @@
+//! Style and correctness checks on the AST, without evaluating it.
+
+use crate::{
+    parser::ast::types::{BinaryOperator, DuplicateKey, Expr, ExprKind, Literal},
+    runtime::{self, BUILTINS_OBJECT},
+};
+use miette::{Diagnostic, LabeledSpan, NamedSource, Severity, SourceSpan};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    str::FromStr,
+};
+use thiserror::Error;
+
+/// How deep interpolations can be nested before [`Rule::NestedInterpolation`] reports them.
+pub const MAX_INTERPOLATION_DEPTH: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    UnusedBinding,
+    Shadowing,
+    BuiltinShadowing,
+    NullComparison,
+    NestedInterpolation,
+    DuplicateKey,
+}
+
+impl Rule {
+    pub const ALL: [Self; 6] = [
+        Self::UnusedBinding,
+        Self::Shadowing,
+        Self::BuiltinShadowing,
+        Self::NullComparison,
+        Self::NestedInterpolation,
+        Self::DuplicateKey,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::UnusedBinding => "unused-binding",
+            Self::Shadowing => "shadowing",
+            Self::BuiltinShadowing => "builtin-shadowing",
+            Self::NullComparison => "null-comparison",
+            Self::NestedInterpolation => "nested-interpolation",
+            Self::DuplicateKey => "duplicate-key",
+        }
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|rule| rule.name() == name)
+            .ok_or_else(|| format!("Unknown lint rule `{name}`"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Level {
+    Allow,
+    #[default]
+    Warn,
+    Deny,
+}
+
+/// The level of every rule, rules that are not configured warn.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    levels: HashMap<Rule, Level>,
+}
+
+impl LintConfig {
+    pub fn level(&self, rule: Rule) -> Level {
+        self.levels.get(&rule).copied().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, rule: Rule, level: Level) -> &mut Self {
+        self.levels.insert(rule, level);
+        self
+    }
+
+    /// Denies the rules that point at likely bugs instead of style, shadowing, shadowed builtins and duplicate keys.
+    pub fn strict() -> Self {
+        let mut config = Self::default();
+        config
+            .set(Rule::Shadowing, Level::Deny)
+            .set(Rule::BuiltinShadowing, Level::Deny)
+            .set(Rule::DuplicateKey, Level::Deny);
+        config
+    }
+}
+
+#[derive(Error, Diagnostic, Debug, Clone, PartialEq)]
+pub enum LintKind {
+    #[error("Unused binding `{name}`")]
+    #[diagnostic(
+        code(tl::lint::unused_binding),
+        help("Remove the binding, or prefix its name with `_` if it is unused on purpose")
+    )]
+    UnusedBinding { name: String },
+
+    #[error("`{name}` shadows a binding of an enclosing scope")]
+    #[diagnostic(code(tl::lint::shadowing))]
+    Shadowing {
+        name: String,
+        #[label("Defined here first")]
+        original: SourceSpan,
+    },
+
+    #[error("`{name}` shadows a builtin")]
+    #[diagnostic(
+        code(tl::lint::builtin_shadowing),
+        help("Rename the binding, the builtin stays accessible as `builtins.{name}`")
+    )]
+    BuiltinShadowing { name: String },
+
+    #[error("Comparison against `null`")]
+    #[diagnostic(
+        code(tl::lint::null_comparison),
+        help("`null` is falsy, use the value as a condition or `maybe` to provide a fallback")
+    )]
+    NullComparison,
+
+    #[error("Interpolation nested more than {max} levels deep")]
+    #[diagnostic(
+        code(tl::lint::nested_interpolation),
+        help("Move the inner parts into `let` bindings")
+    )]
+    NestedInterpolation { max: usize },
+
+    #[error("Duplicate key `{key}`")]
+    #[diagnostic(
+        code(tl::lint::duplicate_key),
+        help("The later value replaces the earlier one, remove one of them")
+    )]
+    DuplicateKey {
+        key: String,
+        #[label("Defined here first")]
+        original: SourceSpan,
+    },
+}
+
+impl LintKind {
+    pub const fn rule(&self) -> Rule {
+        match self {
+            Self::UnusedBinding { .. } => Rule::UnusedBinding,
+            Self::Shadowing { .. } => Rule::Shadowing,
+            Self::BuiltinShadowing { .. } => Rule::BuiltinShadowing,
+            Self::NullComparison => Rule::NullComparison,
+            Self::NestedInterpolation { .. } => Rule::NestedInterpolation,
+            Self::DuplicateKey { .. } => Rule::DuplicateKey,
+        }
+    }
+}
+
+/// A reported issue, rendered as an error if its rule is denied and as a warning otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lint {
+    pub kind: LintKind,
+    pub level: Level,
+
+    pub source: NamedSource<String>,
+    pub span: SourceSpan,
+}
+
+impl Diagnostic for Lint {
+    fn severity(&self) -> Option<Severity> {
+        Some(match self.level {
+            Level::Deny => Severity::Error,
+            Level::Allow | Level::Warn => Severity::Warning,
+        })
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(&self.source)
+    }
+
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.kind.code()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.kind.help()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let extra_label = LabeledSpan::new_primary_with_span(None, self.span);
+
+        match self.kind.labels() {
+            Some(labels) => Some(Box::new(labels.chain(std::iter::once(extra_label)))),
+            None => Some(Box::new(std::iter::once(extra_label))),
+        }
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.kind, f)
+    }
+}
+
+impl std::error::Error for Lint {}
+
+/// Checks `ast` with every rule that is not allowed in `config`, in source order.
+///
+/// The AST only keeps the last value of a key, so the duplicate keys are the ones the parser found,
+/// see [`parse_with_duplicate_keys`](crate::parser::parse_with_duplicate_keys).
+pub fn lint(
+    source: &NamedSource<String>,
+    ast: &Expr,
+    duplicate_keys: &[DuplicateKey],
+    config: &LintConfig,
+) -> Vec<Lint> {
+    let mut linter = Linter {
+        source,
+        config,
+        builtins: runtime::Scope::builtins()
+            .into_iter()
+            .map(|builtin| builtin.name)
+            .chain(std::iter::once(BUILTINS_OBJECT))
+            .collect(),
+        scopes: Vec::new(),
+        interpolation_depth: 0,
+        lints: Vec::new(),
+    };
+
+    linter.walk(ast);
+    for DuplicateKey {
+        key,
+        original,
+        duplicate,
+    } in duplicate_keys
+    {
+        linter.report(
+            LintKind::DuplicateKey {
+                key: key.clone(),
+                original: *original,
+            },
+            *duplicate,
+        );
+    }
+    linter.lints.sort_by_key(|lint| lint.span.offset());
+    linter.lints
+}
+
+struct Binding {
+    name: String,
+    span: SourceSpan,
+    /// Only `let` bindings are reported as unused, not parameters.
+    report_unused: bool,
+}
+
+#[derive(Default)]
+struct Scope {
+    bindings: Vec<Binding>,
+    /// Every identifier inside the scope, including the ones before a binding.
+    /// Functions see the variables of their caller, so a use before the binding still counts.
+    used: HashSet<String>,
+}
+
+struct Linter<'a> {
+    source: &'a NamedSource<String>,
+    config: &'a LintConfig,
+    /// The names of the builtins of the enabled features.
+    builtins: HashSet<&'static str>,
+    scopes: Vec<Scope>,
+    interpolation_depth: usize,
+    lints: Vec<Lint>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, kind: LintKind, span: SourceSpan) {
+        let level = self.config.level(kind.rule());
+        if level == Level::Allow {
+            return;
+        }
+
+        self.lints.push(Lint {
+            kind,
+            level,
+            source: self.source.clone(),
+            span,
+        });
+    }
+
+    fn walk(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Identifier(name) => self.use_binding(name),
+            ExprKind::BinaryOp {
+                left,
+                operator: BinaryOperator::Eq | BinaryOperator::NotEq,
+                right,
+            } if is_null(left) || is_null(right) => {
+                self.report(LintKind::NullComparison, expr.span);
+                self.walk(left);
+                self.walk(right);
+            }
+            ExprKind::Literal(
+                Literal::InterpolatedString(parts) | Literal::InterpolatedPath(parts),
+            ) => {
+                self.interpolation_depth = self.interpolation_depth.saturating_add(1);
+                if self.interpolation_depth == MAX_INTERPOLATION_DEPTH.saturating_add(1) {
+                    self.report(
+                        LintKind::NestedInterpolation {
+                            max: MAX_INTERPOLATION_DEPTH,
+                        },
+                        expr.span,
+                    );
+                }
+
+                for part in parts {
+                    self.walk(part);
+                }
+                self.interpolation_depth = self.interpolation_depth.saturating_sub(1);
+            }
+            ExprKind::LetIn {
+                bindings,
+                expr: body,
+            } => {
+                self.scopes.push(Scope::default());
+
+                for (pattern, value) in bindings {
+                    self.walk(value);
+
+                    for name in pattern.names() {
+                        let span = binding_span(self.source.inner(), name, value.span);
+                        self.declare(name, span, true);
+                    }
+                }
+
+                self.walk(body);
+                self.pop_scope();
+            }
+            ExprKind::FnDecl {
+                args,
+                defaults,
+                rest,
+                expr: body,
+            } => {
+                let bindings = args
+                    .iter()
+                    .chain(rest)
+                    .map(|name| Binding {
+                        name: name.clone(),
+                        span: binding_span(self.source.inner(), name, body.span),
+                        report_unused: false,
+                    })
+                    .collect();
+                self.scopes.push(Scope {
+                    bindings,
+                    used: HashSet::new(),
+                });
+
+                for default in defaults.values() {
+                    self.walk(default);
+                }
+                self.walk(body);
+                self.pop_scope();
+            }
+            ExprKind::Comprehension {
+                expr: body,
+                binding,
+                iterable,
+                condition,
+            } => {
+                self.walk(iterable);
+
+                self.scopes.push(Scope::default());
+                let span = binding_span(self.source.inner(), binding, iterable.span);
+                self.declare(binding, span, false);
+
+                if let Some(condition) = condition {
+                    self.walk(condition);
+                }
+                self.walk(body);
+                self.pop_scope();
+            }
+            _ => {
+                for child in expr.children() {
+                    self.walk(child);
+                }
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &str, span: SourceSpan, report_unused: bool) {
+        let original = self
+            .scopes
+            .iter()
+            .flat_map(|scope| &scope.bindings)
+            .find(|binding| binding.name == name)
+            .map(|binding| binding.span);
+
+        if let Some(original) = original {
+            self.report(
+                LintKind::Shadowing {
+                    name: name.to_string(),
+                    original,
+                },
+                span,
+            );
+        } else if self.builtins.contains(name) {
+            self.report(
+                LintKind::BuiltinShadowing {
+                    name: name.to_string(),
+                },
+                span,
+            );
+        }
+
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.bindings.push(Binding {
+                name: name.to_string(),
+                span,
+                report_unused,
+            });
+        }
+    }
+
+    fn use_binding(&mut self, name: &str) {
+        for scope in &mut self.scopes {
+            scope.used.insert(name.to_string());
+        }
+    }
+
+    fn pop_scope(&mut self) {
+        let Some(scope) = self.scopes.pop() else {
+            return;
+        };
+
+        for binding in scope.bindings {
+            if binding.report_unused
+                && !scope.used.contains(&binding.name)
+                && !binding.name.starts_with('_')
+            {
+                self.report(LintKind::UnusedBinding { name: binding.name }, binding.span);
+            }
+        }
+    }
+}
+
+fn is_null(expr: &Expr) -> bool {
+    matches!(expr.kind, ExprKind::Literal(Literal::Null))
+}
+
+/// The span of the last `name` before `value`, the AST only keeps the span of the bound value.
+pub(crate) fn binding_span(source: &str, name: &str, value: SourceSpan) -> SourceSpan {
+    let is_ident_char = |ch: char| ch.is_alphanumeric() || ch == '_';
+    let before = source.get(..value.offset()).unwrap_or_default();
+
+    before
+        .match_indices(name)
+        .filter(|(index, _)| {
+            let start_ok = before
+                .get(..*index)
+                .and_then(|text| text.chars().next_back())
+                .is_none_or(|ch| !is_ident_char(ch));
+            let end_ok = before
+                .get(index.saturating_add(name.len())..)
+                .and_then(|text| text.chars().next())
+                .is_none_or(|ch| !is_ident_char(ch));
+
+            start_ok && end_ok
+        })
+        .last()
+        .map_or(value, |(index, _)| {
+            SourceSpan::new(index.into(), name.len())
+        })
+}
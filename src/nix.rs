@@ -0,0 +1,1529 @@
+//! Best-effort conversion between tl and Nix expressions, for migrating small Nix expressions.
+//!
+//! Both directions cover literals, `let ... in`, functions and calls, attribute sets and lists.
+//! Anything without a counterpart is a [`NixError::Unsupported`] pointing at it, instead of a translation that
+//! behaves differently. Some differences are not detected: `let` bindings in Nix can refer to later bindings, and a
+//! tl `+` always becomes `+` since merging objects or concatenating arrays depends on the values.
+//! Division is always by floats in tl, so `a / b` becomes `a * 1.0 / b`, and a Nix division only converts if one
+//! of its operands is a float.
+
+use crate::{
+    convert::{escape, key_source},
+    parser::ast::{
+        DEFAULT_MAX_DEPTH,
+        types::{BinaryOperator, Expr, ExprKind, Literal, Pattern},
+    },
+};
+use miette::{Diagnostic, NamedSource, SourceSpan};
+use std::fmt::Write;
+use thiserror::Error;
+
+const NIX_INDENT: &str = "  ";
+const TL_INDENT: &str = "    ";
+const NIX_KEYWORDS: &[&str] = &[
+    "if", "then", "else", "assert", "with", "let", "in", "rec", "inherit", "or",
+];
+
+#[derive(Error, Diagnostic, Debug)]
+pub enum NixError {
+    #[error("{construct} can't be converted")]
+    #[diagnostic(code(tl::nix::unsupported))]
+    Unsupported {
+        construct: String,
+        #[source_code]
+        document: NamedSource<String>,
+        #[label("Here")]
+        at: SourceSpan,
+    },
+
+    #[error("{message}")]
+    #[diagnostic(code(tl::nix::syntax))]
+    Syntax {
+        message: String,
+        #[source_code]
+        document: NamedSource<String>,
+        #[label("Here")]
+        at: SourceSpan,
+    },
+
+    #[error("Expression nested more than {max} levels deep")]
+    #[diagnostic(
+        code(tl::nix::nesting_too_deep),
+        help("Move the inner parts into `let` bindings")
+    )]
+    NestingTooDeep {
+        max: usize,
+        #[source_code]
+        document: NamedSource<String>,
+        #[label("Here")]
+        at: SourceSpan,
+    },
+}
+
+/// A problem and where it is, the document is added once the conversion failed.
+enum Problem {
+    Unsupported(String),
+    Syntax(String),
+    TooDeep(usize),
+}
+
+impl Problem {
+    fn into_error(self, document: NamedSource<String>, at: SourceSpan) -> NixError {
+        match self {
+            Self::Unsupported(construct) => NixError::Unsupported {
+                construct,
+                document,
+                at,
+            },
+            Self::Syntax(message) => NixError::Syntax {
+                message,
+                document,
+                at,
+            },
+            Self::TooDeep(max) => NixError::NestingTooDeep { max, document, at },
+        }
+    }
+}
+
+type Converted<T> = Result<T, (Problem, SourceSpan)>;
+
+fn unsupported<T>(construct: impl Into<String>, at: SourceSpan) -> Converted<T> {
+    Err((Problem::Unsupported(construct.into()), at))
+}
+
+/// Converts a tl expression to Nix source. Type annotations are dropped.
+/// # Errors
+/// This function will return an error if the expression uses something Nix has no counterpart for,
+/// like named arguments or the `%` operator.
+pub fn to_nix(source: &NamedSource<String>, ast: &Expr) -> Result<String, NixError> {
+    nix_expr(ast, 0)
+        .map(|(text, _)| text)
+        .map_err(|(problem, at)| problem.into_error(source.clone(), at))
+}
+
+/// Converts a Nix expression to tl source.
+/// # Errors
+/// This function will return an error if the expression is invalid, nested deeper than [`DEFAULT_MAX_DEPTH`],
+/// or if it uses something tl has no counterpart for, like `with`, attribute set patterns or parentheses that tl
+/// can't express.
+pub fn from_nix(source: &NamedSource<String>) -> Result<String, NixError> {
+    let mut parser = NixParser {
+        text: source.inner(),
+        pos: 0,
+        depth: 0,
+    };
+
+    parser
+        .parse_expr()
+        .and_then(|node| {
+            parser.skip_trivia();
+            if parser.pos < parser.text.len() {
+                return Err(parser.syntax_error("Expected the end of the expression"));
+            }
+            tl_expr(&node, 0)
+        })
+        .map(|(text, _)| text)
+        .map_err(|(problem, at)| problem.into_error(source.clone(), at))
+}
+
+// tl to Nix
+
+/// How loosely Nix expressions bind, operands that bind looser than their position allows are parenthesized.
+const ATOM: u8 = 0;
+const SELECT_OR: u8 = 1;
+const APPLY: u8 = 2;
+const NEGATE: u8 = 3;
+const NOT: u8 = 8;
+const LOOSEST: u8 = 15;
+
+fn nix_operand(expr: &Expr, depth: usize, max_level: u8) -> Converted<String> {
+    let (text, level) = nix_expr(expr, depth)?;
+    Ok(if level > max_level {
+        format!("({text})")
+    } else {
+        text
+    })
+}
+
+/// The Nix source of `expr` and how loosely it binds.
+fn nix_expr(expr: &Expr, depth: usize) -> Converted<(String, u8)> {
+    let indent = NIX_INDENT.repeat(depth);
+    let inner = NIX_INDENT.repeat(depth.saturating_add(1));
+
+    Ok(match &expr.kind {
+        ExprKind::Literal(literal) => nix_literal(literal, expr.span, depth)?,
+        ExprKind::Identifier(name) => (nix_name(name, expr.span)?, ATOM),
+        ExprKind::Not(operand) => (format!("!{}", nix_operand(operand, depth, NOT)?), NOT),
+        ExprKind::BinaryOp {
+            left,
+            operator,
+            right,
+        } => {
+            let (symbol, level) = nix_operator(operator, expr.span)?;
+            // Comparisons don't associate
+            let left_max = if level >= 10 {
+                level.saturating_sub(1)
+            } else {
+                level
+            };
+            let mut left_text = nix_operand(left, depth, left_max)?;
+            let right_text = nix_operand(right, depth, level.saturating_sub(1))?;
+
+            // Nix divides integers without the remainder, a float operand keeps the result of tl
+            let is_float =
+                |operand: &Expr| matches!(operand.kind, ExprKind::Literal(Literal::Float(_)));
+            if *operator == BinaryOperator::Divide && !is_float(left) && !is_float(right) {
+                left_text.push_str(" * 1.0");
+            }
+
+            (format!("{left_text} {symbol} {right_text}"), level)
+        }
+        ExprKind::ArrayIndex { base, index } => (
+            format!(
+                "builtins.elemAt {} {index}",
+                nix_operand(base, depth, ATOM)?
+            ),
+            APPLY,
+        ),
+        ExprKind::ObjectAccess {
+            base,
+            field,
+            fallback,
+        } => {
+            let base = nix_operand(base, depth, ATOM)?;
+            let field = nix_key(field);
+
+            match fallback {
+                Some(fallback) => (
+                    format!("{base}.{field} or {}", nix_operand(fallback, depth, ATOM)?),
+                    SELECT_OR,
+                ),
+                None => (format!("{base}.{field}"), ATOM),
+            }
+        }
+        ExprKind::FnDecl {
+            args,
+            defaults,
+            rest,
+            expr: body,
+        } => {
+            if !defaults.is_empty() {
+                return unsupported("A default argument", expr.span);
+            }
+            if rest.is_some() {
+                return unsupported("A rest parameter", expr.span);
+            }
+            if args.is_empty() {
+                return unsupported("A function without parameters", expr.span);
+            }
+
+            let mut text = String::new();
+            for arg in args {
+                let _ = write!(text, "{}: ", nix_name(arg, expr.span)?);
+            }
+            text.push_str(&nix_expr(body, depth)?.0);
+
+            (text, LOOSEST)
+        }
+        ExprKind::Call {
+            base,
+            args,
+            named_args,
+        } => {
+            if let Some(arg) = named_args.first() {
+                return unsupported("A named argument", arg.span);
+            }
+            if args.is_empty() {
+                return unsupported("A call without arguments", expr.span);
+            }
+
+            if let (ExprKind::Identifier(name), [condition, then, otherwise]) =
+                (&base.kind, args.as_slice())
+                && name == "if"
+            {
+                let text = format!(
+                    "if {} then {} else {}",
+                    nix_expr(condition, depth)?.0,
+                    nix_expr(then, depth)?.0,
+                    nix_expr(otherwise, depth)?.0
+                );
+                return Ok((text, LOOSEST));
+            }
+
+            let mut text = nix_operand(base, depth, APPLY)?;
+            for arg in args {
+                text.push(' ');
+                text.push_str(&nix_operand(arg, depth, ATOM)?);
+            }
+
+            (text, APPLY)
+        }
+        ExprKind::LetIn {
+            bindings,
+            expr: body,
+        } => {
+            let mut text = String::from("let\n");
+            for (pattern, value) in bindings {
+                let value_text = nix_expr(value, depth.saturating_add(1))?.0;
+
+                match pattern {
+                    Pattern::Identifier(name) => {
+                        let name = nix_name(name, value.span)?;
+                        let _ = writeln!(text, "{inner}{name} = {value_text};");
+                    }
+                    Pattern::Object(names) => {
+                        let names = names
+                            .iter()
+                            .map(|name| nix_name(name, value.span))
+                            .collect::<Converted<Vec<_>>>()?;
+                        let _ =
+                            writeln!(text, "{inner}inherit ({value_text}) {};", names.join(" "));
+                    }
+                    Pattern::Array { .. } => return unsupported("An array pattern", value.span),
+                }
+            }
+            let _ = write!(text, "{indent}in\n{indent}{}", nix_expr(body, depth)?.0);
+
+            (text, LOOSEST)
+        }
+        ExprKind::Comprehension {
+            expr: item,
+            binding,
+            iterable,
+            condition,
+        } => {
+            let binding = nix_name(binding, expr.span)?;
+            let item = nix_expr(item, depth)?.0;
+            let mut iterable = nix_operand(iterable, depth, ATOM)?;
+            if let Some(condition) = condition {
+                let condition = nix_expr(condition, depth)?.0;
+                iterable = format!("(builtins.filter ({binding}: {condition}) {iterable})");
+            }
+
+            (format!("map ({binding}: {item}) {iterable}"), APPLY)
+        }
+    })
+}
+
+fn nix_literal(literal: &Literal, span: SourceSpan, depth: usize) -> Converted<(String, u8)> {
+    let indent = NIX_INDENT.repeat(depth);
+    let inner = NIX_INDENT.repeat(depth.saturating_add(1));
+
+    Ok(match literal {
+        Literal::Null => ("null".to_string(), ATOM),
+        Literal::Bool(value) => (value.to_string(), ATOM),
+        Literal::Int(value) => (value.to_string(), if *value < 0 { NEGATE } else { ATOM }),
+        #[cfg(feature = "bigint")]
+        Literal::BigInt(_) => return unsupported("An integer this large", span),
+        Literal::Float(value) => {
+            let mut text = value.to_string();
+            if !text.contains('.') {
+                text.push_str(".0");
+            }
+            (
+                text,
+                if value.is_sign_negative() {
+                    NEGATE
+                } else {
+                    ATOM
+                },
+            )
+        }
+        Literal::String(text) => (format!("\"{}\"", nix_escape(text)), ATOM),
+        Literal::InterpolatedString(parts) => {
+            let mut text = String::from("\"");
+            for part in parts {
+                match &part.kind {
+                    ExprKind::Literal(Literal::String(part)) => text.push_str(&nix_escape(part)),
+                    _ => {
+                        let _ = write!(text, "${{{}}}", nix_expr(part, depth)?.0);
+                    }
+                }
+            }
+            text.push('"');
+            (text, ATOM)
+        }
+        Literal::Path(path) => (nix_path(path.to_str(), span)?, ATOM),
+        Literal::InterpolatedPath(parts) => {
+            // The text between the interpolations is lexed as strings
+            let text_of = |part: &Expr| match &part.kind {
+                ExprKind::Literal(Literal::String(text)) => Some(text.clone()),
+                ExprKind::Literal(Literal::Path(path)) => Some(path.to_string_lossy().into_owned()),
+                _ => None,
+            };
+
+            // Nix only parses a path that starts with a directory before the first interpolation
+            if !parts
+                .first()
+                .and_then(text_of)
+                .is_some_and(|text| text.contains('/'))
+            {
+                return unsupported("A path that starts with an interpolation", span);
+            }
+
+            let mut text = String::new();
+            for part in parts {
+                match text_of(part) {
+                    Some(path) => text.push_str(&nix_path(Some(&path), part.span)?),
+                    None => {
+                        let _ = write!(text, "${{{}}}", nix_expr(part, depth)?.0);
+                    }
+                }
+            }
+            (text, ATOM)
+        }
+        Literal::Array(values) if values.is_empty() => ("[ ]".to_string(), ATOM),
+        Literal::Array(values) => {
+            let nested = values.iter().any(|value| {
+                matches!(
+                    value.kind,
+                    ExprKind::Literal(Literal::Array(_) | Literal::Object(_))
+                )
+            });
+
+            let mut text = String::from("[");
+            for value in values {
+                if nested {
+                    let value = nix_operand(value, depth.saturating_add(1), ATOM)?;
+                    let _ = write!(text, "\n{inner}{value}");
+                } else {
+                    let _ = write!(text, " {}", nix_operand(value, depth, ATOM)?);
+                }
+            }
+            if nested {
+                let _ = write!(text, "\n{indent}]");
+            } else {
+                text.push_str(" ]");
+            }
+
+            (text, ATOM)
+        }
+        Literal::Object(fields) if fields.is_empty() => ("{ }".to_string(), ATOM),
+        Literal::Object(fields) => {
+            let mut text = String::from("{\n");
+            for (key, value) in fields {
+                let value = nix_expr(value, depth.saturating_add(1))?.0;
+                let _ = writeln!(text, "{inner}{} = {value};", nix_key(key));
+            }
+            text.push_str(&indent);
+            text.push('}');
+
+            (text, ATOM)
+        }
+    })
+}
+
+/// The Nix operator for a tl operator and how loosely it binds.
+fn nix_operator(operator: &BinaryOperator, span: SourceSpan) -> Converted<(&'static str, u8)> {
+    Ok(match operator {
+        BinaryOperator::Plus => ("+", 7),
+        BinaryOperator::Minus => ("-", 7),
+        BinaryOperator::Multiply => ("*", 6),
+        BinaryOperator::Divide => ("/", 6),
+        BinaryOperator::Gt => (">", 10),
+        BinaryOperator::GtEq => (">=", 10),
+        BinaryOperator::Lt => ("<", 10),
+        BinaryOperator::LtEq => ("<=", 10),
+        BinaryOperator::Eq => ("==", 11),
+        BinaryOperator::NotEq => ("!=", 11),
+        BinaryOperator::And => ("&&", 12),
+        BinaryOperator::Or => ("||", 13),
+        BinaryOperator::Modulo
+        | BinaryOperator::Power
+        | BinaryOperator::Range
+        | BinaryOperator::RangeInclusive => {
+            return unsupported(format!("The `{operator}` operator"), span);
+        }
+    })
+}
+
+fn is_nix_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '\'' | '-'))
+        && !NIX_KEYWORDS.contains(&name)
+}
+
+fn nix_name(name: &str, span: SourceSpan) -> Converted<String> {
+    if is_nix_identifier(name) {
+        Ok(name.to_string())
+    } else {
+        unsupported(format!("The name `{name}`"), span)
+    }
+}
+
+fn nix_key(key: &str) -> String {
+    if is_nix_identifier(key) {
+        key.to_string()
+    } else {
+        format!("\"{}\"", nix_escape(key))
+    }
+}
+
+fn nix_path(path: Option<&str>, span: SourceSpan) -> Converted<String> {
+    match path {
+        Some(path)
+            if path
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || "/._-+".contains(ch)) =>
+        {
+            Ok(path.to_string())
+        }
+        _ => unsupported("A path with these characters", span),
+    }
+}
+
+fn nix_escape(text: &str) -> String {
+    let mut escaped = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '$' if chars.peek() == Some(&'{') => escaped.push_str("\\$"),
+            ch => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+// Nix to tl
+
+#[derive(Debug, Clone)]
+struct Node {
+    kind: NodeKind,
+    span: SourceSpan,
+}
+
+#[derive(Debug, Clone)]
+enum NodeKind {
+    Null,
+    Bool(bool),
+    Int(isize),
+    Float(f64),
+    String(Vec<StringPart>),
+    /// A path like `./a/${name}.nix`, a plain path is a single text part.
+    Path(Vec<StringPart>),
+    Identifier(String),
+    List(Vec<Node>),
+    /// The fields of an attribute set, by their attribute path.
+    Attrs(Vec<(Vec<String>, Node)>),
+    Let(Vec<(String, Node)>, Box<Node>),
+    /// `a: b: body`, curried functions are a single node.
+    Lambda(Vec<String>, Box<Node>),
+    Apply(Box<Node>, Vec<Node>),
+    Select(Box<Node>, Vec<String>, Option<Box<Node>>),
+    BinaryOp(BinaryOperator, Box<Node>, Box<Node>),
+    Not(Box<Node>),
+    Negate(Box<Node>),
+    If(Box<Node>, Box<Node>, Box<Node>),
+}
+
+#[derive(Debug, Clone)]
+enum StringPart {
+    Text(String),
+    Interpolation(Node),
+}
+
+/// The binary operators of Nix and how tightly they bind, longer operators before their prefixes.
+const NIX_OPERATORS: &[(&str, u8)] = &[
+    ("->", 1),
+    ("||", 2),
+    ("&&", 3),
+    ("==", 4),
+    ("!=", 4),
+    ("<=", 5),
+    (">=", 5),
+    ("<", 5),
+    (">", 5),
+    ("//", 6),
+    ("++", 10),
+    ("+", 8),
+    ("-", 8),
+    ("*", 9),
+    ("/", 9),
+    ("?", 11),
+];
+/// How tightly `!` binds, between `//` and `+`.
+const NIX_NOT: u8 = 7;
+
+struct NixParser<'a> {
+    text: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> NixParser<'a> {
+    fn rest(&self) -> &'a str {
+        self.text.get(self.pos..).unwrap_or_default()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos = self.pos.saturating_add(ch.len_utf8());
+        Some(ch)
+    }
+
+    fn span_from(&self, start: usize) -> SourceSpan {
+        SourceSpan::new(start.into(), self.pos.saturating_sub(start))
+    }
+
+    fn syntax_error(&self, message: &str) -> (Problem, SourceSpan) {
+        let len = self.peek().map_or(0, char::len_utf8);
+        (
+            Problem::Syntax(message.to_string()),
+            SourceSpan::new(self.pos.into(), len),
+        )
+    }
+
+    /// Runs `parse` one nesting level deeper, failing instead if that exceeds [`DEFAULT_MAX_DEPTH`].
+    fn nest<T>(&mut self, parse: impl FnOnce(&mut Self) -> Converted<T>) -> Converted<T> {
+        if self.depth >= DEFAULT_MAX_DEPTH {
+            let len = self.peek().map_or(0, char::len_utf8);
+            return Err((
+                Problem::TooDeep(DEFAULT_MAX_DEPTH),
+                SourceSpan::new(self.pos.into(), len),
+            ));
+        }
+
+        self.depth = self.depth.saturating_add(1);
+        let result = parse(self);
+        self.depth = self.depth.saturating_sub(1);
+
+        result
+    }
+
+    /// Skips whitespace and comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            let rest = self.rest();
+            let skipped = if rest.starts_with('#') {
+                rest.find('\n').unwrap_or(rest.len())
+            } else if rest.starts_with("/*") {
+                rest.find("*/")
+                    .map_or(rest.len(), |end| end.saturating_add(2))
+            } else {
+                rest.len().saturating_sub(rest.trim_start().len())
+            };
+
+            if skipped == 0 {
+                break;
+            }
+            self.pos = self.pos.saturating_add(skipped);
+        }
+    }
+
+    /// Consumes `token` after whitespace if it is next.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_trivia();
+        if self.rest().starts_with(token) {
+            self.pos = self.pos.saturating_add(token.len());
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Converted<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.syntax_error(&format!("Expected `{token}`")))
+        }
+    }
+
+    /// The identifier at the current position, without consuming it.
+    fn peek_identifier(&mut self) -> Option<&'a str> {
+        self.skip_trivia();
+        let rest = self.rest();
+        let len = rest
+            .char_indices()
+            .find(|(index, ch)| {
+                !(ch.is_ascii_alphabetic()
+                    || *ch == '_'
+                    || *index > 0 && (ch.is_ascii_digit() || matches!(ch, '\'' | '-')))
+            })
+            .map_or(rest.len(), |(index, _)| index);
+
+        rest.get(..len).filter(|name| !name.is_empty())
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek_identifier() == Some(keyword) {
+            self.pos = self.pos.saturating_add(keyword.len());
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Converted<()> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.syntax_error(&format!("Expected `{keyword}`")))
+        }
+    }
+
+    fn parse_name(&mut self) -> Converted<String> {
+        let name = self.peek_identifier().map(str::to_string);
+        match name {
+            Some(name) => {
+                self.pos = self.pos.saturating_add(name.len());
+                Ok(name)
+            }
+            None => Err(self.syntax_error("Expected a name")),
+        }
+    }
+
+    fn parse_expr(&mut self) -> Converted<Node> {
+        self.nest(Self::parse_unnested_expr)
+    }
+
+    fn parse_unnested_expr(&mut self) -> Converted<Node> {
+        self.skip_trivia();
+        let start = self.pos;
+
+        if self.eat_keyword("let") {
+            if self.eat("{") {
+                return unsupported("A `let { }` block", self.span_from(start));
+            }
+
+            let mut bindings = Vec::new();
+            while !self.eat_keyword("in") {
+                if self.pos >= self.text.len() {
+                    return Err(self.syntax_error("Expected `in`"));
+                }
+                for (path, value) in self.parse_binding()? {
+                    match <[String; 1]>::try_from(path) {
+                        Ok([name]) => bindings.push((name, value)),
+                        Err(_) => return unsupported("A nested name in `let`", value.span),
+                    }
+                }
+            }
+            let body = self.parse_expr()?;
+
+            return Ok(Node {
+                kind: NodeKind::Let(bindings, Box::new(body)),
+                span: self.span_from(start),
+            });
+        }
+
+        if self.eat_keyword("if") {
+            let condition = self.parse_expr()?;
+            self.expect_keyword("then")?;
+            let then = self.parse_expr()?;
+            self.expect_keyword("else")?;
+            let otherwise = self.parse_expr()?;
+
+            return Ok(Node {
+                kind: NodeKind::If(Box::new(condition), Box::new(then), Box::new(otherwise)),
+                span: self.span_from(start),
+            });
+        }
+
+        for keyword in ["with", "assert"] {
+            if self.eat_keyword(keyword) {
+                return unsupported(format!("A `{keyword}` expression"), self.span_from(start));
+            }
+        }
+
+        if let Some(name) = self.peek_identifier().map(str::to_string)
+            && !NIX_KEYWORDS.contains(&name.as_str())
+        {
+            let name_end = start.saturating_add(name.len());
+            let after = self.text.get(name_end..).unwrap_or_default().trim_start();
+
+            if after.starts_with('@') {
+                return unsupported("An attribute set pattern", self.span_from(start));
+            }
+            if after.starts_with(':') {
+                self.pos = name_end;
+                self.expect(":")?;
+                let body = self.parse_expr()?;
+
+                let (params, body) = match body.kind {
+                    NodeKind::Lambda(mut params, body) => {
+                        params.insert(0, name);
+                        (params, body)
+                    }
+                    _ => (vec![name], Box::new(body)),
+                };
+                return Ok(Node {
+                    kind: NodeKind::Lambda(params, body),
+                    span: self.span_from(start),
+                });
+            }
+        }
+
+        if self.rest().starts_with('{') && self.is_pattern() {
+            return unsupported("An attribute set pattern", self.span_from(start));
+        }
+
+        self.parse_operation(0)
+    }
+
+    /// Whether the `{` at the current position starts a function parameter like `{ a, b ? 1 }:`.
+    fn is_pattern(&mut self) -> bool {
+        let start = self.pos;
+        self.pos = self.pos.saturating_add(1);
+
+        let is_pattern = if self.eat("}") {
+            self.eat(":") || self.eat("@")
+        } else if self.eat("...") {
+            true
+        } else {
+            self.parse_name().is_ok() && (self.eat(",") || self.eat("?") || self.eat("}"))
+        };
+
+        self.pos = start;
+        is_pattern
+    }
+
+    /// Parses binary operations whose operators bind at least as tight as `min_level`.
+    fn parse_operation(&mut self, min_level: u8) -> Converted<Node> {
+        let start = self.pos;
+        let mut left = self.parse_prefix()?;
+
+        loop {
+            self.skip_trivia();
+            let operator_start = self.pos;
+            let Some((symbol, level)) = NIX_OPERATORS
+                .iter()
+                .find(|(symbol, _)| self.rest().starts_with(symbol))
+                .copied()
+            else {
+                break;
+            };
+            if level < min_level {
+                break;
+            }
+            self.pos = self.pos.saturating_add(symbol.len());
+
+            let operator = match symbol {
+                "||" => BinaryOperator::Or,
+                "&&" => BinaryOperator::And,
+                "==" => BinaryOperator::Eq,
+                "!=" => BinaryOperator::NotEq,
+                "<=" => BinaryOperator::LtEq,
+                ">=" => BinaryOperator::GtEq,
+                "<" => BinaryOperator::Lt,
+                ">" => BinaryOperator::Gt,
+                "//" | "++" | "+" => BinaryOperator::Plus,
+                "-" => BinaryOperator::Minus,
+                "*" => BinaryOperator::Multiply,
+                "/" => BinaryOperator::Divide,
+                _ => {
+                    return unsupported(
+                        format!("The `{symbol}` operator"),
+                        self.span_from(operator_start),
+                    );
+                }
+            };
+
+            // `//` and `++` are right associative
+            let right_level = if matches!(symbol, "//" | "++") {
+                level
+            } else {
+                level.saturating_add(1)
+            };
+            let right = self.nest(|parser| parser.parse_operation(right_level))?;
+
+            // tl always divides as floats, so integers would keep the remainder that Nix drops
+            if operator == BinaryOperator::Divide && !is_float(&left) && !is_float(&right) {
+                return unsupported(
+                    "Dividing values that can be integers",
+                    self.span_from(start),
+                );
+            }
+
+            left = Node {
+                kind: NodeKind::BinaryOp(operator, Box::new(left), Box::new(right)),
+                span: self.span_from(start),
+            };
+        }
+
+        Ok(left)
+    }
+
+    fn parse_prefix(&mut self) -> Converted<Node> {
+        self.skip_trivia();
+        let start = self.pos;
+
+        let kind = if self.eat("!") {
+            NodeKind::Not(Box::new(
+                self.nest(|parser| parser.parse_operation(NIX_NOT.saturating_add(1)))?,
+            ))
+        } else if self.eat("-") {
+            NodeKind::Negate(Box::new(self.parse_application()?))
+        } else {
+            return self.parse_application();
+        };
+
+        Ok(Node {
+            kind,
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_application(&mut self) -> Converted<Node> {
+        let start = self.pos;
+        let function = self.parse_select()?;
+
+        let mut args = Vec::new();
+        while self.starts_argument() {
+            args.push(self.parse_select()?);
+        }
+
+        if args.is_empty() {
+            return Ok(function);
+        }
+        Ok(Node {
+            kind: NodeKind::Apply(Box::new(function), args),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Whether the next token starts another argument of a function application.
+    fn starts_argument(&mut self) -> bool {
+        if let Some(name) = self.peek_identifier() {
+            return !NIX_KEYWORDS.contains(&name) || name == "rec";
+        }
+
+        let rest = self.rest();
+        let mut chars = rest.chars();
+        match chars.next() {
+            Some('0'..='9' | '"' | '[' | '{' | '(' | '~') => true,
+            Some('.') => rest.starts_with("./") || rest.starts_with("../"),
+            Some('/') => chars.next().is_some_and(|ch| ch.is_ascii_alphanumeric()),
+            Some('\'') => rest.starts_with("''"),
+            Some('<') => chars.next().is_some_and(|ch| ch.is_ascii_alphabetic()),
+            _ => false,
+        }
+    }
+
+    fn parse_select(&mut self) -> Converted<Node> {
+        self.skip_trivia();
+        let start = self.pos;
+        let base = self.parse_atom()?;
+
+        let mut path = Vec::new();
+        loop {
+            let rest = self.rest();
+            if !rest.starts_with('.') || rest.starts_with("..") {
+                break;
+            }
+            self.pos = self.pos.saturating_add(1);
+            path.push(self.parse_attr_name()?);
+        }
+
+        if path.is_empty() {
+            return Ok(base);
+        }
+
+        let fallback = if self.eat_keyword("or") {
+            Some(Box::new(self.nest(Self::parse_select)?))
+        } else {
+            None
+        };
+
+        Ok(Node {
+            kind: NodeKind::Select(Box::new(base), path, fallback),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_attr_name(&mut self) -> Converted<String> {
+        self.skip_trivia();
+        let start = self.pos;
+
+        if self.rest().starts_with("${") {
+            return unsupported("A dynamic attribute name", SourceSpan::new(start.into(), 2));
+        }
+        if self.eat("\"") {
+            let parts = self.parse_string_parts()?;
+            return match parts.as_slice() {
+                [] => Ok(String::new()),
+                [StringPart::Text(text)] => Ok(text.clone()),
+                _ => unsupported("A dynamic attribute name", self.span_from(start)),
+            };
+        }
+
+        self.parse_name()
+    }
+
+    fn parse_atom(&mut self) -> Converted<Node> {
+        self.skip_trivia();
+        let start = self.pos;
+        let rest = self.rest();
+        let node = |kind, parser: &Self| Node {
+            kind,
+            span: parser.span_from(start),
+        };
+
+        if rest.starts_with(|ch: char| ch.is_ascii_digit()) {
+            return self.parse_number();
+        }
+        if rest.starts_with("''") {
+            self.pos = self.pos.saturating_add(2);
+            let parts = self.parse_indented_string_parts()?;
+            return Ok(node(NodeKind::String(parts), self));
+        }
+        if self.eat("\"") {
+            let parts = self.parse_string_parts()?;
+            return Ok(node(NodeKind::String(parts), self));
+        }
+        if rest.starts_with("./")
+            || rest.starts_with("../")
+            || rest.starts_with('/')
+                && rest
+                    .chars()
+                    .nth(1)
+                    .is_some_and(|ch| ch.is_ascii_alphanumeric())
+        {
+            let mut parts = Vec::new();
+            let mut text = String::new();
+            loop {
+                let rest = self.rest();
+                let len = rest
+                    .find(|ch: char| !(ch.is_ascii_alphanumeric() || "/._-+".contains(ch)))
+                    .unwrap_or(rest.len());
+                text.push_str(rest.get(..len).unwrap_or_default());
+                self.pos = self.pos.saturating_add(len);
+
+                if !self.rest().starts_with("${") {
+                    break;
+                }
+                self.pos = self.pos.saturating_add(2);
+                self.push_interpolation(&mut parts, &mut text)?;
+            }
+            if !text.is_empty() {
+                parts.push(StringPart::Text(text));
+            }
+
+            return Ok(node(NodeKind::Path(parts), self));
+        }
+        if rest.starts_with('~') || rest.starts_with('<') {
+            return unsupported("A home or search path", SourceSpan::new(start.into(), 1));
+        }
+        if self.eat("(") {
+            let expr = self.parse_expr()?;
+            self.expect(")")?;
+            return Ok(expr);
+        }
+        if self.eat("[") {
+            let mut items = Vec::new();
+            while !self.eat("]") {
+                if self.pos >= self.text.len() {
+                    return Err(self.syntax_error("Expected `]`"));
+                }
+                items.push(self.nest(Self::parse_select)?);
+            }
+            return Ok(node(NodeKind::List(items), self));
+        }
+        if rest.starts_with('{') {
+            if self.is_pattern() {
+                return unsupported("An attribute set pattern", SourceSpan::new(start.into(), 1));
+            }
+            self.pos = self.pos.saturating_add(1);
+
+            let mut fields = Vec::new();
+            while !self.eat("}") {
+                if self.pos >= self.text.len() {
+                    return Err(self.syntax_error("Expected `}`"));
+                }
+                fields.extend(self.parse_binding()?);
+            }
+            return Ok(node(NodeKind::Attrs(fields), self));
+        }
+
+        let name = self.parse_name()?;
+        let kind = match name.as_str() {
+            "null" => NodeKind::Null,
+            "true" => NodeKind::Bool(true),
+            "false" => NodeKind::Bool(false),
+            "rec" => return unsupported("A recursive attribute set", self.span_from(start)),
+            name if NIX_KEYWORDS.contains(&name) => {
+                self.pos = start;
+                return Err(self.syntax_error(&format!("Unexpected `{name}`")));
+            }
+            _ => NodeKind::Identifier(name),
+        };
+        Ok(node(kind, self))
+    }
+
+    fn parse_number(&mut self) -> Converted<Node> {
+        let start = self.pos;
+        let rest = self.rest();
+        let len = rest
+            .char_indices()
+            .find(|(index, ch)| {
+                !(ch.is_ascii_digit()
+                    || matches!(ch, '.' | 'e' | 'E')
+                    || matches!(ch, '+' | '-')
+                        && rest
+                            .get(..*index)
+                            .is_some_and(|before| before.ends_with(['e', 'E'])))
+            })
+            .map_or(rest.len(), |(index, _)| index);
+        let number = rest.get(..len).unwrap_or_default();
+        self.pos = self.pos.saturating_add(len);
+
+        let kind = if number.contains(['.', 'e', 'E']) {
+            number.parse().ok().map(NodeKind::Float)
+        } else {
+            number.parse().ok().map(NodeKind::Int)
+        };
+        match kind {
+            Some(kind) => Ok(Node {
+                kind,
+                span: self.span_from(start),
+            }),
+            None => unsupported(format!("The number `{number}`"), self.span_from(start)),
+        }
+    }
+
+    /// Parses an attribute like `a.b = value;` or `inherit (set) a b;`, which defines a field for each name.
+    fn parse_binding(&mut self) -> Converted<Vec<(Vec<String>, Node)>> {
+        self.skip_trivia();
+        let start = self.pos;
+
+        if self.eat_keyword("inherit") {
+            let source = if self.eat("(") {
+                let source = self.parse_expr()?;
+                self.expect(")")?;
+                Some(source)
+            } else {
+                None
+            };
+
+            let mut fields = Vec::new();
+            while !self.eat(";") {
+                let name = self.parse_name()?;
+                let span = self.span_from(start);
+                let kind = match &source {
+                    Some(source) => {
+                        NodeKind::Select(Box::new(source.clone()), vec![name.clone()], None)
+                    }
+                    None => NodeKind::Identifier(name.clone()),
+                };
+                fields.push((vec![name], Node { kind, span }));
+            }
+            return Ok(fields);
+        }
+
+        let mut path = vec![self.parse_attr_name()?];
+        while self.eat(".") {
+            path.push(self.parse_attr_name()?);
+        }
+        self.expect("=")?;
+        let value = self.parse_expr()?;
+        self.expect(";")?;
+
+        Ok(vec![(path, value)])
+    }
+
+    /// Parses the rest of a string after its `"`.
+    fn parse_string_parts(&mut self) -> Converted<Vec<StringPart>> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+
+        loop {
+            match self.bump() {
+                None => return Err(self.syntax_error("Unterminated string")),
+                Some('"') => break,
+                Some('\\') => match self.bump() {
+                    Some('n') => text.push('\n'),
+                    Some('r') => text.push('\r'),
+                    Some('t') => text.push('\t'),
+                    Some(ch) => text.push(ch),
+                    None => return Err(self.syntax_error("Unterminated string")),
+                },
+                // `$${` is a literal `$${`
+                Some('$') if self.rest().starts_with('$') => {
+                    self.bump();
+                    text.push_str("$$");
+                }
+                Some('$') if self.rest().starts_with('{') => {
+                    self.bump();
+                    self.push_interpolation(&mut parts, &mut text)?;
+                }
+                Some(ch) => text.push(ch),
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(StringPart::Text(text));
+        }
+        Ok(parts)
+    }
+
+    /// Parses the rest of an indented string after its `''`, and removes the indentation.
+    fn parse_indented_string_parts(&mut self) -> Converted<Vec<StringPart>> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("'''") {
+                self.pos = self.pos.saturating_add(3);
+                text.push_str("''");
+            } else if rest.starts_with("''$") {
+                self.pos = self.pos.saturating_add(3);
+                text.push('$');
+            } else if rest.starts_with("''\\") {
+                self.pos = self.pos.saturating_add(3);
+                match self.bump() {
+                    Some('n') => text.push('\n'),
+                    Some('r') => text.push('\r'),
+                    Some('t') => text.push('\t'),
+                    Some(ch) => text.push(ch),
+                    None => return Err(self.syntax_error("Unterminated string")),
+                }
+            } else if rest.starts_with("''") {
+                self.pos = self.pos.saturating_add(2);
+                break;
+            } else if rest.starts_with("${") {
+                self.pos = self.pos.saturating_add(2);
+                self.push_interpolation(&mut parts, &mut text)?;
+            } else {
+                match self.bump() {
+                    Some(ch) => text.push(ch),
+                    None => return Err(self.syntax_error("Unterminated string")),
+                }
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(StringPart::Text(text));
+        }
+        Ok(strip_indentation(parts))
+    }
+
+    /// Parses an interpolation after its `${`, the text before it becomes its own part.
+    fn push_interpolation(
+        &mut self,
+        parts: &mut Vec<StringPart>,
+        text: &mut String,
+    ) -> Converted<()> {
+        if !text.is_empty() {
+            parts.push(StringPart::Text(std::mem::take(text)));
+        }
+        parts.push(StringPart::Interpolation(self.parse_expr()?));
+        self.expect("}")
+    }
+}
+
+/// Whether a Nix expression is a float whatever its variables are, like `x * 1.0`.
+fn is_float(node: &Node) -> bool {
+    match &node.kind {
+        NodeKind::Float(_) => true,
+        NodeKind::Negate(operand) => is_float(operand),
+        NodeKind::BinaryOp(
+            BinaryOperator::Minus | BinaryOperator::Multiply | BinaryOperator::Divide,
+            left,
+            right,
+        ) => is_float(left) || is_float(right),
+        _ => false,
+    }
+}
+
+/// Removes the indentation of an indented string like Nix does: a first line of only spaces, the smallest
+/// indentation of the lines with content and the spaces before the closing `''`.
+fn strip_indentation(mut parts: Vec<StringPart>) -> Vec<StringPart> {
+    if let Some(StringPart::Text(text)) = parts.first_mut()
+        && let Some((first_line, rest)) = text.split_once('\n')
+        && first_line.chars().all(|ch| ch == ' ')
+    {
+        *text = rest.to_string();
+    }
+
+    let mut indentation = usize::MAX;
+    let mut at_line_start = true;
+    let mut spaces = 0usize;
+    for part in &parts {
+        match part {
+            StringPart::Text(text) => {
+                for ch in text.chars() {
+                    if ch == '\n' {
+                        at_line_start = true;
+                        spaces = 0;
+                    } else if at_line_start && ch == ' ' {
+                        spaces = spaces.saturating_add(1);
+                    } else if at_line_start {
+                        indentation = indentation.min(spaces);
+                        at_line_start = false;
+                    }
+                }
+            }
+            StringPart::Interpolation(_) => {
+                if at_line_start {
+                    indentation = indentation.min(spaces);
+                    at_line_start = false;
+                }
+            }
+        }
+    }
+
+    let mut at_line_start = true;
+    let mut remaining = indentation;
+    for part in &mut parts {
+        match part {
+            StringPart::Text(text) => {
+                let mut stripped = String::new();
+                for ch in text.chars() {
+                    if at_line_start && ch == ' ' && remaining > 0 {
+                        remaining = remaining.saturating_sub(1);
+                        continue;
+                    }
+                    at_line_start = ch == '\n';
+                    if at_line_start {
+                        remaining = indentation;
+                    }
+                    stripped.push(ch);
+                }
+                *text = stripped;
+            }
+            StringPart::Interpolation(_) => at_line_start = false,
+        }
+    }
+
+    if let Some(StringPart::Text(text)) = parts.last_mut()
+        && let Some(line_start) = text.rfind('\n').map(|index| index.saturating_add(1))
+        && text
+            .get(line_start..)
+            .is_some_and(|last_line| last_line.chars().all(|ch| ch == ' '))
+    {
+        text.truncate(line_start);
+    }
+
+    parts.retain(|part| !matches!(part, StringPart::Text(text) if text.is_empty()));
+    parts
+}
+
+/// Where an expression of tl source can be used without the parentheses tl doesn't have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Form {
+    /// Literals, identifiers, field accesses and calls can be used anywhere.
+    Primary,
+    /// A binary operation with its precedence, it can only be the operand of an operator that binds looser.
+    Operation(u8),
+    /// Objects, arrays, functions, `let` and `!` can't be operands of a binary operation.
+    Statement,
+}
+
+/// The tl source of `node` and where it can be used.
+fn tl_expr(node: &Node, depth: usize) -> Converted<(String, Form)> {
+    let indent = TL_INDENT.repeat(depth);
+    let inner = TL_INDENT.repeat(depth.saturating_add(1));
+
+    Ok(match &node.kind {
+        NodeKind::Null => ("null".to_string(), Form::Primary),
+        NodeKind::Bool(value) => (value.to_string(), Form::Primary),
+        NodeKind::Int(value) => (value.to_string(), Form::Primary),
+        NodeKind::Float(value) => (tl_float(*value), Form::Primary),
+        NodeKind::String(parts) => {
+            let mut text = String::from("\"");
+            for part in parts {
+                match part {
+                    StringPart::Text(part) => text.push_str(&escape(part)),
+                    StringPart::Interpolation(part) => {
+                        let _ = write!(text, "${{ {} }}", tl_expr(part, depth)?.0);
+                    }
+                }
+            }
+            text.push('"');
+            (text, Form::Primary)
+        }
+        NodeKind::Path(parts) => {
+            let mut text = String::new();
+            for part in parts {
+                match part {
+                    StringPart::Text(part) => text.push_str(part),
+                    StringPart::Interpolation(part) => {
+                        let _ = write!(text, "${{{}}}", tl_expr(part, depth)?.0);
+                    }
+                }
+            }
+            (text, Form::Primary)
+        }
+        NodeKind::Identifier(name) => (tl_name(name, node.span)?, Form::Primary),
+        NodeKind::List(items) if items.is_empty() => ("[]".to_string(), Form::Statement),
+        NodeKind::List(items) => {
+            let items = items
+                .iter()
+                .map(|item| tl_expr(item, depth.saturating_add(1)))
+                .collect::<Converted<Vec<_>>>()?;
+            let nested = items
+                .iter()
+                .any(|(text, form)| *form == Form::Statement || text.contains('\n'));
+
+            let text = if nested {
+                let mut text = String::from("[\n");
+                for (item, _) in &items {
+                    let _ = writeln!(text, "{inner}{item}");
+                }
+                let _ = write!(text, "{indent}]");
+                text
+            } else {
+                let items: Vec<_> = items.into_iter().map(|(text, _)| text).collect();
+                format!("[ {} ]", items.join(" "))
+            };
+            (text, Form::Statement)
+        }
+        NodeKind::Attrs(fields) if fields.is_empty() => ("{}".to_string(), Form::Statement),
+        NodeKind::Attrs(fields) => {
+            let mut text = String::from("{\n");
+            for (path, value) in fields {
+                let key = match path.as_slice() {
+                    [key] => key_source(key),
+                    _ if path.iter().all(|key| key_source(key) == *key) => path.join("."),
+                    _ => return unsupported("A nested key with a quoted name", value.span),
+                };
+                let value = tl_expr(value, depth.saturating_add(1))?.0;
+                let _ = writeln!(text, "{inner}{key} = {value}");
+            }
+            let _ = write!(text, "{indent}}}");
+            (text, Form::Statement)
+        }
+        NodeKind::Let(bindings, body) => {
+            let mut text = String::from("let\n");
+            for (name, value) in bindings {
+                let name = tl_name(name, value.span)?;
+                let value = tl_expr(value, depth.saturating_add(1))?.0;
+                let _ = writeln!(text, "{inner}{name} = {value}");
+            }
+            let body = tl_expr(body, depth.saturating_add(1))?.0;
+            let _ = write!(text, "{indent}in\n{inner}{body}");
+            (text, Form::Statement)
+        }
+        NodeKind::Lambda(params, body) => {
+            let params = params
+                .iter()
+                .map(|param| tl_name(param, node.span))
+                .collect::<Converted<Vec<_>>>()?
+                .join(", ");
+            let body = tl_expr(body, depth.saturating_add(1))?.0;
+
+            let text = if body.contains('\n') {
+                format!("({params}) {{\n{inner}{body}\n{indent}}}")
+            } else {
+                format!("({params}) {{ {body} }}")
+            };
+            (text, Form::Statement)
+        }
+        NodeKind::Apply(function, args) => {
+            // Curried calls become a single call, like curried functions
+            let (function, args) = match &function.kind {
+                NodeKind::Apply(inner_function, inner_args) => (
+                    &**inner_function,
+                    inner_args.iter().chain(args).collect::<Vec<_>>(),
+                ),
+                _ => (&**function, args.iter().collect()),
+            };
+            if !matches!(
+                function.kind,
+                NodeKind::Identifier(_) | NodeKind::Select(_, _, None)
+            ) {
+                return unsupported("Calling this expression", function.span);
+            }
+
+            let function = tl_expr(function, depth)?.0;
+            let args = args
+                .into_iter()
+                .map(|arg| Ok(tl_expr(arg, depth)?.0))
+                .collect::<Converted<Vec<_>>>()?;
+            (format!("{function}({})", args.join(", ")), Form::Primary)
+        }
+        NodeKind::Select(base, path, fallback) => {
+            if !matches!(
+                base.kind,
+                NodeKind::Identifier(_) | NodeKind::Select(_, _, None) | NodeKind::Apply(..)
+            ) {
+                return unsupported("Accessing a field of this expression", base.span);
+            }
+
+            let mut text = tl_expr(base, depth)?.0;
+            for field in path {
+                text.push('.');
+                text.push_str(&tl_name(field, node.span)?);
+            }
+
+            if let Some(fallback) = fallback {
+                let (fallback_text, form) = tl_expr(fallback, depth)?;
+                if form != Form::Primary
+                    && !matches!(fallback.kind, NodeKind::List(_) | NodeKind::Attrs(_))
+                {
+                    return unsupported("This fallback", fallback.span);
+                }
+                let _ = write!(text, " or {fallback_text}");
+            }
+            (text, Form::Primary)
+        }
+        NodeKind::BinaryOp(operator, left, right) => {
+            let precedence = operator.precedence();
+            let left = tl_operand(left, depth, |operand| operand >= precedence)?;
+            let right = tl_operand(right, depth, |operand| operand > precedence)?;
+            (
+                format!("{left} {operator} {right}"),
+                Form::Operation(precedence),
+            )
+        }
+        NodeKind::Not(operand) => (format!("!{}", tl_expr(operand, depth)?.0), Form::Statement),
+        NodeKind::Negate(operand) => match &operand.kind {
+            NodeKind::Int(value) => (format!("-{value}"), Form::Primary),
+            NodeKind::Float(value) => (format!("-{}", tl_float(*value)), Form::Primary),
+            _ => {
+                let precedence = BinaryOperator::Minus.precedence();
+                let operand = tl_operand(operand, depth, |operand| operand > precedence)?;
+                (format!("0 - {operand}"), Form::Operation(precedence))
+            }
+        },
+        NodeKind::If(condition, then, otherwise) => (
+            format!(
+                "if({}, {}, {})",
+                tl_expr(condition, depth)?.0,
+                tl_expr(then, depth)?.0,
+                tl_expr(otherwise, depth)?.0
+            ),
+            Form::Primary,
+        ),
+    })
+}
+
+/// The tl source of the operand of a binary operation, if its precedence allows it to be one without parentheses.
+fn tl_operand(node: &Node, depth: usize, allowed: impl Fn(u8) -> bool) -> Converted<String> {
+    let (text, form) = tl_expr(node, depth)?;
+    match form {
+        Form::Primary => Ok(text),
+        Form::Operation(precedence) if allowed(precedence) => Ok(text),
+        _ => unsupported("An operand that needs parentheses", node.span),
+    }
+}
+
+fn tl_name(name: &str, span: SourceSpan) -> Converted<String> {
+    if key_source(name) == name {
+        Ok(name.to_string())
+    } else {
+        unsupported(format!("The name `{name}`"), span)
+    }
+}
+
+fn tl_float(value: f64) -> String {
+    let mut text = value.to_string();
+    if !text.contains('.') {
+        text.push_str(".0");
+    }
+    text
+}
@@ -0,0 +1,119 @@
+use crate::{
+    parser::ast::DEFAULT_MAX_DEPTH,
+    runtime::{
+        DEFAULT_MAX_CALL_DEPTH,
+        inspect::Inspector,
+        types::{NativeFnCtx, ValueResult},
+    },
+};
+use std::{
+    fmt::{self, Debug},
+    ops::ControlFlow,
+    path::PathBuf,
+    rc::Rc,
+};
+
+/// Called with the name of a builtin before it runs, returning [`ControlFlow::Break`] replaces the call with the result.
+/// The arguments are not evaluated yet, since builtins like `if` only evaluate some of them,
+/// use [`NativeFnCtx::get_arg_evaluated`] to inspect them.
+#[derive(Clone)]
+pub struct BuiltinHook(pub BuiltinHookFn);
+
+pub type BuiltinHookFn = Rc<dyn Fn(&str, &NativeFnCtx) -> ControlFlow<ValueResult>>;
+
+impl Debug for BuiltinHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BuiltinHook")
+            .field(&"<native function>")
+            .finish()
+    }
+}
+
+/// Options that change how tl code is parsed and how a [`Scope`](crate::runtime::Scope) evaluates it.
+///
+/// Child scopes, builtins and imported files inherit the options of the scope that created them.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// How deep expressions can be nested before parsing fails, see [`Parser::with_max_depth`](crate::parser::ast::Parser::with_max_depth).
+    /// Every link of a chain like `a.b.c`, `1 + 2 + 3` or a dotted key counts as a level.
+    /// `${...}` interpolations are limited to the same depth, see [`Lexer::with_max_depth`](crate::parser::lexer::Lexer::with_max_depth).
+    pub max_depth: usize,
+    /// How deep tl functions can call each other before evaluation fails, this catches recursion that never ends.
+    pub max_call_depth: usize,
+    /// Report integer overflow as an error instead of saturating the result.
+    /// This has no effect with the `bigint` feature, where integers are promoted instead.
+    pub checked_arithmetic: bool,
+    /// Report binary operations on operands that don't support them as an error instead of evaluating them to `null`.
+    pub strict_types: bool,
+    /// Evaluate division by zero to `null` instead of returning an error.
+    pub lenient_division: bool,
+    /// Require the conditions of `if` and comprehensions to be booleans instead of using truthiness.
+    pub strict_conditions: bool,
+    /// Report accessing a field that doesn't exist as an error instead of evaluating it to `null`.
+    /// Accesses with an `or` fallback still fall back.
+    pub strict_fields: bool,
+    /// Let `interpolateEnv` read the environment variables of the process.
+    /// Off by default, so evaluating untrusted code can not read secrets from the environment.
+    pub allow_env: bool,
+    /// Refuse builtins whose result depends on the environment or that change it, like `pathExists`,
+    /// `interpolateEnv` and `writeFile`. The value then only depends on the source, the imported files and `args`.
+    pub deterministic: bool,
+    /// The arguments passed to the script, available as `args` unless a binding shadows it.
+    pub args: Vec<String>,
+    /// Paths have to stay inside of this directory once `.` and `..` are resolved, relative paths are resolved
+    /// against the current working directory. Any path is allowed if `None`.
+    pub path_root: Option<PathBuf>,
+    /// The file that is imported when `import` is given a directory, `default.tl` if `None`.
+    pub directory_import_file: Option<PathBuf>,
+    /// The largest file in bytes that `import`, `renderTemplate` and `readFileBytes` read, any size if `None`.
+    pub max_file_size: Option<u64>,
+    /// Gets notified about every evaluated expression, used for debugging.
+    pub inspector: Option<Rc<dyn Inspector>>,
+    /// Gets called before every builtin, to audit, deny or replace it.
+    pub on_builtin_call: Option<BuiltinHook>,
+
+    /// Directories that `writeFile` and `mkdirAll` are allowed to write into.
+    /// An empty list denies every write.
+    #[cfg(feature = "fs-write")]
+    pub write_allow_list: Vec<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            checked_arithmetic: false,
+            strict_types: false,
+            lenient_division: false,
+            strict_conditions: false,
+            strict_fields: false,
+            allow_env: false,
+            deterministic: false,
+            args: Vec::new(),
+            path_root: None,
+            directory_import_file: None,
+            max_file_size: None,
+            inspector: None,
+            on_builtin_call: None,
+            #[cfg(feature = "fs-write")]
+            write_allow_list: Vec::new(),
+        }
+    }
+}
+
+impl Options {
+    /// The default options with every check that catches likely mistakes enabled,
+    /// [`LintConfig::strict`](crate::lint::LintConfig::strict) does the same for lints.
+    #[must_use]
+    pub fn strict() -> Self {
+        Self {
+            checked_arithmetic: true,
+            strict_types: true,
+            lenient_division: false,
+            strict_conditions: true,
+            strict_fields: true,
+            ..Self::default()
+        }
+    }
+}
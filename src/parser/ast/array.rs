@@ -6,10 +6,10 @@ use crate::{
     merge_spans,
     parser::{
         ast::{
-            consume,
+            advance, consume,
             types::{Error, ErrorKind},
         },
-        lexer::types::TokenKind,
+        lexer::types::{Token, TokenKind},
     },
 };
 
@@ -38,6 +38,16 @@ impl super::Parser {
             }
 
             let expr = self.parse()?;
+
+            if array.is_empty()
+                && self
+                    .tokens
+                    .get(self.pos)
+                    .is_some_and(|token| token.kind == TokenKind::For)
+            {
+                return self.parse_comprehension(&start, expr);
+            }
+
             array.push(expr);
         }
 
@@ -51,4 +61,55 @@ impl super::Parser {
             merge_spans(start.span, end.span),
         ))
     }
+
+    /// Parses the rest of a list comprehension after its first expression,
+    /// `if` is only a keyword after the iterable since it is a function everywhere else.
+    fn parse_comprehension(&mut self, start: &Token, expr: Expr) -> ExprResult {
+        consume!(self, For);
+
+        let binding_token = advance!(self).ok_or(Error::new(
+            ErrorKind::NoTokensLeft,
+            self.source.clone(),
+            self.closest_span(),
+        ))?;
+
+        let TokenKind::Identifier(binding) = &binding_token.kind else {
+            return Err(Error::new(
+                ErrorKind::ExpectedToken {
+                    expected: "identifier".into(),
+                    found: Some(binding_token.kind.clone()),
+                },
+                self.source.clone(),
+                binding_token.span,
+            ));
+        };
+        let binding = binding.clone();
+
+        consume!(self, In);
+
+        let iterable = self.parse()?;
+
+        let condition = if self
+            .tokens
+            .get(self.pos)
+            .is_some_and(|token| token.kind == TokenKind::Identifier("if".into()))
+        {
+            self.pos = self.pos.saturating_add(1);
+            Some(Box::new(self.parse()?))
+        } else {
+            None
+        };
+
+        let end = consume!(self, RBracket);
+
+        Ok(Expr::new(
+            ExprKind::Comprehension {
+                expr: Box::new(expr),
+                binding,
+                iterable: Box::new(iterable),
+                condition,
+            },
+            merge_spans(start.span, end.span),
+        ))
+    }
 }
@@ -1,6 +1,6 @@
 use super::{
     ExprResult,
-    types::{BinaryOperator, Expr, ExprKind},
+    types::{BinaryOperator, Expr, ExprKind, Literal},
 };
 use crate::{
     merge_spans,
@@ -8,35 +8,19 @@ use crate::{
 };
 
 impl super::Parser {
+    /// Parses binary operations following `left` whose operators bind at least as tight as `min_precedence`.
     pub(super) fn parse_binary_op_with_left(
         &mut self,
         min_precedence: u8,
         mut left: Expr,
     ) -> ExprResult {
-        if self
-            .tokens
-            .get(self.pos)
-            .is_none_or(|token| !token.kind.is_binary_operator())
-        {
-            return Ok(left);
-        }
-
-        while let Some(next_token) = self.tokens.get(self.pos) {
-            if !next_token.kind.is_binary_operator() {
+        let mut links = 0_usize;
+        while let Some(operator_token) = self.tokens.get(self.pos) {
+            if !operator_token.kind.is_binary_operator() {
                 break;
             }
 
-            let operator_token = self
-                .tokens
-                .get(self.pos)
-                .ok_or(Error::new(
-                    ErrorKind::NoTokensLeft,
-                    self.source.clone(),
-                    self.closest_span(),
-                ))?
-                .clone();
-
-            self.pos = self.pos.saturating_add(1);
+            let operator_token = operator_token.clone();
             let operator = BinaryOperator::from_token(operator_token.kind.clone())?;
             let precedence = operator.precedence();
 
@@ -44,6 +28,10 @@ impl super::Parser {
                 break;
             }
 
+            links = links.saturating_add(1);
+            self.check_depth(links, operator_token.span)?;
+            self.pos = self.pos.saturating_add(1);
+
             if self.tokens.get(self.pos).is_none() {
                 return Err(Error::new(
                     ErrorKind::MissingRightSide,
@@ -52,9 +40,39 @@ impl super::Parser {
                 ));
             }
 
-            let right = self.parse_binary_op(precedence.saturating_add(1))?;
+            let right_precedence = if operator.is_right_associative() {
+                precedence
+            } else {
+                precedence.saturating_add(1)
+            };
+
+            let right = self.nest(|parser| parser.parse_binary_op(right_precedence))?;
             let span = merge_spans(left.span, right.span);
 
+            // `**` binds tighter than the minus of a negative literal, `-2 ** 2` is `-1 * 2 ** 2`
+            let negated = (operator == BinaryOperator::Power)
+                .then(|| negated_literal(&left))
+                .flatten();
+            if let Some((minus, base)) = negated {
+                let power = Expr::new(
+                    ExprKind::BinaryOp {
+                        left: Box::new(base),
+                        operator,
+                        right: Box::new(right),
+                    },
+                    span,
+                );
+                left = Expr::new(
+                    ExprKind::BinaryOp {
+                        left: Box::new(minus),
+                        operator: BinaryOperator::Multiply,
+                        right: Box::new(power),
+                    },
+                    span,
+                );
+                continue;
+            }
+
             left = Expr::new(
                 ExprKind::BinaryOp {
                     left: Box::new(left),
@@ -69,70 +87,39 @@ impl super::Parser {
     }
 
     fn parse_binary_op(&mut self, min_precedence: u8) -> ExprResult {
-        let start = self
-            .tokens
-            .get(self.pos)
-            .ok_or(Error::new(
-                ErrorKind::NoTokensLeft,
-                self.source.clone(),
-                self.closest_span(),
-            ))?
-            .clone();
-
-        let mut left = self.parse_literal()?;
-
-        if self
-            .tokens
-            .get(self.pos.saturating_add(1))
-            .is_none_or(|token| !token.kind.is_binary_operator())
-        {
-            return Ok(left);
-        }
-
-        while let Some(next_token) = self.tokens.get(self.pos) {
-            if !next_token.kind.is_binary_operator() {
-                break;
-            }
-
-            let operator_token = self
-                .tokens
-                .get(self.pos)
-                .ok_or(Error::new(
-                    ErrorKind::NoTokensLeft,
-                    self.source.clone(),
-                    self.closest_span(),
-                ))?
-                .clone();
+        let left = self.parse_primary()?;
 
-            self.pos = self.pos.saturating_add(1);
-            let operator = BinaryOperator::from_token(operator_token.kind.clone())?;
-            let precedence = operator.precedence();
-
-            if precedence < min_precedence {
-                break;
-            }
-
-            if self.tokens.get(self.pos).is_none() {
-                return Err(Error::new(
-                    ErrorKind::MissingRightSide,
-                    self.source.clone(),
-                    operator_token.span,
-                ));
-            }
-
-            let right = self.parse_binary_op(precedence.saturating_add(1))?;
-            let span = merge_spans(start.span, right.span);
+        self.parse_binary_op_with_left(min_precedence, left)
+    }
+}
 
-            left = Expr::new(
-                ExprKind::BinaryOp {
-                    left: Box::new(left),
-                    operator,
-                    right: Box::new(right),
-                },
-                span,
-            );
+/// Splits a negative number literal into `-1` for its minus and the positive literal after it.
+fn negated_literal(expr: &Expr) -> Option<(Expr, Expr)> {
+    let ExprKind::Literal(literal) = &expr.kind else {
+        return None;
+    };
+    let positive = match literal {
+        Literal::Int(value) if *value < 0 => match value.checked_neg() {
+            Some(value) => Literal::Int(value),
+            #[cfg(feature = "bigint")]
+            None => Literal::BigInt(num_bigint::BigInt::from(value.unsigned_abs())),
+            #[cfg(not(feature = "bigint"))]
+            None => return None,
+        },
+        #[cfg(feature = "bigint")]
+        Literal::BigInt(value) if value.sign() == num_bigint::Sign::Minus => {
+            Literal::BigInt(value.magnitude().clone().into())
         }
-
-        Ok(left)
-    }
+        Literal::Float(value) if value.is_sign_negative() => Literal::Float(-value),
+        _ => return None,
+    };
+
+    let (offset, len) = (expr.span.offset(), expr.span.len());
+    Some((
+        Expr::new(ExprKind::Literal(Literal::Int(-1)), (offset, 1).into()),
+        Expr::new(
+            ExprKind::Literal(positive),
+            (offset.saturating_add(1), len.saturating_sub(1)).into(),
+        ),
+    ))
 }
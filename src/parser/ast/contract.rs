@@ -0,0 +1,79 @@
+use crate::{
+    merge_spans,
+    parser::{
+        ast::{
+            advance, consume,
+            types::{Contract, Error, ErrorKind, Type},
+        },
+        lexer::types::TokenKind,
+    },
+};
+use miette::SourceSpan;
+
+impl super::Parser {
+    /// Parses the `:: type` after the name of a binding or object key, if there is one.
+    pub(super) fn parse_contract(&mut self) -> Result<Option<Contract>, Error> {
+        if self
+            .tokens
+            .get(self.pos)
+            .is_none_or(|token| token.kind != TokenKind::DoubleColon)
+        {
+            return Ok(None);
+        }
+        consume!(self, DoubleColon);
+
+        let (ty, span) = self.parse_type()?;
+        Ok(Some(Contract { ty, span }))
+    }
+
+    /// Parses a type name, `[type]` or `type?`.
+    fn parse_type(&mut self) -> Result<(Type, SourceSpan), Error> {
+        let token = advance!(self)
+            .ok_or(Error::new(
+                ErrorKind::NoTokensLeft,
+                self.source.clone(),
+                self.closest_span(),
+            ))?
+            .clone();
+
+        let (mut ty, mut span) = match &token.kind {
+            TokenKind::Null => (Type::Null, token.span),
+            TokenKind::Identifier(name) => (
+                Type::from_name(name).ok_or(Error::new(
+                    ErrorKind::UnknownType { name: name.clone() },
+                    self.source.clone(),
+                    token.span,
+                ))?,
+                token.span,
+            ),
+            TokenKind::LBracket => {
+                let (item, _) = self.parse_type()?;
+                let end = consume!(self, RBracket);
+                (
+                    Type::Array(Box::new(item)),
+                    merge_spans(token.span, end.span),
+                )
+            }
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::ExpectedToken {
+                        expected: "type".into(),
+                        found: Some(token.kind.clone()),
+                    },
+                    self.source.clone(),
+                    token.span,
+                ));
+            }
+        };
+
+        while let Some(question) = self.tokens.get(self.pos)
+            && question.kind == TokenKind::Question
+        {
+            span = merge_spans(span, question.span);
+            self.pos = self.pos.saturating_add(1);
+            ty = Type::Optional(Box::new(ty));
+        }
+
+        Ok((ty, span))
+    }
+}
@@ -18,6 +18,13 @@ impl super::Parser {
     /// # Errors
     /// This function will return an error if a AST generation error occurs.
     pub fn parse(&mut self) -> ExprResult {
+        self.nest(Self::parse_expr)
+    }
+
+    fn parse_expr(&mut self) -> ExprResult {
+        // Doc comments before an expression don't document anything
+        self.take_doc();
+
         let token = self.tokens.get(self.pos).ok_or(Error::new(
             ErrorKind::NoTokensLeft,
             self.source.clone(),
@@ -32,7 +39,7 @@ impl super::Parser {
                 if let Some(next_token) = self.tokens.get(self.pos.saturating_add(1))
                     && matches!(
                         next_token.kind,
-                        TokenKind::Identifier(_) | TokenKind::RParen
+                        TokenKind::Identifier(_) | TokenKind::RParen | TokenKind::Spread
                     )
                 {
                     return self.parse_fn_decl();
@@ -69,6 +76,13 @@ impl super::Parser {
     }
 
     pub(super) fn parse_literal(&mut self) -> ExprResult {
+        let expr = self.parse_primary()?;
+
+        self.parse_binary_op_with_left(0, expr)
+    }
+
+    /// Parses a single literal or identifier, without any binary operations following it.
+    pub(super) fn parse_primary(&mut self) -> ExprResult {
         let token = self
             .tokens
             .get(self.pos)
@@ -97,9 +111,11 @@ impl super::Parser {
             TokenKind::Path(v) => literal!(Path(v.clone())),
             TokenKind::InterpolatedPath(v) => self.parse_interpolated_path(v)?,
             TokenKind::Int(v) => literal!(Int(*v)),
+            #[cfg(feature = "bigint")]
+            TokenKind::BigInt(v) => literal!(BigInt(v.clone())),
             TokenKind::Float(v) => literal!(Float(*v)),
             TokenKind::Bool(v) => literal!(Bool(*v)),
-            TokenKind::Identifier(_) => self.parse_ident()?,
+            TokenKind::Identifier(_) => return self.parse_ident(),
             _ => {
                 return Err(Error::new(
                     ErrorKind::UnexpectedToken,
@@ -109,17 +125,15 @@ impl super::Parser {
             }
         };
 
-        let token = self.tokens.get(self.pos);
-
-        if let Some(token) = token {
-            match &token.kind {
-                b if b.is_binary_operator() => {
-                    return self.parse_binary_op_with_left(0, expr);
-                }
-                _ => (),
+        // Calling a literal is an error at runtime, a `(` that doesn't follow directly starts a new expression
+        match self.tokens.get(self.pos) {
+            Some(next)
+                if next.kind == TokenKind::LParen
+                    && next.span.offset() == expr.span.offset().saturating_add(expr.span.len()) =>
+            {
+                self.parse_call(expr)
             }
+            _ => Ok(expr),
         }
-
-        Ok(expr)
     }
 }
@@ -12,6 +12,7 @@ use crate::{
         lexer::types::TokenKind,
     },
 };
+use std::collections::BTreeMap;
 
 impl super::Parser {
     pub(super) fn parse_fn_decl(&mut self) -> ExprResult {
@@ -28,6 +29,8 @@ impl super::Parser {
         // Args
         consume!(self, LParen);
         let mut args = Vec::new();
+        let mut defaults = BTreeMap::new();
+        let mut rest = None;
 
         while let Some(next_token) = self.tokens.get(self.pos) {
             if next_token.kind == TokenKind::RParen {
@@ -41,6 +44,20 @@ impl super::Parser {
                         self.pos = self.pos.saturating_add(1);
                         continue;
                     }
+                    // The rest parameter has to be the last one
+                    TokenKind::Spread => {
+                        consume!(self, Spread);
+                        rest = Some(self.parse_binding_name()?);
+
+                        if self
+                            .tokens
+                            .get(self.pos)
+                            .is_some_and(|token| token.kind == TokenKind::Comma)
+                        {
+                            self.pos = self.pos.saturating_add(1);
+                        }
+                        break;
+                    }
                     _ => {
                         return Err(Error::new(
                             ErrorKind::ExpectedToken {
@@ -66,6 +83,15 @@ impl super::Parser {
 
             self.pos = self.pos.saturating_add(1);
 
+            if self
+                .tokens
+                .get(self.pos)
+                .is_some_and(|token| token.kind == TokenKind::Question)
+            {
+                consume!(self, Question);
+                defaults.insert(name.clone(), self.parse()?);
+            }
+
             args.push(name);
         }
 
@@ -85,6 +111,8 @@ impl super::Parser {
             return Ok(Expr::new(
                 ExprKind::FnDecl {
                     args,
+                    defaults,
+                    rest,
                     expr: Box::new(Expr::lit(Literal::Null, span)),
                 },
                 span,
@@ -101,6 +129,8 @@ impl super::Parser {
         Ok(Expr::new(
             ExprKind::FnDecl {
                 args,
+                defaults,
+                rest,
                 expr: Box::new(expr),
             },
             merge_spans(start.span, end.span),
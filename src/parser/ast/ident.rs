@@ -1,6 +1,6 @@
 use super::{
     ExprResult,
-    types::{Expr, ExprKind},
+    types::{Expr, ExprKind, NamedArg},
 };
 use crate::{
     merge_spans,
@@ -23,10 +23,17 @@ impl super::Parser {
 
         let mut expr = match &token.kind {
             TokenKind::Identifier(name) => Expr::ident(name.clone(), token.span),
-            _ => unreachable!(),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::UnexpectedToken,
+                    self.source.clone(),
+                    token.span,
+                ));
+            }
         };
         let mut full_span = token.span;
 
+        let mut links = 0_usize;
         loop {
             match self.tokens.get(self.pos).map(|t| &t.kind) {
                 // Object field access: .identifier
@@ -54,18 +61,42 @@ impl super::Parser {
                             ));
                         }
                     };
+                    full_span = merge_spans(full_span, field_token.span);
+
+                    // `or` is only a keyword after a field access, `base.field or fallback`
+                    let fallback = match self.tokens.get(self.pos) {
+                        Some(token) if token.kind == TokenKind::Identifier("or".into()) => {
+                            self.pos = self.pos.saturating_add(1);
+                            let fallback = self.parse_fallback()?;
+                            full_span = merge_spans(full_span, fallback.span);
+                            Some(Box::new(fallback))
+                        }
+                        _ => None,
+                    };
+                    let has_fallback = fallback.is_some();
+
                     expr = Expr::new(
                         ExprKind::ObjectAccess {
                             base: Box::new(expr),
                             field: field_name,
+                            fallback,
                         },
-                        merge_spans(full_span, field_token.span),
+                        full_span,
                     );
-                    full_span = merge_spans(full_span, field_token.span);
+
+                    // The fallback ends the expression, `a.b or c.d` accesses `d` of `c`
+                    if has_fallback {
+                        break;
+                    }
                 }
 
                 // Array index access: [expr]
-                Some(TokenKind::LBracket) => {
+                // The bracket has to follow directly, otherwise it starts a new array or pattern
+                Some(TokenKind::LBracket)
+                    if self.tokens.get(self.pos).is_some_and(|token| {
+                        token.span.offset() == full_span.offset().saturating_add(full_span.len())
+                    }) =>
+                {
                     self.pos = self.pos.saturating_add(1);
                     let index_expr = self.parse()?;
                     let end = consume!(self, RBracket);
@@ -92,35 +123,71 @@ impl super::Parser {
 
                 // Function call: (args...)
                 Some(TokenKind::LParen) => {
-                    self.pos = self.pos.saturating_add(1);
-                    let mut args = Vec::new();
-                    while let Some(token) = self.tokens.get(self.pos)
-                        && token.kind != TokenKind::RParen
-                    {
-                        if token.kind == TokenKind::Comma {
-                            self.pos = self.pos.saturating_add(1);
-                            continue;
-                        }
-
-                        args.push(self.parse()?);
-                    }
-                    let end = consume!(self, RParen);
-
-                    expr = Expr::new(
-                        ExprKind::Call {
-                            base: Box::new(expr),
-                            args,
-                        },
-                        merge_spans(full_span, end.span),
-                    );
-
-                    full_span = merge_spans(full_span, end.span);
+                    expr = self.parse_call(expr)?;
+                    full_span = expr.span;
                 }
 
                 _ => break,
             }
+
+            links = links.saturating_add(1);
+            self.check_depth(links, full_span)?;
         }
 
         Ok(expr)
     }
+
+    /// Parses the arguments of a call to `base`, starting at the `(`.
+    pub(super) fn parse_call(&mut self, base: Expr) -> ExprResult {
+        consume!(self, LParen);
+
+        let mut args = Vec::new();
+        let mut named_args = Vec::new();
+        while let Some(token) = self.tokens.get(self.pos).cloned()
+            && token.kind != TokenKind::RParen
+        {
+            if token.kind == TokenKind::Comma {
+                self.pos = self.pos.saturating_add(1);
+                continue;
+            }
+
+            // Named argument: name = value
+            if let TokenKind::Identifier(name) = token.kind
+                && self
+                    .tokens
+                    .get(self.pos.saturating_add(1))
+                    .is_some_and(|token| token.kind == TokenKind::Equals)
+            {
+                self.pos = self.pos.saturating_add(2);
+                named_args.push(NamedArg {
+                    name,
+                    span: token.span,
+                    value: self.parse()?,
+                });
+                continue;
+            }
+
+            args.push(self.parse()?);
+        }
+        let end = consume!(self, RParen);
+        let span = merge_spans(base.span, end.span);
+
+        Ok(Expr::new(
+            ExprKind::Call {
+                base: Box::new(base),
+                args,
+                named_args,
+            },
+            span,
+        ))
+    }
+
+    /// Parses the fallback of `base.field or fallback`, a single value without binary operations.
+    fn parse_fallback(&mut self) -> ExprResult {
+        match self.tokens.get(self.pos).map(|token| &token.kind) {
+            Some(TokenKind::LBrace) => self.parse_object(),
+            Some(TokenKind::LBracket) => self.parse_array(),
+            _ => self.parse_primary(),
+        }
+    }
 }
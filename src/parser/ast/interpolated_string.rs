@@ -10,14 +10,18 @@ use crate::parser::{
 impl super::Parser {
     pub(super) fn parse_interpolated_string(&mut self, v: &[Token]) -> ExprResult {
         let mut result = Vec::new();
-        let start = self.tokens.get(self.pos).ok_or(Error::new(
-            ErrorKind::ExpectedToken {
-                expected: "interpolated string".into(),
-                found: None,
-            },
-            self.source.clone(),
-            self.closest_span(),
-        ))?;
+        let start = self
+            .tokens
+            .get(self.pos)
+            .map(|token| token.span)
+            .ok_or(Error::new(
+                ErrorKind::ExpectedToken {
+                    expected: "interpolated string".into(),
+                    found: None,
+                },
+                self.source.clone(),
+                self.closest_span(),
+            ))?;
 
         for token in v {
             match &token.kind {
@@ -28,11 +32,11 @@ impl super::Parser {
                     ));
                 }
                 TokenKind::InterpolatedString(v) => {
-                    let ast = Self::new(v.clone(), self.source.clone()).parse()?;
+                    let ast = self.parse_nested(v.clone())?;
                     result.push(ast.clone());
                 }
                 _ => {
-                    let ast = Self::new(vec![token.clone()], self.source.clone()).parse()?;
+                    let ast = self.parse_nested(vec![token.clone()])?;
                     result.push(ast.clone());
                 }
             }
@@ -43,7 +47,7 @@ impl super::Parser {
 
         Ok(Expr::new(
             ExprKind::Literal(Literal::InterpolatedString(result)),
-            start.span,
+            start,
         ))
     }
 }
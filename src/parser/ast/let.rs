@@ -3,11 +3,12 @@ use crate::{
     parser::{
         ast::{
             ExprResult, advance, consume,
-            types::{Error, ErrorKind, Expr, ExprKind},
+            types::{Error, ErrorKind, Expr, ExprKind, Pattern},
         },
         lexer::types::TokenKind,
     },
 };
+use miette::SourceSpan;
 
 impl super::Parser {
     pub(super) fn parse_let(&mut self) -> ExprResult {
@@ -26,6 +27,7 @@ impl super::Parser {
         let mut bindings = Vec::new();
 
         loop {
+            let doc = self.take_doc();
             let token = self
                 .tokens
                 .get(self.pos)
@@ -40,29 +42,19 @@ impl super::Parser {
                 break;
             }
 
-            let name_token = advance!(self).ok_or(Error::new(
-                ErrorKind::NoTokensLeft,
-                self.source.clone(),
-                token.span,
-            ))?;
-
-            let name = if let TokenKind::Identifier(name) = &name_token.kind {
-                name.clone()
-            } else {
-                return Err(Error::new(
-                    ErrorKind::ExpectedToken {
-                        expected: "identifier".into(),
-                        found: None,
-                    },
-                    self.source.clone(),
-                    token.span,
-                ));
+            let pattern = match token.kind {
+                TokenKind::LBrace => self.parse_object_pattern()?,
+                TokenKind::LBracket => self.parse_array_pattern()?,
+                _ => Pattern::Identifier(self.parse_binding_name()?),
             };
 
+            let contract = self.parse_contract()?;
             consume!(self, Equals);
 
-            let value = self.parse()?;
-            bindings.push((name, value));
+            let mut value = self.parse()?;
+            value.doc = doc;
+            value.contract = contract.map(Box::new);
+            bindings.push((pattern, value));
         }
 
         consume!(self, In);
@@ -78,4 +70,115 @@ impl super::Parser {
             merge_spans(start.span, end_span),
         ))
     }
+
+    /// Parses `{ a, b }`, commas between the names are optional.
+    fn parse_object_pattern(&mut self) -> Result<Pattern, Error> {
+        consume!(self, LBrace);
+
+        let mut fields = Vec::new();
+        let mut spans = Vec::new();
+        while !self.skip_pattern_separator(&TokenKind::RBrace) {
+            fields.push(self.parse_pattern_name(&fields, &mut spans)?);
+        }
+
+        Ok(Pattern::Object(fields))
+    }
+
+    /// Parses `[ a, b, ...rest ]`, commas between the names are optional.
+    fn parse_array_pattern(&mut self) -> Result<Pattern, Error> {
+        consume!(self, LBracket);
+
+        let mut items = Vec::new();
+        let mut spans = Vec::new();
+        let mut rest = None;
+        while !self.skip_pattern_separator(&TokenKind::RBracket) {
+            if let Some(token) = self.tokens.get(self.pos)
+                && token.kind == TokenKind::Spread
+            {
+                consume!(self, Spread);
+                rest = Some(self.parse_pattern_name(&items, &mut spans)?);
+
+                // The rest binding has to be the last one
+                if !self.skip_pattern_separator(&TokenKind::RBracket) {
+                    consume!(self, RBracket);
+                }
+                break;
+            }
+
+            items.push(self.parse_pattern_name(&items, &mut spans)?);
+        }
+
+        Ok(Pattern::Array { items, rest })
+    }
+
+    /// Skips a comma, returns `true` and consumes `end` if the pattern ends here.
+    fn skip_pattern_separator(&mut self, end: &TokenKind) -> bool {
+        if let Some(token) = self.tokens.get(self.pos)
+            && token.kind == TokenKind::Comma
+        {
+            self.pos = self.pos.saturating_add(1);
+        }
+
+        if let Some(token) = self.tokens.get(self.pos)
+            && token.kind == *end
+        {
+            self.pos = self.pos.saturating_add(1);
+            return true;
+        }
+
+        // Running out of tokens is reported by the next `parse_binding_name`
+        false
+    }
+
+    /// Parses a name of a pattern that isn't one of the `names` before it, `spans` are their spans.
+    fn parse_pattern_name(
+        &mut self,
+        names: &[String],
+        spans: &mut Vec<SourceSpan>,
+    ) -> Result<String, Error> {
+        let span = self
+            .tokens
+            .get(self.pos)
+            .map_or_else(|| self.closest_span(), |token| token.span);
+        let name = self.parse_binding_name()?;
+
+        if let Some(first) = names
+            .iter()
+            .position(|other| *other == name)
+            .and_then(|index| spans.get(index))
+        {
+            return Err(Error::new(
+                ErrorKind::DuplicateBinding {
+                    name,
+                    first: *first,
+                },
+                self.source.clone(),
+                span,
+            ));
+        }
+
+        spans.push(span);
+        Ok(name)
+    }
+
+    pub(super) fn parse_binding_name(&mut self) -> Result<String, Error> {
+        let token = advance!(self).ok_or(Error::new(
+            ErrorKind::NoTokensLeft,
+            self.source.clone(),
+            self.closest_span(),
+        ))?;
+
+        if let TokenKind::Identifier(name) = &token.kind {
+            Ok(name.clone())
+        } else {
+            Err(Error::new(
+                ErrorKind::ExpectedToken {
+                    expected: "identifier".into(),
+                    found: Some(token.kind.clone()),
+                },
+                self.source.clone(),
+                token.span,
+            ))
+        }
+    }
 }
@@ -1,12 +1,14 @@
-use crate::parser::lexer::types::Token;
+use crate::parser::lexer::types::{Token, TokenKind};
 use miette::{NamedSource, SourceSpan};
-use types::{Error, Expr};
+use std::collections::HashMap;
+use types::{DuplicateKey, Error, ErrorKind, Expr};
 
 pub mod types;
 
 // AST Implementations
 mod array;
 mod binary_op;
+mod contract;
 mod expr;
 mod r#fn;
 mod ident;
@@ -17,6 +19,9 @@ mod object;
 
 mod pretty_print;
 
+/// How deep expressions can be nested by default before parsing fails, see [`Parser::with_max_depth`].
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
 #[derive(Debug)]
 pub struct Parser {
     // Input
@@ -26,6 +31,13 @@ pub struct Parser {
     // State
     pos: usize,
     context: Context,
+    depth: usize,
+    max_depth: usize,
+
+    // Output
+    /// The span of the key that every object value was assigned to, by the offset of the value.
+    key_spans: HashMap<usize, SourceSpan>,
+    duplicate_keys: Vec<DuplicateKey>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -40,14 +52,122 @@ pub type ExprResult = Result<Expr, Error>;
 impl Parser {
     pub fn new(tokens: Vec<Token>, source: NamedSource<String>) -> Self {
         Self {
-            tokens,
+            tokens: Self::filter_doc_comments(tokens),
             source,
 
             pos: 0,
             context: Context::TopLevel,
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+
+            key_spans: HashMap::new(),
+            duplicate_keys: Vec::new(),
         }
     }
 
+    /// Sets how deep expressions can be nested before [`ErrorKind::NestingTooDeep`](types::ErrorKind::NestingTooDeep) is returned,
+    /// this keeps deeply nested input from overflowing the stack.
+    #[must_use]
+    pub const fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Keys of object literals that were assigned more than once, in the order they were found.
+    /// Assigning an object to a key twice merges them, so only keys whose value is replaced are duplicates.
+    pub fn duplicate_keys(&self) -> &[DuplicateKey] {
+        &self.duplicate_keys
+    }
+
+    /// Parses the tokens of an interpolation, continuing at the current depth.
+    fn parse_nested(&mut self, tokens: Vec<Token>) -> ExprResult {
+        let mut parser = Self::new(tokens, self.source.clone()).with_max_depth(self.max_depth);
+        parser.depth = self.depth;
+
+        let expr = parser.parse();
+        self.duplicate_keys.append(&mut parser.duplicate_keys);
+        expr
+    }
+
+    /// Removes doc comments that don't document a binding or object key, so they act like normal comments.
+    fn filter_doc_comments(tokens: Vec<Token>) -> Vec<Token> {
+        let documents_key = |rest: &[Token]| {
+            let mut rest = rest
+                .iter()
+                .skip_while(|token| matches!(token.kind, TokenKind::DocComment(_)));
+
+            matches!(
+                (
+                    rest.next().map(|token| &token.kind),
+                    rest.next().map(|token| &token.kind)
+                ),
+                (
+                    Some(TokenKind::Identifier(_) | TokenKind::String(_)),
+                    Some(TokenKind::Equals | TokenKind::Dot | TokenKind::DoubleColon)
+                )
+            )
+        };
+
+        let mut filtered = Vec::with_capacity(tokens.len());
+        for (index, token) in tokens.iter().enumerate() {
+            if matches!(token.kind, TokenKind::DocComment(_))
+                && !tokens
+                    .get(index.saturating_add(1)..)
+                    .is_some_and(documents_key)
+            {
+                continue;
+            }
+
+            filtered.push(token.clone());
+        }
+
+        filtered
+    }
+
+    /// Consumes the doc comments at the current position, joining their lines.
+    fn take_doc(&mut self) -> Option<String> {
+        let mut lines = Vec::new();
+
+        while let Some(Token {
+            kind: TokenKind::DocComment(line),
+            ..
+        }) = self.tokens.get(self.pos)
+        {
+            lines.push(line.clone());
+            self.pos = self.pos.saturating_add(1);
+        }
+
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// Runs `parse` one nesting level deeper, failing instead if that exceeds the maximum depth.
+    fn nest(&mut self, parse: impl FnOnce(&mut Self) -> ExprResult) -> ExprResult {
+        self.check_depth(0, self.closest_span())?;
+
+        self.depth = self.depth.saturating_add(1);
+        let result = parse(self);
+        self.depth = self.depth.saturating_sub(1);
+
+        result
+    }
+
+    /// Fails if an expression `levels` levels below the current depth exceeds the maximum depth.
+    /// Chains like `a.b.c`, `1 + 2 + 3` and dotted keys nest without recursing in the parser,
+    /// but evaluating and dropping them recurses, so each link counts as a level.
+    fn check_depth(&self, levels: usize, span: SourceSpan) -> Result<(), Error> {
+        if self.depth.saturating_add(levels) >= self.max_depth {
+            return Err(Error::new(
+                ErrorKind::NestingTooDeep {
+                    max: self.max_depth,
+                },
+                self.source.clone(),
+                span,
+            ));
+        }
+
+        Ok(())
+    }
+
     /// Return a span that contains the current line the parser is on.
     fn closest_span(&self) -> SourceSpan {
         if let Some(token) = self.tokens.get(self.pos) {
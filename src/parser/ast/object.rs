@@ -7,11 +7,12 @@ use crate::{
     parser::{
         ast::{
             advance, consume,
-            types::{Error, ErrorKind},
+            types::{DuplicateKey, Error, ErrorKind},
         },
         lexer::types::TokenKind,
     },
 };
+use miette::SourceSpan;
 use std::collections::BTreeMap;
 
 impl super::Parser {
@@ -33,6 +34,7 @@ impl super::Parser {
         let mut fields = BTreeMap::new();
 
         loop {
+            let doc = self.take_doc();
             let token = self.tokens.get(self.pos).ok_or(Error::new(
                 ErrorKind::NoTokensLeft,
                 self.source.clone(),
@@ -45,6 +47,7 @@ impl super::Parser {
             }
 
             let mut key_parts = Vec::new();
+            let mut key_span: Option<SourceSpan> = None;
             loop {
                 let token = advance!(self).ok_or(Error::new(
                     ErrorKind::NoTokensLeft,
@@ -55,6 +58,10 @@ impl super::Parser {
                 match &token.kind {
                     TokenKind::Identifier(name) | TokenKind::String(name) => {
                         key_parts.push(name.clone());
+                        key_span = Some(match key_span {
+                            Some(start) => merge_spans(start, token.span),
+                            None => token.span,
+                        });
                     }
                     _ => {
                         return Err(Error::new(
@@ -77,6 +84,12 @@ impl super::Parser {
                 break;
             }
 
+            if let Some(key_span) = key_span {
+                self.check_depth(key_parts.len(), key_span)?;
+            }
+
+            let contract = self.parse_contract()?;
+
             match advance!(self) {
                 Some(token) => match token.kind {
                     TokenKind::Equals => (),
@@ -104,10 +117,15 @@ impl super::Parser {
                 }
             }
 
-            let value = self.parse()?;
+            let mut value = self.parse()?;
+            value.doc = doc;
+            value.contract = contract.map(Box::new);
+            if let Some(key_span) = key_span {
+                self.key_spans.insert(value.span.offset(), key_span);
+            }
             let nested = Self::nest_object(key_parts, value);
 
-            Self::merge_object(&mut fields, nested);
+            self.merge_object(&mut fields, nested, &mut Vec::new());
         }
 
         self.context = last_context;
@@ -126,25 +144,29 @@ impl super::Parser {
         ))
     }
 
+    /// Wraps `value` in an object for each part, `value` itself if there are none.
     fn nest_object(mut parts: Vec<String>, value: Expr) -> Expr {
-        #[allow(clippy::unwrap_used)]
-        let last = parts.pop().unwrap();
-
-        let mut inner = BTreeMap::new();
-        inner.insert(last, value.clone());
-
-        let mut expr = Expr::new(ExprKind::Literal(Literal::Object(inner)), value.span);
+        let mut expr = value;
 
         while let Some(part) = parts.pop() {
-            let mut outer = BTreeMap::new();
-            outer.insert(part, expr.clone());
-            expr = Expr::new(ExprKind::Literal(Literal::Object(outer)), expr.span);
+            let span = expr.span;
+            expr = Expr::new(
+                ExprKind::Literal(Literal::Object(BTreeMap::from([(part, expr)]))),
+                span,
+            );
         }
 
         expr
     }
 
-    fn merge_object(target: &mut BTreeMap<String, Expr>, nested: Expr) {
+    /// Merges the fields of `nested` into `target`, objects assigned to the same key are merged recursively.
+    /// `path` is the path of `target` in the outermost object, for reporting duplicate keys.
+    fn merge_object(
+        &mut self,
+        target: &mut BTreeMap<String, Expr>,
+        nested: Expr,
+        path: &mut Vec<String>,
+    ) {
         if let ExprKind::Literal(Literal::Object(new_map)) = nested.kind {
             for (k, v) in new_map {
                 if let Some(existing) = target.get_mut(&k)
@@ -153,11 +175,34 @@ impl super::Parser {
                         ExprKind::Literal(Literal::Object(new_sub)),
                     ) = (&mut existing.kind, v.kind.clone())
                 {
+                    path.push(k);
                     for (nk, nv) in new_sub {
-                        Self::merge_object(existing_map, Self::nest_object(vec![nk], nv));
+                        self.merge_object(existing_map, Self::nest_object(vec![nk], nv), path);
                     }
+                    path.pop();
                     continue;
                 }
+
+                if let Some(existing) = target.get(&k) {
+                    let key_span = |value: &Expr| {
+                        self.key_spans
+                            .get(&value.span.offset())
+                            .copied()
+                            .unwrap_or(value.span)
+                    };
+                    let duplicate = DuplicateKey {
+                        key: path
+                            .iter()
+                            .chain([&k])
+                            .cloned()
+                            .collect::<Vec<_>>()
+                            .join("."),
+                        original: key_span(existing),
+                        duplicate: key_span(&v),
+                    };
+                    self.duplicate_keys.push(duplicate);
+                }
+
                 target.insert(k, v);
             }
         }
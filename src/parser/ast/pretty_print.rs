@@ -1,4 +1,7 @@
-use crate::parser::ast::types::{Expr, ExprKind, Literal};
+use crate::{
+    line_column,
+    parser::ast::types::{Expr, ExprKind, Literal, NamedArg, Pattern},
+};
 use colored::Colorize;
 use miette::SourceSpan;
 use std::fmt::Write;
@@ -40,6 +43,16 @@ impl super::Parser {
                         ")".dimmed(),
                         self.pretty_print_span(expr.span).dimmed(),
                     ),
+                    #[cfg(feature = "bigint")]
+                    Literal::BigInt(v) => format!(
+                        "{}{}{}{}{} {}",
+                        pad,
+                        "BigInt".bright_blue(),
+                        "(".dimmed(),
+                        v.to_string().yellow(),
+                        ")".dimmed(),
+                        self.pretty_print_span(expr.span).dimmed(),
+                    ),
                     Literal::Float(v) => format!(
                         "{}{}{}{}{} {}",
                         pad,
@@ -189,7 +202,11 @@ impl super::Parser {
                 out.push('\n');
                 let _ = writeln!(out, "{pad}  index: {}", index.to_string().yellow());
             }
-            ExprKind::ObjectAccess { base, field } => {
+            ExprKind::ObjectAccess {
+                base,
+                field,
+                fallback,
+            } => {
                 let _ = writeln!(
                     out,
                     "{pad}{} {}",
@@ -203,8 +220,20 @@ impl super::Parser {
                 );
                 out.push('\n');
                 let _ = writeln!(out, "{pad}  field: {}", field.yellow());
+                if let Some(fallback) = fallback {
+                    let _ = write!(out, "{pad}  fallback: ");
+                    out.push_str(
+                        self.pretty_print_expr(fallback, indent.saturating_add(1))
+                            .trim_start(),
+                    );
+                }
             }
-            ExprKind::FnDecl { args, expr } => {
+            ExprKind::FnDecl {
+                args,
+                defaults,
+                rest,
+                expr,
+            } => {
                 let _ = write!(
                     out,
                     "{pad}{} {} {}\n",
@@ -214,7 +243,18 @@ impl super::Parser {
                 );
 
                 for arg in args {
-                    let _ = writeln!(out, "{pad}  arg: {}", arg.magenta());
+                    let _ = write!(out, "{pad}  arg: {}", arg.magenta());
+                    if let Some(default) = defaults.get(arg) {
+                        let _ = write!(out, " {} ", "?".cyan());
+                        out.push_str(
+                            self.pretty_print_expr(default, indent.saturating_add(1))
+                                .trim(),
+                        );
+                    }
+                    out.push('\n');
+                }
+                if let Some(rest) = rest {
+                    let _ = writeln!(out, "{pad}  rest: {}", rest.magenta());
                 }
 
                 let _ = write!(out, "{pad}  expr: ");
@@ -226,7 +266,11 @@ impl super::Parser {
 
                 let _ = writeln!(out, "{pad}{}", "}".dimmed());
             }
-            ExprKind::Call { base, args } => {
+            ExprKind::Call {
+                base,
+                args,
+                named_args,
+            } => {
                 let _ = writeln!(
                     out,
                     "{pad}{} {}",
@@ -245,6 +289,13 @@ impl super::Parser {
                             .trim_start(),
                     );
                 }
+                for NamedArg { name, value, .. } in named_args {
+                    let _ = write!(out, "{pad}  arg {} {} ", name.magenta(), "=".cyan());
+                    out.push_str(
+                        self.pretty_print_expr(value, indent.saturating_add(1))
+                            .trim_start(),
+                    );
+                }
             }
             ExprKind::LetIn {
                 bindings,
@@ -256,7 +307,20 @@ impl super::Parser {
                     "LetIn".bright_magenta(),
                     self.pretty_print_span(expr.span).dimmed(),
                 );
-                for (name, val) in bindings {
+                for (pattern, val) in bindings {
+                    let name = match pattern {
+                        Pattern::Identifier(name) => name.clone(),
+                        Pattern::Object(fields) => format!("{{ {} }}", fields.join(", ")),
+                        Pattern::Array { items, rest } => {
+                            let mut items = items.clone();
+                            items.extend(rest.iter().map(|rest| format!("...{rest}")));
+                            format!("[ {} ]", items.join(", "))
+                        }
+                    };
+                    let name = match &val.contract {
+                        Some(contract) => format!("{name} :: {}", contract.ty),
+                        None => name,
+                    };
                     let _ = write!(out, "{pad}  {name} {} ", "=".cyan());
                     out.push_str(self.pretty_print_expr(val, indent.saturating_add(1)).trim());
                     out.push('\n');
@@ -264,31 +328,46 @@ impl super::Parser {
                 let _ = writeln!(out, "\n{pad}  expr:");
                 out.push_str(&self.pretty_print_expr(body, indent.saturating_add(2)));
             }
+            ExprKind::Comprehension {
+                expr: body,
+                binding,
+                iterable,
+                condition,
+            } => {
+                let _ = writeln!(
+                    out,
+                    "{pad}{} {}",
+                    "Comprehension".bright_magenta(),
+                    self.pretty_print_span(expr.span).dimmed(),
+                );
+                let _ = writeln!(out, "{pad}  binding: {}", binding.magenta());
+                let _ = write!(out, "{pad}  iterable: ");
+                out.push_str(
+                    self.pretty_print_expr(iterable, indent.saturating_add(1))
+                        .trim_start(),
+                );
+                if let Some(condition) = condition {
+                    let _ = write!(out, "{pad}  condition: ");
+                    out.push_str(
+                        self.pretty_print_expr(condition, indent.saturating_add(1))
+                            .trim_start(),
+                    );
+                }
+                let _ = writeln!(out, "{pad}  expr:");
+                out.push_str(&self.pretty_print_expr(body, indent.saturating_add(2)));
+            }
         }
 
         out
     }
 
+    /// The line and column of the end of `span`.
     fn pretty_print_span(&self, span: SourceSpan) -> String {
-        let mut line: usize = 1;
-        let mut col: usize = 1;
-        let mut byte_index = 0;
-
-        for c in self.source.inner().chars() {
-            if byte_index == span.offset().saturating_add(span.len()) {
-                break;
-            }
-
-            if c == '\n' {
-                line = line.saturating_add(1);
-                col = 1;
-            } else {
-                col = col.saturating_add(1);
-            }
-
-            byte_index = byte_index.saturating_add(c.len_utf8());
-        }
+        let (line, column) = line_column(
+            self.source.inner(),
+            span.offset().saturating_add(span.len()),
+        );
 
-        format!("{line}:{col}")
+        format!("{line}:{column}")
     }
 }
@@ -7,6 +7,10 @@ use thiserror::Error;
 pub struct Expr {
     pub kind: ExprKind,
     pub span: SourceSpan,
+    /// The doc comment of the binding or object key this expression is the value of.
+    pub doc: Option<String>,
+    /// The type annotation of the binding or object key this expression is the value of, `name :: type`.
+    pub contract: Option<Box<Contract>>,
 }
 
 impl Default for Expr {
@@ -17,7 +21,12 @@ impl Default for Expr {
 
 impl Expr {
     pub const fn new(kind: ExprKind, span: SourceSpan) -> Self {
-        Self { kind, span }
+        Self {
+            kind,
+            span,
+            doc: None,
+            contract: None,
+        }
     }
 
     pub const fn lit(literal: Literal, span: SourceSpan) -> Self {
@@ -46,6 +55,131 @@ impl Expr {
             _ => None,
         }
     }
+
+    /// The expressions directly inside of this one, in source order.
+    pub fn children(&self) -> Vec<&Self> {
+        match &self.kind {
+            ExprKind::Literal(
+                Literal::InterpolatedString(exprs)
+                | Literal::InterpolatedPath(exprs)
+                | Literal::Array(exprs),
+            ) => exprs.iter().collect(),
+            ExprKind::Literal(Literal::Object(fields)) => {
+                let mut fields: Vec<_> = fields.values().collect();
+                fields.sort_by_key(|field| field.span.offset());
+                fields
+            }
+            ExprKind::Literal(_) | ExprKind::Identifier(_) => Vec::new(),
+            ExprKind::Not(expr) | ExprKind::ArrayIndex { base: expr, .. } => vec![expr],
+            ExprKind::ObjectAccess { base, fallback, .. } => {
+                [&**base].into_iter().chain(fallback.as_deref()).collect()
+            }
+            ExprKind::BinaryOp { left, right, .. } => vec![left, right],
+            ExprKind::FnDecl { defaults, expr, .. } => {
+                let mut children: Vec<_> = defaults.values().collect();
+                children.sort_by_key(|default| default.span.offset());
+                children.push(expr);
+                children
+            }
+            ExprKind::Call {
+                base,
+                args,
+                named_args,
+            } => {
+                let mut children: Vec<_> = args
+                    .iter()
+                    .chain(named_args.iter().map(|arg| &arg.value))
+                    .collect();
+                children.sort_by_key(|arg| arg.span.offset());
+                children.insert(0, base);
+                children
+            }
+            ExprKind::LetIn { bindings, expr } => bindings
+                .iter()
+                .map(|(_, value)| value)
+                .chain([&**expr])
+                .collect(),
+            ExprKind::Comprehension {
+                expr,
+                iterable,
+                condition,
+                ..
+            } => [&**expr, iterable]
+                .into_iter()
+                .chain(condition.as_deref())
+                .collect(),
+        }
+    }
+
+    /// Whether `self` and `other` are the same expression, ignoring spans and doc comments.
+    pub fn same_structure(&self, other: &Self) -> bool {
+        let mut lhs = self.clone();
+        let mut rhs = other.clone();
+        lhs.clear_spans();
+        rhs.clear_spans();
+
+        lhs == rhs
+    }
+
+    fn clear_spans(&mut self) {
+        self.span = SourceSpan::new(0.into(), 0);
+        self.doc = None;
+
+        if let ExprKind::Call { named_args, .. } = &mut self.kind {
+            for arg in named_args {
+                arg.span = SourceSpan::new(0.into(), 0);
+            }
+        }
+
+        for child in self.children_mut() {
+            child.clear_spans();
+        }
+    }
+
+    /// Like [`Expr::children`], but not in source order.
+    fn children_mut(&mut self) -> Vec<&mut Self> {
+        match &mut self.kind {
+            ExprKind::Literal(
+                Literal::InterpolatedString(exprs)
+                | Literal::InterpolatedPath(exprs)
+                | Literal::Array(exprs),
+            ) => exprs.iter_mut().collect(),
+            ExprKind::Literal(Literal::Object(fields)) => fields.values_mut().collect(),
+            ExprKind::Literal(_) | ExprKind::Identifier(_) => Vec::new(),
+            ExprKind::Not(expr) | ExprKind::ArrayIndex { base: expr, .. } => vec![expr],
+            ExprKind::ObjectAccess { base, fallback, .. } => [&mut **base]
+                .into_iter()
+                .chain(fallback.as_deref_mut())
+                .collect(),
+            ExprKind::BinaryOp { left, right, .. } => vec![left, right],
+            ExprKind::FnDecl { defaults, expr, .. } => {
+                defaults.values_mut().chain([&mut **expr]).collect()
+            }
+            ExprKind::Call {
+                base,
+                args,
+                named_args,
+            } => [&mut **base]
+                .into_iter()
+                .chain(args)
+                .chain(named_args.iter_mut().map(|arg| &mut arg.value))
+                .collect(),
+            ExprKind::LetIn { bindings, expr } => bindings
+                .iter_mut()
+                .map(|(_, value)| value)
+                .chain([&mut **expr])
+                .collect(),
+            ExprKind::Comprehension {
+                expr,
+                iterable,
+                condition,
+                ..
+            } => [&mut **expr, &mut **iterable]
+                .into_iter()
+                .chain(condition.as_deref_mut())
+                .collect(),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -65,25 +199,177 @@ pub enum ExprKind {
     ObjectAccess {
         base: Box<Expr>,
         field: String,
+        /// `base.field or fallback`, used when the field is missing or `null`.
+        fallback: Option<Box<Expr>>,
     },
     FnDecl {
         args: Vec<String>,
+        /// `name ? default`, used when the argument is omitted.
+        defaults: BTreeMap<String, Expr>,
+        /// `...rest`, binds the remaining arguments as an array.
+        rest: Option<String>,
         expr: Box<Expr>,
     },
     Call {
         base: Box<Expr>,
         args: Vec<Expr>,
+        named_args: Vec<NamedArg>,
     },
     LetIn {
-        bindings: Vec<(String, Expr)>,
+        bindings: Vec<(Pattern, Expr)>,
+        expr: Box<Expr>,
+    },
+    /// `[ expr for binding in iterable if condition ]`
+    Comprehension {
         expr: Box<Expr>,
+        binding: String,
+        iterable: Box<Expr>,
+        condition: Option<Box<Expr>>,
     },
 }
 
+/// An argument passed by name at a call site, `name = value`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct NamedArg {
+    pub name: String,
+    /// The span of `name`.
+    pub span: SourceSpan,
+    pub value: Expr,
+}
+
+/// The left side of a binding in a `let` expression.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Pattern {
+    /// `name`
+    Identifier(String),
+    /// `{ host, port }`, binds fields to variables of the same name
+    Object(Vec<String>),
+    /// `[ first, ...rest ]`, binds items by position and the remaining items to `rest`
+    Array {
+        items: Vec<String>,
+        rest: Option<String>,
+    },
+}
+
+impl Pattern {
+    /// The names of the variables the pattern binds, in order.
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            Self::Identifier(name) => vec![name.as_str()],
+            Self::Object(fields) => fields.iter().map(String::as_str).collect(),
+            Self::Array { items, rest } => items.iter().chain(rest).map(String::as_str).collect(),
+        }
+    }
+}
+
+/// A key of an object literal that is assigned twice, the second value replaces the first one.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DuplicateKey {
+    /// The full path of the key, like `server.port`.
+    pub key: String,
+    pub original: SourceSpan,
+    pub duplicate: SourceSpan,
+}
+
+/// A type annotation, checked against the value when it is bound.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Contract {
+    pub ty: Type,
+    /// The span of the type after `::`.
+    pub span: SourceSpan,
+}
+
+/// The types that can be used in a [`Contract`].
+#[derive(Debug, PartialEq, Clone)]
+pub enum Type {
+    /// `any`
+    Any,
+    /// `null`
+    Null,
+    /// `bool`
+    Boolean,
+    /// `int`
+    Int,
+    /// `float`
+    Float,
+    /// `number`, an int or a float
+    Number,
+    /// `string`
+    String,
+    /// `path`
+    Path,
+    /// `array` for any items, `[type]` for items of a single type
+    Array(Box<Type>),
+    /// `object`
+    Object,
+    /// `function`, including builtins
+    Function,
+    /// `type?`, the type or `null`
+    Optional(Box<Type>),
+}
+
+impl Type {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "any" => Self::Any,
+            "null" => Self::Null,
+            "bool" => Self::Boolean,
+            "int" => Self::Int,
+            "float" => Self::Float,
+            "number" => Self::Number,
+            "string" => Self::String,
+            "path" => Self::Path,
+            "array" => Self::Array(Box::new(Self::Any)),
+            "object" => Self::Object,
+            "function" => Self::Function,
+            _ => return None,
+        })
+    }
+
+    /// Parses a type written like in an annotation, like `[string]?`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+
+        if let Some(inner) = text.strip_suffix('?') {
+            return Some(Self::Optional(Box::new(Self::parse(inner)?)));
+        }
+        if let Some(item) = text
+            .strip_prefix('[')
+            .and_then(|text| text.strip_suffix(']'))
+        {
+            return Some(Self::Array(Box::new(Self::parse(item)?)));
+        }
+
+        Self::from_name(text)
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any => write!(f, "any"),
+            Self::Null => write!(f, "null"),
+            Self::Boolean => write!(f, "bool"),
+            Self::Int => write!(f, "int"),
+            Self::Float => write!(f, "float"),
+            Self::Number => write!(f, "number"),
+            Self::String => write!(f, "string"),
+            Self::Path => write!(f, "path"),
+            Self::Array(item) if **item == Self::Any => write!(f, "array"),
+            Self::Array(item) => write!(f, "[{item}]"),
+            Self::Object => write!(f, "object"),
+            Self::Function => write!(f, "function"),
+            Self::Optional(ty) => write!(f, "{ty}?"),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Literal {
     Null,
     Int(isize),
+    #[cfg(feature = "bigint")]
+    BigInt(num_bigint::BigInt),
     Float(f64),
     Bool(bool),
     String(String),
@@ -102,6 +388,12 @@ pub enum BinaryOperator {
     Multiply,
     Divide,
     Modulo,
+    /// **
+    Power,
+    /// ..
+    Range,
+    /// ..=
+    RangeInclusive,
 
     // Logic Operators
     /// ==
@@ -125,12 +417,32 @@ pub enum BinaryOperator {
 impl BinaryOperator {
     pub fn precedence(&self) -> u8 {
         match self {
-            Self::Plus | Self::Minus => 1,
-            Self::Multiply | Self::Divide => 2,
-            _ => 0,
+            Self::Or => 1,
+            Self::And => 2,
+            Self::Eq | Self::NotEq | Self::Gt | Self::GtEq | Self::Lt | Self::LtEq => 3,
+            Self::Range | Self::RangeInclusive => 4,
+            Self::Plus | Self::Minus => 5,
+            Self::Multiply | Self::Divide | Self::Modulo => 6,
+            Self::Power => 7,
         }
     }
 
+    pub fn is_arithmetic(&self) -> bool {
+        matches!(
+            self,
+            Self::Plus | Self::Minus | Self::Multiply | Self::Divide | Self::Modulo | Self::Power
+        )
+    }
+
+    pub fn is_ordering(&self) -> bool {
+        matches!(self, Self::Gt | Self::GtEq | Self::Lt | Self::LtEq)
+    }
+
+    /// Whether `a op b op c` groups as `a op (b op c)` instead of `(a op b) op c`.
+    pub fn is_right_associative(&self) -> bool {
+        matches!(self, Self::Power)
+    }
+
     /// # Errors
     /// This function will return an error if the token type is not a binary operator.
     pub fn from_token(kind: TokenKind) -> Result<Self, Error> {
@@ -141,6 +453,9 @@ impl BinaryOperator {
             TokenKind::Multiply => Ok(Self::Multiply),
             TokenKind::Slash => Ok(Self::Divide),
             TokenKind::Modulo => Ok(Self::Modulo),
+            TokenKind::Power => Ok(Self::Power),
+            TokenKind::Range => Ok(Self::Range),
+            TokenKind::RangeInclusive => Ok(Self::RangeInclusive),
 
             // Logic Operators
             TokenKind::Eq => Ok(Self::Eq),
@@ -178,6 +493,9 @@ impl Display for BinaryOperator {
                 Self::Multiply => "*",
                 Self::Divide => "/",
                 Self::Modulo => "%",
+                Self::Power => "**",
+                Self::Range => "..",
+                Self::RangeInclusive => "..=",
 
                 // Logic Operators
                 Self::Eq => "==",
@@ -233,14 +551,42 @@ pub enum ErrorKind {
         found: Option<TokenKind>,
     },
 
+    #[error("Unknown type `{name}`")]
+    #[diagnostic(
+        code(tl::parser::ast::unknown_type),
+        help(
+            "Use one of `any`, `null`, `bool`, `int`, `float`, `number`, `string`, `path`, `array`, `object` or `function`"
+        )
+    )]
+    UnknownType { name: String },
+
     #[error("Unexpected token")]
     #[diagnostic(code(tl::parser::ast::unexpected_token))]
     UnexpectedToken,
 
+    #[error("`{name}` is bound twice in the same pattern")]
+    #[diagnostic(
+        code(tl::parser::ast::duplicate_binding),
+        help("Rename one of them, otherwise the second one would hide the first")
+    )]
+    DuplicateBinding {
+        name: String,
+        #[label("First bound here")]
+        first: SourceSpan,
+    },
+
     #[error("No tokens left")]
     #[diagnostic(code(tl::parser::ast::no_tokens_left))]
     NoTokensLeft,
 
+    #[error("Expression nested more than {max} levels deep")]
+    #[diagnostic(
+        code(tl::parser::ast::nesting_too_deep),
+        help("Move the inner parts into `let` bindings")
+    )]
+    NestingTooDeep { max: usize },
+
     #[error(transparent)]
+    #[diagnostic(transparent)]
     TokenizationError(#[from] lexer::types::Error),
 }
@@ -1,150 +1,513 @@
 use miette::{NamedSource, SourceSpan};
-use std::{iter::Peekable, path::PathBuf, str::Chars};
+use std::{iter::Peekable, mem, path::PathBuf, str::Chars};
 use types::{Error, Token, TokenKind};
 
-use crate::parser::lexer::types::ErrorKind;
+use crate::parser::{ast::DEFAULT_MAX_DEPTH, lexer::types::ErrorKind};
 
 pub mod types;
 
+/// Tokenizes `source` including whitespace and comments, for tools like formatters.
+/// The texts of the tokens concatenated reproduce the source exactly.
+/// # Errors
+/// This function will return an error if a tokenization error occurs.
+pub fn lex(source: &NamedSource<String>) -> Result<Vec<Token>, Error> {
+    let tokens = Lexer::new(source.clone()).tokenize()?;
+    let text = source.inner();
+
+    // Spans are moved to char boundaries and clamped, so no text is skipped or repeated
+    let boundary = |mut index: usize| {
+        index = index.min(text.len());
+        while !text.is_char_boundary(index) {
+            index = index.saturating_add(1);
+        }
+        index
+    };
+
+    let mut lossless = Vec::with_capacity(tokens.len().saturating_mul(2));
+    let mut cursor = 0;
+
+    for token in tokens {
+        let start = boundary(token.span.offset().max(cursor));
+        let end = boundary(token.span.offset().saturating_add(token.span.len())).max(start);
+
+        push_trivia(text, cursor, start, &mut lossless);
+        lossless.push(Token::new(
+            token.kind,
+            (start, end.saturating_sub(start)).into(),
+        ));
+        cursor = end;
+    }
+
+    push_trivia(text, cursor, text.len(), &mut lossless);
+
+    Ok(lossless)
+}
+
+/// Splits the text between two tokens into whitespace and comments.
+fn push_trivia(text: &str, start: usize, end: usize, tokens: &mut Vec<Token>) {
+    let mut pos = start;
+
+    while let Some(rest) = text.get(pos..end)
+        && !rest.is_empty()
+    {
+        let (kind, len) = if let Some(comment) = rest.strip_prefix("//") {
+            let comment = comment.split('\n').next().unwrap_or_default();
+            (
+                TokenKind::Comment(comment.to_string()),
+                comment.len().saturating_add(2),
+            )
+        } else {
+            let len = rest
+                .find(|ch: char| !ch.is_whitespace())
+                .unwrap_or(rest.len());
+
+            // Anything else would be a token the lexer skipped, keep it so the text is still complete
+            let len = if len == 0 {
+                rest.find("//")
+                    .filter(|index| *index > 0)
+                    .unwrap_or(rest.len())
+            } else {
+                len
+            };
+
+            (
+                TokenKind::Whitespace(rest.get(..len).unwrap_or_default().to_string()),
+                len,
+            )
+        };
+
+        tokens.push(Token::new(kind, (pos, len).into()));
+        pos = pos.saturating_add(len);
+    }
+}
+
 pub struct Lexer {
     pub(crate) source: NamedSource<String>,
     pub(crate) pos: usize,
+    max_depth: usize,
+}
+
+/// How deep interpolations are nested at the current position, and how deep they can be nested.
+#[derive(Debug, Clone, Copy)]
+struct Depth {
+    current: usize,
+    max: usize,
+}
+
+impl Depth {
+    const fn nested(self) -> Self {
+        Self {
+            current: self.current.saturating_add(1),
+            max: self.max,
+        }
+    }
+}
+
+/// The scanned contents of a string literal or template.
+struct StringBody {
+    /// Literal parts and interpolated expressions, in source order.
+    values: Vec<Token>,
+    /// The last literal part of the string.
+    buffer: String,
+    closed: bool,
 }
 
 impl Lexer {
     pub fn new(source: NamedSource<String>) -> Self {
-        Self { source, pos: 0 }
+        Self {
+            source,
+            pos: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Sets how deep `${...}` interpolations can be nested before [`ErrorKind::InterpolationTooDeep`] is returned.
+    /// Each level is tokenized recursively, so this keeps pathological input from overflowing the stack.
+    #[must_use]
+    pub const fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    const fn depth(&self) -> Depth {
+        Depth {
+            current: 0,
+            max: self.max_depth,
+        }
     }
 
     /// Tokenizes the source code inside the [`Parser`] struct.
     /// # Errors
     /// This function will return an error if a tokenization error occurs.
     pub fn tokenize(&mut self) -> Result<Vec<Token>, Error> {
-        let mut tokens = Vec::new();
-        let mut chars = self.source.inner().chars().peekable();
+        let mut tokens = self.tokens();
+        let collected = tokens.by_ref().collect();
+        self.pos = tokens.pos;
 
-        macro_rules! push_token {
-            ($token:ident, $len:expr) => {{
-                tokens.push(Token::new(TokenKind::$token, (self.pos, $len).into()));
-                self.pos = self.pos.saturating_add($len);
-                chars.next();
-            }};
+        collected
+    }
+
+    /// Returns an iterator that tokenizes the source lazily, it stops after the first error.
+    pub fn tokens(&self) -> Tokens<'_> {
+        Tokens {
+            source: &self.source,
+            chars: rest(&self.source, self.pos).chars().peekable(),
+            pos: self.pos,
+            depth: self.depth(),
+            failed: false,
         }
+    }
+
+    /// Tokenizes the source code as a template, treating the whole source like the inside of a string literal.
+    /// Quotes and backslashes are kept as-is, only `${...}` is interpolated and `\${` is a literal `${`.
+    /// # Errors
+    /// This function will return an error if an interpolation can not be tokenized.
+    pub fn tokenize_template(&mut self) -> Result<Token, Error> {
+        let start = self.pos;
+        let depth = self.depth();
+        let mut chars = rest(&self.source, start).chars().peekable();
+
+        let StringBody { values, .. } =
+            Self::scan_string_body(&self.source, &mut self.pos, &mut chars, false, depth)?;
+
+        Ok(Token::new(
+            TokenKind::InterpolatedString(values),
+            (start, self.pos.saturating_sub(start)).into(),
+        ))
+    }
+
+    /// Scans the inside of a string, splitting it into literal parts and `${...}` interpolations.
+    /// If `quoted` is set, escapes are handled and the scan stops after the closing quote,
+    /// otherwise everything until the end of the input is scanned.
+    fn scan_string_body(
+        source: &NamedSource<String>,
+        pos: &mut usize,
+        chars: &mut Peekable<Chars<'_>>,
+        quoted: bool,
+        depth: Depth,
+    ) -> Result<StringBody, Error> {
+        let mut segment_start = *pos;
+        let mut closed = false;
+        let mut values = Vec::new();
+        let mut buffer = String::new();
+
+        while let Some(&ch) = chars.peek() {
+            match ch {
+                '"' if quoted => {
+                    chars.next();
+                    *pos = pos.saturating_add(1);
+                    closed = true;
+                    break;
+                }
+
+                '\\' if is_escaped_interpolation(chars) => {
+                    Self::skip_escaped_interpolation(pos, chars);
+                    buffer.push_str("${");
+                }
 
-        let is_valid_char = |ch: char, dots: bool| {
-            if ch == '.' && !dots {
-                return false;
+                '\\' if quoted => {
+                    chars.next();
+                    *pos = pos.saturating_add(1);
+                    if let Some(&escaped_char) = chars.peek() {
+                        buffer.push(escape(escaped_char));
+                        chars.next();
+                        *pos = pos.saturating_add(escaped_char.len_utf8());
+                    }
+                }
+
+                '$' if chars.clone().nth(1) == Some('{') => {
+                    if !buffer.is_empty() {
+                        values.push(Token::new(
+                            TokenKind::String(buffer.clone()),
+                            (segment_start, pos.saturating_sub(segment_start)).into(),
+                        ));
+                        buffer.clear();
+                    }
+
+                    values.push(Self::scan_interpolation(
+                        source,
+                        pos,
+                        chars,
+                        TokenKind::InterpolatedString,
+                        depth,
+                    )?);
+                    segment_start = *pos;
+                }
+
+                _ => {
+                    buffer.push(ch);
+                    chars.next();
+                    *pos = pos.saturating_add(ch.len_utf8());
+                }
+            }
+        }
+
+        if !buffer.is_empty() {
+            // Don't include the closing quote in the span
+            let end = if closed { pos.saturating_sub(1) } else { *pos };
+
+            values.push(Token::new(
+                TokenKind::String(buffer.clone()),
+                (segment_start, end.saturating_sub(segment_start)).into(),
+            ));
+        }
+
+        Ok(StringBody {
+            values,
+            buffer,
+            closed,
+        })
+    }
+
+    /// Skips a `\${`, which is a literal `${` instead of an interpolation.
+    fn skip_escaped_interpolation(pos: &mut usize, chars: &mut Peekable<Chars<'_>>) {
+        chars.next();
+        chars.next();
+        chars.next();
+        *pos = pos.saturating_add(3);
+    }
+
+    /// Scans a `${...}` interpolation, `chars` has to be at the `$`.
+    /// Braces inside are balanced, so objects can be used in the expression.
+    /// An expression of a single token is returned as-is, otherwise its tokens are wrapped with `wrap`.
+    fn scan_interpolation(
+        source: &NamedSource<String>,
+        pos: &mut usize,
+        chars: &mut Peekable<Chars<'_>>,
+        wrap: fn(Vec<Token>) -> TokenKind,
+        depth: Depth,
+    ) -> Result<Token, Error> {
+        let start = *pos;
+
+        if depth.current >= depth.max {
+            return Err(Error::new(
+                ErrorKind::InterpolationTooDeep { max: depth.max },
+                source.clone(),
+                (start, 2).into(),
+            ));
+        }
+
+        // Consume `${`
+        chars.next();
+        chars.next();
+        *pos = pos.saturating_add(2);
+
+        let content_start = *pos;
+        let mut braces: usize = 1;
+
+        for ch in chars.by_ref() {
+            *pos = pos.saturating_add(ch.len_utf8());
+
+            match ch {
+                '{' => braces = braces.saturating_add(1),
+                '}' => {
+                    braces = braces.saturating_sub(1);
+                    if braces == 0 {
+                        break;
+                    }
+                }
+                _ => {}
             }
+        }
+
+        if braces != 0 {
+            return Err(Error::new(
+                ErrorKind::UnclosedInterpolation,
+                source.clone(),
+                (start, pos.saturating_sub(start)).into(),
+            ));
+        }
+
+        // Don't include the closing brace
+        let end = pos.saturating_sub(1);
+
+        // Spans and errors of the expression refer to the whole source
+        let content = source.inner().get(content_start..end).unwrap_or_default();
+        let mut tokens = Tokens {
+            source,
+            chars: content.chars().peekable(),
+            pos: content_start,
+            depth: depth.nested(),
+            failed: false,
+        }
+        .collect::<Result<Vec<_>, _>>()?;
 
-            ch.is_alphanumeric() || matches!(ch, '_' | '.' | '=' | '!' | '<' | '>' | '&' | '|')
+        if tokens.len() == 1
+            && let Some(token) = tokens.pop()
+        {
+            return Ok(token);
+        }
+
+        // The span covers the expression without the whitespace around it
+        let (start, end) = match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => (
+                first.span.offset(),
+                last.span.offset().saturating_add(last.span.len()),
+            ),
+            _ => (content_start, end),
         };
 
-        while let Some(&ch) = chars.peek() {
+        Ok(Token::new(
+            wrap(tokens),
+            (start, end.saturating_sub(start)).into(),
+        ))
+    }
+}
+
+/// A lazy iterator over the tokens of a [`Lexer`], created with [`Lexer::tokens`].
+pub struct Tokens<'a> {
+    source: &'a NamedSource<String>,
+    chars: Peekable<Chars<'a>>,
+    pos: usize,
+    depth: Depth,
+    failed: bool,
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = Result<Token, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let token = self.next_token().transpose();
+        self.failed = matches!(token, Some(Err(_)));
+
+        token
+    }
+}
+
+impl Tokens<'_> {
+    /// Scans a path literal, it becomes an [`TokenKind::InterpolatedPath`] if it contains `${...}`.
+    fn scan_path(&mut self) -> Result<Token, Error> {
+        let start = self.pos;
+        let mut segment_start = self.pos;
+        let mut parts = Vec::new();
+        let mut buffer = String::new();
+
+        // The closing braces of escaped interpolations are part of the path
+        let mut escaped_braces: usize = 0;
+
+        while let Some(&ch) = self.chars.peek() {
+            match ch {
+                '}' if escaped_braces > 0 => {
+                    escaped_braces = escaped_braces.saturating_sub(1);
+                    buffer.push(ch);
+                    self.chars.next();
+                    self.pos = self.pos.saturating_add(1);
+                }
+                '"' | ',' | ')' | '}' | ']' => break,
+                _ if ch.is_whitespace() => break,
+                '\\' if is_escaped_interpolation(&self.chars) => {
+                    Lexer::skip_escaped_interpolation(&mut self.pos, &mut self.chars);
+                    buffer.push_str("${");
+                    escaped_braces = escaped_braces.saturating_add(1);
+                }
+                '$' if self.chars.clone().nth(1) == Some('{') => {
+                    if !buffer.is_empty() {
+                        parts.push(Token::new(
+                            TokenKind::String(mem::take(&mut buffer)),
+                            (segment_start, self.pos.saturating_sub(segment_start)).into(),
+                        ));
+                    }
+
+                    parts.push(Lexer::scan_interpolation(
+                        self.source,
+                        &mut self.pos,
+                        &mut self.chars,
+                        TokenKind::InterpolatedPath,
+                        self.depth,
+                    )?);
+                    segment_start = self.pos;
+                }
+                _ => {
+                    buffer.push(ch);
+                    self.chars.next();
+                    self.pos = self.pos.saturating_add(ch.len_utf8());
+                }
+            }
+        }
+
+        let span = (start, self.pos.saturating_sub(start)).into();
+
+        if parts.is_empty() {
+            return Ok(Token::new(TokenKind::Path(PathBuf::from(buffer)), span));
+        }
+
+        if !buffer.is_empty() {
+            parts.push(Token::new(
+                TokenKind::String(buffer),
+                (segment_start, self.pos.saturating_sub(segment_start)).into(),
+            ));
+        }
+
+        Ok(Token::new(TokenKind::InterpolatedPath(parts), span))
+    }
+
+    /// Scans the next token, skipping whitespace and comments.
+    fn next_token(&mut self) -> Result<Option<Token>, Error> {
+        macro_rules! push_token {
+            ($token:ident, $len:expr) => {{
+                let token = Token::new(TokenKind::$token, (self.pos, $len).into());
+                self.pos = self.pos.saturating_add($len);
+                self.chars.next();
+                return Ok(Some(token));
+            }};
+        }
+
+        while let Some(&ch) = self.chars.peek() {
             match ch {
                 // Whitespace
                 ' ' | '\t' | '\n' | '\r' => {
-                    chars.next();
+                    self.chars.next();
                     self.pos = self.pos.saturating_add(1);
                 }
+                // Doc comments
+                '#' if self.chars.clone().nth(1) == Some('#') => {
+                    let start = self.pos;
+                    self.chars.next();
+                    self.chars.next();
+                    self.pos = self.pos.saturating_add(2);
+
+                    let mut text = String::new();
+                    while let Some(&ch) = self.chars.peek()
+                        && ch != '\n'
+                    {
+                        text.push(ch);
+                        self.chars.next();
+                        self.pos = self.pos.saturating_add(ch.len_utf8());
+                    }
+
+                    let text = text.strip_prefix(' ').unwrap_or(&text).trim_end();
+                    return Ok(Some(Token::new(
+                        TokenKind::DocComment(text.to_string()),
+                        (start, self.pos.saturating_sub(start)).into(),
+                    )));
+                }
                 // Comments / Slash operator
                 '/' => {
                     // Look ahead to distinguish between comment vs path
-                    if let Some(next_ch) = chars.clone().nth(1) {
+                    if let Some(next_ch) = self.chars.clone().nth(1) {
                         if next_ch == '/' {
-                            chars.next();
-                            chars.next();
+                            self.chars.next();
+                            self.chars.next();
                             self.pos = self.pos.saturating_add(2);
-                            while let Some(&ch) = chars.peek() {
+                            while let Some(&ch) = self.chars.peek() {
                                 if ch == '\n' {
                                     break;
                                 }
-                                chars.next();
-                                self.pos = self.pos.saturating_add(1);
+                                self.chars.next();
+                                self.pos = self.pos.saturating_add(ch.len_utf8());
                             }
                             continue;
                         }
 
                         if next_ch == ' ' {
                             push_token!(Slash, 1);
-                            continue;
                         }
 
-                        let pos_start = self.pos;
-
-                        let path_token = {
-                            let chars: &mut Peekable<Chars<'_>> = &mut chars;
-                            let mut path_buf = String::new();
-                            let mut interpolated_tokens = Vec::new();
-                            let mut start_interpolation = false;
-
-                            while let Some(&ch) = chars.peek() {
-                                match ch {
-                                    '"' | ' ' | '\n' | '\t' | '\r' | ',' | ')' | '}' | ']' => break,
-                                    '$' if chars.clone().nth(1) == Some('{') => {
-                                        if !path_buf.is_empty() {
-                                            interpolated_tokens.push(Token::new(
-                                                TokenKind::String(path_buf.clone()),
-                                                (self.pos, path_buf.len()).into(),
-                                            ));
-                                            path_buf.clear();
-                                        }
-
-                                        // Consume `${`
-                                        chars.next();
-                                        chars.next();
-                                        self.pos = self.pos.saturating_add(2);
-                                        start_interpolation = true;
-
-                                        let mut nested = String::new();
-                                        let mut depth: i32 = 1;
-                                        for nch in chars.by_ref() {
-                                            self.pos = self.pos.saturating_add(1);
-                                            match nch {
-                                                '{' => depth = depth.saturating_add(1),
-                                                '}' => {
-                                                    depth = depth.saturating_sub(1);
-                                                    if depth == 0 {
-                                                        break;
-                                                    }
-                                                }
-                                                _ => {}
-                                            }
-                                            nested.push(nch);
-                                        }
-
-                                        let mut nested_lexer = Self {
-                                            source: NamedSource::new(self.source.name(), nested),
-                                            pos: self.pos,
-                                        };
-                                        let nested = nested_lexer.tokenize()?;
-                                        interpolated_tokens.extend(nested);
-                                    }
-                                    _ => {
-                                        path_buf.push(ch);
-                                        chars.next();
-                                        self.pos = self.pos.saturating_add(1);
-                                    }
-                                }
-                            }
-
-                            if start_interpolation {
-                                if !path_buf.is_empty() {
-                                    let len = path_buf.len();
-                                    interpolated_tokens.push(Token::new(
-                                        TokenKind::String(path_buf),
-                                        (pos_start, len).into(),
-                                    ));
-                                }
-                                Ok(TokenKind::InterpolatedPath(interpolated_tokens))
-                            } else {
-                                Ok(TokenKind::Path(PathBuf::from(path_buf)))
-                            }
-                        }?;
-
-                        tokens.push(Token::new(
-                            path_token,
-                            (pos_start, self.pos.saturating_sub(pos_start)).into(),
-                        ));
+                        return self.scan_path().map(Some);
                     }
                 }
 
@@ -158,93 +521,63 @@ impl Lexer {
 
                 // Binary operators
                 '+' => push_token!(Plus, 1),
-                '*' => push_token!(Multiply, 1),
+                '*' => {
+                    if self.chars.clone().nth(1) == Some('*') {
+                        self.chars.next();
+                        push_token!(Power, 2);
+                    } else {
+                        push_token!(Multiply, 1);
+                    }
+                }
                 '%' => push_token!(Modulo, 1),
 
                 // Misc
                 ',' => push_token!(Comma, 1),
-                ':' => push_token!(Colon, 1),
+                ':' => {
+                    if self.chars.clone().nth(1) == Some(':') {
+                        self.chars.next();
+                        push_token!(DoubleColon, 2);
+                    } else {
+                        push_token!(Colon, 1);
+                    }
+                }
+                '?' => push_token!(Question, 1),
                 '.' => {
-                    if let Some(next_ch) = chars.clone().nth(1)
-                        && matches!(next_ch, '/' | '.')
+                    if self.chars.clone().nth(1) == Some('.')
+                        && self.chars.clone().nth(2) == Some('.')
+                        && self
+                            .chars
+                            .clone()
+                            .nth(3)
+                            .is_some_and(|ch| ch.is_alphabetic() || ch == '_')
                     {
-                        let pos_start = self.pos;
-
-                        let path_token = {
-                            let mut path_buf = String::new();
-                            let mut interpolated_tokens = Vec::new();
-                            let mut start_interpolation = false;
-
-                            while let Some(&ch) = chars.peek() {
-                                match ch {
-                                    '"' | ' ' | '\n' | '\t' | ',' | ')' | '}' | ']' => break,
-                                    '$' if chars.clone().nth(1) == Some('{') => {
-                                        // Flush current path segment if any
-                                        if !path_buf.is_empty() {
-                                            interpolated_tokens.push(Token::new(
-                                                TokenKind::String(path_buf.clone()),
-                                                (self.pos, path_buf.len()).into(),
-                                            ));
-                                            path_buf.clear();
-                                        }
-
-                                        // Consume `${`
-                                        chars.next();
-                                        chars.next();
-                                        self.pos = self.pos.saturating_add(2);
-                                        start_interpolation = true;
-
-                                        let mut nested = String::new();
-                                        let mut depth: i32 = 1;
-                                        for nch in chars.by_ref() {
-                                            self.pos = self.pos.saturating_add(1);
-                                            match nch {
-                                                '{' => depth = depth.saturating_add(1),
-                                                '}' => {
-                                                    depth = depth.saturating_sub(1);
-                                                    if depth == 0 {
-                                                        break;
-                                                    }
-                                                }
-                                                _ => {}
-                                            }
-                                            nested.push(nch);
-                                        }
-
-                                        let mut nested_lexer = Self {
-                                            source: NamedSource::new(self.source.name(), nested),
-                                            pos: self.pos,
-                                        };
-                                        let nested = nested_lexer.tokenize()?;
-                                        interpolated_tokens.extend(nested);
-                                    }
-                                    _ => {
-                                        path_buf.push(ch);
-                                        chars.next();
-                                        self.pos = self.pos.saturating_add(1);
-                                    }
-                                }
-                            }
+                        self.chars.next();
+                        self.chars.next();
+                        push_token!(Spread, 3);
+                    }
 
-                            if start_interpolation {
-                                if !path_buf.is_empty() {
-                                    let len = path_buf.len();
-                                    interpolated_tokens.push(Token::new(
-                                        TokenKind::String(path_buf),
-                                        (pos_start, len).into(),
-                                    ));
-                                }
-                                Ok(TokenKind::InterpolatedPath(interpolated_tokens))
-                            } else {
-                                Ok(TokenKind::Path(PathBuf::from(path_buf)))
-                            }
-                        }?;
+                    // `..` followed by `/`, a delimiter or whitespace is a path to the parent directory
+                    if self.chars.clone().nth(1) == Some('.')
+                        && self
+                            .chars
+                            .clone()
+                            .nth(2)
+                            .is_some_and(|ch| is_valid_char(ch, false) || matches!(ch, '-' | '('))
+                    {
+                        self.chars.next();
 
-                        tokens.push(Token::new(
-                            path_token,
-                            (pos_start, self.pos.saturating_sub(pos_start)).into(),
-                        ));
-                        continue;
+                        if self.chars.clone().nth(1) == Some('=') {
+                            self.chars.next();
+                            push_token!(RangeInclusive, 3);
+                        } else {
+                            push_token!(Range, 2);
+                        }
+                    }
+
+                    if let Some(next_ch) = self.chars.clone().nth(1)
+                        && matches!(next_ch, '/' | '.')
+                    {
+                        return self.scan_path().map(Some);
                     }
 
                     push_token!(Dot, 1);
@@ -254,119 +587,21 @@ impl Lexer {
                 #[allow(clippy::range_minus_one, reason = "Exclusive ranges can not be used")]
                 '"' => {
                     let original_pos = self.pos;
-                    let mut start = self.pos;
-                    let mut closed = false;
-                    let mut values = Vec::new();
-                    let mut buffer = String::new();
 
-                    chars.next();
+                    self.chars.next();
                     self.pos = self.pos.saturating_add(1);
 
-                    while let Some(&ch) = chars.peek() {
-                        match ch {
-                            '"' => {
-                                chars.next();
-                                self.pos = self.pos.saturating_add(1);
-                                closed = true;
-                                break;
-                            }
-
-                            '\\' => {
-                                chars.next();
-                                self.pos = self.pos.saturating_add(1);
-                                if let Some(&escaped_char) = chars.peek() {
-                                    buffer.push(escape(escaped_char));
-                                    chars.next();
-                                    self.pos = self.pos.saturating_add(1);
-                                }
-                            }
-
-                            '$' => {
-                                if chars.clone().nth(1) == Some('{') {
-                                    if !buffer.is_empty() {
-                                        values.push(Token::new(
-                                            TokenKind::String(buffer.clone()),
-                                            (start.saturating_add(1), buffer.len()).into(),
-                                        ));
-                                        buffer.clear();
-                                    }
-
-                                    // Consume `${`
-                                    chars.next();
-                                    chars.next();
-                                    self.pos = self.pos.saturating_add(2);
-
-                                    let nested_start = self.pos;
-                                    let mut nested_depth: i32 = 1;
-                                    let mut nested_content = String::new();
-
-                                    for nested_char in &mut chars {
-                                        self.pos = self.pos.saturating_add(1);
-
-                                        match nested_char {
-                                            '{' => nested_depth = nested_depth.saturating_add(1),
-                                            '}' => {
-                                                nested_depth = nested_depth.saturating_sub(1);
-                                                if nested_depth == 0 {
-                                                    start = self.pos;
-                                                    break;
-                                                }
-                                            }
-                                            _ => {}
-                                        }
-
-                                        nested_content.push(nested_char);
-                                    }
-
-                                    if nested_depth != 0 {
-                                        return Err(Error::new(
-                                            ErrorKind::UnclosedInterpolation,
-                                            self.source.clone(),
-                                            (nested_start, self.pos.saturating_sub(nested_start))
-                                                .into(),
-                                        ));
-                                    }
-
-                                    let mut nested_lexer = Self {
-                                        source: NamedSource::new(
-                                            self.source.name(),
-                                            nested_content,
-                                        ),
-                                        pos: nested_start,
-                                    };
-
-                                    let nested_tokens = nested_lexer.tokenize()?;
-
-                                    if nested_tokens.len() == 1 {
-                                        values.extend(nested_tokens);
-                                    } else {
-                                        values.push(Token::new(
-                                            TokenKind::InterpolatedString(nested_tokens),
-                                            (nested_start, self.pos.saturating_sub(nested_start))
-                                                .into(),
-                                        ));
-                                    }
-                                } else {
-                                    buffer.push('$');
-                                    chars.next();
-                                    self.pos = self.pos.saturating_add(1);
-                                }
-                            }
-
-                            _ => {
-                                buffer.push(ch);
-                                chars.next();
-                                self.pos = self.pos.saturating_add(1);
-                            }
-                        }
-                    }
-
-                    if !buffer.is_empty() {
-                        values.push(Token::new(
-                            TokenKind::String(buffer.clone()),
-                            (start, self.pos.saturating_sub(start.saturating_add(1))).into(),
-                        ));
-                    }
+                    let StringBody {
+                        values,
+                        buffer,
+                        closed,
+                    } = Lexer::scan_string_body(
+                        self.source,
+                        &mut self.pos,
+                        &mut self.chars,
+                        true,
+                        self.depth,
+                    )?;
 
                     if !closed {
                         return Err(Error::new(
@@ -377,15 +612,15 @@ impl Lexer {
                     }
 
                     if values.len() <= 1 {
-                        tokens.push(Token::new(
+                        return Ok(Some(Token::new(
                             TokenKind::String(buffer),
-                            (start, self.pos.saturating_sub(start)).into(),
-                        ));
+                            (original_pos, self.pos.saturating_sub(original_pos)).into(),
+                        )));
                     } else {
-                        tokens.push(Token::new(
+                        return Ok(Some(Token::new(
                             TokenKind::InterpolatedString(values),
                             (original_pos, self.pos.saturating_sub(original_pos)).into(),
-                        ));
+                        )));
                     }
                 }
 
@@ -393,15 +628,20 @@ impl Lexer {
                 _ if ch.is_ascii_digit() || ch == '.' || ch == '-' => {
                     let mut value = String::new();
 
-                    while let Some(&ch) = chars.peek()
+                    while let Some(&ch) = self.chars.peek()
                         && (ch.is_ascii_digit() || ch == '.' || ch == '-')
                     {
+                        // `..` starts a range, e.g. `1..10`
+                        if ch == '.' && self.chars.clone().nth(1) == Some('.') {
+                            break;
+                        }
+
                         value.push(ch);
-                        chars.next();
+                        self.chars.next();
 
                         // If character is a bang break and the next character is not a equals.
                         // This is to properly handle the `Not` token.
-                        if ch == '!' && chars.peek() != Some(&'=') {
+                        if ch == '!' && self.chars.peek() != Some(&'=') {
                             break;
                         }
                     }
@@ -410,7 +650,7 @@ impl Lexer {
                     match value.as_str() {
                         "-" => push_token!(Minus, 1),
                         _ if value.parse::<i64>().is_ok() => {
-                            tokens.push(Token::new(
+                            return Ok(Some(Token::new(
                                 TokenKind::Int(value.parse::<isize>().map_err(|error| {
                                     Error::new(
                                         ErrorKind::ParseIntError(error),
@@ -419,10 +659,18 @@ impl Lexer {
                                     )
                                 })?),
                                 (self.pos.saturating_sub(value.len()), value.len()).into(),
-                            ));
+                            )));
+                        }
+                        // Integers that don't fit into an `isize`
+                        #[cfg(feature = "bigint")]
+                        _ if let Ok(v) = value.parse::<num_bigint::BigInt>() => {
+                            return Ok(Some(Token::new(
+                                TokenKind::BigInt(v),
+                                (self.pos.saturating_sub(value.len()), value.len()).into(),
+                            )));
                         }
                         _ if value.parse::<f64>().is_ok() => {
-                            tokens.push(Token::new(
+                            return Ok(Some(Token::new(
                                 TokenKind::Float(value.parse::<f64>().map_err(|error| {
                                     Error::new(
                                         ErrorKind::ParseFloatError(error),
@@ -431,7 +679,7 @@ impl Lexer {
                                     )
                                 })?),
                                 (self.pos.saturating_sub(value.len()), value.len()).into(),
-                            ));
+                            )));
                         }
                         _ => (),
                     }
@@ -441,31 +689,31 @@ impl Lexer {
                 _ if is_valid_char(ch, false) => {
                     let mut value = String::new();
 
-                    while let Some(&ch) = chars.peek()
+                    while let Some(&ch) = self.chars.peek()
                         && is_valid_char(ch, false)
                     {
                         value.push(ch);
-                        chars.next();
+                        self.chars.next();
 
                         // If character is a bang break and the next character is not a equals.
                         // This is to properly handle the `Not` token.
-                        if ch == '!' && chars.peek() != Some(&'=') {
+                        if ch == '!' && self.chars.peek() != Some(&'=') {
                             break;
                         }
                     }
 
                     macro_rules! push_long_token {
                         ($token:ident) => {{
-                            tokens.push(Token::new(
+                            return Ok(Some(Token::new(
                                 TokenKind::$token,
                                 (self.pos.saturating_sub(value.len()), value.len()).into(),
-                            ));
+                            )));
                         }};
                         ($token:ident($value:expr)) => {{
-                            tokens.push(Token::new(
+                            return Ok(Some(Token::new(
                                 TokenKind::$token($value),
                                 (self.pos.saturating_sub(value.len()), value.len()).into(),
-                            ));
+                            )));
                         }};
                     }
 
@@ -481,6 +729,7 @@ impl Lexer {
                         // Keywords
                         "let" => push_long_token!(Let),
                         "in" => push_long_token!(In),
+                        "for" => push_long_token!(For),
 
                         // Logic operators
                         "==" => push_long_token!(Eq),
@@ -503,14 +752,33 @@ impl Lexer {
                     return Err(Error::new(
                         ErrorKind::UnexpectedToken,
                         self.source.clone(),
-                        SourceSpan::new(self.pos.saturating_sub(1).into(), 1),
+                        SourceSpan::new(self.pos.into(), ch.len_utf8()),
                     ));
                 }
             }
         }
 
-        Ok(tokens)
+        Ok(None)
+    }
+}
+
+fn is_valid_char(ch: char, dots: bool) -> bool {
+    if ch == '.' && !dots {
+        return false;
     }
+
+    ch.is_alphanumeric() || matches!(ch, '_' | '.' | '=' | '!' | '<' | '>' | '&' | '|')
+}
+
+/// The source after the byte offset `pos`.
+fn rest(source: &NamedSource<String>, pos: usize) -> &str {
+    source.inner().get(pos..).unwrap_or_default()
+}
+
+/// Whether `chars` is at a `\${`.
+fn is_escaped_interpolation(chars: &Peekable<Chars<'_>>) -> bool {
+    let mut chars = chars.clone();
+    chars.next() == Some('\\') && chars.next() == Some('$') && chars.next() == Some('{')
 }
 
 fn escape(ch: char) -> char {
@@ -17,6 +17,17 @@ impl Token {
     pub fn new(kind: TokenKind, span: SourceSpan) -> Self {
         Self { kind, span }
     }
+
+    /// The text of the token in the source it was lexed from.
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        source
+            .get(self.span.offset()..self.span.offset().saturating_add(self.span.len()))
+            .unwrap_or_default()
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        matches!(self.kind, TokenKind::Whitespace(_) | TokenKind::Comment(_))
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -27,6 +38,8 @@ pub enum TokenKind {
     InterpolatedPath(Vec<Token>),
     Path(PathBuf),
     Int(isize),
+    #[cfg(feature = "bigint")]
+    BigInt(num_bigint::BigInt),
     Float(f64),
     Bool(bool),
     Null,
@@ -37,6 +50,7 @@ pub enum TokenKind {
     // Keywords
     Let,
     In,
+    For,
 
     // Logic Operators
     /// ==
@@ -65,6 +79,12 @@ pub enum TokenKind {
     Multiply,
     Slash,
     Modulo,
+    /// **
+    Power,
+    /// ..
+    Range,
+    /// ..=
+    RangeInclusive,
 
     // Brackets
     /// (
@@ -84,7 +104,20 @@ pub enum TokenKind {
     Equals,
     Comma,
     Colon,
+    /// ::
+    DoubleColon,
     Dot,
+    /// ...
+    Spread,
+    /// ?
+    Question,
+    /// `## text`, documents the binding or object key after it
+    DocComment(String),
+
+    // Trivia, only produced by [`lex`](super::lex)
+    Whitespace(String),
+    /// `// text`, without the leading slashes
+    Comment(String),
 }
 
 impl TokenKind {
@@ -97,6 +130,9 @@ impl TokenKind {
                 | Self::Multiply
                 | Self::Slash
                 | Self::Modulo
+                | Self::Power
+                | Self::Range
+                | Self::RangeInclusive
 
                 // Logic Operators
                 | Self::Eq
@@ -111,6 +147,11 @@ impl TokenKind {
     }
 
     pub fn is_number(&self) -> bool {
+        #[cfg(feature = "bigint")]
+        if matches!(self, Self::BigInt(_)) {
+            return true;
+        }
+
         matches!(self, Self::Int(_) | Self::Float(_))
     }
 }
@@ -125,6 +166,8 @@ impl Display for TokenKind {
             Self::InterpolatedPath(_) => write!(f, "interpolated path"),
             Self::Path(v) => write!(f, "{}", v.display()),
             Self::Int(v) => write!(f, "{v}"),
+            #[cfg(feature = "bigint")]
+            Self::BigInt(v) => write!(f, "{v}"),
             Self::Float(v) => write!(f, "{v}"),
             Self::Bool(v) => write!(f, "{v}"),
             Self::Null => write!(f, "null"),
@@ -135,6 +178,7 @@ impl Display for TokenKind {
             // Keywords
             Self::Let => write!(f, "let"),
             Self::In => write!(f, "in"),
+            Self::For => write!(f, "for"),
 
             // Logic Operators
             Self::Eq => write!(f, "=="),
@@ -153,6 +197,9 @@ impl Display for TokenKind {
             Self::Multiply => write!(f, "*"),
             Self::Slash => write!(f, "/"),
             Self::Modulo => write!(f, "%"),
+            Self::Power => write!(f, "**"),
+            Self::Range => write!(f, ".."),
+            Self::RangeInclusive => write!(f, "..="),
 
             // Brackets
             Self::LParen => write!(f, "("),
@@ -166,7 +213,13 @@ impl Display for TokenKind {
             Self::Equals => write!(f, "="),
             Self::Comma => write!(f, ","),
             Self::Colon => write!(f, ":"),
+            Self::DoubleColon => write!(f, "::"),
             Self::Dot => write!(f, "."),
+            Self::Spread => write!(f, "..."),
+            Self::Question => write!(f, "?"),
+            Self::DocComment(v) => write!(f, "## {v}"),
+            Self::Whitespace(v) => write!(f, "{v}"),
+            Self::Comment(v) => write!(f, "//{v}"),
         }
     }
 }
@@ -177,8 +230,10 @@ pub type Error = crate::Error<ErrorKind>;
 #[error("Lexer error")]
 pub enum ErrorKind {
     #[error(transparent)]
+    #[diagnostic(code(tl::parser::lexer::invalid_int))]
     ParseIntError(#[from] ParseIntError),
     #[error(transparent)]
+    #[diagnostic(code(tl::parser::lexer::invalid_float))]
     ParseFloatError(#[from] ParseFloatError),
 
     #[error("Unclosed string literal")]
@@ -188,11 +243,19 @@ pub enum ErrorKind {
     #[diagnostic(code(tl::parser::lexer::unclosed_interpolation))]
     UnclosedInterpolation,
 
+    #[error("Interpolations nested more than {max} levels deep")]
+    #[diagnostic(
+        code(tl::parser::lexer::interpolation_too_deep),
+        help("Move the inner interpolations into `let` bindings")
+    )]
+    InterpolationTooDeep { max: usize },
+
     #[error("Unexpected token")]
     #[diagnostic(code(tl::parser::lexer::unexpected_token))]
     UnexpectedToken,
 
     #[error(transparent)]
+    #[diagnostic(code(tl::parser::lexer::io))]
     IO(#[from] io::Error),
 }
 
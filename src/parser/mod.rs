@@ -1,20 +1,59 @@
-use crate::parser::{
-    ast::types::{Error, ErrorKind},
-    lexer::Lexer,
+use crate::{
+    Options,
+    parser::{
+        ast::types::{DuplicateKey, Error, ErrorKind, Expr},
+        lexer::Lexer,
+    },
 };
 use miette::NamedSource;
 
 pub mod ast;
 pub mod lexer;
 
+/// Parses a source with the default [`Options`].
 /// # Errors
 /// This function will return an error if either the tokenization or AST generation fails.
 pub fn parse(source: &NamedSource<String>) -> ast::ExprResult {
-    let mut lexer = Lexer::new(source.clone());
+    parse_with_options(source, &Options::default())
+}
+
+/// # Errors
+/// This function will return an error if either the tokenization or AST generation fails.
+pub fn parse_with_options(source: &NamedSource<String>, options: &Options) -> ast::ExprResult {
+    parse_with_duplicate_keys(source, options).map(|(ast, _)| ast)
+}
+
+/// Parses a source like [`parse_with_options`], also returning the keys of object literals that are assigned twice,
+/// see [`Parser::duplicate_keys`](ast::Parser::duplicate_keys).
+/// # Errors
+/// This function will return an error if either the tokenization or AST generation fails.
+pub fn parse_with_duplicate_keys(
+    source: &NamedSource<String>,
+    options: &Options,
+) -> Result<(Expr, Vec<DuplicateKey>), Error> {
+    let mut lexer = Lexer::new(source.clone()).with_max_depth(options.max_depth);
     let tokens = lexer.tokenize().map_err(|err| {
         let span = err.span;
         Error::new(ErrorKind::TokenizationError(err), source.clone(), span)
     })?;
 
-    ast::Parser::new(tokens, source.clone()).parse()
+    let mut parser = ast::Parser::new(tokens, source.clone()).with_max_depth(options.max_depth);
+    let ast = parser.parse()?;
+
+    Ok((ast, parser.duplicate_keys().to_vec()))
+}
+
+/// Parses a template, where the whole source is treated like the inside of a string literal.
+/// # Errors
+/// This function will return an error if either the tokenization or AST generation of an interpolation fails.
+pub fn parse_template(source: &NamedSource<String>, options: &Options) -> ast::ExprResult {
+    let mut lexer = Lexer::new(source.clone()).with_max_depth(options.max_depth);
+    let token = lexer.tokenize_template().map_err(|err| {
+        let span = err.span;
+        Error::new(ErrorKind::TokenizationError(err), source.clone(), span)
+    })?;
+
+    ast::Parser::new(vec![token], source.clone())
+        .with_max_depth(options.max_depth)
+        .parse()
 }
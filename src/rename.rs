@@ -0,0 +1,316 @@
+//! Renaming a binding and its references, without changing anything else in the source.
+
+use crate::{
+    lint::binding_span,
+    parser::ast::types::{Expr, ExprKind, Pattern},
+    runtime::{self, BUILTINS_OBJECT},
+    xref::{Binding, BindingKind, CrossReference, Location, cross_reference},
+};
+use miette::{Diagnostic, NamedSource, SourceSpan};
+use std::collections::HashMap;
+use thiserror::Error;
+
+const KEYWORDS: &[&str] = &["null", "true", "false", "let", "in", "for"];
+
+#[derive(Error, Diagnostic, Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    #[error("`{name}` is not a valid identifier")]
+    #[diagnostic(
+        code(tl::rename::invalid_name),
+        help(
+            "Identifiers start with a letter or `_`, followed by letters, digits or `_`, and can not be a keyword"
+        )
+    )]
+    InvalidName { name: String },
+
+    #[error("There is no binding named `{name}`")]
+    #[diagnostic(code(tl::rename::not_found))]
+    NotFound { name: String },
+
+    #[error("There are {count} bindings named `{name}`")]
+    #[diagnostic(
+        code(tl::rename::ambiguous),
+        help("Rename a single binding by its position with `rename_at`")
+    )]
+    Ambiguous { name: String, count: usize },
+
+    #[error("`{name}` is already used in this file")]
+    #[diagnostic(
+        code(tl::rename::collision),
+        help("Renaming could make references resolve to a different binding, pick another name")
+    )]
+    Collision {
+        name: String,
+        #[label("Used here")]
+        existing: SourceSpan,
+    },
+
+    #[error("`{name}` is a builtin")]
+    #[diagnostic(code(tl::rename::builtin))]
+    Builtin { name: String },
+
+    #[error("`{name}` is bound by an object pattern")]
+    #[diagnostic(
+        code(tl::rename::object_pattern),
+        help(
+            "The name of the binding is also the field it reads, renaming it would read another field"
+        )
+    )]
+    ObjectPattern {
+        name: String,
+        #[label("Bound here")]
+        pattern: SourceSpan,
+    },
+
+    #[error(
+        "`{name}` is passed as a named argument to a call that can't be traced to its function"
+    )]
+    #[diagnostic(
+        code(tl::rename::named_argument),
+        help(
+            "Only calls of a function bound with `let` by its name are renamed with its parameters"
+        )
+    )]
+    NamedArgument {
+        name: String,
+        #[label("Passed here")]
+        argument: SourceSpan,
+    },
+}
+
+/// Renames the only binding named `old` to `new`, see [`rename_at`].
+/// # Errors
+/// This function will return an error if there is not exactly one binding named `old`, or if [`rename_at`] fails.
+pub fn rename(
+    source: &NamedSource<String>,
+    ast: &Expr,
+    old: &str,
+    new: &str,
+) -> Result<String, RenameError> {
+    let xref = cross_reference(source, ast);
+    let mut bindings = xref.bindings.iter().filter(|binding| binding.name == old);
+
+    match (bindings.next(), bindings.count()) {
+        (None, _) => Err(RenameError::NotFound {
+            name: old.to_string(),
+        }),
+        (Some(binding), 0) => apply(source, ast, &xref, binding, new),
+        (Some(_), others) => Err(RenameError::Ambiguous {
+            name: old.to_string(),
+            count: others.saturating_add(1),
+        }),
+    }
+}
+
+/// Renames the binding that is defined or referenced at a byte offset, and every reference to it.
+///
+/// Only the spans of the binding and its references change, the rest of the source stays the same.
+/// Renaming a parameter also renames the named arguments that pass it, like `x` in `f(x = 1)`.
+/// Renaming is refused if `new` is a keyword, a builtin, or a name that is already bound or referenced
+/// anywhere in the file, as references could resolve to another binding afterwards.
+/// # Errors
+/// This function will return an error if there is no binding at `offset` or the rename is refused.
+pub fn rename_at(
+    source: &NamedSource<String>,
+    ast: &Expr,
+    offset: usize,
+    new: &str,
+) -> Result<String, RenameError> {
+    let xref = cross_reference(source, ast);
+    let binding = xref
+        .binding_at(offset)
+        .ok_or_else(|| RenameError::NotFound {
+            name: source
+                .inner()
+                .get(offset..)
+                .unwrap_or_default()
+                .chars()
+                .take_while(|char| char.is_alphanumeric() || *char == '_')
+                .collect(),
+        })?;
+
+    apply(source, ast, &xref, binding, new)
+}
+
+fn apply(
+    source: &NamedSource<String>,
+    ast: &Expr,
+    xref: &CrossReference,
+    binding: &Binding,
+    new: &str,
+) -> Result<String, RenameError> {
+    let name = new.to_string();
+    let mut chars = new.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|char| char.is_alphabetic() || char == '_')
+        && chars.all(|char| char.is_alphanumeric() || char == '_')
+        && !KEYWORDS.contains(&new);
+    if !valid {
+        return Err(RenameError::InvalidName { name });
+    }
+
+    if binding.kind == BindingKind::ObjectPattern {
+        return Err(RenameError::ObjectPattern {
+            name: binding.name.clone(),
+            pattern: span(binding.definition),
+        });
+    }
+
+    let is_builtin = new == BUILTINS_OBJECT
+        || runtime::Scope::builtins()
+            .iter()
+            .any(|builtin| builtin.name == new);
+    if is_builtin {
+        return Err(RenameError::Builtin { name });
+    }
+
+    let existing = xref
+        .bindings
+        .iter()
+        .filter(|other| other.name == new)
+        .map(|other| other.definition)
+        .chain(
+            xref.unresolved
+                .iter()
+                .filter(|reference| reference.name == new)
+                .map(|reference| reference.location),
+        )
+        .next();
+    if let Some(existing) = existing {
+        return Err(RenameError::Collision {
+            name,
+            existing: span(existing),
+        });
+    }
+
+    let arguments = match binding.kind {
+        BindingKind::Parameter => named_arguments(source, ast, xref, binding)?,
+        _ => Vec::new(),
+    };
+
+    let mut locations: Vec<_> = std::iter::once(binding.definition)
+        .chain(binding.references.iter().copied())
+        .chain(arguments)
+        .collect();
+    locations.sort_by_key(|location| location.offset);
+
+    let text = source.inner();
+    let mut renamed = String::with_capacity(text.len());
+    let mut end = 0;
+    for location in locations {
+        let Some(before) = text.get(end..location.offset) else {
+            continue;
+        };
+        renamed.push_str(before);
+
+        // A span that does not cover the old name is left alone instead of corrupting the source
+        let after = location.offset.saturating_add(location.length);
+        match text.get(location.offset..after) {
+            Some(old) if old == binding.name => renamed.push_str(new),
+            Some(old) => renamed.push_str(old),
+            None => continue,
+        }
+        end = after;
+    }
+    renamed.push_str(text.get(end..).unwrap_or_default());
+
+    Ok(renamed)
+}
+
+/// The named arguments like `f(x = 1)` that pass `parameter`, so they are renamed with it.
+/// Calls are traced through the name of a `let` binding of the function, a named argument with the name of the
+/// parameter that is passed to any other call might be passed to the function as well, so it refuses the rename.
+fn named_arguments(
+    source: &NamedSource<String>,
+    ast: &Expr,
+    xref: &CrossReference,
+    parameter: &Binding,
+) -> Result<Vec<Location>, RenameError> {
+    let mut exprs = Vec::new();
+    let mut stack = vec![ast];
+    while let Some(expr) = stack.pop() {
+        exprs.push(expr);
+        stack.extend(expr.children());
+    }
+
+    // The innermost function that declares the parameter, its parameters are written before its body
+    let function = exprs
+        .iter()
+        .filter(|expr| match &expr.kind {
+            ExprKind::FnDecl {
+                args, expr: body, ..
+            } => {
+                args.contains(&parameter.name)
+                    && expr.span.offset() <= parameter.definition.offset
+                    && parameter.definition.offset < body.span.offset()
+            }
+            _ => false,
+        })
+        .min_by_key(|expr| expr.span.len())
+        .map(|expr| expr.span);
+
+    // The references of `let` bindings of functions are calls that can be traced, by the span of the function
+    let callees: HashMap<usize, SourceSpan> = exprs
+        .iter()
+        .filter_map(|expr| match &expr.kind {
+            ExprKind::LetIn { bindings, .. } => Some(bindings),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|(pattern, value)| match (pattern, &value.kind) {
+            (Pattern::Identifier(name), ExprKind::FnDecl { .. }) => {
+                let definition = binding_span(source.inner(), name, value.span).offset();
+                let binding = xref.bindings.iter().find(|binding| {
+                    binding.name == *name && binding.definition.offset == definition
+                })?;
+                Some((binding, value.span))
+            }
+            _ => None,
+        })
+        .flat_map(|(binding, function)| {
+            binding
+                .references
+                .iter()
+                .map(move |reference| (reference.offset, function))
+        })
+        .collect();
+
+    let mut arguments = Vec::new();
+    for expr in exprs {
+        let ExprKind::Call {
+            base, named_args, ..
+        } = &expr.kind
+        else {
+            continue;
+        };
+
+        let callee = match base.kind {
+            ExprKind::Identifier(_) => callees.get(&base.span.offset()).copied(),
+            _ => None,
+        };
+        // Calls of other functions keep their arguments
+        if callee.is_some() && callee != function {
+            continue;
+        }
+
+        for argument in named_args
+            .iter()
+            .filter(|argument| argument.name == parameter.name)
+        {
+            if callee.is_none() {
+                return Err(RenameError::NamedArgument {
+                    name: parameter.name.clone(),
+                    argument: argument.span,
+                });
+            }
+            arguments.push(Location::new(source, argument.span));
+        }
+    }
+
+    Ok(arguments)
+}
+
+fn span(location: Location) -> SourceSpan {
+    SourceSpan::new(location.offset.into(), location.length)
+}
@@ -0,0 +1,130 @@
+//! How reports of errors are rendered, for terminals that can't show the default unicode and colors.
+
+use miette::{Diagnostic, GraphicalReportHandler, GraphicalTheme, ThemeCharacters, ThemeStyles};
+use std::{env, fmt};
+
+/// The colors of a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Color {
+    /// Monochrome, for dumb terminals, serial consoles and log files.
+    None,
+    /// The 16 ANSI colors, which follow the theme of the terminal.
+    #[default]
+    Ansi,
+    /// Fixed RGB colors.
+    Rgb,
+}
+
+/// How reports are rendered, see [`ReportStyle::install`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportStyle {
+    /// Draw labels and boxes with unicode characters instead of ASCII.
+    pub unicode: bool,
+    pub color: Color,
+    /// How many lines around a label are shown.
+    pub context_lines: usize,
+    /// Adds a link to the documentation of the error code to every report, the code is appended to this URL
+    /// after a `/`, like `https://example.org/errors/tl::runtime::division_by_zero`.
+    pub docs_url: Option<String>,
+}
+
+impl Default for ReportStyle {
+    fn default() -> Self {
+        Self {
+            unicode: true,
+            color: Color::Ansi,
+            context_lines: 1,
+            docs_url: None,
+        }
+    }
+}
+
+impl ReportStyle {
+    /// Monochrome ASCII, which renders anywhere.
+    pub fn plain() -> Self {
+        Self {
+            unicode: false,
+            color: Color::None,
+            ..Self::default()
+        }
+    }
+
+    /// The default style, or [`ReportStyle::plain`] if `TERM` is `dumb`. Colors are off if `NO_COLOR` is set.
+    pub fn from_env() -> Self {
+        let mut style = if env::var_os("TERM").is_some_and(|term| term == "dumb") {
+            Self::plain()
+        } else {
+            Self::default()
+        };
+        if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            style.color = Color::None;
+        }
+
+        style
+    }
+
+    pub fn handler(&self) -> ReportHandler {
+        let theme = GraphicalTheme {
+            characters: if self.unicode {
+                ThemeCharacters::unicode()
+            } else {
+                ThemeCharacters::ascii()
+            },
+            styles: match self.color {
+                Color::None => ThemeStyles::none(),
+                Color::Ansi => ThemeStyles::ansi(),
+                Color::Rgb => ThemeStyles::rgb(),
+            },
+        };
+
+        ReportHandler {
+            graphical: GraphicalReportHandler::new_themed(theme)
+                .with_context_lines(self.context_lines)
+                .with_links(false),
+            docs_url: self.docs_url.clone(),
+        }
+    }
+
+    /// Renders every [`miette::Report`] of the process in this style.
+    /// # Errors
+    /// This function will return an error if the report handler was already set.
+    pub fn install(self) -> Result<(), miette::InstallError> {
+        miette::set_hook(Box::new(move |_| Box::new(self.handler())))
+    }
+}
+
+/// A [`miette::ReportHandler`] for a [`ReportStyle`].
+#[derive(Debug, Clone)]
+pub struct ReportHandler {
+    graphical: GraphicalReportHandler,
+    docs_url: Option<String>,
+}
+
+impl ReportHandler {
+    /// Renders `diagnostic` without installing the handler.
+    pub fn render(&self, diagnostic: &dyn Diagnostic) -> String {
+        let mut out = String::new();
+        let _ = self.render_to(&mut out, diagnostic);
+        out
+    }
+
+    fn render_to(&self, f: &mut impl fmt::Write, diagnostic: &dyn Diagnostic) -> fmt::Result {
+        self.graphical.render_report(f, diagnostic)?;
+
+        if let (Some(url), Some(code)) = (&self.docs_url, diagnostic.code()) {
+            writeln!(f, "\n  docs: {}/{code}", url.trim_end_matches('/'))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl miette::ReportHandler for ReportHandler {
+    fn debug(&self, diagnostic: &dyn Diagnostic, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return fmt::Debug::fmt(diagnostic, f);
+        }
+
+        self.render_to(f, diagnostic)
+    }
+}
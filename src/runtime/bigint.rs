@@ -0,0 +1,133 @@
+use super::{
+    ValueKind,
+    types::{MAX_BIG_INT_BITS, cmp_fraction},
+};
+use num_bigint::{BigInt, Sign};
+use num_traits::{FromPrimitive, One, ToPrimitive};
+use std::cmp::Ordering;
+
+impl ValueKind {
+    /// Creates an integer, only using a [`BigInt`] if the value doesn't fit into an `isize`.
+    pub fn from_big_int(value: BigInt) -> Self {
+        isize::try_from(&value).map_or_else(|_| Self::BigInt(value), Self::Int)
+    }
+
+    pub fn as_big_int(&self) -> Option<BigInt> {
+        match self {
+            Self::Int(v) => Some(BigInt::from(*v)),
+            Self::BigInt(v) => Some(v.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Int(v) => Some(*v as f64),
+            Self::BigInt(v) => v.to_f64(),
+            Self::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn is_big_int(&self) -> bool {
+        matches!(self, Self::BigInt(_))
+    }
+}
+
+/// Evaluates an integer operation, promoting the result to a [`BigInt`] if it overflows an `isize`.
+///
+/// Returns `None` if the operation should be handled by the regular `isize`/`f64` implementation,
+/// that is if no operand is a big integer and the operation doesn't overflow, or the operands aren't numbers.
+pub(super) fn promote(
+    lhs: &ValueKind,
+    rhs: &ValueKind,
+    checked: impl Fn(isize, isize) -> Option<isize>,
+    big: impl Fn(BigInt, BigInt) -> Option<BigInt>,
+    float: impl Fn(f64, f64) -> f64,
+) -> Option<ValueKind> {
+    match (lhs, rhs) {
+        (ValueKind::Int(lhs), ValueKind::Int(rhs)) if checked(*lhs, *rhs).is_some() => None,
+        (ValueKind::Int(_) | ValueKind::BigInt(_), ValueKind::Int(_) | ValueKind::BigInt(_)) => {
+            big(lhs.as_big_int()?, rhs.as_big_int()?).map(ValueKind::from_big_int)
+        }
+        (ValueKind::BigInt(_), ValueKind::Float(_))
+        | (ValueKind::Float(_), ValueKind::BigInt(_)) => {
+            Some(ValueKind::Float(float(lhs.as_f64()?, rhs.as_f64()?)))
+        }
+        _ => None,
+    }
+}
+
+/// Raises `base` to `exponent` like [`promote`], if either is a big integer or the result overflows an `isize`.
+/// Exponents that don't fit into a `u32` only have a result for the bases -1, 0 and 1,
+/// [`pow_too_large`] rejects the others before.
+pub(super) fn pow(base: &ValueKind, exponent: &ValueKind) -> Option<ValueKind> {
+    match (base, exponent) {
+        (ValueKind::Int(base), ValueKind::Int(exponent))
+            if u32::try_from(*exponent)
+                .ok()
+                .and_then(|exponent| base.checked_pow(exponent))
+                .is_some() =>
+        {
+            None
+        }
+        (ValueKind::Int(_) | ValueKind::BigInt(_), ValueKind::Int(_) | ValueKind::BigInt(_)) => {
+            let (base_int, exponent_int) = (base.as_big_int()?, exponent.as_big_int()?);
+            if exponent_int.sign() == Sign::Minus {
+                return Some(ValueKind::Float(base.as_f64()?.powf(exponent.as_f64()?)));
+            }
+
+            Some(match u32::try_from(&exponent_int) {
+                Ok(exponent) => ValueKind::from_big_int(base_int.pow(exponent)),
+                // Only the parity of the exponent matters for -1
+                Err(_) if base_int == BigInt::from(-1) && !exponent_int.bit(0) => ValueKind::Int(1),
+                Err(_) if base_int.magnitude().is_one() || base_int.sign() == Sign::NoSign => {
+                    ValueKind::Int(isize::try_from(&base_int).ok()?)
+                }
+                Err(_) => ValueKind::Float(base.as_f64()?.powf(exponent.as_f64()?)),
+            })
+        }
+        (ValueKind::BigInt(_), ValueKind::Float(_))
+        | (ValueKind::Float(_), ValueKind::BigInt(_)) => {
+            Some(ValueKind::Float(base.as_f64()?.powf(exponent.as_f64()?)))
+        }
+        _ => None,
+    }
+}
+
+/// Whether raising `base` to `exponent` results in an integer with more than [`MAX_BIG_INT_BITS`] bits.
+pub(super) fn pow_too_large(base: &ValueKind, exponent: &ValueKind) -> bool {
+    let (Some(base), Some(exponent)) = (base.as_big_int(), exponent.as_big_int()) else {
+        return false;
+    };
+    if exponent.sign() != Sign::Plus || base.magnitude().bits() < 2 {
+        return false;
+    }
+
+    // The result has at least `exponent` bits for every bit of the base after the first
+    exponent.to_u64().is_none_or(|exponent| {
+        base.magnitude()
+            .bits()
+            .saturating_sub(1)
+            .saturating_mul(exponent)
+            > MAX_BIG_INT_BITS
+    })
+}
+
+/// Compares a big integer with a float exactly, like [`cmp_int_with_float`](super::types::cmp_int_with_float).
+pub(super) fn cmp_with_float(int: &BigInt, float: f64) -> Ordering {
+    if float.is_nan() {
+        return if float.is_sign_negative() {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        };
+    }
+
+    match BigInt::from_f64(float.trunc()) {
+        Some(whole) => int.cmp(&whole).then_with(|| cmp_fraction(float)),
+        // Infinities
+        None if float > 0.0 => Ordering::Less,
+        None => Ordering::Greater,
+    }
+}
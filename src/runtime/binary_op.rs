@@ -1,9 +1,14 @@
-use super::{ValueResult, types::Value};
+use super::{
+    ValueResult,
+    types::{Error, ErrorKind, MAX_ARRAY_LENGTH, MAX_STRING_LENGTH, Value},
+};
 use crate::{
     merge_spans,
     parser::ast::types::{BinaryOperator, Expr},
     runtime::ValueKind,
 };
+use miette::SourceSpan;
+use std::cmp::Ordering;
 
 impl super::Scope {
     pub(super) fn eval_binary_op(
@@ -15,38 +20,199 @@ impl super::Scope {
         let lhs = self.eval_expr(left)?;
         let rhs = self.eval_expr(right)?;
 
+        self.apply_binary_op(left, operator, right, lhs, rhs)
+    }
+
+    /// Applies `operator` to the evaluated operands. Kept out of [`Self::eval_binary_op`] so its large frame
+    /// isn't on the stack while the operands of long chains like `1 + 1 + 1` are evaluated.
+    #[inline(never)]
+    fn apply_binary_op(
+        &self,
+        left: &Expr,
+        operator: &BinaryOperator,
+        right: &Expr,
+        lhs: Value,
+        rhs: Value,
+    ) -> ValueResult {
+        let divides_by_zero =
+            matches!(operator, BinaryOperator::Divide | BinaryOperator::Modulo) && rhs.is_zero();
+
+        if divides_by_zero && !self.options.lenient_division {
+            return Err(Error::new(
+                ErrorKind::DivisionByZero {
+                    divisor: right.span,
+                },
+                self.source.clone(),
+                right.span,
+            ));
+        }
+
+        if *operator == BinaryOperator::Plus
+            && let Some(frozen) = lhs.frozen
+            && let (ValueKind::Object(target), ValueKind::Object(fields)) = (&lhs.kind, &rhs.kind)
+            && let Some((key, value)) = fields.iter().find(|(key, _)| target.contains_key(*key))
+        {
+            return Err(Error::new(
+                ErrorKind::FrozenOverride {
+                    key: key.clone(),
+                    frozen,
+                    overridden: value.span,
+                },
+                self.source.clone(),
+                right.span,
+            ));
+        }
+
+        let lhs_type = lhs.type_of();
+        let rhs_type = rhs.type_of();
+
+        if matches!(
+            operator,
+            BinaryOperator::Range | BinaryOperator::RangeInclusive
+        ) {
+            return match (&lhs.kind, &rhs.kind) {
+                (ValueKind::Int(start), ValueKind::Int(end)) => {
+                    let inclusive = *operator == BinaryOperator::RangeInclusive;
+                    let span = merge_spans(lhs.span, rhs.span);
+
+                    if range_len(*start, *end, inclusive) > MAX_ARRAY_LENGTH {
+                        return Err(Error::new(
+                            ErrorKind::TooLarge {
+                                limit: MAX_ARRAY_LENGTH,
+                                unit: "items".to_string(),
+                                operation: span,
+                            },
+                            self.source.clone(),
+                            span,
+                        ));
+                    }
+
+                    Ok(range(*start, *end, inclusive, span))
+                }
+                _ => Err(Error::new(
+                    ErrorKind::MismatchedOperandTypes {
+                        operator: operator.to_string(),
+                        lhs_type: lhs_type.into(),
+                        rhs_type: rhs_type.into(),
+                        lhs: left.span,
+                        rhs: right.span,
+                    },
+                    self.source.clone(),
+                    self.operator_span(left, operator, right),
+                )),
+            };
+        }
+
+        if *operator == BinaryOperator::Multiply
+            && let (ValueKind::String(text), ValueKind::Int(count)) = (&lhs.kind, &rhs.kind)
+            && text
+                .len()
+                .saturating_mul(usize::try_from(*count).unwrap_or_default())
+                > MAX_STRING_LENGTH
+        {
+            let span = merge_spans(lhs.span, rhs.span);
+
+            return Err(Error::new(
+                ErrorKind::TooLarge {
+                    limit: MAX_STRING_LENGTH,
+                    unit: "bytes".to_string(),
+                    operation: span,
+                },
+                self.source.clone(),
+                span,
+            ));
+        }
+
+        #[cfg(feature = "bigint")]
+        if *operator == BinaryOperator::Power && super::bigint::pow_too_large(&lhs.kind, &rhs.kind)
+        {
+            let span = merge_spans(lhs.span, rhs.span);
+
+            return Err(Error::new(
+                ErrorKind::TooLarge {
+                    limit: usize::try_from(super::types::MAX_BIG_INT_BITS).unwrap_or(usize::MAX),
+                    unit: "bits".to_string(),
+                    operation: span,
+                },
+                self.source.clone(),
+                span,
+            ));
+        }
+
+        let comparable = lhs.kind.is_comparable_with(&rhs.kind);
+
+        // Big integers can't overflow
+        if self.options.checked_arithmetic
+            && cfg!(not(feature = "bigint"))
+            && let (ValueKind::Int(lhs_int), ValueKind::Int(rhs_int)) = (&lhs.kind, &rhs.kind)
+            && let Some(result) = checked_int_op(operator, *lhs_int, *rhs_int)
+        {
+            let span = merge_spans(lhs.span, rhs.span);
+
+            return match result {
+                Some(result) => Ok(Value::new(ValueKind::Int(result), span)),
+                None => {
+                    let operation = self.operator_span(left, operator, right);
+
+                    Err(Error::new(
+                        ErrorKind::IntegerOverflow { operation },
+                        self.source.clone(),
+                        operation,
+                    ))
+                }
+            };
+        }
+
+        // Values of different types have a total order for sorting, but operators only compare numbers and strings
+        let ordering = if comparable {
+            lhs.cmp(&rhs)
+        } else {
+            Ordering::Equal
+        };
+
         #[allow(
             clippy::arithmetic_side_effects,
             reason = "Arthimetic operation implementations for `Value` uses saturating ops where it can."
         )]
-        Ok(match operator {
+        let value = match operator {
             BinaryOperator::Plus => lhs + rhs,
             BinaryOperator::Minus => lhs - rhs,
             BinaryOperator::Multiply => lhs * rhs,
             BinaryOperator::Divide => lhs / rhs,
             BinaryOperator::Modulo => lhs % rhs,
+            BinaryOperator::Power => lhs.pow(rhs),
+            // Ranges are evaluated before
+            BinaryOperator::Range | BinaryOperator::RangeInclusive => {
+                return Err(Error::new(
+                    ErrorKind::Internal {
+                        message: format!("`{operator}` was evaluated like an arithmetic operator"),
+                    },
+                    self.source.clone(),
+                    self.operator_span(left, operator, right),
+                ));
+            }
             BinaryOperator::Eq => Value::new(
-                ValueKind::Boolean(lhs == rhs),
+                ValueKind::Boolean(lhs.loose_eq(&rhs)),
                 merge_spans(lhs.span, rhs.span),
             ),
             BinaryOperator::NotEq => Value::new(
-                ValueKind::Boolean(lhs != rhs),
+                ValueKind::Boolean(!lhs.loose_eq(&rhs)),
                 merge_spans(lhs.span, rhs.span),
             ),
             BinaryOperator::Gt => Value::new(
-                ValueKind::Boolean(lhs > rhs),
+                ValueKind::Boolean(ordering.is_gt()),
                 merge_spans(lhs.span, rhs.span),
             ),
             BinaryOperator::GtEq => Value::new(
-                ValueKind::Boolean(lhs >= rhs),
+                ValueKind::Boolean(ordering.is_ge()),
                 merge_spans(lhs.span, rhs.span),
             ),
             BinaryOperator::Lt => Value::new(
-                ValueKind::Boolean(lhs < rhs),
+                ValueKind::Boolean(ordering.is_lt()),
                 merge_spans(lhs.span, rhs.span),
             ),
             BinaryOperator::LtEq => Value::new(
-                ValueKind::Boolean(lhs <= rhs),
+                ValueKind::Boolean(ordering.is_le()),
                 merge_spans(lhs.span, rhs.span),
             ),
             BinaryOperator::And => Value::new(
@@ -57,6 +223,84 @@ impl super::Scope {
                 ValueKind::Boolean(lhs.or(&rhs)),
                 merge_spans(lhs.span, rhs.span),
             ),
-        })
+        };
+
+        // Unsupported arithmetic evaluates to null and unsupported comparisons are always equal
+        let unsupported =
+            (operator.is_arithmetic() && matches!(value.kind, ValueKind::Null) && !divides_by_zero)
+                || (operator.is_ordering() && !comparable);
+
+        if self.options.strict_types && unsupported {
+            return Err(Error::new(
+                ErrorKind::MismatchedOperandTypes {
+                    operator: operator.to_string(),
+                    lhs_type: lhs_type.into(),
+                    rhs_type: rhs_type.into(),
+                    lhs: left.span,
+                    rhs: right.span,
+                },
+                self.source.clone(),
+                self.operator_span(left, operator, right),
+            ));
+        }
+
+        Ok(value)
+    }
+
+    /// Finds the span of `operator` in the source between its two operands.
+    fn operator_span(&self, left: &Expr, operator: &BinaryOperator, right: &Expr) -> SourceSpan {
+        let start = left.span.offset().saturating_add(left.span.len());
+        let end = right.span.offset();
+        let operator = operator.to_string();
+
+        self.source
+            .inner()
+            .get(start..end)
+            .and_then(|between| between.find(&operator))
+            .map_or_else(
+                || SourceSpan::new(start.into(), end.saturating_sub(start)),
+                |offset| SourceSpan::new(start.saturating_add(offset).into(), operator.len()),
+            )
+    }
+}
+
+/// The number of integers from `start` up to `end`, saturating at `usize::MAX`.
+fn range_len(start: isize, end: isize, inclusive: bool) -> usize {
+    if end < start {
+        return 0;
+    }
+
+    end.abs_diff(start).saturating_add(usize::from(inclusive))
+}
+
+/// Creates an array of the integers from `start` up to `end`, empty if `end` comes before `start`.
+fn range(start: isize, end: isize, inclusive: bool, span: SourceSpan) -> Value {
+    let values = if inclusive {
+        (start..=end)
+            .map(|value| Value::new(ValueKind::Int(value), span))
+            .collect()
+    } else {
+        (start..end)
+            .map(|value| Value::new(ValueKind::Int(value), span))
+            .collect()
+    };
+
+    Value::new(ValueKind::Array(values), span)
+}
+
+/// Evaluates integer operations that can overflow, returning `None` if `operator` can't overflow
+/// for these operands and `Some(None)` if the operation overflowed.
+fn checked_int_op(operator: &BinaryOperator, lhs: isize, rhs: isize) -> Option<Option<isize>> {
+    match operator {
+        BinaryOperator::Plus => Some(lhs.checked_add(rhs)),
+        BinaryOperator::Minus => Some(lhs.checked_sub(rhs)),
+        BinaryOperator::Multiply => Some(lhs.checked_mul(rhs)),
+        // Negative exponents result in floats and -1, 0 and 1 can be raised to any power
+        BinaryOperator::Power if rhs >= 0 && !(-1..=1).contains(&lhs) => Some(
+            u32::try_from(rhs)
+                .ok()
+                .and_then(|exponent| lhs.checked_pow(exponent)),
+        ),
+        _ => None,
     }
 }
@@ -0,0 +1,49 @@
+use super::types::{Value, ValueKind};
+use std::collections::BTreeMap;
+
+/// Assembles an object [`Value`] field by field, for host code that defines inputs with [`Scope::define`](super::Scope::define).
+#[derive(Debug, Clone, Default)]
+pub struct ValueBuilder {
+    fields: BTreeMap<String, Value>,
+}
+
+impl ValueBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets `key` to `value`, replacing an earlier value of the same key.
+    #[must_use]
+    pub fn field(mut self, key: impl ToString, value: impl Into<Value>) -> Self {
+        self.fields.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Sets `key` to an object built by `build`.
+    #[must_use]
+    pub fn object(self, key: impl ToString, build: impl FnOnce(Self) -> Self) -> Self {
+        let object = build(Self::new());
+        self.field(key, object)
+    }
+
+    /// Sets `key` to an array of `items`.
+    #[must_use]
+    pub fn array<T: Into<Value>>(
+        self,
+        key: impl ToString,
+        items: impl IntoIterator<Item = T>,
+    ) -> Self {
+        let items: Vec<Value> = items.into_iter().map(Into::into).collect();
+        self.field(key, items)
+    }
+
+    pub fn build(self) -> Value {
+        self.into()
+    }
+}
+
+impl From<ValueBuilder> for ValueKind {
+    fn from(val: ValueBuilder) -> Self {
+        Self::Object(val.fields)
+    }
+}
@@ -0,0 +1,132 @@
+use super::BuiltinInfo;
+use crate::runtime::types::{Error, NativeFnCtx, Value, ValueKind, ValueResult};
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[
+    BuiltinInfo {
+        name: "sort",
+        params: &[("array", "array")],
+        doc: "Sorts an array in ascending order, values of different types are ordered by their type.",
+        feature: None,
+        func: sort,
+    },
+    BuiltinInfo {
+        name: "toSet",
+        params: &[("array", "array")],
+        doc: "Removes the items that are equal to an item before them, like `deepEq` compares them.",
+        feature: None,
+        func: to_set,
+    },
+    BuiltinInfo {
+        name: "union",
+        params: &[("lhs", "array"), ("rhs", "array")],
+        doc: "The items that are in either array, without duplicates.",
+        feature: None,
+        func: union,
+    },
+    BuiltinInfo {
+        name: "intersection",
+        params: &[("lhs", "array"), ("rhs", "array")],
+        doc: "The items of `lhs` that are also in `rhs`, without duplicates.",
+        feature: None,
+        func: intersection,
+    },
+    BuiltinInfo {
+        name: "difference",
+        params: &[("lhs", "array"), ("rhs", "array")],
+        doc: "The items of `lhs` that are not in `rhs`, without duplicates.",
+        feature: None,
+        func: difference,
+    },
+    BuiltinInfo {
+        name: "symmetricDifference",
+        params: &[("lhs", "array"), ("rhs", "array")],
+        doc: "The items that are in only one of the arrays, without duplicates.",
+        feature: None,
+        func: symmetric_difference,
+    },
+];
+
+fn sort(ctx: NativeFnCtx) -> ValueResult {
+    let mut array = ctx.ensure_is_array(ctx.get_arg_evaluated(0, 1)?)?;
+    array.data.sort();
+
+    Ok(Value::new(ValueKind::Array(array.data), ctx.expr.span))
+}
+
+fn to_set(ctx: NativeFnCtx) -> ValueResult {
+    let array = ctx.ensure_is_array(ctx.get_arg_evaluated(0, 1)?)?;
+
+    Ok(Value::new(
+        ValueKind::Array(dedup(array.data)),
+        ctx.expr.span,
+    ))
+}
+
+fn union(ctx: NativeFnCtx) -> ValueResult {
+    let (mut lhs, rhs) = operands(&ctx)?;
+    lhs.extend(rhs);
+
+    Ok(Value::new(ValueKind::Array(dedup(lhs)), ctx.expr.span))
+}
+
+fn intersection(ctx: NativeFnCtx) -> ValueResult {
+    let (lhs, rhs) = operands(&ctx)?;
+    let items = dedup(lhs)
+        .into_iter()
+        .filter(|item| contains(&rhs, item))
+        .collect();
+
+    Ok(Value::new(ValueKind::Array(items), ctx.expr.span))
+}
+
+fn difference(ctx: NativeFnCtx) -> ValueResult {
+    let (lhs, rhs) = operands(&ctx)?;
+
+    Ok(Value::new(
+        ValueKind::Array(without(lhs, &rhs)),
+        ctx.expr.span,
+    ))
+}
+
+fn symmetric_difference(ctx: NativeFnCtx) -> ValueResult {
+    let (lhs, rhs) = operands(&ctx)?;
+
+    let mut items = without(lhs.clone(), &rhs);
+    items.extend(without(rhs, &lhs));
+
+    Ok(Value::new(ValueKind::Array(items), ctx.expr.span))
+}
+
+/// Evaluates the two arrays of a set operation.
+fn operands(ctx: &NativeFnCtx) -> Result<(Vec<Value>, Vec<Value>), Error> {
+    let lhs = ctx.ensure_is_array(ctx.get_arg_evaluated(0, 2)?)?;
+    let rhs = ctx.ensure_is_array(ctx.get_arg_evaluated(1, 2)?)?;
+
+    Ok((lhs.data, rhs.data))
+}
+
+fn contains(items: &[Value], value: &Value) -> bool {
+    items.iter().any(|item| item.deep_eq(value))
+}
+
+/// Keeps the first of equal items, in the order of `items`.
+/// Values have no hash and only a total order for sorting, so the items are compared with each other.
+fn dedup(items: Vec<Value>) -> Vec<Value> {
+    let mut unique: Vec<Value> = Vec::with_capacity(items.len());
+
+    for item in items {
+        if !contains(&unique, &item) {
+            unique.push(item);
+        }
+    }
+
+    unique
+}
+
+/// The items of `items` that are not in `removed`, without duplicates.
+fn without(items: Vec<Value>, removed: &[Value]) -> Vec<Value> {
+    dedup(items)
+        .into_iter()
+        .filter(|item| !contains(removed, item))
+        .collect()
+}
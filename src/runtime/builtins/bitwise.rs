@@ -0,0 +1,117 @@
+use super::BuiltinInfo;
+use crate::runtime::types::{Error, ErrorKind, NativeFnCtx, Value, ValueKind, ValueResult};
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[
+    BuiltinInfo {
+        name: "band",
+        params: &[("lhs", "int"), ("rhs", "int")],
+        doc: "Bitwise and of two integers.",
+        feature: None,
+        func: band,
+    },
+    BuiltinInfo {
+        name: "bor",
+        params: &[("lhs", "int"), ("rhs", "int")],
+        doc: "Bitwise or of two integers.",
+        feature: None,
+        func: bor,
+    },
+    BuiltinInfo {
+        name: "bxor",
+        params: &[("lhs", "int"), ("rhs", "int")],
+        doc: "Bitwise exclusive or of two integers.",
+        feature: None,
+        func: bxor,
+    },
+    BuiltinInfo {
+        name: "bnot",
+        params: &[("value", "int")],
+        doc: "Flips every bit of an integer.",
+        feature: None,
+        func: bnot,
+    },
+    BuiltinInfo {
+        name: "shl",
+        params: &[("value", "int"), ("amount", "int")],
+        doc: "Shifts the bits of an integer to the left.",
+        feature: None,
+        func: shl,
+    },
+    BuiltinInfo {
+        name: "shr",
+        params: &[("value", "int"), ("amount", "int")],
+        doc: "Arithmetically shifts the bits of an integer to the right.",
+        feature: None,
+        func: shr,
+    },
+];
+
+fn band(ctx: NativeFnCtx) -> ValueResult {
+    int_op(&ctx, |lhs, rhs| lhs & rhs)
+}
+
+fn bor(ctx: NativeFnCtx) -> ValueResult {
+    int_op(&ctx, |lhs, rhs| lhs | rhs)
+}
+
+fn bxor(ctx: NativeFnCtx) -> ValueResult {
+    int_op(&ctx, |lhs, rhs| lhs ^ rhs)
+}
+
+fn bnot(ctx: NativeFnCtx) -> ValueResult {
+    let value = ctx.ensure_is_int(ctx.get_arg_evaluated(0, 1)?)?;
+
+    Ok(Value::new(ValueKind::Int(!value.data), ctx.expr.span))
+}
+
+fn shl(ctx: NativeFnCtx) -> ValueResult {
+    let (value, amount) = shift_args(&ctx)?;
+
+    // Shifting out every bit results in 0
+    Ok(Value::new(
+        ValueKind::Int(value.checked_shl(amount).unwrap_or(0)),
+        ctx.expr.span,
+    ))
+}
+
+fn shr(ctx: NativeFnCtx) -> ValueResult {
+    let (value, amount) = shift_args(&ctx)?;
+
+    // Arithmetic shift, shifting out every bit leaves only the sign
+    let shifted = value
+        .checked_shr(amount)
+        .unwrap_or(if value < 0 { -1 } else { 0 });
+
+    Ok(Value::new(ValueKind::Int(shifted), ctx.expr.span))
+}
+fn int_op(ctx: &NativeFnCtx, op: impl Fn(isize, isize) -> isize) -> ValueResult {
+    let lhs = ctx.ensure_is_int(ctx.get_arg_evaluated(0, 2)?)?;
+    let rhs = ctx.ensure_is_int(ctx.get_arg_evaluated(1, 2)?)?;
+
+    Ok(Value::new(
+        ValueKind::Int(op(lhs.data, rhs.data)),
+        ctx.expr.span,
+    ))
+}
+
+fn shift_args(ctx: &NativeFnCtx) -> Result<(isize, u32), Error> {
+    let value = ctx.ensure_is_int(ctx.get_arg_evaluated(0, 2)?)?;
+    let amount = ctx.ensure_is_int(ctx.get_arg_evaluated(1, 2)?)?;
+
+    let amount = match u32::try_from(amount.data) {
+        Ok(amount) => amount,
+        // Too big to fit into an `u32`, which shifts out every bit anyway
+        Err(_) if amount.data > 0 => u32::MAX,
+        Err(_) => {
+            let span = ctx.get_arg(1, 2)?.span;
+
+            return Err(Error::new(
+                ErrorKind::NegativeShift { amount: span },
+                ctx.source.clone(),
+                span,
+            ));
+        }
+    };
+
+    Ok((value.data, amount))
+}
@@ -0,0 +1,457 @@
+use super::BuiltinInfo;
+use crate::{
+    parser::ast::types::{Expr, ExprKind, Literal},
+    runtime::{
+        call::CallArgs,
+        import::import_file,
+        types::{Builtin, Error, ErrorKind, NativeFnCtx, Value, ValueKind, ValueResult},
+    },
+};
+use miette::SourceSpan;
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[
+    BuiltinInfo {
+        name: "if",
+        params: &[("condition", "any"), ("then", "any"), ("else", "any")],
+        doc: "Evaluates `then` if `condition` is truthy and `else` otherwise, only one of them is evaluated.",
+        feature: None,
+        func: r#if,
+    },
+    BuiltinInfo {
+        name: "maybe",
+        params: &[("value", "any"), ("fallback", "any")],
+        doc: "Returns `value` if it is truthy, otherwise evaluates `fallback`.",
+        feature: None,
+        func: maybe,
+    },
+    BuiltinInfo {
+        name: "orDefault",
+        params: &[("value", "any"), ("fallback", "any")],
+        doc: "Returns `value` unless it is `null`, otherwise evaluates `fallback`.",
+        feature: None,
+        func: or_default,
+    },
+    BuiltinInfo {
+        name: "isEmpty",
+        params: &[("value", "any")],
+        doc: "Whether a string, array or object has nothing in it, `null` is empty too.",
+        feature: None,
+        func: is_empty,
+    },
+    BuiltinInfo {
+        name: "default",
+        params: &[("value", "any"), ("fallback", "any")],
+        doc: "Returns `value` unless it is empty like `isEmpty` checks, otherwise evaluates `fallback`.",
+        feature: None,
+        func: default,
+    },
+    BuiltinInfo {
+        name: "coalesce",
+        params: &[("...values", "any")],
+        doc: "Returns the first argument that is not `null`, the arguments after it are not evaluated.",
+        feature: None,
+        func: coalesce,
+    },
+    BuiltinInfo {
+        name: "deepEq",
+        params: &[("a", "any"), ("b", "any")],
+        doc: "Whether two values are structurally equal, unlike `==` values of different types are never equal.",
+        feature: None,
+        func: deep_eq,
+    },
+    BuiltinInfo {
+        name: "import",
+        params: &[("path", "path")],
+        doc: "Evaluates a file, directories import their `default.tl`.",
+        feature: None,
+        func: import,
+    },
+    BuiltinInfo {
+        name: "importWith",
+        params: &[("path", "path"), ("overrides", "object")],
+        doc: "Calls the function a file evaluates to, with the fields of `overrides` as named arguments.",
+        feature: None,
+        func: import_with,
+    },
+    BuiltinInfo {
+        name: "memoize",
+        params: &[("function", "function")],
+        doc: "Wraps a function so that calls with the same arguments return the result of the first call.",
+        feature: None,
+        func: memoize,
+    },
+    BuiltinInfo {
+        name: "fix",
+        params: &[("function", "function")],
+        doc: "Returns the object that `function` returns when it is called with that object, so fields can refer to each other through the parameter. Fields are evaluated when they are accessed.",
+        feature: None,
+        func: fix,
+    },
+    BuiltinInfo {
+        name: "freeze",
+        params: &[("value", "any")],
+        doc: "Makes a value and everything nested in it read-only, merging fields into it with `+` that override its own is an error.",
+        feature: None,
+        func: freeze,
+    },
+];
+
+fn r#if(ctx: NativeFnCtx) -> ValueResult {
+    let args_len = 3;
+
+    let cond = ctx.get_arg(0, args_len)?;
+    let then_branch = ctx.get_arg(1, args_len)?;
+    let else_branch = ctx.get_arg(2, args_len)?;
+
+    let mut scope = ctx.new_scope();
+
+    let cond_value = scope.eval_expr(&cond)?;
+
+    if scope.is_condition_met(&cond_value, cond.span)? {
+        return scope.eval_expr(&then_branch);
+    }
+
+    scope.eval_expr(&else_branch)
+}
+
+fn maybe(ctx: NativeFnCtx) -> ValueResult {
+    let cond = ctx.get_arg(0, 2)?;
+    let then = ctx.get_arg(1, 2)?;
+
+    let mut scope = ctx.new_scope();
+
+    let cond = scope.eval_expr(&cond)?;
+
+    if cond.is_truthy() {
+        return Ok(cond);
+    }
+
+    scope.eval_expr(&then)
+}
+
+fn or_default(ctx: NativeFnCtx) -> ValueResult {
+    let value = ctx.get_arg(0, 2)?;
+    let fallback = ctx.get_arg(1, 2)?;
+
+    let mut scope = ctx.new_scope();
+
+    let value = scope.eval_expr(&value)?;
+
+    if matches!(value.kind, ValueKind::Null) {
+        return scope.eval_expr(&fallback);
+    }
+
+    Ok(value)
+}
+
+fn is_empty(ctx: NativeFnCtx) -> ValueResult {
+    let value = ctx.get_arg_evaluated(0, 1)?;
+
+    let Some(empty) = emptiness(&value.kind) else {
+        return Err(Error::new(
+            ErrorKind::MismatchedTypes {
+                expected: "string, array, object or null".to_string(),
+                got: value.type_of().into(),
+            },
+            ctx.source.clone(),
+            value.span,
+        ));
+    };
+
+    Ok(Value::new(ValueKind::Boolean(empty), ctx.expr.span))
+}
+
+fn default(ctx: NativeFnCtx) -> ValueResult {
+    let value = ctx.get_arg(0, 2)?;
+    let fallback = ctx.get_arg(1, 2)?;
+
+    let mut scope = ctx.new_scope();
+
+    let value = scope.eval_expr(&value)?;
+
+    // Numbers and booleans are never empty, `0` and `false` are meaningful settings
+    if emptiness(&value.kind) == Some(true) {
+        return scope.eval_expr(&fallback);
+    }
+
+    Ok(value)
+}
+
+fn coalesce(ctx: NativeFnCtx) -> ValueResult {
+    let mut scope = ctx.new_scope();
+
+    for arg in ctx.expr_args()? {
+        let value = scope.eval_expr(&arg)?;
+
+        if !matches!(value.kind, ValueKind::Null) {
+            return Ok(value);
+        }
+    }
+
+    Ok(Value::new(ValueKind::Null, ctx.expr.span))
+}
+
+/// Whether `value` is `null` or an empty string, array or object, `None` for values that can't be empty.
+fn emptiness(value: &ValueKind) -> Option<bool> {
+    match value {
+        ValueKind::Null => Some(true),
+        ValueKind::String(string) => Some(string.is_empty()),
+        ValueKind::Array(array) => Some(array.is_empty()),
+        ValueKind::Object(object) => Some(object.is_empty()),
+        _ => None,
+    }
+}
+
+fn deep_eq(ctx: NativeFnCtx) -> ValueResult {
+    let a = ctx.force(ctx.get_arg_evaluated(0, 2)?)?;
+    let b = ctx.force(ctx.get_arg_evaluated(1, 2)?)?;
+
+    Ok(Value::new(ValueKind::Boolean(a.deep_eq(&b)), ctx.expr.span))
+}
+
+fn freeze(ctx: NativeFnCtx) -> ValueResult {
+    let mut value = ctx.get_arg_evaluated(0, 1)?;
+    value.freeze(ctx.expr.span);
+
+    Ok(value)
+}
+
+fn import(ctx: NativeFnCtx) -> ValueResult {
+    import_file(&ctx, 1)
+}
+
+fn import_with(ctx: NativeFnCtx) -> ValueResult {
+    let module = import_file(&ctx, 2)?;
+    let overrides = ctx.ensure_is_object(ctx.get_arg_evaluated(1, 2)?)?;
+
+    let args = CallArgs {
+        positional: Vec::new(),
+        named: overrides
+            .data
+            .into_iter()
+            .map(|(name, value)| (name, overrides.span, value))
+            .collect(),
+        span: ctx.call_args_span(),
+    };
+
+    ctx.new_scope()
+        .call_function(module, "importWith", args, ctx.expr.span)
+}
+
+fn memoize(ctx: NativeFnCtx) -> ValueResult {
+    let function = ctx.get_arg_evaluated(0, 1)?;
+    ctx.ensure_is_function(function.clone())?;
+
+    let cache: RefCell<BTreeMap<Vec<CacheKey>, Value>> = RefCell::new(BTreeMap::new());
+    let memoized = move |ctx: NativeFnCtx| {
+        let positional = ctx
+            .expr_args_evaluated()?
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        // Calls with functions as arguments aren't cached, functions can't be told apart reliably
+        let key = positional
+            .iter()
+            .map(CacheKey::new)
+            .collect::<Option<Vec<_>>>();
+        if let Some(result) = key
+            .as_ref()
+            .and_then(|key| cache.borrow().get(key).cloned())
+        {
+            return Ok(result);
+        }
+
+        let args = CallArgs {
+            positional,
+            named: Vec::new(),
+            span: ctx.call_args_span(),
+        };
+        let result =
+            ctx.new_scope()
+                .call_function(function.clone(), "memoize", args, ctx.expr.span)?;
+
+        if let Some(key) = key {
+            cache.borrow_mut().insert(key, result.clone());
+        }
+        Ok(result)
+    };
+
+    Ok(Value::new(
+        ValueKind::Builtin(Builtin(Rc::new(memoized))),
+        ctx.expr.span,
+    ))
+}
+
+/// An argument of a memoized function, unlike [`Value`] it tells `1` and `1.0` apart.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum CacheKey {
+    Null,
+    Boolean(bool),
+    Int(isize),
+    #[cfg(feature = "bigint")]
+    BigInt(num_bigint::BigInt),
+    Float(u64),
+    String(String),
+    Path(std::path::PathBuf),
+    Array(Vec<CacheKey>),
+    Object(BTreeMap<String, CacheKey>),
+}
+
+impl CacheKey {
+    /// The key of `value`, `None` if it contains a function.
+    fn new(value: &Value) -> Option<Self> {
+        Some(match &value.kind {
+            ValueKind::Null => Self::Null,
+            ValueKind::Boolean(bool) => Self::Boolean(*bool),
+            ValueKind::Int(int) => Self::Int(*int),
+            #[cfg(feature = "bigint")]
+            ValueKind::BigInt(int) => Self::BigInt(int.clone()),
+            ValueKind::Float(float) => Self::Float(float.to_bits()),
+            ValueKind::String(string) => Self::String(string.clone()),
+            ValueKind::Path(path) => Self::Path(path.clone()),
+            ValueKind::Array(items) => {
+                Self::Array(items.iter().map(Self::new).collect::<Option<_>>()?)
+            }
+            ValueKind::Object(fields) => Self::Object(
+                fields
+                    .iter()
+                    .map(|(key, value)| Some((key.clone(), Self::new(value)?)))
+                    .collect::<Option<_>>()?,
+            ),
+            ValueKind::Function { .. } | ValueKind::Builtin(_) | ValueKind::Lazy(_) => return None,
+        })
+    }
+}
+
+/// The state of a `fix` call, every field is evaluated at most once.
+struct Fixpoint {
+    ctx: NativeFnCtx,
+    param: String,
+    fields: BTreeMap<String, Expr>,
+    values: RefCell<BTreeMap<String, Value>>,
+    /// The fields that are being evaluated, in the order they were accessed.
+    evaluating: RefCell<Vec<String>>,
+}
+
+fn fix(ctx: NativeFnCtx) -> ValueResult {
+    let function = ctx.get_arg_evaluated(0, 1)?;
+
+    let (param, fields, origin) = match &function.kind {
+        ValueKind::Function {
+            args,
+            rest: None,
+            expr,
+            origin,
+            ..
+        } => match (args.as_slice(), &expr.kind) {
+            ([param], ExprKind::Literal(Literal::Object(fields))) => {
+                (param.clone(), fields.clone(), origin.clone())
+            }
+            _ => return Err(invalid_fixpoint(&ctx, &function)),
+        },
+        _ => return Err(invalid_fixpoint(&ctx, &function)),
+    };
+
+    let span = ctx.expr.span;
+    // The fields resolve their paths against the file the function is defined in
+    let fixpoint = Rc::new(Fixpoint {
+        ctx: NativeFnCtx { origin, ..ctx },
+        param,
+        fields,
+        values: RefCell::new(BTreeMap::new()),
+        evaluating: RefCell::new(Vec::new()),
+    });
+
+    let values = fixpoint
+        .fields
+        .keys()
+        .map(|key| Ok((key.clone(), force_field(&fixpoint, key, span)?)))
+        .collect::<Result<_, Error>>()?;
+
+    // Fields can hold the object passed to the function, whose fields are still lazy
+    fixpoint
+        .ctx
+        .force(Value::new(ValueKind::Object(values), span))
+}
+
+fn invalid_fixpoint(ctx: &NativeFnCtx, function: &Value) -> Error {
+    Error::new(
+        ErrorKind::InvalidFixpoint {
+            function: function.span,
+            type_name: function.type_of().into(),
+        },
+        ctx.source.clone(),
+        ctx.expr.span,
+    )
+}
+
+/// The object passed to the function of a `fix` call, its fields are evaluated when they are accessed.
+fn self_object(fixpoint: &Rc<Fixpoint>) -> Value {
+    let span = fixpoint.ctx.expr.span;
+    let fields = fixpoint
+        .fields
+        .keys()
+        .map(|key| {
+            let fixpoint = Rc::clone(fixpoint);
+            let name = key.clone();
+            let thunk = move |ctx: NativeFnCtx| force_field(&fixpoint, &name, ctx.expr.span);
+
+            (
+                key.clone(),
+                Value::new(ValueKind::Lazy(Builtin(Rc::new(thunk))), span),
+            )
+        })
+        .collect();
+
+    Value::new(ValueKind::Object(fields), span)
+}
+
+/// Evaluates the field `key` of a `fix` call, accessed at `access`.
+fn force_field(fixpoint: &Rc<Fixpoint>, key: &str, access: SourceSpan) -> ValueResult {
+    if let Some(value) = fixpoint.values.borrow().get(key) {
+        return Ok(value.clone());
+    }
+    let Some(expr) = fixpoint.fields.get(key) else {
+        return Ok(Value::new(ValueKind::Null, access));
+    };
+
+    if let Some(start) = fixpoint
+        .evaluating
+        .borrow()
+        .iter()
+        .position(|field| field == key)
+    {
+        let cycle = fixpoint
+            .evaluating
+            .borrow()
+            .iter()
+            .skip(start)
+            .map(String::as_str)
+            .chain(std::iter::once(key))
+            .collect::<Vec<_>>()
+            .join(" -> ");
+
+        return Err(Error::new(
+            ErrorKind::InfiniteRecursion {
+                field: key.to_string(),
+                cycle,
+                definition: expr.span,
+            },
+            fixpoint.ctx.source.clone(),
+            access,
+        ));
+    }
+
+    fixpoint.evaluating.borrow_mut().push(key.to_string());
+    let mut scope = fixpoint.ctx.new_scope();
+    scope.define(&fixpoint.param, self_object(fixpoint));
+    let result = scope.eval_expr(expr);
+    fixpoint.evaluating.borrow_mut().pop();
+
+    let value = result?;
+    fixpoint
+        .values
+        .borrow_mut()
+        .insert(key.to_string(), value.clone());
+    Ok(value)
+}
@@ -0,0 +1,68 @@
+use super::BuiltinInfo;
+use crate::{
+    normalize_path,
+    runtime::types::{Error, ErrorKind, NativeFnCtx, Value, ValueKind, ValueResult},
+};
+use miette::SourceSpan;
+use std::{fs, path::Path};
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[
+    BuiltinInfo {
+        name: "writeFile",
+        params: &[("path", "path"), ("content", "string")],
+        doc: "Writes a string to a file inside of the write allow-list.",
+        feature: Some("fs-write"),
+        func: write_file,
+    },
+    BuiltinInfo {
+        name: "mkdirAll",
+        params: &[("path", "path")],
+        doc: "Creates a directory and all of its parents inside of the write allow-list.",
+        feature: Some("fs-write"),
+        func: mkdir_all,
+    },
+];
+
+fn write_file(ctx: NativeFnCtx) -> ValueResult {
+    ctx.ensure_deterministic("writeFile")?;
+    let path = ctx.ensure_is_path(ctx.get_arg_evaluated(0, 2)?)?;
+    let content = ctx.ensure_is_string(ctx.get_arg_evaluated(1, 2)?)?;
+
+    ensure_writable(&ctx, &path.data, path.span)?;
+    fs::write(&path.data, content.data)
+        .map_err(|err| Error::new(err.into(), ctx.source.clone(), path.span))?;
+
+    Ok(Value::new(ValueKind::Path(path.data), ctx.expr.span))
+}
+
+fn mkdir_all(ctx: NativeFnCtx) -> ValueResult {
+    ctx.ensure_deterministic("mkdirAll")?;
+    let path = ctx.ensure_is_path(ctx.get_arg_evaluated(0, 1)?)?;
+
+    ensure_writable(&ctx, &path.data, path.span)?;
+    fs::create_dir_all(&path.data)
+        .map_err(|err| Error::new(err.into(), ctx.source.clone(), path.span))?;
+
+    Ok(Value::new(ValueKind::Path(path.data), ctx.expr.span))
+}
+
+/// Checks that `path` is inside one of the directories of the write allow-list.
+fn ensure_writable(ctx: &NativeFnCtx, path: &Path, span: SourceSpan) -> Result<(), Error> {
+    let path = normalize_path(path);
+
+    let allowed = ctx
+        .options
+        .write_allow_list
+        .iter()
+        .any(|dir| path.starts_with(normalize_path(dir)));
+
+    if allowed {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorKind::WriteNotAllowed { path: span },
+            ctx.source.clone(),
+            span,
+        ))
+    }
+}
@@ -0,0 +1,423 @@
+use super::BuiltinInfo;
+use crate::{
+    merge_spans,
+    runtime::types::{
+        Error, ErrorKind, ExtractedValue, MAX_PRECISION, NativeFnCtx, Value, ValueKind,
+        ValueResult, round_float,
+    },
+};
+use miette::SourceSpan;
+use std::ops::{Add, Div, Mul};
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[
+    BuiltinInfo {
+        name: "div",
+        params: &[("lhs", "int"), ("rhs", "int")],
+        doc: "Integer division rounding towards negative infinity.",
+        feature: None,
+        func: div,
+    },
+    BuiltinInfo {
+        name: "round",
+        params: &[("value", "number"), ("digits", "int")],
+        doc: "Rounds a number to `digits` decimal places, integers are returned as-is.",
+        feature: None,
+        func: round,
+    },
+    BuiltinInfo {
+        name: "toFixed",
+        params: &[("digits", "int"), ("value", "number")],
+        doc: "Formats a number as a string with exactly `digits` decimal places.",
+        feature: None,
+        func: to_fixed,
+    },
+    BuiltinInfo {
+        name: "clamp",
+        params: &[("value", "number"), ("min", "number"), ("max", "number")],
+        doc: "Limits a number to the range from `min` to `max`.",
+        feature: None,
+        func: clamp,
+    },
+    BuiltinInfo {
+        name: "sign",
+        params: &[("value", "number")],
+        doc: "-1 for negative numbers, 1 for positive numbers and 0 for zero, floats stay floats.",
+        feature: None,
+        func: sign,
+    },
+    BuiltinInfo {
+        name: "gcd",
+        params: &[("lhs", "int"), ("rhs", "int")],
+        doc: "The greatest common divisor of two integers, which is never negative.",
+        feature: None,
+        func: gcd,
+    },
+    BuiltinInfo {
+        name: "lcm",
+        params: &[("lhs", "int"), ("rhs", "int")],
+        doc: "The least common multiple of two integers, which is never negative.",
+        feature: None,
+        func: lcm,
+    },
+    BuiltinInfo {
+        name: "sum",
+        params: &[("values", "array")],
+        doc: "Adds up an array of numbers, 0 for an empty array.",
+        feature: None,
+        func: sum,
+    },
+    BuiltinInfo {
+        name: "product",
+        params: &[("values", "array")],
+        doc: "Multiplies an array of numbers, 1 for an empty array.",
+        feature: None,
+        func: product,
+    },
+    BuiltinInfo {
+        name: "mean",
+        params: &[("values", "array")],
+        doc: "The arithmetic mean of an array of numbers as a float.",
+        feature: None,
+        func: mean,
+    },
+    BuiltinInfo {
+        name: "minOf",
+        params: &[("values", "array")],
+        doc: "The smallest number of an array.",
+        feature: None,
+        func: min_of,
+    },
+    BuiltinInfo {
+        name: "maxOf",
+        params: &[("values", "array")],
+        doc: "The biggest number of an array.",
+        feature: None,
+        func: max_of,
+    },
+];
+
+fn div(ctx: NativeFnCtx) -> ValueResult {
+    let lhs = ctx.ensure_is_int(ctx.get_arg_evaluated(0, 2)?)?;
+    let rhs = ctx.ensure_is_int(ctx.get_arg_evaluated(1, 2)?)?;
+
+    if rhs.data == 0 && !ctx.options.lenient_division {
+        let divisor = ctx.get_arg(1, 2)?.span;
+
+        return Err(Error::new(
+            ErrorKind::DivisionByZero { divisor },
+            ctx.source.clone(),
+            divisor,
+        ));
+    }
+
+    if ctx.options.checked_arithmetic && lhs.data == isize::MIN && rhs.data == -1 {
+        return Err(Error::new(
+            ErrorKind::IntegerOverflow {
+                operation: ctx.expr.span,
+            },
+            ctx.source.clone(),
+            ctx.expr.span,
+        ));
+    }
+
+    Ok(Value::new(
+        floor_div(lhs.data, rhs.data).map_or(ValueKind::Null, ValueKind::Int),
+        ctx.expr.span,
+    ))
+}
+
+fn round(ctx: NativeFnCtx) -> ValueResult {
+    let value = ctx.get_arg_evaluated(0, 2)?;
+    let digits = precision(&ctx, 1)?;
+
+    match value.kind {
+        ValueKind::Int(_) => Ok(Value::new(value.kind, ctx.expr.span)),
+        _ => {
+            let value = ctx.ensure_is_float(value)?;
+            Ok(Value::new(
+                ValueKind::Float(round_float(value.data, digits)),
+                ctx.expr.span,
+            ))
+        }
+    }
+}
+
+fn to_fixed(ctx: NativeFnCtx) -> ValueResult {
+    let digits = precision(&ctx, 0)?;
+    let value = ctx.get_arg_evaluated(1, 2)?;
+
+    let formatted = match value.kind {
+        ValueKind::Int(value) if digits == 0 => value.to_string(),
+        ValueKind::Int(value) => format!("{value}.{}", "0".repeat(digits)),
+        #[cfg(feature = "bigint")]
+        ValueKind::BigInt(value) if digits == 0 => value.to_string(),
+        #[cfg(feature = "bigint")]
+        ValueKind::BigInt(value) => format!("{value}.{}", "0".repeat(digits)),
+        _ => format!("{:.digits$}", ctx.ensure_is_float(value)?.data),
+    };
+
+    Ok(Value::new(ValueKind::String(formatted), ctx.expr.span))
+}
+
+fn clamp(ctx: NativeFnCtx) -> ValueResult {
+    let value = ensure_is_number(&ctx, ctx.get_arg_evaluated(0, 3)?)?;
+    let min = ensure_is_number(&ctx, ctx.get_arg_evaluated(1, 3)?)?;
+    let max = ensure_is_number(&ctx, ctx.get_arg_evaluated(2, 3)?)?;
+
+    if min > max {
+        let range = merge_spans(min.span, max.span);
+        return Err(Error::new(
+            ErrorKind::InvalidRange { range },
+            ctx.source.clone(),
+            range,
+        ));
+    }
+
+    let clamped = if value < min {
+        min
+    } else if value > max {
+        max
+    } else {
+        value
+    };
+
+    Ok(Value::new(clamped.kind, ctx.expr.span))
+}
+
+fn sign(ctx: NativeFnCtx) -> ValueResult {
+    let value = ensure_is_number(&ctx, ctx.get_arg_evaluated(0, 1)?)?;
+    let zero = Value::new(ValueKind::Int(0), value.span);
+
+    let sign = match value.kind {
+        ValueKind::Float(float) if float.is_nan() => ValueKind::Float(float),
+        ValueKind::Float(0.0) => ValueKind::Float(0.0),
+        ValueKind::Float(float) => ValueKind::Float(float.signum()),
+        _ => ValueKind::Int(match value.cmp(&zero) {
+            std::cmp::Ordering::Less => -1,
+            std::cmp::Ordering::Equal => 0,
+            std::cmp::Ordering::Greater => 1,
+        }),
+    };
+
+    Ok(Value::new(sign, ctx.expr.span))
+}
+
+fn gcd(ctx: NativeFnCtx) -> ValueResult {
+    let lhs = ctx.ensure_is_int(ctx.get_arg_evaluated(0, 2)?)?;
+    let rhs = ctx.ensure_is_int(ctx.get_arg_evaluated(1, 2)?)?;
+
+    int_result(&ctx, Some(unsigned_gcd(lhs.data, rhs.data)))
+}
+
+fn lcm(ctx: NativeFnCtx) -> ValueResult {
+    let lhs = ctx.ensure_is_int(ctx.get_arg_evaluated(0, 2)?)?;
+    let rhs = ctx.ensure_is_int(ctx.get_arg_evaluated(1, 2)?)?;
+
+    let lcm = match unsigned_gcd(lhs.data, rhs.data) {
+        0 => Some(0),
+        gcd => lhs
+            .data
+            .unsigned_abs()
+            .checked_div(gcd)
+            .and_then(|lhs| lhs.checked_mul(rhs.data.unsigned_abs())),
+    };
+
+    int_result(&ctx, lcm)
+}
+
+/// The greatest common divisor of the absolute values, which doesn't fit into an `isize` for `isize::MIN`.
+fn unsigned_gcd(lhs: isize, rhs: isize) -> usize {
+    let (mut lhs, mut rhs) = (lhs.unsigned_abs(), rhs.unsigned_abs());
+
+    while rhs != 0 {
+        (lhs, rhs) = (rhs, lhs.checked_rem(rhs).unwrap_or_default());
+    }
+
+    lhs
+}
+
+/// Converts the result of an unsigned operation back to an integer,
+/// `None` and results above `isize::MAX` overflow like the arithmetic operators do.
+fn int_result(ctx: &NativeFnCtx, result: Option<usize>) -> ValueResult {
+    match result.and_then(|result| isize::try_from(result).ok()) {
+        Some(result) => Ok(Value::new(ValueKind::Int(result), ctx.expr.span)),
+        None if ctx.options.checked_arithmetic => Err(Error::new(
+            ErrorKind::IntegerOverflow {
+                operation: ctx.expr.span,
+            },
+            ctx.source.clone(),
+            ctx.expr.span,
+        )),
+        None => Ok(Value::new(ValueKind::Int(isize::MAX), ctx.expr.span)),
+    }
+}
+
+fn sum(ctx: NativeFnCtx) -> ValueResult {
+    let values = ensure_numbers(&ctx)?;
+    fold_numbers(&ctx, values.data, 0, isize::checked_add, ValueKind::add)
+}
+
+fn product(ctx: NativeFnCtx) -> ValueResult {
+    let values = ensure_numbers(&ctx)?;
+    fold_numbers(&ctx, values.data, 1, isize::checked_mul, ValueKind::mul)
+}
+
+fn mean(ctx: NativeFnCtx) -> ValueResult {
+    let values = ensure_numbers(&ctx)?;
+    let len = ensure_not_empty(&ctx, "mean", &values.data, values.span)?;
+
+    let sum = fold_numbers(&ctx, values.data, 0, isize::checked_add, ValueKind::add)?;
+    let len = isize::try_from(len).map_or(ValueKind::Float(len as f64), ValueKind::Int);
+
+    Ok(Value::new(sum.kind.div(len), ctx.expr.span))
+}
+
+fn min_of(ctx: NativeFnCtx) -> ValueResult {
+    let values = ensure_numbers(&ctx)?;
+    ensure_not_empty(&ctx, "minOf", &values.data, values.span)?;
+
+    let min = values
+        .data
+        .into_iter()
+        .min()
+        .map_or(ValueKind::Null, |min| min.kind);
+    Ok(Value::new(min, ctx.expr.span))
+}
+
+fn max_of(ctx: NativeFnCtx) -> ValueResult {
+    let values = ensure_numbers(&ctx)?;
+    ensure_not_empty(&ctx, "maxOf", &values.data, values.span)?;
+
+    let max = values
+        .data
+        .into_iter()
+        .max()
+        .map_or(ValueKind::Null, |max| max.kind);
+    Ok(Value::new(max, ctx.expr.span))
+}
+
+fn is_number(value: &ValueKind) -> bool {
+    #[cfg(feature = "bigint")]
+    if matches!(value, ValueKind::BigInt(_)) {
+        return true;
+    }
+
+    matches!(value, ValueKind::Int(_) | ValueKind::Float(_))
+}
+
+fn ensure_is_number(ctx: &NativeFnCtx, value: Value) -> ValueResult {
+    if is_number(&value.kind) {
+        return Ok(value);
+    }
+
+    Err(Error::new(
+        ErrorKind::MismatchedTypes {
+            expected: "number".to_string(),
+            got: value.type_of().into(),
+        },
+        ctx.source.clone(),
+        value.span,
+    ))
+}
+
+/// Evaluates the first argument as an array of numbers.
+fn ensure_numbers(ctx: &NativeFnCtx) -> Result<ExtractedValue<Vec<Value>>, Error> {
+    let values = ctx.ensure_is_array(ctx.get_arg_evaluated(0, 1)?)?;
+
+    for value in &values.data {
+        ensure_is_number(ctx, value.clone())?;
+    }
+
+    Ok(values)
+}
+
+/// The length of `values`, which has to have at least one number.
+fn ensure_not_empty(
+    ctx: &NativeFnCtx,
+    name: &str,
+    values: &[Value],
+    span: SourceSpan,
+) -> Result<usize, Error> {
+    if values.is_empty() {
+        return Err(Error::new(
+            ErrorKind::EmptyAggregate {
+                name: name.to_string(),
+                array: span,
+            },
+            ctx.source.clone(),
+            span,
+        ));
+    }
+
+    Ok(values.len())
+}
+
+/// Combines numbers with `operator` like the arithmetic operators do,
+/// integers overflow with [`Options::checked_arithmetic`](crate::Options::checked_arithmetic).
+fn fold_numbers(
+    ctx: &NativeFnCtx,
+    values: Vec<Value>,
+    identity: isize,
+    checked: fn(isize, isize) -> Option<isize>,
+    operator: fn(ValueKind, ValueKind) -> ValueKind,
+) -> ValueResult {
+    let mut result = ValueKind::Int(identity);
+
+    for value in values {
+        // Big integers can't overflow
+        if ctx.options.checked_arithmetic
+            && cfg!(not(feature = "bigint"))
+            && let (ValueKind::Int(lhs), ValueKind::Int(rhs)) = (&result, &value.kind)
+        {
+            let Some(checked) = checked(*lhs, *rhs) else {
+                return Err(Error::new(
+                    ErrorKind::IntegerOverflow {
+                        operation: ctx.expr.span,
+                    },
+                    ctx.source.clone(),
+                    ctx.expr.span,
+                ));
+            };
+            result = ValueKind::Int(checked);
+            continue;
+        }
+
+        result = operator(result, value.kind);
+    }
+
+    Ok(Value::new(result, ctx.expr.span))
+}
+
+/// Evaluates the argument at `index` as a number of decimal places, up to [`MAX_PRECISION`].
+fn precision(ctx: &NativeFnCtx, index: usize) -> Result<usize, Error> {
+    let digits = ctx.ensure_is_int(ctx.get_arg_evaluated(index, 2)?)?;
+    let span = ctx.get_arg(index, 2).map_or(digits.span, |arg| arg.span);
+
+    let kind = match usize::try_from(digits.data) {
+        Ok(digits) if digits <= MAX_PRECISION => return Ok(digits),
+        Ok(_) => ErrorKind::PrecisionTooLarge {
+            limit: MAX_PRECISION,
+            digits: span,
+        },
+        Err(_) => ErrorKind::NegativePrecision { digits: span },
+    };
+
+    Err(Error::new(kind, ctx.source.clone(), span))
+}
+
+/// Integer division rounding towards negative infinity, `None` if `rhs` is zero.
+fn floor_div(lhs: isize, rhs: isize) -> Option<isize> {
+    if rhs == 0 {
+        return None;
+    }
+
+    // `isize::MIN / -1` is the only division that can overflow
+    let quotient = lhs.checked_div(rhs).unwrap_or(isize::MAX);
+    let remainder = lhs.checked_rem(rhs).unwrap_or(0);
+
+    if remainder != 0 && (remainder < 0) != (rhs < 0) {
+        Some(quotient.saturating_sub(1))
+    } else {
+        Some(quotient)
+    }
+}
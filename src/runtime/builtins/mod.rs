@@ -0,0 +1,354 @@
+use super::{
+    Scope,
+    types::{Builtin, NativeFnCtx, Value, ValueKind, ValueResult},
+};
+use std::rc::Rc;
+
+/// The name of the object that contains every builtin, so they stay accessible when shadowed.
+pub(crate) const BUILTINS_OBJECT: &str = "builtins";
+
+mod array;
+mod bitwise;
+mod core;
+#[cfg(feature = "fs-write")]
+mod fs;
+mod math;
+mod object;
+mod option;
+mod overrides;
+#[cfg(feature = "fs")]
+mod path;
+mod random;
+#[cfg(feature = "semver")]
+mod semver;
+mod sequence;
+mod string;
+#[cfg(feature = "fs")]
+mod template;
+mod units;
+#[cfg(feature = "url")]
+mod url;
+
+/// Metadata of a builtin function of the standard library.
+#[derive(Debug, Clone, Copy)]
+pub struct BuiltinInfo {
+    pub name: &'static str,
+    /// The name and type of each parameter.
+    pub params: &'static [(&'static str, &'static str)],
+    pub doc: &'static str,
+    /// The cargo feature that has to be enabled for this builtin to exist.
+    pub feature: Option<&'static str>,
+    pub func: fn(NativeFnCtx) -> ValueResult,
+}
+
+impl BuiltinInfo {
+    pub const fn arity(&self) -> usize {
+        self.params.len()
+    }
+
+    /// The name and parameters of the builtin, like `div(a: int, b: int)`.
+    pub fn signature(&self) -> String {
+        let params: Vec<_> = self
+            .params
+            .iter()
+            .map(|(name, ty)| format!("{name}: {ty}"))
+            .collect();
+
+        format!("{}({})", self.name, params.join(", "))
+    }
+}
+
+/// Every group of builtins that is enabled.
+const REGISTRY: &[&[BuiltinInfo]] = &[
+    core::BUILTINS,
+    array::BUILTINS,
+    bitwise::BUILTINS,
+    #[cfg(feature = "fs-write")]
+    fs::BUILTINS,
+    math::BUILTINS,
+    object::BUILTINS,
+    option::BUILTINS,
+    overrides::BUILTINS,
+    #[cfg(feature = "fs")]
+    path::BUILTINS,
+    random::BUILTINS,
+    #[cfg(feature = "semver")]
+    semver::BUILTINS,
+    sequence::BUILTINS,
+    string::BUILTINS,
+    #[cfg(feature = "fs")]
+    template::BUILTINS,
+    units::BUILTINS,
+    #[cfg(feature = "url")]
+    url::BUILTINS,
+];
+
+impl Scope {
+    /// Defines a native function that can be called from tl code.
+    pub fn define_builtin(
+        &mut self,
+        name: impl ToString,
+        func: impl Fn(NativeFnCtx) -> ValueResult + 'static,
+    ) {
+        self.define(name, Value::new_builtin(Builtin(Rc::new(func)).into()));
+    }
+
+    /// Returns the metadata of every builtin of the standard library with the enabled features.
+    /// Builtins defined with [`Scope::define_builtin`] are not included.
+    pub fn builtins() -> Vec<&'static BuiltinInfo> {
+        REGISTRY.iter().copied().flatten().collect()
+    }
+
+    /// Removes a builtin of the standard library, both the variable and the field of the `builtins` object.
+    /// A variable that shadows the builtin is kept.
+    pub fn remove_builtin(&mut self, name: &str) {
+        if self
+            .variables
+            .get(name)
+            .is_some_and(|value| matches!(value.kind, ValueKind::Builtin(_)))
+        {
+            self.variables.remove(name);
+        }
+
+        if let Some(Value {
+            kind: ValueKind::Object(builtins),
+            ..
+        }) = self.variables.get_mut(BUILTINS_OBJECT)
+        {
+            builtins.remove(name);
+        }
+    }
+
+    pub(super) fn define_builtins(&mut self) {
+        for builtin in Self::builtins() {
+            self.define_builtin(builtin.name, builtin.func);
+        }
+
+        // Defined last so it contains every other builtin
+        let builtins = self
+            .variables
+            .iter()
+            .filter(|(_, value)| matches!(value.kind, ValueKind::Builtin(_)))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        self.define(
+            BUILTINS_OBJECT,
+            Value::new_builtin(ValueKind::Object(builtins)),
+        );
+    }
+}
+
+// TODO: Port these to the registry
+
+// let variables = self.variables.clone();
+// self.define(
+//     "map",
+//     Builtin::Strict {
+//         params: 2,
+//         func: Rc::new(move |args, _| {
+//             let Some(Value::Function {
+//                 args: callback_args,
+//                 expr: callback,
+//             }) = args.first()
+//             else {
+//                 return Err(Box::new(Error::new(
+//                     ErrorType::NativeFnError(
+//                         "`map` requires a function with a single argument as the first argument".to_string(),
+//                     ),
+//                     None,
+//                 )));
+//             };
+//             let Some(Value::Array(list)) = args.get(1) else {
+//                 return Err(Box::new(Error::new(
+//                     ErrorType::NativeFnError(
+//                         "`map` requires an array the second argument".to_string(),
+//                     ),
+//                     None,
+//                 )));
+//             };
+
+//             let mut new_list = Vec::with_capacity(list.len());
+
+//             if callback_args.len() != 1 {
+//                 return Err(Box::new(Error::new(
+//                     ErrorType::NativeFnError(
+//                         "`map` requires a function with a single argument as the first argument".to_string(),
+//                     ),
+//                     None,
+//                 )));
+//             }
+
+//             let Some(callback_arg) = callback_args.first() else {
+//                 unreachable!("length was checked before");
+//             };
+
+//             for item in list {
+//                 let mut scope = Scope::new(variables.clone(), Source::from_text(""), callback.clone());
+//                 scope.define(callback_arg.clone(), item.clone());
+//                 new_list.push(scope.eval()?);
+//             }
+
+//             Ok(Value::Array(new_list))
+//         }),
+//     },
+// );
+// self.define(
+//     "join",
+//     Builtin::Strict {
+//         params: 2,
+//         func: Rc::new(|args, _| {
+//             let Some(Value::Array(list)) = args.first() else {
+//                 return Err(Box::new(Error::new(
+//                     ErrorType::NativeFnError(
+//                         "`join` requires an array the first argument".to_string(),
+//                     ),
+//                     None,
+//                 )));
+//             };
+//             let Some(Value::String(sep)) = args.get(1) else {
+//                 return Err(Box::new(Error::new(
+//                     ErrorType::NativeFnError(
+//                         "`join` requires a separator string as the second argument"
+//                             .to_string(),
+//                     ),
+//                     None,
+//                 )));
+//             };
+
+//             #[allow(clippy::arithmetic_side_effects)]
+//             Ok(Value::String(
+//                 list.iter()
+//                     .map(Value::to_string)
+//                     .reduce(|a, b| a + sep + &b)
+//                     .unwrap_or_default(),
+//             ))
+//         }),
+//     },
+// );
+
+//     #[cfg(feature = "fs")]
+//     self.define(
+//         "readFile",
+//         Builtin::Strict {
+//             params: 1,
+//             func: Rc::new(|args, _| {
+//                 let Some(Value::Path(path)) = args.first() else {
+//                     return Err(Box::new(Error::new(
+//                         ErrorType::NativeFnError(
+//                             "`readFile` requires a path as input".to_string(),
+//                         ),
+//                         None,
+//                     )));
+//                 };
+//                 let content =
+//                     fs::read_to_string(path).map_err(|err| Box::new(err.into()))?;
+
+//                 Ok(Value::String(content))
+//             }),
+//         },
+//     );
+
+//     #[cfg(feature = "fs")]
+//     self.define(
+//         "readDir",
+//         Builtin::Strict {
+//             params: 1,
+//             func: Rc::new(|args, _| {
+//                 use crate::object;
+
+//                 let Some(Value::Path(path)) = args.first() else {
+//                     return Err(Box::new(Error::new(
+//                         ErrorType::NativeFnError(
+//                             "`readDir` requires a path as input".to_string(),
+//                         ),
+//                         None,
+//                     )));
+//                 };
+//                 let content = fs::read_dir(path).map_err(|err| Box::new(err.into()))?;
+//                 let content = content
+//                     .into_iter()
+//                     .filter_map(Result::ok)
+//                     .map(|entry| {
+//                         object! {
+//                             path: Value::Path(entry.path()),
+//                             type: Value::String(
+//                                 match entry.file_type() {
+//                                     Ok(f) if f.is_file() => "file",
+//                                     Ok(f) if f.is_dir() => "dir",
+//                                     Ok(f) if f.is_symlink() => "symlink",
+//                                     _ => "other",
+//                                 }
+//                                 .into()
+//                             )
+//                         }
+//                     })
+//                     .collect::<Vec<_>>();
+
+//                 Ok(Value::Array(content))
+//             }),
+//         },
+//     );
+
+//     #[cfg(feature = "toml")]
+//     self.define(
+//         "toml",
+//         Builtin::Strict {
+//             params: 1,
+//             func: Rc::new(|args, _| {
+//                 fn convert_value(toml: toml::Value) -> ValueResult {
+//                     use std::collections::BTreeMap;
+
+//                     Ok(match toml {
+//                         toml::Value::String(v) => Value::String(v),
+//                         toml::Value::Integer(v) => {
+//                             Value::Int(v.try_into().map_err(|_| {
+//                                 Box::new(Error::new(
+//                                     ErrorType::NativeFnError(
+//                                         "Failed to convert integer while parsing toml file"
+//                                             .into(),
+//                                     ),
+//                                     None,
+//                                 ))
+//                             })?)
+//                         }
+//                         toml::Value::Float(v) => Value::Float(v),
+//                         toml::Value::Boolean(v) => Value::Boolean(v),
+//                         // TODO: This could probably be better.
+//                         toml::Value::Datetime(v) => Value::String(v.to_string()),
+//                         toml::Value::Array(v) => {
+//                             let mut values = Vec::new();
+
+//                             for toml_value in v {
+//                                 values.push(convert_value(toml_value)?);
+//                             }
+
+//                             Value::Array(values)
+//                         }
+//                         toml::Value::Table(v) => {
+//                             let mut object = BTreeMap::new();
+
+//                             for field in v {
+//                                 object.insert(field.0, convert_value(field.1)?);
+//                             }
+
+//                             Value::Object(object)
+//                         }
+//                     })
+//                 }
+
+//                 let Some(Value::String(content)) = args.first() else {
+//                     return Err(Box::new(Error::new(
+//                         ErrorType::NativeFnError(
+//                             "`toml` requires a toml string as an input".to_string(),
+//                         ),
+//                         None,
+//                     )));
+//                 };
+//                 let toml = toml::from_str::<toml::Value>(content)
+//                     .map_err(|err| Box::new(err.into()))?;
+
+//                 convert_value(toml)
+//             }),
+//         },
+//     );
+// }
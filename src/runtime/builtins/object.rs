@@ -0,0 +1,212 @@
+use super::BuiltinInfo;
+use crate::runtime::{
+    call::CallArgs,
+    types::{Error, ErrorKind, NativeFnCtx, Value, ValueKind, ValueResult},
+};
+use std::collections::BTreeMap;
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[
+    BuiltinInfo {
+        name: "objectKeys",
+        params: &[("object", "object")],
+        doc: "Returns the keys of an object in order.",
+        feature: None,
+        func: object_keys,
+    },
+    BuiltinInfo {
+        name: "diff",
+        params: &[("old", "any"), ("new", "any")],
+        doc: "Returns the `added`, `removed` and `changed` values between two values, each with the `path` where it happened.",
+        feature: None,
+        func: diff,
+    },
+    BuiltinInfo {
+        name: "getPath",
+        params: &[("object", "object"), ("path", "any")],
+        doc: "Returns the value at `path` in nested objects, either a string like `\"a.b.c\"` or an array of keys. Returns `null` if a key is missing.",
+        feature: None,
+        func: get_path,
+    },
+    BuiltinInfo {
+        name: "setPath",
+        params: &[("object", "object"), ("path", "any"), ("value", "any")],
+        doc: "Returns a copy of the object with the value at `path` replaced, missing objects along the path are created.",
+        feature: None,
+        func: set_path,
+    },
+    BuiltinInfo {
+        name: "updatePath",
+        params: &[
+            ("object", "object"),
+            ("path", "any"),
+            ("function", "function"),
+        ],
+        doc: "Returns a copy of the object with the value at `path` replaced by `function(value)`, `value` is `null` if it is missing.",
+        feature: None,
+        func: update_path,
+    },
+];
+
+fn object_keys(ctx: NativeFnCtx) -> ValueResult {
+    let object = ctx.ensure_is_object(ctx.get_arg_evaluated(0, 1)?)?;
+    let keys = object
+        .data
+        .into_keys()
+        .map(|key| Value::new(ValueKind::String(key), ctx.expr.span))
+        .collect();
+
+    Ok(Value::new(ValueKind::Array(keys), ctx.expr.span))
+}
+
+fn diff(ctx: NativeFnCtx) -> ValueResult {
+    let old = ctx.get_arg_evaluated(0, 2)?;
+    let new = ctx.get_arg_evaluated(1, 2)?;
+
+    let span = ctx.expr.span;
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+
+    for change in crate::diff::diff(&old, &new) {
+        let mut fields = BTreeMap::from([(
+            "path".to_string(),
+            Value::new(ValueKind::String(change.path.to_string()), span),
+        )]);
+
+        let list = match (change.old, change.new) {
+            (None, None) => continue,
+            (None, Some(value)) => {
+                fields.insert("value".to_string(), value);
+                &mut added
+            }
+            (Some(value), None) => {
+                fields.insert("value".to_string(), value);
+                &mut removed
+            }
+            (Some(old), Some(new)) => {
+                fields.insert("old".to_string(), old);
+                fields.insert("new".to_string(), new);
+                &mut changed
+            }
+        };
+        list.push(Value::new(ValueKind::Object(fields), span));
+    }
+
+    Ok(Value::new(
+        ValueKind::Object(BTreeMap::from([
+            (
+                "added".to_string(),
+                Value::new(ValueKind::Array(added), span),
+            ),
+            (
+                "removed".to_string(),
+                Value::new(ValueKind::Array(removed), span),
+            ),
+            (
+                "changed".to_string(),
+                Value::new(ValueKind::Array(changed), span),
+            ),
+        ])),
+        span,
+    ))
+}
+
+fn get_path(ctx: NativeFnCtx) -> ValueResult {
+    let object = ctx.get_arg_evaluated(0, 2)?;
+    ctx.ensure_is_object(object.clone())?;
+    let keys = field_path(&ctx, ctx.get_arg_evaluated(1, 2)?)?;
+
+    let value = keys
+        .iter()
+        .try_fold(object, |value, key| ctx.access(&value, key))?;
+    ctx.force(value)
+}
+
+fn set_path(ctx: NativeFnCtx) -> ValueResult {
+    let object = ctx.get_arg_evaluated(0, 3)?;
+    let keys = field_path(&ctx, ctx.get_arg_evaluated(1, 3)?)?;
+    let value = ctx.get_arg_evaluated(2, 3)?;
+
+    set(&ctx, object, &keys, value)
+}
+
+fn update_path(ctx: NativeFnCtx) -> ValueResult {
+    let object = ctx.get_arg_evaluated(0, 3)?;
+    let keys = field_path(&ctx, ctx.get_arg_evaluated(1, 3)?)?;
+    let function = ctx.get_arg_evaluated(2, 3)?;
+
+    ctx.ensure_is_object(object.clone())?;
+    let current = keys
+        .iter()
+        .try_fold(object.clone(), |value, key| ctx.access(&value, key))?;
+    let current = ctx.force(current)?;
+    let args = CallArgs {
+        positional: vec![current],
+        named: Vec::new(),
+        span: ctx.call_args_span(),
+    };
+    let value = ctx
+        .new_scope()
+        .call_function(function, "updatePath", args, ctx.expr.span)?;
+
+    set(&ctx, object, &keys, value)
+}
+
+/// The keys of a path argument, either a string separated by dots or an array of strings.
+fn field_path(ctx: &NativeFnCtx, path: Value) -> Result<Vec<String>, Error> {
+    match path.kind {
+        ValueKind::String(path) => Ok(path.split('.').map(str::to_string).collect()),
+        ValueKind::Array(keys) => keys
+            .into_iter()
+            .map(|key| Ok(ctx.ensure_is_string(key)?.data))
+            .collect(),
+        _ => Err(Error::new(
+            ErrorKind::MismatchedTypes {
+                expected: "string or array".into(),
+                got: path.type_of().into(),
+            },
+            ctx.source.clone(),
+            ctx.expr.span,
+        )),
+    }
+}
+
+/// Replaces the value at `keys` in `target`, creating objects for missing or `null` keys along the way.
+fn set(ctx: &NativeFnCtx, target: Value, keys: &[String], value: Value) -> ValueResult {
+    let Some((key, rest)) = keys.split_first() else {
+        return Ok(value);
+    };
+
+    let frozen = target.frozen;
+    let mut fields = ctx.ensure_is_object(target)?.data;
+
+    let value = if rest.is_empty() {
+        if let Some(frozen) = frozen
+            && fields.contains_key(key)
+        {
+            return Err(Error::new(
+                ErrorKind::FrozenOverride {
+                    key: key.clone(),
+                    frozen,
+                    overridden: ctx.expr.span,
+                },
+                ctx.source.clone(),
+                ctx.expr.span,
+            ));
+        }
+        value
+    } else {
+        let child = match fields.remove(key) {
+            Some(child) if !matches!(child.kind, ValueKind::Null) => child,
+            _ => Value::new(ValueKind::Object(BTreeMap::new()), ctx.expr.span),
+        };
+        set(ctx, child, rest, value)?
+    };
+    fields.insert(key.clone(), value);
+
+    Ok(Value {
+        kind: ValueKind::Object(fields),
+        span: ctx.expr.span,
+        frozen,
+    })
+}
@@ -0,0 +1,226 @@
+use super::BuiltinInfo;
+use crate::{
+    parser::ast::types::Type,
+    runtime::types::{Error, ErrorKind, NativeFnCtx, Value, ValueKind, ValueResult},
+};
+use miette::SourceSpan;
+use std::collections::BTreeMap;
+
+/// The `_type` of the objects created by `mkOption`.
+const OPTION_TYPE: &str = "option";
+/// The fields that `mkOption` accepts.
+const OPTION_FIELDS: [&str; 3] = ["type", "default", "description"];
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[
+    BuiltinInfo {
+        name: "mkOption",
+        params: &[("declaration", "object")],
+        doc: "Declares an option for `evalOptions` with an optional `type` like `\"[string]\"`, `default` and `description`.",
+        feature: None,
+        func: mk_option,
+    },
+    BuiltinInfo {
+        name: "evalOptions",
+        params: &[("declarations", "object"), ("definitions", "object")],
+        doc: "Checks the definitions against nested objects of options declared with `mkOption`, and returns the value of every option with the defaults filled in.",
+        feature: None,
+        func: eval_options,
+    },
+];
+
+fn mk_option(ctx: NativeFnCtx) -> ValueResult {
+    let declaration = ctx.ensure_is_object(ctx.get_arg_evaluated(0, 1)?)?;
+    let mut fields = declaration.data;
+
+    if let Some((key, value)) = fields
+        .iter()
+        .find(|(key, _)| !OPTION_FIELDS.contains(&key.as_str()))
+    {
+        return Err(Error::new(
+            ErrorKind::UnknownOption {
+                option: key.clone(),
+                definition: value.span,
+                known: Some(format!("`mkOption` accepts {}", OPTION_FIELDS.join(", "))),
+            },
+            ctx.source.clone(),
+            value.span,
+        ));
+    }
+
+    let ty = match fields.remove("type") {
+        Some(ty) => {
+            let span = ty.span;
+            let name = ctx.ensure_is_string(ty)?.data;
+            if Type::parse(&name).is_none() {
+                return Err(Error::new(
+                    ErrorKind::UnknownType { name, ty: span },
+                    ctx.source.clone(),
+                    span,
+                ));
+            }
+            Value::new(ValueKind::String(name), span)
+        }
+        None => Value::new(ValueKind::String(Type::Any.to_string()), ctx.expr.span),
+    };
+    if let Some(description) = fields.get("description") {
+        ctx.ensure_is_string(description.clone())?;
+    }
+
+    fields.insert("type".to_string(), ty);
+    fields.insert(
+        "_type".to_string(),
+        Value::new(ValueKind::String(OPTION_TYPE.to_string()), ctx.expr.span),
+    );
+
+    Ok(Value::new(ValueKind::Object(fields), ctx.expr.span))
+}
+
+fn eval_options(ctx: NativeFnCtx) -> ValueResult {
+    let declarations = ctx.ensure_is_object(ctx.get_arg_evaluated(0, 2)?)?.data;
+    let definitions = ctx.ensure_is_object(ctx.get_arg_evaluated(1, 2)?)?;
+
+    let values = eval_declarations(&ctx, &declarations, definitions.data, &mut Vec::new())?;
+    Ok(Value::new(ValueKind::Object(values), ctx.expr.span))
+}
+
+/// Whether `value` was created by `mkOption`, other objects in the declarations group options.
+fn is_option(value: &Value) -> bool {
+    matches!(
+        &value.kind,
+        ValueKind::Object(fields)
+            if matches!(fields.get("_type").map(|ty| &ty.kind), Some(ValueKind::String(ty)) if ty == OPTION_TYPE)
+    )
+}
+
+/// Resolves the options of one level of the declarations, `path` is the keys leading to it.
+fn eval_declarations(
+    ctx: &NativeFnCtx,
+    declarations: &BTreeMap<String, Value>,
+    mut definitions: BTreeMap<String, Value>,
+    path: &mut Vec<String>,
+) -> Result<BTreeMap<String, Value>, Error> {
+    if let Some((key, definition)) = definitions
+        .iter()
+        .find(|(key, _)| !declarations.contains_key(*key))
+    {
+        let known: Vec<_> = declarations.keys().map(|key| format!("`{key}`")).collect();
+        return Err(Error::new(
+            ErrorKind::UnknownOption {
+                option: option_name(path, key),
+                definition: definition.span,
+                known: (!known.is_empty())
+                    .then(|| format!("The options declared here are {}", known.join(", "))),
+            },
+            ctx.source.clone(),
+            definition.span,
+        ));
+    }
+
+    let mut values = BTreeMap::new();
+    for (key, declaration) in declarations {
+        let definition = definitions.remove(key);
+
+        let value = match &declaration.kind {
+            ValueKind::Object(option) if is_option(declaration) => {
+                eval_option(ctx, path, key, declaration, option, definition)?
+            }
+            ValueKind::Object(group) => {
+                let definitions = match definition {
+                    None => BTreeMap::new(),
+                    Some(Value {
+                        kind: ValueKind::Object(definitions),
+                        ..
+                    }) => definitions,
+                    Some(definition) => {
+                        return Err(invalid_option(
+                            ctx,
+                            option_name(path, key),
+                            &Type::Object,
+                            &definition,
+                            declaration.span,
+                        ));
+                    }
+                };
+
+                path.push(key.clone());
+                let values = eval_declarations(ctx, group, definitions, path)?;
+                path.pop();
+                Value::new(ValueKind::Object(values), declaration.span)
+            }
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::MismatchedTypes {
+                        expected: "option or object".into(),
+                        got: declaration.type_of().into(),
+                    },
+                    ctx.source.clone(),
+                    declaration.span,
+                ));
+            }
+        };
+
+        values.insert(key.clone(), value);
+    }
+
+    Ok(values)
+}
+
+/// The definition of a single option, or its default if it is not defined.
+fn eval_option(
+    ctx: &NativeFnCtx,
+    path: &[String],
+    key: &str,
+    declaration: &Value,
+    option: &BTreeMap<String, Value>,
+    definition: Option<Value>,
+) -> ValueResult {
+    let name = option_name(path, key);
+    let Some(value) = definition.or_else(|| option.get("default").cloned()) else {
+        return Err(Error::new(
+            ErrorKind::MissingOption {
+                option: name,
+                declaration: declaration.span,
+            },
+            ctx.source.clone(),
+            ctx.expr.span,
+        ));
+    };
+
+    let ty = match option.get("type").map(|ty| &ty.kind) {
+        Some(ValueKind::String(ty)) => Type::parse(ty).unwrap_or(Type::Any),
+        _ => Type::Any,
+    };
+    if !value.kind.satisfies(&ty) {
+        return Err(invalid_option(ctx, name, &ty, &value, declaration.span));
+    }
+
+    Ok(value)
+}
+
+fn invalid_option(
+    ctx: &NativeFnCtx,
+    option: String,
+    expected: &Type,
+    value: &Value,
+    declaration: SourceSpan,
+) -> Error {
+    Error::new(
+        ErrorKind::InvalidOption {
+            option,
+            expected: expected.to_string(),
+            found: value.type_of().into(),
+            definition: value.span,
+            declaration,
+        },
+        ctx.source.clone(),
+        value.span,
+    )
+}
+
+fn option_name(path: &[String], key: &str) -> String {
+    path.iter()
+        .map(String::as_str)
+        .chain(std::iter::once(key))
+        .collect::<Vec<_>>()
+        .join(".")
+}
@@ -0,0 +1,198 @@
+use super::BuiltinInfo;
+use crate::runtime::types::{Error, ErrorKind, NativeFnCtx, Value, ValueKind, ValueResult};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The priority of values without a marker, lower priorities win.
+const DEFAULT_PRIORITY: isize = 100;
+const MK_DEFAULT_PRIORITY: isize = 1000;
+const MK_FORCE_PRIORITY: isize = 50;
+
+/// The `_type` of the objects created by `mkOverride`, `mkDefault` and `mkForce`.
+const OVERRIDE_TYPE: &str = "override";
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[
+    BuiltinInfo {
+        name: "applyOverrides",
+        params: &[("base", "object"), ("overlays", "array")],
+        doc: "Deeply merges each overlay into `base` in order. Objects are merged by key, any other value replaces the earlier one unless that has a lower priority.",
+        feature: None,
+        func: apply_overrides,
+    },
+    BuiltinInfo {
+        name: "mkOverride",
+        params: &[("priority", "int"), ("value", "any")],
+        doc: "Marks a value with a priority for `applyOverrides`, lower priorities win. Unmarked values have a priority of 100.",
+        feature: None,
+        func: mk_override,
+    },
+    BuiltinInfo {
+        name: "mkDefault",
+        params: &[("value", "any")],
+        doc: "Marks a value that any unmarked value replaces in `applyOverrides`, the same as `mkOverride(1000, value)`.",
+        feature: None,
+        func: mk_default,
+    },
+    BuiltinInfo {
+        name: "mkForce",
+        params: &[("value", "any")],
+        doc: "Marks a value that unmarked values can not replace in `applyOverrides`, the same as `mkOverride(50, value)`.",
+        feature: None,
+        func: mk_force,
+    },
+];
+
+fn apply_overrides(ctx: NativeFnCtx) -> ValueResult {
+    let base = ctx.get_arg_evaluated(0, 2)?;
+    ctx.ensure_is_object(base.clone())?;
+    let overlays = ctx.ensure_is_array(ctx.get_arg_evaluated(1, 2)?)?.data;
+
+    let layers = std::iter::once(base)
+        .chain(overlays)
+        .map(|layer| {
+            ctx.ensure_is_object(layer.clone())?;
+            Ok(layer)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    resolve(&ctx, layers)
+}
+
+fn mk_override(ctx: NativeFnCtx) -> ValueResult {
+    let priority = ctx.ensure_is_int(ctx.get_arg_evaluated(0, 2)?)?.data;
+    let value = ctx.get_arg_evaluated(1, 2)?;
+
+    Ok(marker(&ctx, priority, value))
+}
+
+fn mk_default(ctx: NativeFnCtx) -> ValueResult {
+    let value = ctx.get_arg_evaluated(0, 1)?;
+    Ok(marker(&ctx, MK_DEFAULT_PRIORITY, value))
+}
+
+fn mk_force(ctx: NativeFnCtx) -> ValueResult {
+    let value = ctx.get_arg_evaluated(0, 1)?;
+    Ok(marker(&ctx, MK_FORCE_PRIORITY, value))
+}
+
+/// A marked value is an object of its `priority` and `content`, tagged with an `_type` of `override`.
+fn marker(ctx: &NativeFnCtx, priority: isize, content: Value) -> Value {
+    let span = ctx.expr.span;
+
+    Value::new(
+        ValueKind::Object(BTreeMap::from([
+            (
+                "_type".to_string(),
+                Value::new(ValueKind::String(OVERRIDE_TYPE.to_string()), span),
+            ),
+            (
+                "priority".to_string(),
+                Value::new(ValueKind::Int(priority), span),
+            ),
+            ("content".to_string(), content),
+        ])),
+        span,
+    )
+}
+
+/// Splits a value into its priority and content, unmarked values have the default priority.
+fn unmark(value: Value) -> (isize, Value) {
+    if let ValueKind::Object(fields) = &value.kind
+        && let Some(ValueKind::String(ty)) = fields.get("_type").map(|ty| &ty.kind)
+        && ty == OVERRIDE_TYPE
+        && let Some(ValueKind::Int(priority)) = fields.get("priority").map(|p| &p.kind)
+        && let Some(content) = fields.get("content")
+    {
+        return (*priority, content.clone());
+    }
+
+    (DEFAULT_PRIORITY, value)
+}
+
+/// Combines the definitions of a value from every layer, in order.
+/// Only the definitions with the lowest priority count. Of those, the objects after the last other value are
+/// merged by key, otherwise the last definition wins.
+/// # Errors
+/// This function will return an error if a definition overrides a key of a frozen object, whatever its priority.
+fn resolve(ctx: &NativeFnCtx, definitions: Vec<Value>) -> ValueResult {
+    ensure_not_frozen(ctx, &definitions)?;
+
+    let definitions: Vec<_> = definitions.into_iter().map(unmark).collect();
+    let Some(best) = definitions.iter().map(|(priority, _)| *priority).min() else {
+        return Ok(Value::new(ValueKind::Null, ctx.expr.span));
+    };
+
+    let mut objects: Vec<BTreeMap<String, Value>> = Vec::new();
+    let mut last = None;
+    for (priority, value) in definitions {
+        if priority != best {
+            continue;
+        }
+
+        match value.kind {
+            ValueKind::Object(fields) => {
+                last = None;
+                objects.push(fields);
+            }
+            _ => {
+                objects.clear();
+                last = Some(value);
+            }
+        }
+    }
+
+    if let Some(value) = last {
+        return Ok(value);
+    }
+
+    let keys: BTreeSet<String> = objects
+        .iter()
+        .flat_map(|fields| fields.keys())
+        .cloned()
+        .collect();
+    let fields = keys
+        .into_iter()
+        .map(|key| {
+            let definitions = objects
+                .iter()
+                .filter_map(|fields| fields.get(&key).cloned())
+                .collect();
+            let value = resolve(ctx, definitions)?;
+            Ok((key, value))
+        })
+        .collect::<Result<_, Error>>()?;
+
+    Ok(Value::new(ValueKind::Object(fields), ctx.expr.span))
+}
+
+/// Fails if a definition has a key of a frozen object before it, like `+` and `setPath` do.
+fn ensure_not_frozen(ctx: &NativeFnCtx, definitions: &[Value]) -> Result<(), Error> {
+    for (index, definition) in definitions.iter().enumerate() {
+        let (Some(frozen), ValueKind::Object(target)) = (definition.frozen, &definition.kind)
+        else {
+            continue;
+        };
+
+        let overridden = definitions
+            .iter()
+            .skip(index.saturating_add(1))
+            .filter_map(|later| match &unmark(later.clone()).1.kind {
+                ValueKind::Object(fields) => Some(fields.clone()),
+                _ => None,
+            })
+            .find_map(|fields| fields.into_iter().find(|(key, _)| target.contains_key(key)));
+
+        if let Some((key, value)) = overridden {
+            return Err(Error::new(
+                ErrorKind::FrozenOverride {
+                    key,
+                    frozen,
+                    overridden: value.span,
+                },
+                ctx.source.clone(),
+                ctx.expr.span,
+            ));
+        }
+    }
+
+    Ok(())
+}
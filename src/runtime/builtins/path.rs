@@ -0,0 +1,163 @@
+use super::BuiltinInfo;
+use crate::{
+    runtime::{
+        import::{parent_dir, read_file},
+        types::{Error, ErrorKind, NativeFnCtx, Value, ValueKind, ValueResult},
+    },
+    utils::{clean_path, normalize_path},
+};
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[
+    BuiltinInfo {
+        name: "parent",
+        params: &[("path", "path")],
+        doc: "The directory that contains `path`, the root is its own parent.",
+        feature: None,
+        func: parent,
+    },
+    BuiltinInfo {
+        name: "join",
+        params: &[("path", "path"), ("segment", "any")],
+        doc: "Appends a relative path or a string to `path` and resolves its `.` and `..` components.",
+        feature: None,
+        func: join,
+    },
+    BuiltinInfo {
+        name: "relativeTo",
+        params: &[("base", "path"), ("path", "path")],
+        doc: "The relative path that leads from the directory `base` to `path`, like `../lib/mod.tl`.",
+        feature: None,
+        func: relative_to,
+    },
+    BuiltinInfo {
+        name: "canonicalize",
+        params: &[("path", "path")],
+        doc: "The absolute path of an existing file with symbolic links resolved.",
+        feature: Some("fs"),
+        func: canonicalize,
+    },
+    BuiltinInfo {
+        name: "pathExists",
+        params: &[("path", "path")],
+        doc: "Whether a file or directory exists at `path`.",
+        feature: Some("fs"),
+        func: path_exists,
+    },
+    BuiltinInfo {
+        name: "readFileBytes",
+        params: &[("path", "path")],
+        doc: "Reads a file of any encoding as an array of bytes between 0 and 255, `fromBytes` decodes UTF-8.",
+        feature: Some("fs"),
+        func: read_file_bytes,
+    },
+];
+
+fn parent(ctx: NativeFnCtx) -> ValueResult {
+    let path = ctx.ensure_is_path(ctx.get_arg_evaluated(0, 1)?)?;
+    let path = clean_path(&path.data);
+
+    let parent = match path.components().next_back() {
+        Some(Component::Normal(_)) => parent_dir(&path).to_path_buf(),
+        // `..` and `.` have no name to remove
+        Some(Component::CurDir | Component::ParentDir) => clean_path(&path.join("..")),
+        Some(Component::RootDir | Component::Prefix(_)) | None => path,
+    };
+
+    Ok(Value::new(ValueKind::Path(parent), ctx.expr.span))
+}
+
+fn join(ctx: NativeFnCtx) -> ValueResult {
+    let path = ctx.ensure_is_path(ctx.get_arg_evaluated(0, 2)?)?;
+    let segment = ctx.get_arg_evaluated(1, 2)?;
+    let span = segment.span;
+
+    let segment = match segment.kind {
+        ValueKind::String(segment) => PathBuf::from(segment),
+        _ => ctx.ensure_is_path(segment)?.data,
+    };
+
+    if segment.has_root() {
+        return Err(Error::new(
+            ErrorKind::AbsoluteSegment { segment: span },
+            ctx.source.clone(),
+            span,
+        ));
+    }
+
+    Ok(Value::new(
+        ValueKind::Path(clean_path(&path.data.join(segment))),
+        ctx.expr.span,
+    ))
+}
+
+fn relative_to(ctx: NativeFnCtx) -> ValueResult {
+    let base = ctx.ensure_is_path(ctx.get_arg_evaluated(0, 2)?)?;
+    let path = ctx.ensure_is_path(ctx.get_arg_evaluated(1, 2)?)?;
+
+    Ok(Value::new(
+        ValueKind::Path(relative_path(&base.data, &path.data)),
+        ctx.expr.span,
+    ))
+}
+
+/// The path from the directory `base` to `path`, compared without reading the file system.
+/// Relative paths are compared as if they were in the working directory.
+fn relative_path(base: &Path, path: &Path) -> PathBuf {
+    let base = normalize_path(base);
+    let path = normalize_path(path);
+
+    let common = base
+        .components()
+        .zip(path.components())
+        .take_while(|(lhs, rhs)| lhs == rhs)
+        .count();
+
+    let mut relative = PathBuf::from(".");
+    for _ in base.components().skip(common) {
+        relative.push("..");
+    }
+    relative.extend(path.components().skip(common));
+
+    clean_path(&relative)
+}
+
+fn canonicalize(ctx: NativeFnCtx) -> ValueResult {
+    ctx.ensure_deterministic("canonicalize")?;
+    let path = ctx.ensure_is_path(ctx.get_arg_evaluated(0, 1)?)?;
+
+    let canonical = fs::canonicalize(&path.data)
+        .map_err(|err| Error::new(err.into(), ctx.source.clone(), path.span))?;
+    // Symbolic links can lead out of the root that the path was checked against
+    ctx.new_scope().ensure_in_root(&canonical, path.span)?;
+
+    Ok(Value::new(ValueKind::Path(canonical), ctx.expr.span))
+}
+
+fn path_exists(ctx: NativeFnCtx) -> ValueResult {
+    ctx.ensure_deterministic("pathExists")?;
+    let path = ctx.ensure_is_path(ctx.get_arg_evaluated(0, 1)?)?;
+
+    Ok(Value::new(
+        ValueKind::Boolean(path.data.exists()),
+        ctx.expr.span,
+    ))
+}
+
+fn read_file_bytes(ctx: NativeFnCtx) -> ValueResult {
+    let path = ctx.ensure_is_path(ctx.get_arg_evaluated(0, 1)?)?;
+
+    if let Some(inspector) = &ctx.options.inspector {
+        inspector.import(&path.data);
+    }
+
+    let bytes = read_file(&ctx, &path.data, path.span)?
+        .into_iter()
+        .map(|byte| Value::new(ValueKind::Int(isize::from(byte)), ctx.expr.span))
+        .collect();
+
+    Ok(Value::new(ValueKind::Array(bytes), ctx.expr.span))
+}
@@ -0,0 +1,141 @@
+use super::BuiltinInfo;
+use crate::runtime::types::{
+    Builtin, Error, ErrorKind, NativeFnCtx, Value, ValueKind, ValueResult,
+};
+use miette::SourceSpan;
+use std::{collections::BTreeMap, rc::Rc};
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[
+    BuiltinInfo {
+        name: "random",
+        params: &[("seed", "int")],
+        doc: "Creates a deterministic random number generator from a seed.",
+        feature: None,
+        func: random,
+    },
+    BuiltinInfo {
+        name: "randInt",
+        params: &[("generator", "generator"), ("lo", "int"), ("hi", "int")],
+        doc: "Returns an integer in the inclusive range `lo..=hi`.",
+        feature: None,
+        func: rand_int,
+    },
+    BuiltinInfo {
+        name: "randFloat",
+        params: &[("generator", "generator")],
+        doc: "Returns a float in the range [0, 1).",
+        feature: None,
+        func: rand_float,
+    },
+];
+
+fn random(ctx: NativeFnCtx) -> ValueResult {
+    let seed = ctx.ensure_is_int(ctx.get_arg_evaluated(0, 1)?)?;
+
+    Ok(generator(seed.data as u64, ctx.expr.span))
+}
+
+fn rand_int(ctx: NativeFnCtx) -> ValueResult {
+    let state = generator_state(&ctx, ctx.get_arg_evaluated(0, 3)?)?;
+    let lo = ctx.ensure_is_int(ctx.get_arg_evaluated(1, 3)?)?.data;
+    let hi = ctx.ensure_is_int(ctx.get_arg_evaluated(2, 3)?)?.data;
+
+    if lo > hi {
+        return Err(Error::new(
+            ErrorKind::InvalidRange {
+                range: ctx.call_args_span(),
+            },
+            ctx.source.clone(),
+            ctx.expr.span,
+        ));
+    }
+
+    let offset = match (hi.abs_diff(lo) as u64).checked_add(1) {
+        Some(len) => uniform_below(state, len),
+        // The range covers every possible integer
+        None => split_mix(state).1,
+    };
+
+    Ok(Value::new(
+        ValueKind::Int(lo.wrapping_add_unsigned(offset as usize)),
+        ctx.expr.span,
+    ))
+}
+
+#[allow(
+    clippy::float_arithmetic,
+    reason = "Converting the output into a float in [0, 1) requires float math"
+)]
+fn rand_float(ctx: NativeFnCtx) -> ValueResult {
+    let state = generator_state(&ctx, ctx.get_arg_evaluated(0, 1)?)?;
+    let (_, output) = split_mix(state);
+
+    // Use the upper 53 bits, the precision of a f64 mantissa
+    let value = output.wrapping_shr(11) as f64 / (1u64 << 53) as f64;
+
+    Ok(Value::new(ValueKind::Float(value), ctx.expr.span))
+}
+
+/// Creates a generator object, `next` returns the generator that comes after it.
+fn generator(state: u64, span: SourceSpan) -> Value {
+    let next = Builtin(Rc::new(move |ctx| {
+        let (next_state, _) = split_mix(state);
+        Ok(generator(next_state, ctx.expr.span))
+    }));
+
+    Value::new(
+        ValueKind::Object(BTreeMap::from([
+            (
+                "state".to_string(),
+                Value::new(ValueKind::Int(state as isize), span),
+            ),
+            (
+                "next".to_string(),
+                Value::new(ValueKind::Builtin(next), span),
+            ),
+        ])),
+        span,
+    )
+}
+
+fn generator_state(ctx: &NativeFnCtx, value: Value) -> Result<u64, Error> {
+    let type_of = value.type_of();
+
+    match value.access("state").kind {
+        ValueKind::Int(state) => Ok(state as u64),
+        _ => Err(Error::new(
+            ErrorKind::MismatchedTypes {
+                expected: "generator".into(),
+                got: type_of.into(),
+            },
+            ctx.source.clone(),
+            ctx.expr.span,
+        )),
+    }
+}
+
+/// An output of the generator at `state` that is uniform in `0..len`.
+/// The remainder of any output would favor small numbers if `len` doesn't divide 2^64,
+/// so outputs below `2^64 % len` are rejected and the states after it are tried instead.
+fn uniform_below(mut state: u64, len: u64) -> u64 {
+    let threshold = len.wrapping_neg().checked_rem(len).unwrap_or_default();
+
+    loop {
+        let (next_state, output) = split_mix(state);
+        if output >= threshold {
+            return output.checked_rem(len).unwrap_or_default();
+        }
+        state = next_state;
+    }
+}
+
+/// Advances a `SplitMix64` state, returning the next state and the output for the current one.
+fn split_mix(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+    let mut z = state;
+    z = (z ^ z.wrapping_shr(30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ z.wrapping_shr(27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+    (state, z ^ z.wrapping_shr(31))
+}
@@ -0,0 +1,341 @@
+use super::BuiltinInfo;
+use crate::runtime::types::{Error, ErrorKind, NativeFnCtx, Value, ValueKind, ValueResult};
+use miette::SourceSpan;
+use std::{cmp::Ordering, collections::BTreeMap};
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[
+    BuiltinInfo {
+        name: "parseSemver",
+        params: &[("version", "string")],
+        doc: "Splits a semantic version into its `major`, `minor`, `patch`, `pre` and `build`.",
+        feature: Some("semver"),
+        func: parse_semver,
+    },
+    BuiltinInfo {
+        name: "semverSatisfies",
+        params: &[("version", "string"), ("range", "string")],
+        doc: "Checks if a semantic version is in a range like `^1.2`, `>=1.0.0 <2.0.0` or `1.x || 2.x`.",
+        feature: Some("semver"),
+        func: semver_satisfies,
+    },
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Version<'a> {
+    major: isize,
+    minor: isize,
+    patch: isize,
+    pre: Option<&'a str>,
+    build: Option<&'a str>,
+}
+
+impl Version<'_> {
+    const fn new(major: isize, minor: isize, patch: isize) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            pre: None,
+            build: None,
+        }
+    }
+
+    /// The precedence of semver, build metadata is ignored.
+    fn precedence(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre, other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(pre), Some(other)) => compare_pre(pre, other),
+            })
+    }
+}
+
+/// A version where the missing or wildcard parts are `None`, as written in ranges.
+struct Partial<'a> {
+    major: Option<isize>,
+    minor: Option<isize>,
+    patch: Option<isize>,
+    pre: Option<&'a str>,
+}
+
+impl<'a> Partial<'a> {
+    fn floor(&self) -> Version<'a> {
+        Version {
+            pre: self.pre,
+            ..Version::new(
+                self.major.unwrap_or(0),
+                self.minor.unwrap_or(0),
+                self.patch.unwrap_or(0),
+            )
+        }
+    }
+
+    /// The first version after every version that matches the partial, if some part is missing.
+    fn ceil(&self) -> Option<Version<'a>> {
+        match (self.major, self.minor, self.patch) {
+            (None, _, _) | (Some(_), Some(_), Some(_)) => None,
+            (Some(major), None, _) => Some(Version::new(major.saturating_add(1), 0, 0)),
+            (Some(major), Some(minor), None) => {
+                Some(Version::new(major, minor.saturating_add(1), 0))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Eq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+type Comparator<'a> = (Op, Version<'a>);
+
+fn parse_semver(ctx: NativeFnCtx) -> ValueResult {
+    let version = ctx.ensure_is_string(ctx.get_arg_evaluated(0, 1)?)?;
+    let parsed =
+        parse_version(version.data.trim()).map_err(|reason| invalid(&ctx, reason, version.span))?;
+
+    let span = ctx.expr.span;
+    let string = |value: Option<&str>| {
+        Value::new(
+            value.map_or(ValueKind::Null, |value| {
+                ValueKind::String(value.to_string())
+            }),
+            span,
+        )
+    };
+    let int = |value: isize| Value::new(ValueKind::Int(value), span);
+
+    Ok(Value::new(
+        ValueKind::Object(BTreeMap::from([
+            ("major".to_string(), int(parsed.major)),
+            ("minor".to_string(), int(parsed.minor)),
+            ("patch".to_string(), int(parsed.patch)),
+            ("pre".to_string(), string(parsed.pre)),
+            ("build".to_string(), string(parsed.build)),
+        ])),
+        span,
+    ))
+}
+
+fn semver_satisfies(ctx: NativeFnCtx) -> ValueResult {
+    let version = ctx.ensure_is_string(ctx.get_arg_evaluated(0, 2)?)?;
+    let range = ctx.ensure_is_string(ctx.get_arg_evaluated(1, 2)?)?;
+
+    let version =
+        parse_version(version.data.trim()).map_err(|reason| invalid(&ctx, reason, version.span))?;
+    let alternatives =
+        parse_range(&range.data).map_err(|reason| invalid(&ctx, reason, range.span))?;
+
+    let satisfied = alternatives.iter().any(|comparators| {
+        comparators.iter().all(|(op, bound)| {
+            let ordering = version.precedence(bound);
+            match op {
+                Op::Eq => ordering.is_eq(),
+                Op::Lt => ordering.is_lt(),
+                Op::LtEq => ordering.is_le(),
+                Op::Gt => ordering.is_gt(),
+                Op::GtEq => ordering.is_ge(),
+            }
+        })
+    });
+
+    Ok(Value::new(ValueKind::Boolean(satisfied), ctx.expr.span))
+}
+
+fn invalid(ctx: &NativeFnCtx, reason: &str, span: SourceSpan) -> Error {
+    Error::new(
+        ErrorKind::InvalidSemver {
+            reason: reason.to_string(),
+            version: span,
+        },
+        ctx.source.clone(),
+        span,
+    )
+}
+
+/// Parses `major.minor.patch[-pre][+build]`.
+fn parse_version(version: &str) -> Result<Version<'_>, &'static str> {
+    let partial = parse_partial(version)?;
+    let (Some(major), Some(minor), Some(patch)) = (partial.major, partial.minor, partial.patch)
+    else {
+        return Err("A version needs a major, minor and patch number");
+    };
+
+    Ok(Version {
+        major,
+        minor,
+        patch,
+        pre: partial.pre,
+        build: version.split_once('+').map(|(_, build)| build),
+    })
+}
+
+fn parse_partial(version: &str) -> Result<Partial<'_>, &'static str> {
+    let version = version.strip_prefix('v').unwrap_or(version);
+    let (version, build) = match version.split_once('+') {
+        Some((version, build)) => (version, Some(build)),
+        None => (version, None),
+    };
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    };
+
+    for identifiers in [pre, build].into_iter().flatten() {
+        if identifiers.split('.').any(|identifier| {
+            identifier.is_empty()
+                || !identifier
+                    .chars()
+                    .all(|char| char.is_ascii_alphanumeric() || char == '-')
+        }) {
+            return Err(
+                "Pre-release and build identifiers can only contain letters, digits and `-`",
+            );
+        }
+    }
+
+    let mut numbers = core.split('.').map(|number| match number {
+        "x" | "X" | "*" => Ok(None),
+        _ if number.is_empty() || !number.chars().all(|char| char.is_ascii_digit()) => {
+            Err("Version numbers can only contain digits")
+        }
+        _ if number.len() > 1 && number.starts_with('0') => {
+            Err("Version numbers can not have leading zeros")
+        }
+        _ => number
+            .parse()
+            .map(Some)
+            .map_err(|_| "The version number is too big"),
+    });
+
+    let major = numbers.next().transpose()?.flatten();
+    let minor = numbers.next().transpose()?.flatten();
+    let patch = numbers.next().transpose()?.flatten();
+    if numbers.next().is_some() {
+        return Err("A version has at most three numbers");
+    }
+
+    // Everything after a wildcard is a wildcard
+    let minor = major.and(minor);
+    let patch = minor.and(patch);
+
+    Ok(Partial {
+        major,
+        minor,
+        patch,
+        pre,
+    })
+}
+
+/// Parses alternatives separated by `||`, that are made of comparators separated by spaces or commas.
+fn parse_range(range: &str) -> Result<Vec<Vec<Comparator<'_>>>, &'static str> {
+    range
+        .split("||")
+        .map(|alternative| {
+            let mut comparators = Vec::new();
+            let mut words = alternative
+                .split(|char: char| char.is_whitespace() || char == ',')
+                .filter(|word| !word.is_empty())
+                .peekable();
+
+            while let Some(word) = words.next() {
+                // `>= 1.2.3` is the same as `>=1.2.3`
+                let word = if word.chars().all(|char| "<>=~^".contains(char)) {
+                    let Some(version) = words.next() else {
+                        return Err("A comparison is missing its version");
+                    };
+                    (word, version)
+                } else {
+                    let operator_len = word
+                        .find(|char: char| !"<>=~^".contains(char))
+                        .unwrap_or(word.len());
+                    word.split_at(operator_len)
+                };
+
+                comparators.extend(expand(word.0, &parse_partial(word.1)?)?);
+            }
+
+            Ok(comparators)
+        })
+        .collect()
+}
+
+/// Turns a comparison with a partial version into comparisons with full versions.
+fn expand<'a>(operator: &str, partial: &Partial<'a>) -> Result<Vec<Comparator<'a>>, &'static str> {
+    let floor = partial.floor();
+    let ceil = partial.ceil();
+
+    Ok(match operator {
+        "" | "=" => match ceil {
+            _ if partial.major.is_none() => Vec::new(),
+            Some(ceil) => vec![(Op::GtEq, floor), (Op::Lt, ceil)],
+            None => vec![(Op::Eq, floor)],
+        },
+        ">" => match ceil {
+            Some(ceil) => vec![(Op::GtEq, ceil)],
+            None if partial.major.is_none() => vec![(Op::Lt, Version::new(0, 0, 0))],
+            None => vec![(Op::Gt, floor)],
+        },
+        ">=" => vec![(Op::GtEq, floor)],
+        "<" => vec![(Op::Lt, floor)],
+        "<=" => match ceil {
+            Some(ceil) => vec![(Op::Lt, ceil)],
+            None if partial.major.is_none() => Vec::new(),
+            None => vec![(Op::LtEq, floor)],
+        },
+        "~" => {
+            let ceil = match (partial.major, partial.minor) {
+                (Some(major), None) => Version::new(major.saturating_add(1), 0, 0),
+                (Some(major), Some(minor)) => Version::new(major, minor.saturating_add(1), 0),
+                (None, _) => return Ok(Vec::new()),
+            };
+            vec![(Op::GtEq, floor), (Op::Lt, ceil)]
+        }
+        "^" => {
+            // The first non-zero number is the one that can not change
+            let ceil = match (partial.major, partial.minor, partial.patch) {
+                (None, _, _) => return Ok(Vec::new()),
+                (Some(0), Some(0), Some(patch)) => Version::new(0, 0, patch.saturating_add(1)),
+                (Some(0), Some(minor), _) => Version::new(0, minor.saturating_add(1), 0),
+                (Some(major), _, _) => Version::new(major.saturating_add(1), 0, 0),
+            };
+            vec![(Op::GtEq, floor), (Op::Lt, ceil)]
+        }
+        _ => {
+            return Err(
+                "Unknown comparison operator, expected one of `=`, `<`, `<=`, `>`, `>=`, `~` or `^`",
+            );
+        }
+    })
+}
+
+/// Compares dot separated pre-release identifiers, numeric identifiers are lower than alphanumeric ones.
+fn compare_pre(pre: &str, other: &str) -> Ordering {
+    let mut left = pre.split('.');
+    let mut right = other.split('.');
+
+    loop {
+        let ordering = match (left.next(), right.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(left), Some(right)) => match (left.parse::<u64>(), right.parse::<u64>()) {
+                (Ok(left), Ok(right)) => left.cmp(&right),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => left.cmp(right),
+            },
+        };
+
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
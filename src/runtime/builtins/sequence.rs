@@ -0,0 +1,156 @@
+use super::BuiltinInfo;
+use crate::runtime::{
+    call::CallArgs,
+    types::{
+        Builtin, Error, ErrorKind, MAX_ARRAY_LENGTH, NativeFnCtx, Value, ValueKind, ValueResult,
+    },
+};
+use miette::SourceSpan;
+use std::{collections::BTreeMap, rc::Rc};
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[
+    BuiltinInfo {
+        name: "generate",
+        params: &[("function", "function")],
+        doc: "Creates an infinite sequence of `function(0)`, `function(1)` and so on.",
+        feature: None,
+        func: generate,
+    },
+    BuiltinInfo {
+        name: "iterate",
+        params: &[("function", "function"), ("start", "any")],
+        doc: "Creates an infinite sequence of `start`, `function(start)`, `function(function(start))` and so on.",
+        feature: None,
+        func: iterate,
+    },
+    BuiltinInfo {
+        name: "take",
+        params: &[("count", "int"), ("sequence", "sequence")],
+        doc: "Returns the first `count` values of a sequence or array as an array.",
+        feature: None,
+        func: take,
+    },
+];
+
+fn generate(ctx: NativeFnCtx) -> ValueResult {
+    let function = ctx.get_arg_evaluated(0, 1)?;
+    ctx.ensure_is_function(function.clone())?;
+
+    generate_node(&ctx, function, 0)
+}
+
+fn iterate(ctx: NativeFnCtx) -> ValueResult {
+    let function = ctx.get_arg_evaluated(0, 2)?;
+    ctx.ensure_is_function(function.clone())?;
+    let start = ctx.get_arg_evaluated(1, 2)?;
+
+    Ok(iterate_node(function, start, ctx.expr.span))
+}
+
+fn take(ctx: NativeFnCtx) -> ValueResult {
+    let count = ctx.ensure_is_int(ctx.get_arg_evaluated(0, 2)?)?;
+    let count_span = count.span;
+    let Ok(count) = usize::try_from(count.data) else {
+        return Err(Error::new(
+            ErrorKind::InvalidRange { range: count_span },
+            ctx.source.clone(),
+            ctx.expr.span,
+        ));
+    };
+
+    let mut sequence = ctx.get_arg_evaluated(1, 2)?;
+    if let ValueKind::Array(array) = sequence.kind {
+        return Ok(Value::new(
+            ValueKind::Array(array.into_iter().take(count).collect()),
+            ctx.expr.span,
+        ));
+    }
+
+    // Sequences are infinite, so the count alone decides how large the array gets
+    if count > MAX_ARRAY_LENGTH {
+        return Err(Error::new(
+            ErrorKind::TooLarge {
+                limit: MAX_ARRAY_LENGTH,
+                unit: "items".to_string(),
+                operation: count_span,
+            },
+            ctx.source.clone(),
+            ctx.expr.span,
+        ));
+    }
+
+    let mut values = Vec::new();
+    while values.len() < count {
+        let type_of = sequence.type_of();
+        values.push(sequence.access("value"));
+
+        match sequence.access("next").kind {
+            ValueKind::Builtin(Builtin(next)) => sequence = next(ctx.clone())?,
+            // A sequence without `next` is finite
+            ValueKind::Null if matches!(sequence.kind, ValueKind::Object(_)) => break,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::MismatchedTypes {
+                        expected: "sequence".into(),
+                        got: type_of.into(),
+                    },
+                    ctx.source.clone(),
+                    ctx.expr.span,
+                ));
+            }
+        }
+    }
+
+    Ok(Value::new(ValueKind::Array(values), ctx.expr.span))
+}
+
+/// Creates the sequence node of `function(index)`, `next` creates the node of the following index.
+fn generate_node(ctx: &NativeFnCtx, function: Value, index: isize) -> ValueResult {
+    let value = call(
+        ctx,
+        function.clone(),
+        "generate",
+        Value::new(ValueKind::Int(index), ctx.expr.span),
+    )?;
+    let next = Builtin(Rc::new(move |ctx| {
+        generate_node(&ctx, function.clone(), index.saturating_add(1))
+    }));
+
+    Ok(node(value, next, ctx.expr.span))
+}
+
+/// Creates the sequence node of `value`, `next` creates the node of `function(value)`.
+fn iterate_node(function: Value, value: Value, span: SourceSpan) -> Value {
+    let current = value.clone();
+    let next = Builtin(Rc::new(move |ctx| {
+        let value = call(&ctx, function.clone(), "iterate", current.clone())?;
+        Ok(iterate_node(function.clone(), value, ctx.expr.span))
+    }));
+
+    node(value, next, span)
+}
+
+/// A sequence is an object of its first `value` and a `next` builtin that returns the rest of it.
+fn node(value: Value, next: Builtin, span: SourceSpan) -> Value {
+    Value::new(
+        ValueKind::Object(BTreeMap::from([
+            ("value".to_string(), value),
+            (
+                "next".to_string(),
+                Value::new(ValueKind::Builtin(next), span),
+            ),
+        ])),
+        span,
+    )
+}
+
+fn call(ctx: &NativeFnCtx, function: Value, name: &str, argument: Value) -> ValueResult {
+    let args = CallArgs {
+        positional: vec![argument],
+        named: Vec::new(),
+        span: ctx.call_args_span(),
+    };
+
+    ctx.new_scope()
+        .call_function(function, name, args, ctx.expr.span)
+}
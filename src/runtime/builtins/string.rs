@@ -0,0 +1,403 @@
+//! String builtins work on characters as they are displayed (extended grapheme clusters), not on UTF-8 bytes,
+//! so an `e` with a combining accent or an emoji with a skin tone is a single character. `codepoints` works on unicode scalar values,
+//! and `byteLen`, `toBytes` and `fromBytes` work on the UTF-8 encoding instead.
+
+use super::BuiltinInfo;
+use crate::runtime::types::{Error, ErrorKind, NativeFnCtx, Value, ValueKind, ValueResult};
+use unicode_segmentation::UnicodeSegmentation;
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[
+    BuiltinInfo {
+        name: "strLen",
+        params: &[("string", "string")],
+        doc: "Returns the number of characters in a string as they are displayed.",
+        feature: None,
+        func: str_len,
+    },
+    BuiltinInfo {
+        name: "charAt",
+        params: &[("string", "string"), ("index", "int")],
+        doc: "Returns the character at an index, counted like `strLen`.",
+        feature: None,
+        func: char_at,
+    },
+    BuiltinInfo {
+        name: "chars",
+        params: &[("string", "string")],
+        doc: "Splits a string into an array of its characters, counted like `strLen`.",
+        feature: None,
+        func: chars,
+    },
+    BuiltinInfo {
+        name: "codepoints",
+        params: &[("string", "string")],
+        doc: "Returns the codepoint of each unicode character in a string.",
+        feature: None,
+        func: codepoints,
+    },
+    BuiltinInfo {
+        name: "fromCodepoint",
+        params: &[("codepoint", "int")],
+        doc: "Converts a codepoint into a string with a single character.",
+        feature: None,
+        func: from_codepoint,
+    },
+    BuiltinInfo {
+        name: "byteLen",
+        params: &[("string", "string")],
+        doc: "Returns the number of bytes of a string encoded as UTF-8.",
+        feature: None,
+        func: byte_len,
+    },
+    BuiltinInfo {
+        name: "isAscii",
+        params: &[("string", "string")],
+        doc: "Whether every character of a string is ASCII, in which case its length in characters and bytes is the same.",
+        feature: None,
+        func: is_ascii,
+    },
+    BuiltinInfo {
+        name: "toBytes",
+        params: &[("string", "string")],
+        doc: "Encodes a string as UTF-8, returning an array of bytes between 0 and 255.",
+        feature: None,
+        func: to_bytes,
+    },
+    BuiltinInfo {
+        name: "fromBytes",
+        params: &[("bytes", "array")],
+        doc: "Decodes an array of bytes as UTF-8, invalid UTF-8 is an error.",
+        feature: None,
+        func: from_bytes,
+    },
+    BuiltinInfo {
+        name: "escapeShell",
+        params: &[("string", "string")],
+        doc: "Quotes a string as a single POSIX shell word.",
+        feature: None,
+        func: escape_shell,
+    },
+    BuiltinInfo {
+        name: "escapeJson",
+        params: &[("string", "string")],
+        doc: "Escapes a string for the inside of a JSON string, without the surrounding quotes.",
+        feature: None,
+        func: escape_json,
+    },
+    BuiltinInfo {
+        name: "escapeXml",
+        params: &[("string", "string")],
+        doc: "Escapes the characters of a string that are special in XML text and attributes.",
+        feature: None,
+        func: escape_xml,
+    },
+    BuiltinInfo {
+        name: "escapeHtml",
+        params: &[("string", "string")],
+        doc: "Escapes the characters of a string that are special in HTML text and attributes.",
+        feature: None,
+        func: escape_html,
+    },
+    BuiltinInfo {
+        name: "escapeRegex",
+        params: &[("string", "string")],
+        doc: "Escapes the regex metacharacters of a string so it matches literally.",
+        feature: None,
+        func: escape_regex,
+    },
+    BuiltinInfo {
+        name: "interpolateEnv",
+        params: &[("string", "string")],
+        doc: "Replaces `$VAR`, `${VAR}` and `${VAR:-default}` with environment variables, only if `Options::allow_env` is set.",
+        feature: None,
+        func: interpolate_env,
+    },
+];
+
+fn str_len(ctx: NativeFnCtx) -> ValueResult {
+    let string = ctx.ensure_is_string(ctx.get_arg_evaluated(0, 1)?)?;
+    let length = string.data.graphemes(true).count();
+
+    Ok(Value::new(
+        ValueKind::Int(isize::try_from(length).unwrap_or(isize::MAX)),
+        ctx.expr.span,
+    ))
+}
+
+fn char_at(ctx: NativeFnCtx) -> ValueResult {
+    let string = ctx.ensure_is_string(ctx.get_arg_evaluated(0, 2)?)?;
+    let index = ctx.ensure_is_int(ctx.get_arg_evaluated(1, 2)?)?;
+
+    let char = usize::try_from(index.data)
+        .ok()
+        .and_then(|index| string.data.graphemes(true).nth(index));
+
+    match char {
+        Some(char) => Ok(Value::new(
+            ValueKind::String(char.to_string()),
+            ctx.expr.span,
+        )),
+        None => {
+            let span = ctx.get_arg(1, 2)?.span;
+
+            Err(Error::new(
+                ErrorKind::CharIndexOutOfBounds {
+                    length: string.data.graphemes(true).count(),
+                    index: span,
+                },
+                ctx.source.clone(),
+                span,
+            ))
+        }
+    }
+}
+
+fn chars(ctx: NativeFnCtx) -> ValueResult {
+    let string = ctx.ensure_is_string(ctx.get_arg_evaluated(0, 1)?)?;
+    let chars = string
+        .data
+        .graphemes(true)
+        .map(|char| Value::new(ValueKind::String(char.to_string()), ctx.expr.span))
+        .collect();
+
+    Ok(Value::new(ValueKind::Array(chars), ctx.expr.span))
+}
+
+fn codepoints(ctx: NativeFnCtx) -> ValueResult {
+    let string = ctx.ensure_is_string(ctx.get_arg_evaluated(0, 1)?)?;
+    let codepoints = string
+        .data
+        .chars()
+        .map(|char| Value::new(ValueKind::Int(u32::from(char) as isize), ctx.expr.span))
+        .collect();
+
+    Ok(Value::new(ValueKind::Array(codepoints), ctx.expr.span))
+}
+
+fn from_codepoint(ctx: NativeFnCtx) -> ValueResult {
+    let codepoint = ctx.ensure_is_int(ctx.get_arg_evaluated(0, 1)?)?;
+
+    match u32::try_from(codepoint.data).ok().and_then(char::from_u32) {
+        Some(char) => Ok(Value::new(
+            ValueKind::String(char.to_string()),
+            ctx.expr.span,
+        )),
+        None => {
+            let span = ctx.get_arg(0, 1)?.span;
+
+            Err(Error::new(
+                ErrorKind::InvalidCodepoint { codepoint: span },
+                ctx.source.clone(),
+                span,
+            ))
+        }
+    }
+}
+
+fn byte_len(ctx: NativeFnCtx) -> ValueResult {
+    let string = ctx.ensure_is_string(ctx.get_arg_evaluated(0, 1)?)?;
+
+    Ok(Value::new(
+        ValueKind::Int(isize::try_from(string.data.len()).unwrap_or(isize::MAX)),
+        ctx.expr.span,
+    ))
+}
+
+fn is_ascii(ctx: NativeFnCtx) -> ValueResult {
+    let string = ctx.ensure_is_string(ctx.get_arg_evaluated(0, 1)?)?;
+
+    Ok(Value::new(
+        ValueKind::Boolean(string.data.is_ascii()),
+        ctx.expr.span,
+    ))
+}
+
+fn to_bytes(ctx: NativeFnCtx) -> ValueResult {
+    let string = ctx.ensure_is_string(ctx.get_arg_evaluated(0, 1)?)?;
+    let bytes = string
+        .data
+        .bytes()
+        .map(|byte| Value::new(ValueKind::Int(isize::from(byte)), ctx.expr.span))
+        .collect();
+
+    Ok(Value::new(ValueKind::Array(bytes), ctx.expr.span))
+}
+
+fn from_bytes(ctx: NativeFnCtx) -> ValueResult {
+    let array = ctx.ensure_is_array(ctx.get_arg_evaluated(0, 1)?)?;
+
+    let mut bytes = Vec::with_capacity(array.data.len());
+    for item in array.data {
+        let byte = ctx.ensure_is_int(item)?;
+
+        match u8::try_from(byte.data) {
+            Ok(byte) => bytes.push(byte),
+            Err(_) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidByte { byte: byte.span },
+                    ctx.source.clone(),
+                    byte.span,
+                ));
+            }
+        }
+    }
+
+    match String::from_utf8(bytes) {
+        Ok(string) => Ok(Value::new(ValueKind::String(string), ctx.expr.span)),
+        Err(err) => Err(Error::new(
+            ErrorKind::InvalidUtf8 {
+                valid_up_to: err.utf8_error().valid_up_to(),
+                bytes: array.span,
+            },
+            ctx.source.clone(),
+            array.span,
+        )),
+    }
+}
+
+fn escape_shell(ctx: NativeFnCtx) -> ValueResult {
+    escape_with(&ctx, |string| {
+        // Inside single quotes nothing is special, a quote ends the word and is added escaped
+        format!("'{}'", string.replace('\'', r"'\''"))
+    })
+}
+
+fn escape_json(ctx: NativeFnCtx) -> ValueResult {
+    escape_with(&ctx, |string| {
+        let mut escaped = String::with_capacity(string.len());
+        for char in string.chars() {
+            match char {
+                '"' => escaped.push_str(r#"\""#),
+                '\\' => escaped.push_str(r"\\"),
+                '\n' => escaped.push_str(r"\n"),
+                '\r' => escaped.push_str(r"\r"),
+                '\t' => escaped.push_str(r"\t"),
+                // `<` is escaped too, so the result can be placed inside a `<script>` tag
+                '\u{0}'..='\u{1f}' | '<' | '\u{2028}' | '\u{2029}' => {
+                    escaped.push_str(&format!(r"\u{:04x}", u32::from(char)));
+                }
+                _ => escaped.push(char),
+            }
+        }
+        escaped
+    })
+}
+
+fn escape_xml(ctx: NativeFnCtx) -> ValueResult {
+    escape_with(&ctx, |string| escape_markup(string, "&apos;"))
+}
+
+fn escape_html(ctx: NativeFnCtx) -> ValueResult {
+    // `&apos;` is not part of HTML 4
+    escape_with(&ctx, |string| escape_markup(string, "&#39;"))
+}
+
+fn escape_regex(ctx: NativeFnCtx) -> ValueResult {
+    escape_with(&ctx, |string| {
+        let mut escaped = String::with_capacity(string.len());
+        for char in string.chars() {
+            if "\\.+*?()|[]{}^$#&-~".contains(char) {
+                escaped.push('\\');
+            }
+            escaped.push(char);
+        }
+        escaped
+    })
+}
+
+fn interpolate_env(ctx: NativeFnCtx) -> ValueResult {
+    ctx.ensure_deterministic("interpolateEnv")?;
+    let string = ctx.ensure_is_string(ctx.get_arg_evaluated(0, 1)?)?;
+
+    if !ctx.options.allow_env {
+        return Err(Error::new(
+            ErrorKind::BuiltinDenied {
+                name: "interpolateEnv".into(),
+            },
+            ctx.source.clone(),
+            ctx.expr.span,
+        ));
+    }
+
+    let is_name_start = |char: char| char.is_ascii_alphabetic() || char == '_';
+    let is_name_char = |char: char| char.is_ascii_alphanumeric() || char == '_';
+
+    let mut result = String::with_capacity(string.data.len());
+    let mut rest = string.data.as_str();
+    while let Some(index) = rest.find('$') {
+        result.push_str(rest.get(..index).unwrap_or_default());
+        let after = rest.get(index.saturating_add(1)..).unwrap_or_default();
+
+        // `${NAME}` or `${NAME:-default}`
+        let braced = after.strip_prefix('{').and_then(|inner| {
+            let (reference, after) = inner.split_once('}')?;
+            let (name, default) = match reference.split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (reference, None),
+            };
+            let valid = name.starts_with(is_name_start) && name.chars().all(is_name_char);
+
+            valid.then_some((name, default, after))
+        });
+        // `$NAME`
+        let plain = || {
+            let len = after
+                .find(|char: char| !is_name_char(char))
+                .unwrap_or(after.len());
+            let (name, after) = after.split_at(len);
+
+            name.starts_with(is_name_start)
+                .then_some((name, None, after))
+        };
+
+        let Some((name, default, after)) = braced.or_else(plain) else {
+            // A `$` that does not start a reference is kept
+            result.push('$');
+            rest = after;
+            continue;
+        };
+
+        match (std::env::var(name), default) {
+            (Ok(value), _) => result.push_str(&value),
+            (Err(_), Some(default)) => result.push_str(default),
+            (Err(_), None) => {
+                return Err(Error::new(
+                    ErrorKind::EnvVarNotSet {
+                        name: name.to_string(),
+                        string: string.span,
+                    },
+                    ctx.source.clone(),
+                    string.span,
+                ));
+            }
+        }
+        rest = after;
+    }
+    result.push_str(rest);
+
+    Ok(Value::new(ValueKind::String(result), ctx.expr.span))
+}
+
+fn escape_markup(string: &str, apostrophe: &str) -> String {
+    let mut escaped = String::with_capacity(string.len());
+    for char in string.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str(apostrophe),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+fn escape_with(ctx: &NativeFnCtx, escape: impl FnOnce(&str) -> String) -> ValueResult {
+    let string = ctx.ensure_is_string(ctx.get_arg_evaluated(0, 1)?)?;
+
+    Ok(Value::new(
+        ValueKind::String(escape(&string.data)),
+        ctx.expr.span,
+    ))
+}
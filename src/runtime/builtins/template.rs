@@ -0,0 +1,50 @@
+use super::BuiltinInfo;
+use crate::{
+    parser::parse_template,
+    runtime::{
+        Scope,
+        import::{eval_file_scope, read_text},
+        types::{Error, NativeFnCtx, Value, ValueKind, ValueResult},
+    },
+};
+use miette::NamedSource;
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[BuiltinInfo {
+    name: "renderTemplate",
+    params: &[("path", "path"), ("variables", "object")],
+    doc: "Renders a template file with the fields of `variables` defined.",
+    feature: Some("fs"),
+    func: render_template,
+}];
+
+fn render_template(ctx: NativeFnCtx) -> ValueResult {
+    let path = ctx.ensure_is_path(ctx.get_arg_evaluated(0, 2)?)?;
+    let vars = ctx.ensure_is_object(ctx.get_arg_evaluated(1, 2)?)?;
+
+    if let Some(inspector) = &ctx.options.inspector {
+        inspector.import(&path.data);
+    }
+
+    let file = read_text(&ctx, &path.data, path.span)?;
+    let source = NamedSource::new(path.data.display().to_string(), file);
+    let ast = parse_template(&source, &ctx.options).map_err(|err| {
+        let span = err.span;
+        let source = err.source.clone();
+        Error::new(err.into(), source, span)
+    })?;
+
+    let mut scope = Scope::without_builtins(ctx.variables.clone(), source, ast);
+    scope.set_options(ctx.options.clone());
+    scope.define_location(&path.data);
+
+    for (name, value) in vars.data {
+        scope.define(name, value);
+    }
+
+    let rendered = eval_file_scope(&ctx, &path.data, path.span, &mut scope)?;
+
+    Ok(Value::new(
+        ValueKind::String(rendered.to_string()),
+        ctx.expr.span,
+    ))
+}
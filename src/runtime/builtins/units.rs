@@ -0,0 +1,263 @@
+//! Human-friendly durations like `1h30m` and byte sizes like `512MiB`.
+
+use super::BuiltinInfo;
+use crate::runtime::types::{Error, ErrorKind, NativeFnCtx, Value, ValueKind, ValueResult};
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[
+    BuiltinInfo {
+        name: "parseDuration",
+        params: &[("duration", "string")],
+        doc: "Converts a duration like `1h30m` or `1.5d` into seconds, with the units `w`, `d`, `h`, `m` and `s`.",
+        feature: None,
+        func: parse_duration,
+    },
+    BuiltinInfo {
+        name: "formatDuration",
+        params: &[("seconds", "int")],
+        doc: "Formats seconds as a duration like `1d2h30m`.",
+        feature: None,
+        func: format_duration,
+    },
+    BuiltinInfo {
+        name: "parseBytes",
+        params: &[("size", "string")],
+        doc: "Converts a size like `512MiB` or `1.5GB` into bytes, `KiB` is 1024 bytes and `KB` is 1000 bytes.",
+        feature: None,
+        func: parse_bytes,
+    },
+    BuiltinInfo {
+        name: "formatBytes",
+        params: &[("bytes", "int")],
+        doc: "Formats bytes as a size with binary units like `1.5GiB`, with up to two decimals.",
+        feature: None,
+        func: format_bytes,
+    },
+];
+
+const DURATION_UNITS: &[(&str, u128)] = &[
+    ("w", 604_800),
+    ("d", 86_400),
+    ("h", 3_600),
+    ("m", 60),
+    ("s", 1),
+];
+
+const BYTE_UNITS: &[(&str, u128)] = &[
+    ("b", 1),
+    ("kb", 1_000),
+    ("mb", 1_000_000),
+    ("gb", 1_000_000_000),
+    ("tb", 1_000_000_000_000),
+    ("pb", 1_000_000_000_000_000),
+    ("eb", 1_000_000_000_000_000_000),
+    ("kib", 1 << 10),
+    ("mib", 1 << 20),
+    ("gib", 1 << 30),
+    ("tib", 1 << 40),
+    ("pib", 1 << 50),
+    ("eib", 1 << 60),
+];
+
+/// The units of [`format_bytes`], largest first.
+const FORMAT_UNITS: &[(&str, u128)] = &[
+    ("EiB", 1 << 60),
+    ("PiB", 1 << 50),
+    ("TiB", 1 << 40),
+    ("GiB", 1 << 30),
+    ("MiB", 1 << 20),
+    ("KiB", 1 << 10),
+];
+
+/// A number with an optional fraction and the unit after it, like `1.5` and `h`.
+struct Quantity<'a> {
+    whole: u128,
+    fraction: &'a str,
+    unit: &'a str,
+}
+
+impl Quantity<'_> {
+    /// The quantity in the base unit, the part of the fraction smaller than the base unit is dropped.
+    fn scale(&self, multiplier: u128) -> Option<u128> {
+        let mut fraction: u128 = 0;
+        let mut divisor: u128 = 1;
+        for digit in self.fraction.chars().filter_map(|char| char.to_digit(10)) {
+            fraction = fraction.checked_mul(10)?.checked_add(digit.into())?;
+            divisor = divisor.checked_mul(10)?;
+        }
+
+        self.whole
+            .checked_mul(multiplier)?
+            .checked_add(fraction.checked_mul(multiplier)?.checked_div(divisor)?)
+    }
+}
+
+fn parse_duration(ctx: NativeFnCtx) -> ValueResult {
+    let duration = ctx.ensure_is_string(ctx.get_arg_evaluated(0, 1)?)?;
+    let invalid = |reason: &str| invalid(&ctx, reason, duration.span);
+
+    let text = duration.data.trim();
+    if text == "0" {
+        return Ok(Value::new(ValueKind::Int(0), ctx.expr.span));
+    }
+
+    let mut seconds: u128 = 0;
+    for quantity in split_quantities(text).map_err(invalid)? {
+        let Some((_, multiplier)) = DURATION_UNITS
+            .iter()
+            .find(|(unit, _)| *unit == quantity.unit)
+        else {
+            return Err(invalid(
+                "Unknown duration unit, expected one of `w`, `d`, `h`, `m` or `s`",
+            ));
+        };
+
+        seconds = quantity
+            .scale(*multiplier)
+            .and_then(|value| seconds.checked_add(value))
+            .ok_or_else(|| invalid("The duration is too long"))?;
+    }
+
+    let seconds = isize::try_from(seconds).map_err(|_| invalid("The duration is too long"))?;
+    Ok(Value::new(ValueKind::Int(seconds), ctx.expr.span))
+}
+
+fn format_duration(ctx: NativeFnCtx) -> ValueResult {
+    let seconds = ctx.ensure_is_int(ctx.get_arg_evaluated(0, 1)?)?;
+
+    let mut remaining = u128::try_from(seconds.data.unsigned_abs()).unwrap_or(u128::MAX);
+    let mut formatted = String::new();
+    if seconds.data < 0 {
+        formatted.push('-');
+    }
+
+    // Weeks are left out, `14d` is easier to read than `2w`
+    for (unit, multiplier) in DURATION_UNITS.iter().skip(1) {
+        let count = remaining.checked_div(*multiplier).unwrap_or_default();
+        if count > 0 {
+            formatted.push_str(&format!("{count}{unit}"));
+            remaining = remaining.checked_rem(*multiplier).unwrap_or_default();
+        }
+    }
+    if seconds.data == 0 {
+        formatted.push_str("0s");
+    }
+
+    Ok(Value::new(ValueKind::String(formatted), ctx.expr.span))
+}
+
+fn parse_bytes(ctx: NativeFnCtx) -> ValueResult {
+    let size = ctx.ensure_is_string(ctx.get_arg_evaluated(0, 1)?)?;
+    let invalid = |reason: &str| invalid(&ctx, reason, size.span);
+
+    let quantities = split_quantities(size.data.trim()).map_err(invalid)?;
+    let [quantity] = quantities.as_slice() else {
+        return Err(invalid("A size has a single number and unit"));
+    };
+
+    // A plain number is a number of bytes
+    let unit = if quantity.unit.is_empty() {
+        "b"
+    } else {
+        &quantity.unit.to_ascii_lowercase()
+    };
+    let Some((_, multiplier)) = BYTE_UNITS.iter().find(|(name, _)| *name == unit) else {
+        return Err(invalid(
+            "Unknown size unit, expected `B` or one of `KB`, `MB`, `GB`, `TB`, `PB` and `EB` or their `KiB` forms",
+        ));
+    };
+
+    let bytes = quantity
+        .scale(*multiplier)
+        .and_then(|bytes| isize::try_from(bytes).ok())
+        .ok_or_else(|| invalid("The size is too big"))?;
+    Ok(Value::new(ValueKind::Int(bytes), ctx.expr.span))
+}
+
+fn format_bytes(ctx: NativeFnCtx) -> ValueResult {
+    let bytes = ctx.ensure_is_int(ctx.get_arg_evaluated(0, 1)?)?;
+    let value = u128::try_from(bytes.data.unsigned_abs()).unwrap_or(u128::MAX);
+    let sign = if bytes.data < 0 { "-" } else { "" };
+
+    let (unit, multiplier) = FORMAT_UNITS
+        .iter()
+        .copied()
+        .find(|(_, multiplier)| value >= *multiplier)
+        .unwrap_or(("B", 1));
+
+    let whole = value.checked_div(multiplier).unwrap_or_default();
+    let hundredths = value
+        .checked_rem(multiplier)
+        .and_then(|rest| rest.checked_mul(100))
+        .and_then(|rest| rest.checked_div(multiplier))
+        .unwrap_or_default();
+
+    let formatted = match hundredths {
+        0 => format!("{sign}{whole}{unit}"),
+        _ => {
+            let decimals = format!("{hundredths:02}");
+            format!("{sign}{whole}.{}{unit}", decimals.trim_end_matches('0'))
+        }
+    };
+
+    Ok(Value::new(ValueKind::String(formatted), ctx.expr.span))
+}
+
+/// Splits text like `1h 30.5m` into its quantities, whitespace is allowed between and inside of them.
+fn split_quantities(text: &str) -> Result<Vec<Quantity<'_>>, &'static str> {
+    let mut quantities = Vec::new();
+    let mut rest = text.trim_start();
+
+    while !rest.is_empty() {
+        let whole_len = rest
+            .find(|char: char| !char.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (whole, after) = rest.split_at(whole_len);
+        if whole.is_empty() {
+            return Err("Expected a number");
+        }
+        let whole = whole.parse().map_err(|_| "The number is too big")?;
+
+        let (fraction, after) = match after.strip_prefix('.') {
+            Some(after) => {
+                let fraction_len = after
+                    .find(|char: char| !char.is_ascii_digit())
+                    .unwrap_or(after.len());
+                if fraction_len == 0 {
+                    return Err("Expected digits after the decimal point");
+                }
+                after.split_at(fraction_len)
+            }
+            None => ("", after),
+        };
+
+        let after = after.trim_start();
+        let unit_len = after
+            .find(|char: char| !char.is_alphabetic())
+            .unwrap_or(after.len());
+        let (unit, after) = after.split_at(unit_len);
+
+        quantities.push(Quantity {
+            whole,
+            fraction,
+            unit,
+        });
+        rest = after.trim_start();
+    }
+
+    if quantities.is_empty() {
+        return Err("Expected a number");
+    }
+
+    Ok(quantities)
+}
+
+fn invalid(ctx: &NativeFnCtx, reason: &str, span: miette::SourceSpan) -> Error {
+    Error::new(
+        ErrorKind::InvalidQuantity {
+            reason: reason.to_string(),
+            value: span,
+        },
+        ctx.source.clone(),
+        span,
+    )
+}
@@ -0,0 +1,162 @@
+use super::BuiltinInfo;
+use crate::runtime::types::{Error, ErrorKind, NativeFnCtx, Value, ValueKind, ValueResult};
+use std::collections::BTreeMap;
+
+pub(super) const BUILTINS: &[BuiltinInfo] = &[BuiltinInfo {
+    name: "parseUrl",
+    params: &[("url", "string")],
+    doc: "Splits a URL into its `scheme`, `user`, `host`, `port`, `path`, `query` and `fragment`.",
+    feature: Some("url"),
+    func: parse_url,
+}];
+
+/// The ports used when a URL of these schemes does not have one.
+const DEFAULT_PORTS: &[(&str, u16)] = &[
+    ("http", 80),
+    ("https", 443),
+    ("ws", 80),
+    ("wss", 443),
+    ("ftp", 21),
+    ("ssh", 22),
+];
+
+struct Url<'a> {
+    scheme: String,
+    user: Option<&'a str>,
+    host: Option<String>,
+    port: Option<u16>,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>,
+}
+
+fn parse_url(ctx: NativeFnCtx) -> ValueResult {
+    let url = ctx.ensure_is_string(ctx.get_arg_evaluated(0, 1)?)?;
+    let parsed = split_url(&url.data).map_err(|reason| {
+        Error::new(
+            ErrorKind::InvalidUrl {
+                reason: reason.to_string(),
+                url: url.span,
+            },
+            ctx.source.clone(),
+            url.span,
+        )
+    })?;
+
+    let span = ctx.expr.span;
+    let string = |value: Option<&str>| {
+        Value::new(
+            value.map_or(ValueKind::Null, |value| {
+                ValueKind::String(value.to_string())
+            }),
+            span,
+        )
+    };
+    let port = parsed.port.or_else(|| {
+        DEFAULT_PORTS
+            .iter()
+            .find(|(scheme, _)| *scheme == parsed.scheme)
+            .map(|(_, port)| *port)
+    });
+
+    Ok(Value::new(
+        ValueKind::Object(BTreeMap::from([
+            ("scheme".to_string(), string(Some(&parsed.scheme))),
+            ("user".to_string(), string(parsed.user)),
+            ("host".to_string(), string(parsed.host.as_deref())),
+            (
+                "port".to_string(),
+                Value::new(
+                    port.map_or(ValueKind::Null, |port| {
+                        ValueKind::Int(isize::try_from(port).unwrap_or(isize::MAX))
+                    }),
+                    span,
+                ),
+            ),
+            ("path".to_string(), string(Some(parsed.path))),
+            ("query".to_string(), string(parsed.query)),
+            ("fragment".to_string(), string(parsed.fragment)),
+        ])),
+        span,
+    ))
+}
+
+/// Splits `scheme:[//[user@]host[:port]]path[?query][#fragment]`, without percent-decoding any part.
+fn split_url(url: &str) -> Result<Url<'_>, &'static str> {
+    let (scheme, rest) = url.split_once(':').ok_or("The URL has no scheme")?;
+    let mut scheme_chars = scheme.chars();
+    if !scheme_chars
+        .next()
+        .is_some_and(|char| char.is_ascii_alphabetic())
+        || !scheme_chars.all(|char| char.is_ascii_alphanumeric() || "+-.".contains(char))
+    {
+        return Err(
+            "The scheme has to start with a letter and only contain letters, digits, `+`, `-` and `.`",
+        );
+    }
+
+    let (rest, fragment) = match rest.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (rest, None),
+    };
+    let (rest, query) = match rest.split_once('?') {
+        Some((rest, query)) => (rest, Some(query)),
+        None => (rest, None),
+    };
+
+    let mut url = Url {
+        scheme: scheme.to_ascii_lowercase(),
+        user: None,
+        host: None,
+        port: None,
+        path: rest,
+        query,
+        fragment,
+    };
+
+    let Some(rest) = rest.strip_prefix("//") else {
+        return Ok(url);
+    };
+    let (authority, path) = rest
+        .find('/')
+        .map_or((rest, ""), |index| rest.split_at(index));
+    url.path = if path.is_empty() { "/" } else { path };
+
+    let host_port = match authority.rsplit_once('@') {
+        Some((user, host_port)) => {
+            url.user = Some(user);
+            host_port
+        }
+        None => authority,
+    };
+
+    let (host, port) = if let Some(ipv6) = host_port.strip_prefix('[') {
+        let (host, after) = ipv6
+            .split_once(']')
+            .ok_or("The IPv6 address is missing its `]`")?;
+        match after.strip_prefix(':') {
+            Some(port) => (host, Some(port)),
+            None if after.is_empty() => (host, None),
+            None => return Err("Unexpected characters after the IPv6 address"),
+        }
+    } else {
+        match host_port.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (host_port, None),
+        }
+    };
+
+    if host.is_empty() {
+        return Err("The URL has no host");
+    }
+    url.host = Some(host.to_ascii_lowercase());
+    url.port = match port {
+        Some("") | None => None,
+        Some(port) => Some(
+            port.parse()
+                .map_err(|_| "The port has to be a number between 0 and 65535")?,
+        ),
+    };
+
+    Ok(url)
+}
@@ -1,71 +1,308 @@
+use miette::SourceSpan;
+use std::{cell::Cell, collections::BTreeMap, ops::ControlFlow};
+
 use super::{
     ValueResult,
-    types::{Builtin, Error, ErrorKind},
+    types::{Builtin, Error, ErrorKind, Value},
 };
 use crate::{
-    merge_spans,
-    parser::ast::types::{Expr, ExprKind},
+    BuiltinHook, merge_spans,
+    parser::ast::types::{Expr, ExprKind, NamedArg},
     runtime::{ValueKind, types::NativeFnCtx},
 };
 
+/// How deep tl functions can call each other by default, see [`Options::max_call_depth`](crate::Options::max_call_depth).
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+thread_local! {
+    /// How many tl functions are being called inside of each other on this thread.
+    /// Every call creates its own scope, so scopes can't count it themselves.
+    static CALL_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Evaluated arguments of a call to a tl function.
+pub(super) struct CallArgs {
+    pub positional: Vec<Value>,
+    /// The name, the span of the name and the value of each named argument.
+    pub named: Vec<(String, SourceSpan, Value)>,
+    /// The span of all arguments, used for arity errors.
+    pub span: SourceSpan,
+}
+
 impl super::Scope {
     pub(super) fn eval_call(&mut self, expr: &Expr) -> ValueResult {
-        let ExprKind::Call { base, args } = &expr.kind else {
-            unreachable!()
+        let ExprKind::Call {
+            base,
+            args,
+            named_args,
+        } = &expr.kind
+        else {
+            return Err(Error::new(
+                ErrorKind::Internal {
+                    message: "a call was evaluated without a call expression".to_string(),
+                },
+                self.source.clone(),
+                expr.span,
+            ));
         };
 
         let function = self.eval_expr(base)?;
         let name = base.as_ident().unwrap_or("<unknown name>".into());
 
         match function.kind {
-            ValueKind::Function {
-                args: ref parameters,
-                expr: ref body,
-            } => {
-                let mut evaluated_args = Vec::with_capacity(args.len());
+            ValueKind::Function { .. } => {
+                let mut positional = Vec::with_capacity(args.len());
                 for expr in args {
-                    evaluated_args.push(self.eval_expr(expr)?);
+                    positional.push(self.eval_expr(expr)?);
                 }
 
-                if args.len() != parameters.len() {
-                    let args = if let Some(first) = args.iter().next()
-                        && let Some(last) = args.iter().last()
-                    {
-                        merge_spans(first.span, last.span)
-                    } else {
-                        base.span
-                    };
+                let mut named = Vec::with_capacity(named_args.len());
+                for NamedArg { name, span, value } in named_args {
+                    named.push((name.clone(), *span, self.eval_expr(value)?));
+                }
 
+                let args = CallArgs {
+                    positional,
+                    named,
+                    span: args_span(base, args, named_args),
+                };
+
+                self.call_function(function, &name, args, expr.span)
+            }
+            ValueKind::Builtin(Builtin(builtin)) => {
+                // Builtins only take positional arguments
+                if let Some(NamedArg { span, .. }) = named_args.first() {
                     return Err(Error::new(
-                        ErrorKind::ArgsMismatch {
-                            len: parameters.len(),
-                            args,
-                        },
+                        ErrorKind::UnknownArgument { argument: *span },
                         self.source.clone(),
-                        expr.span,
+                        *span,
                     ));
                 }
 
-                let scope = self.create_scope(body.clone());
-
-                for (param, arg) in parameters.iter().zip(evaluated_args) {
-                    scope.define(param, arg);
-                }
-
-                scope.define(&name, function);
-
-                scope.eval()
-            }
-            ValueKind::Builtin(Builtin(builtin)) => {
                 let ctx = NativeFnCtx {
                     expr: expr.clone(),
                     variables: self.variables.clone(),
                     source: self.source.clone(),
+                    options: self.options.clone(),
+                    origin: self.origin.clone(),
                 };
 
+                if let Some(BuiltinHook(hook)) = &self.options.on_builtin_call
+                    && let ControlFlow::Break(result) = hook(&builtin_name(base), &ctx)
+                {
+                    return result;
+                }
+
                 builtin(ctx)
             }
-            _ => unreachable!("`function` was filtered before to only match for functions"),
+            _ => Err(Error::new(
+                ErrorKind::NotCallable {
+                    type_name: function.type_of().into(),
+                    call: call_parens_span(base, expr),
+                    value: function.span,
+                },
+                self.source.clone(),
+                expr.span,
+            )),
+        }
+    }
+
+    /// Calls a tl function with already evaluated arguments, `function` is defined as `name` inside of its body.
+    /// # Errors
+    /// This function will return an error if the arguments don't match the parameters of `function` or its body fails to evaluate.
+    pub(super) fn call_function(
+        &mut self,
+        function: Value,
+        name: &str,
+        args: CallArgs,
+        call_span: SourceSpan,
+    ) -> ValueResult {
+        let ValueKind::Function {
+            args: ref parameters,
+            ref defaults,
+            ref rest,
+            expr: ref body,
+            source: ref definition_source,
+            ref origin,
+        } = function.kind
+        else {
+            return Err(Error::new(
+                ErrorKind::MismatchedTypes {
+                    expected: "function".into(),
+                    got: function.type_of().into(),
+                },
+                self.source.clone(),
+                call_span,
+            ));
+        };
+
+        let given = args.positional.len().saturating_add(args.named.len());
+
+        // Named arguments are matched first, positional arguments fill the remaining parameters in order
+        let mut values: Vec<Option<Value>> = vec![None; parameters.len()];
+        for (name, span, value) in args.named {
+            let Some(slot) = parameters
+                .iter()
+                .position(|parameter| *parameter == name)
+                .and_then(|index| values.get_mut(index))
+            else {
+                return Err(Error::new(
+                    ErrorKind::UnknownArgument { argument: span },
+                    self.source.clone(),
+                    span,
+                ));
+            };
+
+            if slot.is_some() {
+                return Err(Error::new(
+                    ErrorKind::DuplicateArgument { argument: span },
+                    self.source.clone(),
+                    span,
+                ));
+            }
+
+            *slot = Some(value);
+        }
+
+        let required = parameters
+            .iter()
+            .filter(|parameter| !defaults.contains_key(*parameter))
+            .count();
+
+        let source = self.source.clone();
+        let args_mismatch = |len, variadic| {
+            // The definition can only be labeled if the function is defined in the source of the call
+            let definition = (definition_source.name() == source.name()
+                && definition_source.inner() == source.inner())
+            .then_some(function.span);
+
+            Error::new(
+                ErrorKind::ArgsMismatch {
+                    len,
+                    variadic,
+                    given,
+                    args: args.span,
+                    definition,
+                    signature: Some(signature(name, parameters, defaults, rest.as_deref())),
+                },
+                source.clone(),
+                call_span,
+            )
+        };
+
+        if given > parameters.len() && rest.is_none() {
+            return Err(args_mismatch(parameters.len(), false));
         }
+
+        let depth = CALL_DEPTH.get();
+        if depth >= self.options.max_call_depth {
+            return Err(Error::new(
+                ErrorKind::CallTooDeep {
+                    limit: self.options.max_call_depth,
+                    call: call_span,
+                },
+                self.source.clone(),
+                call_span,
+            ));
+        }
+
+        let scope = self.create_scope(body.clone());
+        // Path literals in the body resolve against the file the function is defined in, not the one calling it
+        scope.origin.clone_from(origin);
+
+        // Parameters without an argument use their default, which can refer to the parameters before it
+        let named: Vec<bool> = values.iter().map(Option::is_some).collect();
+        let mut positional = args.positional.into_iter();
+        for ((index, param), value) in parameters.iter().enumerate().zip(values) {
+            let value = match value.or_else(|| positional.next()) {
+                Some(value) => value,
+                None => match defaults.get(param) {
+                    Some(default) => scope.eval_expr(default)?,
+                    None => {
+                        // Positional arguments also fill the parameters with a default before this one
+                        let named_after = named
+                            .iter()
+                            .skip(index.saturating_add(1))
+                            .filter(|named| **named)
+                            .count();
+                        let needed = index.saturating_add(1).saturating_add(named_after);
+
+                        return Err(args_mismatch(
+                            required.max(needed),
+                            required < parameters.len() || rest.is_some(),
+                        ));
+                    }
+                },
+            };
+
+            scope.define(param, value);
+        }
+
+        if let Some(rest) = rest {
+            scope.define(
+                rest,
+                Value::new(ValueKind::Array(positional.collect()), call_span),
+            );
+        }
+
+        scope.define(name, function.clone());
+
+        CALL_DEPTH.set(depth.saturating_add(1));
+        let result = scope.eval();
+        CALL_DEPTH.set(depth);
+
+        result
+    }
+}
+
+/// The span of all arguments of a call, or of the called expression if there are none.
+fn args_span(base: &Expr, args: &[Expr], named_args: &[NamedArg]) -> SourceSpan {
+    let mut spans = args.iter().map(|arg| arg.span).chain(
+        named_args
+            .iter()
+            .map(|arg| merge_spans(arg.span, arg.value.span)),
+    );
+
+    match spans.next() {
+        Some(first) => spans.fold(first, merge_spans),
+        None => base.span,
+    }
+}
+
+/// The span of the parentheses of a call, everything after the called expression.
+fn call_parens_span(base: &Expr, call: &Expr) -> SourceSpan {
+    let start = base.span.offset().saturating_add(base.span.len());
+    let end = call.span.offset().saturating_add(call.span.len());
+
+    (start, end.saturating_sub(start)).into()
+}
+
+/// The parameters of a tl function as they are written, like `greet(name, greeting?, ...rest)`.
+fn signature(
+    name: &str,
+    parameters: &[String],
+    defaults: &BTreeMap<String, Expr>,
+    rest: Option<&str>,
+) -> String {
+    let params: Vec<_> = parameters
+        .iter()
+        .map(|parameter| {
+            if defaults.contains_key(parameter) {
+                format!("{parameter}?")
+            } else {
+                parameter.clone()
+            }
+        })
+        .chain(rest.map(|rest| format!("...{rest}")))
+        .collect();
+
+    format!("{name}({})", params.join(", "))
+}
+
+/// The name a builtin is called by, `builtins.name` is called `name` as well.
+pub(super) fn builtin_name(base: &Expr) -> String {
+    match &base.kind {
+        ExprKind::Identifier(name) | ExprKind::ObjectAccess { field: name, .. } => name.clone(),
+        _ => "<unknown name>".into(),
     }
 }
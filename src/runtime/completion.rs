@@ -0,0 +1,79 @@
+use super::{Scope, ValueKind};
+use crate::parser::lexer::{Lexer, types::TokenKind};
+use miette::NamedSource;
+use std::collections::BTreeSet;
+
+impl Scope {
+    /// Returns the completions for the word at the end of `line`, sorted and without duplicates.
+    /// After a `.` the fields of the object before it are completed, otherwise variables and builtins.
+    /// Returns nothing if `line` fails to tokenize or doesn't end in a completable word.
+    pub fn completions(&self, line: &str) -> Vec<String> {
+        let Ok(tokens) = Lexer::new(NamedSource::new("completion", line.to_string())).tokenize()
+        else {
+            return Vec::new();
+        };
+
+        // The word being typed has to end the line, `foo ` starts a new one
+        let (prefix, mut tokens) = match tokens.split_last() {
+            Some((last, rest))
+                if last.span.offset().saturating_add(last.span.len()) == line.len() =>
+            {
+                match &last.kind {
+                    TokenKind::Identifier(name) => (name.as_str(), rest),
+                    TokenKind::Dot => ("", tokens.as_slice()),
+                    _ => return Vec::new(),
+                }
+            }
+            None => ("", &[][..]),
+            Some(_) if line.ends_with(char::is_whitespace) => ("", &[][..]),
+            Some(_) => return Vec::new(),
+        };
+
+        // Collect the field accesses before the prefix, `a.b.` yields `[a, b]`
+        let mut path = Vec::new();
+        while let Some((TokenKind::Dot, rest)) =
+            tokens.split_last().map(|(last, rest)| (&last.kind, rest))
+        {
+            let Some((TokenKind::Identifier(name), rest)) =
+                rest.split_last().map(|(last, rest)| (&last.kind, rest))
+            else {
+                return Vec::new();
+            };
+
+            path.push(name.as_str());
+            tokens = rest;
+        }
+
+        let candidates: BTreeSet<String> = match path.pop() {
+            None => self
+                .variables
+                .keys()
+                .cloned()
+                .chain(
+                    Self::builtins()
+                        .iter()
+                        .map(|builtin| builtin.name.to_string()),
+                )
+                .collect(),
+            Some(root) => {
+                let Some(mut value) = self.fetch_var(&root).cloned() else {
+                    return Vec::new();
+                };
+
+                for field in path.into_iter().rev() {
+                    value = value.access(field);
+                }
+
+                match value.kind {
+                    ValueKind::Object(fields) => fields.into_keys().collect(),
+                    _ => return Vec::new(),
+                }
+            }
+        };
+
+        candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(prefix))
+            .collect()
+    }
+}
@@ -0,0 +1,70 @@
+use super::{
+    Scope,
+    types::{Error, Value, ValueResult},
+};
+use crate::{
+    Options,
+    parser::{
+        ast::types::{Expr, Literal},
+        parse_with_options,
+    },
+};
+use miette::{NamedSource, SourceSpan};
+use std::collections::HashMap;
+
+/// Evaluates many expressions against the same variables, the builtins are only defined once.
+#[derive(Debug)]
+pub struct Engine {
+    base: Scope,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        let base = Scope::new(
+            HashMap::new(),
+            NamedSource::new("engine", String::new()),
+            Expr::lit(Literal::Null, SourceSpan::new(0.into(), 0)),
+        );
+
+        Self { base }
+    }
+
+    pub fn options(&self) -> &Options {
+        self.base.options()
+    }
+
+    pub fn set_options(&mut self, options: Options) {
+        self.base.set_options(options);
+    }
+
+    /// Defines a variable for every expression evaluated afterwards.
+    pub fn define(&mut self, name: impl ToString, value: impl Into<Value>) {
+        self.base.define(name, value);
+    }
+
+    /// Parses and evaluates `source` with the options of the engine.
+    /// # Errors
+    /// This function will return an error if either a parsing or an evaluation error occurs.
+    pub fn eval(&self, source: NamedSource<String>) -> ValueResult {
+        let ast = parse_with_options(&source, self.options()).map_err(|err| {
+            let span = err.span;
+            let source = err.source.clone();
+            Error::new(err.into(), source, span)
+        })?;
+
+        self.eval_ast(source, ast)
+    }
+
+    /// Evaluates an expression that was parsed before, `source` is the source it was parsed from.
+    /// # Errors
+    /// This function will return an error if an evaluation error occurs.
+    pub fn eval_ast(&self, source: NamedSource<String>, ast: Expr) -> ValueResult {
+        self.base.fork(source, ast).eval()
+    }
+}
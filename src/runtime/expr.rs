@@ -1,88 +1,377 @@
-use miette::SourceSpan;
+use miette::{NamedSource, SourceSpan};
 
 use super::{
     ValueResult,
-    types::{Error, ErrorKind, Value},
+    types::{Builtin, Error, ErrorKind, NativeFnCtx, Value},
 };
 use crate::{
-    parser::ast::types::{Expr, ExprKind, Literal},
-    runtime::{Scope, ValueKind},
+    parser::ast::types::{Contract, Expr, ExprKind, Literal, Pattern},
+    runtime::{Scope, ValueKind, resolve_relative},
+    utils::{clean_path, normalize_path},
 };
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, path::Path, rc::Rc};
+
+/// How much stack has to be left before evaluating an expression, a single level of nesting
+/// takes a few dozen kilobytes in debug builds.
+const STACK_RED_ZONE: usize = 256 * 1024;
+/// How much stack is allocated whenever the remaining stack is smaller than [`STACK_RED_ZONE`].
+const STACK_GROWTH: usize = 4 * 1024 * 1024;
+
+/// The arguments of the script, see [`Options::args`](crate::Options::args).
+pub(crate) const ARGS_VARIABLE: &str = "args";
 
 impl super::Scope {
     pub(super) fn eval_expr(&mut self, expr: &Expr) -> ValueResult {
+        // Evaluation recurses for every level of nesting, grow the stack instead of overflowing it
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_GROWTH, || self.eval_expr_nested(expr))
+    }
+
+    fn eval_expr_nested(&mut self, expr: &Expr) -> ValueResult {
+        let result = match self.options.inspector.clone() {
+            None => self.eval_expr_kind(expr),
+            Some(inspector) => {
+                inspector.enter(&self.frame(expr));
+                let result = self.eval_expr_kind(expr);
+                inspector.exit(&self.frame(expr), &result);
+                result
+            }
+        };
+
+        match &expr.contract {
+            Some(contract) => self.check_contract(contract, expr, result?),
+            None => result,
+        }
+    }
+
+    /// A child scope of this one with the `let` bindings defined, for evaluating `body`.
+    #[inline(never)]
+    pub(super) fn let_scope(
+        &self,
+        bindings: &[(Pattern, Expr)],
+        body: &Expr,
+    ) -> Result<Scope, Error> {
+        let mut child_scope =
+            Scope::without_builtins(self.variables.clone(), self.source.clone(), body.clone());
+        child_scope.set_options(self.options.clone());
+        child_scope.origin.clone_from(&self.origin);
+
+        for (pattern, expr) in bindings {
+            let value = child_scope.eval_expr(expr)?;
+            child_scope.bind_pattern(pattern, value, expr.span)?;
+
+            if let Some(inspector) = &self.options.inspector {
+                for name in pattern.names() {
+                    if let Some(value) = child_scope.fetch_var(&name) {
+                        inspector.bind(name, value, &child_scope.frame(expr));
+                    }
+                }
+            }
+        }
+
+        Ok(child_scope)
+    }
+
+    /// Evaluates `base.field`, lazy fields of an object created by `fix` are evaluated here.
+    #[inline(never)]
+    fn eval_object_access(
+        &mut self,
+        expr: &Expr,
+        base: &Expr,
+        field: &str,
+        fallback: Option<&Expr>,
+    ) -> ValueResult {
+        let base_value = self.eval_expr(base)?;
+        let missing =
+            !matches!(&base_value.kind, ValueKind::Object(fields) if fields.contains_key(field));
+
+        if self.options.strict_fields && missing && fallback.is_none() {
+            return Err(Error::new(
+                ErrorKind::MissingField {
+                    field: field.to_string(),
+                    base_type: base_value.type_of().into(),
+                    base: base.span,
+                },
+                self.source.clone(),
+                expr.span,
+            ));
+        }
+
+        let value = match base_value.access(field) {
+            Value {
+                kind: ValueKind::Lazy(Builtin(thunk)),
+                ..
+            } => thunk(NativeFnCtx {
+                expr: expr.clone(),
+                variables: self.variables.clone(),
+                source: self.source.clone(),
+                options: self.options.clone(),
+                origin: self.origin.clone(),
+            })?,
+            value => value,
+        };
+
+        match fallback {
+            Some(fallback) if matches!(value.kind, ValueKind::Null) => self.eval_expr(fallback),
+            _ => Ok(value),
+        }
+    }
+
+    /// Checks the value of `expr` against the type annotation of its binding.
+    #[inline(never)]
+    fn check_contract(&self, contract: &Contract, expr: &Expr, value: Value) -> ValueResult {
+        if value.kind.satisfies(&contract.ty) {
+            return Ok(value);
+        }
+
+        Err(Error::new(
+            ErrorKind::ContractViolation {
+                expected: contract.ty.to_string(),
+                found: value.type_of().into(),
+                annotation: contract.span,
+                value: expr.span,
+            },
+            self.source.clone(),
+            expr.span,
+        ))
+    }
+
+    /// Dispatches on the kind of `expr`, the arms are separate functions so the frame of this function,
+    /// which is on the stack once for every level of nesting, stays small.
+    fn eval_expr_kind(&mut self, expr: &Expr) -> ValueResult {
         match &expr.kind {
             ExprKind::Literal(literal) => self.eval_literal(literal, expr.span),
-            ExprKind::Not(body) => Ok(Value::new(
-                ValueKind::Boolean(!self.eval_expr(body)?.is_truthy()),
-                expr.span,
-            )),
-            ExprKind::Identifier(ident) => Ok(self
-                .fetch_var(ident)
-                .ok_or(Error::new(
-                    ErrorKind::VariableNotInScope {
-                        variable: expr.span,
-                    },
-                    self.source.clone(),
+            ExprKind::Not(body) => {
+                let value = self.eval_expr(body)?;
+                Ok(Value::new(
+                    ValueKind::Boolean(!value.is_truthy()),
                     expr.span,
-                ))?
-                .clone()),
-            ExprKind::ArrayIndex { base, index } => {
-                let base = self.eval_expr(base)?;
-                let item = base.try_index(*index);
-
-                match item {
-                    Ok(item) => Ok(item.clone()),
-                    Err(len) => Err(Error::new(
-                        ErrorKind::IndexOutOfBounds {
-                            length: len,
-                            // TODO: Add span for the index itself, not the full expr
-                            index: expr.span,
-                        },
-                        self.source.clone(),
-                        expr.span,
-                    )),
-                }
-            }
-            ExprKind::ObjectAccess { base, field } => {
-                let base = self.eval_expr(base)?;
-                Ok(base.access(field))
+                ))
             }
+            ExprKind::Identifier(ident) => self.eval_identifier(ident, expr.span),
+            ExprKind::ArrayIndex { base, index } => self.eval_array_index(base, *index, expr.span),
+            ExprKind::ObjectAccess {
+                base,
+                field,
+                fallback,
+            } => self.eval_object_access(expr, base, field, fallback.as_deref()),
             ExprKind::BinaryOp {
                 left,
                 operator,
                 right,
-            } => Ok(self.eval_binary_op(left, operator, right)?),
-            ExprKind::FnDecl { args, expr: body } => Ok(Value::new(
-                ValueKind::Function {
-                    args: args.clone(),
-                    expr: *body.clone(),
-                },
+            } => self.eval_binary_op(left, operator, right),
+            ExprKind::FnDecl {
+                args,
+                defaults,
+                rest,
+                expr: body,
+            } => Ok(function_value(
+                args,
+                defaults,
+                rest.as_ref(),
+                body,
+                &self.source,
+                self.origin.as_ref(),
                 expr.span,
             )),
             ExprKind::Call { .. } => self.eval_call(expr),
             ExprKind::LetIn {
                 bindings,
                 expr: body,
-            } => {
-                let mut child_scope =
-                    Scope::new(self.variables.clone(), self.source.clone(), *body.clone());
+            } => self.let_scope(bindings, body)?.eval_expr(body),
+            ExprKind::Comprehension {
+                expr: body,
+                binding,
+                iterable,
+                condition,
+            } => self.eval_comprehension(body, binding, iterable, condition.as_deref(), expr.span),
+        }
+    }
+
+    #[inline(never)]
+    fn eval_identifier(&self, ident: &str, span: SourceSpan) -> ValueResult {
+        match self.fetch_var(&ident) {
+            Some(value) => Ok(value.clone()),
+            // Read from the options so imported files see the same arguments
+            None if ident == ARGS_VARIABLE => Ok(Value::new(
+                ValueKind::Array(
+                    self.options
+                        .args
+                        .iter()
+                        .map(|arg| Value::new(ValueKind::String(arg.clone()), span))
+                        .collect(),
+                ),
+                span,
+            )),
+            None => Err(Error::new(
+                ErrorKind::VariableNotInScope { variable: span },
+                self.source.clone(),
+                span,
+            )),
+        }
+    }
+
+    #[inline(never)]
+    fn eval_array_index(&mut self, base: &Expr, index: usize, span: SourceSpan) -> ValueResult {
+        let base = self.eval_expr(base)?;
+        let item = base.try_index(index);
 
-                for (name, expr) in bindings {
-                    let value = child_scope.eval_expr(expr)?;
-                    child_scope.define(name, value);
+        match item {
+            Ok(item) => Ok(item.clone()),
+            Err(len) => Err(Error::new(
+                ErrorKind::IndexOutOfBounds {
+                    length: len,
+                    // TODO: Add span for the index itself, not the full expr
+                    index: span,
+                },
+                self.source.clone(),
+                span,
+            )),
+        }
+    }
+
+    #[inline(never)]
+    fn eval_comprehension(
+        &mut self,
+        body: &Expr,
+        binding: &str,
+        iterable: &Expr,
+        condition: Option<&Expr>,
+        span: SourceSpan,
+    ) -> ValueResult {
+        let items = self.eval_expr(iterable)?;
+        let ValueKind::Array(items) = items.kind else {
+            return Err(Error::new(
+                ErrorKind::MismatchedTypes {
+                    expected: ValueKind::Array(Vec::new()).type_of().to_string(),
+                    got: items.type_of().to_string(),
+                },
+                self.source.clone(),
+                iterable.span,
+            ));
+        };
+
+        let mut child_scope =
+            Scope::without_builtins(self.variables.clone(), self.source.clone(), body.clone());
+        child_scope.set_options(self.options.clone());
+        child_scope.origin.clone_from(&self.origin);
+
+        let mut values = Vec::new();
+        for item in items {
+            child_scope.define(binding, item);
+
+            if let Some(condition) = condition {
+                let value = child_scope.eval_expr(condition)?;
+                if !child_scope.is_condition_met(&value, condition.span)? {
+                    continue;
+                }
+            }
+
+            values.push(child_scope.eval_expr(body)?);
+        }
+
+        Ok(Value::new(ValueKind::Array(values), span))
+    }
+
+    /// Defines the variables of `pattern`, `span` is the span of the expression that evaluated to `value`.
+    fn bind_pattern(
+        &mut self,
+        pattern: &Pattern,
+        value: Value,
+        span: SourceSpan,
+    ) -> Result<(), Error> {
+        match pattern {
+            Pattern::Identifier(name) => self.define(name, value),
+            Pattern::Object(fields) => {
+                if !matches!(value.kind, ValueKind::Object(_)) {
+                    return Err(self.mismatched_pattern_type(
+                        &ValueKind::Object(BTreeMap::new()),
+                        &value,
+                        span,
+                    ));
                 }
 
-                child_scope.eval_expr(body)
+                // Missing fields are null, like when accessing them with `.`
+                for field in fields {
+                    self.define(field, value.access(field));
+                }
             }
+            Pattern::Array { items, rest } => {
+                let ValueKind::Array(values) = value.kind else {
+                    return Err(self.mismatched_pattern_type(
+                        &ValueKind::Array(Vec::new()),
+                        &value,
+                        span,
+                    ));
+                };
+
+                if values.len() < items.len() {
+                    return Err(Error::new(
+                        ErrorKind::IndexOutOfBounds {
+                            length: values.len(),
+                            index: span,
+                        },
+                        self.source.clone(),
+                        span,
+                    ));
+                }
+
+                let mut values = values.into_iter();
+                for (name, value) in items.iter().zip(values.by_ref()) {
+                    self.define(name, value);
+                }
+
+                if let Some(rest) = rest {
+                    self.define(rest, Value::new(ValueKind::Array(values.collect()), span));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether a condition holds, it has to be a boolean with [`Options::strict_conditions`](super::Options::strict_conditions).
+    /// `span` is the span of the condition expression.
+    pub(super) fn is_condition_met(
+        &self,
+        condition: &Value,
+        span: SourceSpan,
+    ) -> Result<bool, Error> {
+        match condition.kind {
+            ValueKind::Boolean(value) => Ok(value),
+            _ if self.options.strict_conditions => Err(Error::new(
+                ErrorKind::NonBooleanCondition {
+                    type_name: condition.type_of().to_string(),
+                    condition: span,
+                },
+                self.source.clone(),
+                span,
+            )),
+            _ => Ok(condition.is_truthy()),
         }
     }
 
+    fn mismatched_pattern_type(
+        &self,
+        expected: &ValueKind,
+        value: &Value,
+        span: SourceSpan,
+    ) -> Error {
+        Error::new(
+            ErrorKind::MismatchedTypes {
+                expected: expected.type_of().to_string(),
+                got: value.type_of().to_string(),
+            },
+            self.source.clone(),
+            span,
+        )
+    }
+
     pub(super) fn eval_literal(&mut self, literal: &Literal, span: SourceSpan) -> ValueResult {
         match literal {
             Literal::Null => Ok(Value::new(ValueKind::Null, span)),
             Literal::Int(v) => Ok(Value::new(ValueKind::Int(*v), span)),
+            #[cfg(feature = "bigint")]
+            Literal::BigInt(v) => Ok(Value::new(ValueKind::BigInt(v.clone()), span)),
             Literal::Float(v) => Ok(Value::new(ValueKind::Float(*v), span)),
             Literal::Bool(v) => Ok(Value::new(ValueKind::Boolean(*v), span)),
             Literal::String(v) => Ok(Value::new(ValueKind::String(v.clone()), span)),
@@ -96,16 +385,40 @@ impl super::Scope {
 
                 Ok(Value::new(ValueKind::String(value), span))
             }
-            Literal::Path(path) => Ok(Value::new(ValueKind::Path(path.clone()), span)),
+            Literal::Path(path) => {
+                let path = match self.origin() {
+                    Some(dir) => resolve_relative(dir, path),
+                    None => path.clone(),
+                };
+                self.ensure_in_root(&path, span)?;
+                Ok(Value::new(ValueKind::Path(path), span))
+            }
             Literal::InterpolatedPath(v) => {
                 let mut value = String::new();
 
                 for expr in v {
-                    let expr = self.eval_expr(expr)?;
-                    value.push_str(&expr.to_string());
+                    let part = self.eval_expr(expr)?.to_string();
+
+                    if let Some(character) = part.chars().find(|ch| ch.is_control()) {
+                        return Err(Error::new(
+                            ErrorKind::InvalidPathCharacter {
+                                character,
+                                part: expr.span,
+                            },
+                            self.source.clone(),
+                            span,
+                        ));
+                    }
+
+                    value.push_str(&part);
                 }
 
-                Ok(Value::new(ValueKind::Path(value.into()), span))
+                let path = match self.origin() {
+                    Some(dir) => resolve_relative(dir, Path::new(&value)),
+                    None => clean_path(Path::new(&value)),
+                };
+                self.ensure_in_root(&path, span)?;
+                Ok(Value::new(ValueKind::Path(path), span))
             }
             Literal::Array(v) => {
                 let mut values = Vec::new();
@@ -127,4 +440,50 @@ impl super::Scope {
             }
         }
     }
+
+    /// Checks that `path` is inside of [`Options::path_root`](crate::Options::path_root), if there is one.
+    pub(super) fn ensure_in_root(&self, path: &Path, span: SourceSpan) -> Result<(), Error> {
+        let Some(root) = &self.options.path_root else {
+            return Ok(());
+        };
+
+        let resolved = normalize_path(path);
+        if resolved.starts_with(normalize_path(root)) {
+            return Ok(());
+        }
+
+        Err(Error::new(
+            ErrorKind::PathOutsideRoot {
+                root: root.display().to_string(),
+                path: span,
+                resolved: resolved.display().to_string(),
+            },
+            self.source.clone(),
+            span,
+        ))
+    }
+}
+
+/// The value of a function declaration.
+#[inline(never)]
+fn function_value(
+    args: &[String],
+    defaults: &BTreeMap<String, Expr>,
+    rest: Option<&String>,
+    body: &Expr,
+    source: &NamedSource<String>,
+    origin: Option<&Rc<Path>>,
+    span: SourceSpan,
+) -> Value {
+    Value::new(
+        ValueKind::Function {
+            args: args.to_vec(),
+            defaults: defaults.clone(),
+            rest: rest.cloned(),
+            expr: body.clone(),
+            source: Box::new(source.clone()),
+            origin: origin.cloned(),
+        },
+        span,
+    )
 }
@@ -0,0 +1,211 @@
+use super::{
+    Scope,
+    types::{Error, ErrorKind, NativeFnCtx, Value, ValueKind, ValueResult},
+};
+use crate::{Options, parser::parse_with_options, utils::clean_path};
+use miette::{NamedSource, SourceSpan};
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// The file that is imported from a directory if [`Options::directory_import_file`] is not set.
+pub(super) const DEFAULT_DIRECTORY_IMPORT_FILE: &str = "default.tl";
+
+/// The variable with the path of the file that is being evaluated.
+pub(crate) const FILE_VARIABLE: &str = "__file__";
+/// The variable with the directory of the file that is being evaluated.
+pub(crate) const DIR_VARIABLE: &str = "__dir__";
+
+thread_local! {
+    /// The files that are being imported or rendered inside of each other on this thread, outermost first.
+    static EVALUATING: RefCell<Vec<PathBuf>> = const { RefCell::new(Vec::new()) };
+}
+
+impl Scope {
+    /// Defines `__file__` and `__dir__` as the location of `path`, the file this scope evaluates.
+    pub fn define_location(&mut self, path: &Path) {
+        let dir = parent_dir(path);
+        self.origin = Some(Rc::from(dir));
+
+        self.define(
+            DIR_VARIABLE,
+            Value::new_builtin(ValueKind::Path(dir.to_path_buf())),
+        );
+        self.define(
+            FILE_VARIABLE,
+            Value::new_builtin(ValueKind::Path(path.to_path_buf())),
+        );
+    }
+
+    /// The directory of the file this scope evaluates, set by [`Scope::define_location`].
+    /// Child scopes inherit it and functions keep the one they are defined with,
+    /// so it is the directory of the file a path is written in even if `__dir__` is shadowed.
+    pub fn origin(&self) -> Option<&Path> {
+        self.origin.as_deref()
+    }
+}
+
+/// The directory of the file at `path`, `.` for a file name without a directory.
+pub(crate) fn parent_dir(path: &Path) -> &Path {
+    path.parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+}
+
+/// Resolves a path literal like `./lib.tl` against `dir`, the directory of the file it is written in.
+/// Absolute paths are kept as they are.
+pub(crate) fn resolve_relative(dir: &Path, path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+
+    clean_path(&dir.join(path))
+}
+
+/// Resolves the path given to `import` to the file that should be evaluated.
+pub(crate) fn resolve(path: &Path, options: &Options) -> PathBuf {
+    if !path.is_dir() {
+        return path.to_path_buf();
+    }
+
+    match &options.directory_import_file {
+        Some(file) => path.join(file),
+        None => path.join(DEFAULT_DIRECTORY_IMPORT_FILE),
+    }
+}
+
+/// Evaluates the file at the path in the first argument of a call to `import` or `importWith`.
+pub(super) fn import_file(ctx: &NativeFnCtx, expected_len: usize) -> ValueResult {
+    let (path, path_span) = {
+        let path = ctx.ensure_is_path(ctx.get_arg_evaluated(0, expected_len)?)?;
+        (resolve(&path.data, &ctx.options), path.span)
+    };
+
+    if let Some(inspector) = &ctx.options.inspector {
+        inspector.import(&path);
+    }
+
+    let file = read_text(ctx, &path, path_span)?;
+    let source = NamedSource::new(path.display().to_string(), file);
+    let ast = parse_with_options(&source, &ctx.options).map_err(|err| {
+        let span = err.span;
+        let source = err.source.clone();
+        Error::new(err.into(), source, span)
+    })?;
+
+    let mut scope = Scope::without_builtins(ctx.variables.clone(), source, ast);
+    scope.set_options(ctx.options.clone());
+    scope.define_location(&path);
+
+    eval_file_scope(ctx, &path, path_span, &mut scope)
+}
+
+/// Evaluates `scope`, which evaluates the file at `path` for a builtin called in `ctx`.
+/// Fails instead if the file is already being evaluated further up, so files that import each other
+/// report the cycle instead of recursing until the stack overflows. `span` is the span of the path argument.
+pub(crate) fn eval_file_scope(
+    ctx: &NativeFnCtx,
+    path: &Path,
+    span: SourceSpan,
+    scope: &mut Scope,
+) -> ValueResult {
+    let file = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+    let cycle = EVALUATING.with_borrow(|evaluating| {
+        let start = evaluating.iter().position(|other| *other == file)?;
+        let cycle = evaluating
+            .get(start..)
+            .unwrap_or_default()
+            .iter()
+            .chain([&file])
+            .map(|file| file.display().to_string())
+            .collect::<Vec<_>>();
+        Some(cycle.join(" -> "))
+    });
+    if let Some(cycle) = cycle {
+        return Err(Error::new(
+            ErrorKind::ImportCycle { cycle, path: span },
+            ctx.source.clone(),
+            span,
+        ));
+    }
+
+    EVALUATING.with_borrow_mut(|evaluating| evaluating.push(file));
+    let result = scope.eval();
+    EVALUATING.with_borrow_mut(Vec::pop);
+
+    result
+}
+
+/// Reads the file at `path` for a builtin called in `ctx`, `span` is the span of the path argument.
+/// Reading stops once the file is larger than [`Options::max_file_size`], so huge files are never loaded whole.
+pub(crate) fn read_file(
+    ctx: &NativeFnCtx,
+    path: &Path,
+    span: SourceSpan,
+) -> Result<Vec<u8>, Error> {
+    let io_error = |err: std::io::Error| Error::new(err.into(), ctx.source.clone(), span);
+
+    let file = File::open(path).map_err(io_error)?;
+    let mut bytes = Vec::new();
+    match ctx.options.max_file_size {
+        Some(limit) => {
+            file.take(limit.saturating_add(1))
+                .read_to_end(&mut bytes)
+                .map_err(io_error)?;
+
+            if u64::try_from(bytes.len()).is_ok_and(|len| len > limit) {
+                return Err(Error::new(
+                    ErrorKind::FileTooLarge { limit, path: span },
+                    ctx.source.clone(),
+                    span,
+                ));
+            }
+        }
+        None => {
+            let mut file = file;
+            file.read_to_end(&mut bytes).map_err(io_error)?;
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Reads the file at `path` as UTF-8 text like [`read_file`], without a leading byte order mark.
+pub(crate) fn read_text(ctx: &NativeFnCtx, path: &Path, span: SourceSpan) -> Result<String, Error> {
+    let bytes = read_file(ctx, path, span)?;
+
+    match String::from_utf8(bytes) {
+        Ok(text) => Ok(match text.strip_prefix('\u{feff}') {
+            Some(text) => text.to_string(),
+            None => text,
+        }),
+        Err(err) => Err(Error::new(
+            ErrorKind::InvalidUtf8File {
+                valid_up_to: err.utf8_error().valid_up_to(),
+                path: span,
+                hint: charset_hint(err.as_bytes()),
+            },
+            ctx.source.clone(),
+            span,
+        )),
+    }
+}
+
+/// A guess at what the bytes of a file that is not valid UTF-8 are, and how to read them.
+pub(crate) fn charset_hint(bytes: &[u8]) -> String {
+    if bytes.starts_with(&[0xff, 0xfe]) || bytes.starts_with(&[0xfe, 0xff]) {
+        "The file starts with a UTF-16 byte order mark, convert it with `iconv -f UTF-16 -t UTF-8`"
+            .to_string()
+    } else if bytes.contains(&0) {
+        "The file looks like binary data, read it with `readFileBytes` instead".to_string()
+    } else {
+        "The file might be Latin-1 or Windows-1252, convert it with `iconv -f WINDOWS-1252 -t UTF-8`, \
+         or read it with `readFileBytes`"
+            .to_string()
+    }
+}
@@ -0,0 +1,150 @@
+use super::{Frame, Inspector, line_at};
+use crate::parser::ast::types::Expr;
+use miette::{NamedSource, SourceSpan};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+/// An [`Inspector`] that records which expressions were evaluated and how often.
+///
+/// The reports take the AST of a file, because expressions that are never evaluated are never seen by the inspector.
+#[derive(Debug, Default)]
+pub struct Coverage {
+    /// The evaluation count of every expression, by file and span.
+    hits: RefCell<HashMap<String, HashMap<SourceSpan, usize>>>,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How often the expression at `span` in `file` was evaluated.
+    pub fn hits(&self, file: &str, span: SourceSpan) -> usize {
+        self.hits
+            .borrow()
+            .get(file)
+            .and_then(|hits| hits.get(&span))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// The outermost expressions of `ast` that were never evaluated, in source order.
+    pub fn unreached(&self, file: &str, ast: &Expr) -> Vec<SourceSpan> {
+        let mut unreached = Vec::new();
+        let mut stack = vec![ast];
+
+        while let Some(expr) = stack.pop() {
+            if self.hits(file, expr.span) == 0 {
+                unreached.push(expr.span);
+            } else {
+                stack.extend(expr.children().into_iter().rev());
+            }
+        }
+
+        unreached
+    }
+
+    /// The highest evaluation count of the expressions that start on each line.
+    fn line_hits(&self, source: &NamedSource<String>, ast: &Expr) -> BTreeMap<usize, usize> {
+        let mut lines = BTreeMap::new();
+        let mut stack = vec![ast];
+
+        while let Some(expr) = stack.pop() {
+            let line = line_at(source.inner(), expr.span.offset());
+            let hits = self.hits(source.name(), expr.span);
+
+            lines
+                .entry(line)
+                .and_modify(|line_hits: &mut usize| *line_hits = (*line_hits).max(hits))
+                .or_insert(hits);
+            stack.extend(expr.children());
+        }
+
+        lines
+    }
+
+    /// Renders the coverage of a file in the LCOV tracefile format.
+    pub fn lcov(&self, source: &NamedSource<String>, ast: &Expr) -> String {
+        let lines = self.line_hits(source, ast);
+        let mut out = format!("SF:{}\n", source.name());
+
+        for (line, hits) in &lines {
+            let _ = writeln!(out, "DA:{line},{hits}");
+        }
+
+        let _ = writeln!(out, "LF:{}", lines.len());
+        let _ = writeln!(
+            out,
+            "LH:{}",
+            lines.values().filter(|hits| **hits > 0).count()
+        );
+        out.push_str("end_of_record\n");
+
+        out
+    }
+
+    /// Renders the source of a file with the evaluation count of each line,
+    /// and the expressions that were never evaluated marked with `^` below them.
+    pub fn annotate(&self, source: &NamedSource<String>, ast: &Expr) -> String {
+        let lines = self.line_hits(source, ast);
+        let unreached = self.unreached(source.name(), ast);
+        let mut out = String::new();
+        let mut line_start = 0usize;
+
+        for (index, line) in source.inner().split('\n').enumerate() {
+            let number = index.saturating_add(1);
+            let line_end = line_start.saturating_add(line.len());
+
+            match lines.get(&number) {
+                Some(hits) => {
+                    let _ = writeln!(out, "{hits:>6} | {line}");
+                }
+                None => {
+                    let _ = writeln!(out, "{:>6} | {line}", "");
+                }
+            }
+
+            // Only the part of a span on its first line is marked
+            let mut markers = String::new();
+            for span in unreached
+                .iter()
+                .filter(|span| (line_start..line_end).contains(&span.offset()))
+            {
+                let column = span.offset().saturating_sub(line_start);
+                let len = span.len().min(line_end.saturating_sub(span.offset()));
+                let before = line.get(..column).map_or(0, |text| text.chars().count());
+                let width = line
+                    .get(column..column.saturating_add(len))
+                    .map_or(1, |text| text.chars().count().max(1));
+
+                let padding = before.saturating_sub(markers.chars().count());
+                markers.push_str(&" ".repeat(padding));
+                markers.push_str(&"^".repeat(width));
+            }
+
+            if !markers.is_empty() {
+                let _ = writeln!(out, "{:>6} | {markers}", "");
+            }
+
+            line_start = line_end.saturating_add(1);
+        }
+
+        out
+    }
+}
+
+impl Inspector for Coverage {
+    fn enter(&self, frame: &Frame) {
+        let mut hits = self.hits.borrow_mut();
+        let count = hits
+            .entry(frame.source.name().to_string())
+            .or_default()
+            .entry(frame.expr.span)
+            .or_default();
+
+        *count = count.saturating_add(1);
+    }
+}
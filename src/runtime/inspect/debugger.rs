@@ -0,0 +1,101 @@
+use super::{Frame, Inspector};
+use crate::runtime::Value;
+use std::{
+    cell::Cell,
+    fmt::{self, Debug},
+};
+
+/// Where the [`Debugger`] pauses the evaluation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// The first expression on a line of a file, lines start at 1.
+    Line { file: String, line: usize },
+    /// After a `let` binding with this name is defined.
+    Binding(String),
+}
+
+/// How the evaluation continues after a pause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resume {
+    /// Pause again before the next expression.
+    Step,
+    /// Run until the next breakpoint.
+    Continue,
+}
+
+type PauseFn = dyn Fn(&Frame, Option<&Breakpoint>) -> Resume;
+
+/// An [`Inspector`] that pauses the evaluation on breakpoints or after every expression.
+///
+/// A pause calls `on_pause` with the current frame and the breakpoint that was hit, `None` when stepping.
+/// It blocks the evaluation until it returns, so it can wait for user input.
+pub struct Debugger {
+    pub breakpoints: Vec<Breakpoint>,
+    on_pause: Box<PauseFn>,
+    stepping: Cell<bool>,
+    /// The line of the last entered expression, so a line breakpoint only pauses once per visit.
+    last_line: Cell<Option<usize>>,
+}
+
+impl Debugger {
+    pub fn new(
+        breakpoints: Vec<Breakpoint>,
+        on_pause: impl Fn(&Frame, Option<&Breakpoint>) -> Resume + 'static,
+    ) -> Self {
+        Self {
+            breakpoints,
+            on_pause: Box::new(on_pause),
+            stepping: Cell::new(false),
+            last_line: Cell::new(None),
+        }
+    }
+
+    /// Pauses before the next expression, like the first step of a debugging session.
+    pub fn step(&self) {
+        self.stepping.set(true);
+    }
+
+    fn pause(&self, frame: &Frame, breakpoint: Option<&Breakpoint>) {
+        let resume = (self.on_pause)(frame, breakpoint);
+        self.stepping.set(resume == Resume::Step);
+    }
+}
+
+impl Inspector for Debugger {
+    fn enter(&self, frame: &Frame) {
+        let line = frame.line();
+        let new_line = self.last_line.replace(Some(line)) != Some(line);
+
+        if self.stepping.get() {
+            return self.pause(frame, None);
+        }
+
+        let breakpoint = self.breakpoints.iter().find(|breakpoint| {
+            matches!(breakpoint, Breakpoint::Line { file, line: bp_line }
+                if new_line && *bp_line == line && file == frame.source.name())
+        });
+
+        if let Some(breakpoint) = breakpoint {
+            self.pause(frame, Some(breakpoint));
+        }
+    }
+
+    fn bind(&self, name: &str, _value: &Value, frame: &Frame) {
+        let breakpoint = self.breakpoints.iter().find(
+            |breakpoint| matches!(breakpoint, Breakpoint::Binding(binding) if binding == name),
+        );
+
+        if let Some(breakpoint) = breakpoint {
+            self.pause(frame, Some(breakpoint));
+        }
+    }
+}
+
+impl Debug for Debugger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Debugger")
+            .field("breakpoints", &self.breakpoints)
+            .field("stepping", &self.stepping.get())
+            .finish_non_exhaustive()
+    }
+}
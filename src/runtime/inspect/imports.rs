@@ -0,0 +1,59 @@
+use super::{Frame, Inspector};
+use crate::runtime::{Value, types::ValueResult};
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// An [`Inspector`] that records every imported file, and passes every other event on to `inner`.
+#[derive(Debug, Default)]
+pub struct ImportLog {
+    inner: Option<Rc<dyn Inspector>>,
+    imports: RefCell<Vec<PathBuf>>,
+}
+
+impl ImportLog {
+    pub fn new(inner: Option<Rc<dyn Inspector>>) -> Self {
+        Self {
+            inner,
+            imports: RefCell::default(),
+        }
+    }
+
+    /// The imported files in the order they were first imported, without duplicates.
+    pub fn imports(&self) -> Vec<PathBuf> {
+        self.imports.borrow().clone()
+    }
+}
+
+impl Inspector for ImportLog {
+    fn enter(&self, frame: &Frame) {
+        if let Some(inner) = &self.inner {
+            inner.enter(frame);
+        }
+    }
+
+    fn exit(&self, frame: &Frame, result: &ValueResult) {
+        if let Some(inner) = &self.inner {
+            inner.exit(frame, result);
+        }
+    }
+
+    fn bind(&self, name: &str, value: &Value, frame: &Frame) {
+        if let Some(inner) = &self.inner {
+            inner.bind(name, value, frame);
+        }
+    }
+
+    fn import(&self, path: &Path) {
+        if let Some(inner) = &self.inner {
+            inner.import(path);
+        }
+
+        let mut imports = self.imports.borrow_mut();
+        if !imports.iter().any(|import| import == path) {
+            imports.push(path.to_path_buf());
+        }
+    }
+}
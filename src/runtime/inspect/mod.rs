@@ -0,0 +1,68 @@
+//! Hooks into the evaluation of expressions, for tools like debuggers, profilers and coverage reports.
+
+use super::{Scope, Value, types::ValueResult};
+use crate::parser::ast::types::Expr;
+use miette::NamedSource;
+use std::{collections::HashMap, fmt::Debug, path::Path};
+
+mod coverage;
+mod debugger;
+mod imports;
+mod profile;
+mod provenance;
+mod trace;
+pub use coverage::Coverage;
+pub use debugger::{Breakpoint, Debugger, Resume};
+pub use imports::ImportLog;
+pub use profile::{CallStats, Profiler};
+pub use provenance::{Provenance, Step, StepKind};
+pub use trace::Tracer;
+
+/// Observes the evaluation of a [`Scope`], set with [`Options::inspector`](super::Options::inspector).
+///
+/// Every method does nothing by default.
+pub trait Inspector: Debug {
+    /// Called before an expression is evaluated.
+    fn enter(&self, _frame: &Frame) {}
+
+    /// Called after an expression is evaluated.
+    fn exit(&self, _frame: &Frame, _result: &ValueResult) {}
+
+    /// Called after a `let` binding is defined, `frame` is the expression it was bound to.
+    fn bind(&self, _name: &str, _value: &Value, _frame: &Frame) {}
+
+    /// Called before an imported file or a template is evaluated, or a file is read by `readFileBytes`,
+    /// with the path of the file after resolving directories.
+    fn import(&self, _path: &Path) {}
+}
+
+/// The state of the evaluation at an expression.
+pub struct Frame<'a> {
+    pub expr: &'a Expr,
+    pub variables: &'a HashMap<String, Value>,
+    pub source: &'a NamedSource<String>,
+}
+
+impl Frame<'_> {
+    /// The line the expression starts on, starting at 1.
+    pub fn line(&self) -> usize {
+        line_at(self.source.inner(), self.expr.span.offset())
+    }
+}
+
+/// The line of a byte offset into `text`, starting at 1.
+fn line_at(text: &str, offset: usize) -> usize {
+    text.get(..offset)
+        .map_or(0, |before| before.matches('\n').count())
+        .saturating_add(1)
+}
+
+impl Scope {
+    pub(super) fn frame<'a>(&'a self, expr: &'a Expr) -> Frame<'a> {
+        Frame {
+            expr,
+            variables: &self.variables,
+            source: &self.source,
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use super::{Frame, Inspector};
+use crate::{parser::ast::types::ExprKind, runtime::types::ValueResult};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// The calls to a single function.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CallStats {
+    pub count: usize,
+    /// Includes the time spent in the functions it calls.
+    pub total: Duration,
+}
+
+/// An [`Inspector`] that counts and times the calls to every function and builtin.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    stats: RefCell<HashMap<String, CallStats>>,
+    /// The start of every call that is currently being evaluated.
+    running: RefCell<Vec<Instant>>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The stats of every called function, the slowest first.
+    pub fn report(&self) -> Vec<(String, CallStats)> {
+        let mut report: Vec<_> = self
+            .stats
+            .borrow()
+            .iter()
+            .map(|(name, stats)| (name.clone(), *stats))
+            .collect();
+        report.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.total));
+
+        report
+    }
+}
+
+impl Inspector for Profiler {
+    fn enter(&self, frame: &Frame) {
+        if matches!(frame.expr.kind, ExprKind::Call { .. }) {
+            self.running.borrow_mut().push(Instant::now());
+        }
+    }
+
+    fn exit(&self, frame: &Frame, _result: &ValueResult) {
+        let ExprKind::Call { base, .. } = &frame.expr.kind else {
+            return;
+        };
+        let Some(start) = self.running.borrow_mut().pop() else {
+            return;
+        };
+
+        let name = base.as_ident().unwrap_or("<unknown name>".into());
+        let mut stats = self.stats.borrow_mut();
+        let stats = stats.entry(name).or_default();
+
+        stats.count = stats.count.saturating_add(1);
+        stats.total = stats.total.saturating_add(start.elapsed());
+    }
+}
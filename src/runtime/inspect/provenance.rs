@@ -0,0 +1,159 @@
+use super::{Frame, Inspector};
+use crate::{
+    line_column,
+    runtime::{Value, types::ValueResult},
+};
+use miette::SourceSpan;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, hash_map::Entry},
+    path::Path,
+    rc::Rc,
+};
+
+/// How an expression took part in producing a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepKind {
+    /// The expression created the value.
+    Defined,
+    /// The value was bound to a name with `let`.
+    Bound { name: String },
+    /// The expression evaluated to the value without creating it, like a variable or an object access.
+    Read,
+}
+
+/// An expression that a value was derived from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub kind: StepKind,
+    pub file: String,
+    pub span: SourceSpan,
+    /// The line and column of the expression, both starting at 1.
+    pub line: usize,
+    pub column: usize,
+    /// The first line of the source of the expression.
+    pub text: String,
+}
+
+#[derive(Debug)]
+struct Record {
+    kind: StepKind,
+    file: String,
+    span: SourceSpan,
+    value_span: SourceSpan,
+    type_name: &'static str,
+}
+
+/// An [`Inspector`] that records the expressions every value was derived from, and passes every event on to `inner`.
+///
+/// Values only keep the span they were created at, so values are told apart by that span and their type.
+/// Values created at the same span in different files have the steps of both.
+#[derive(Debug, Default)]
+pub struct Provenance {
+    inner: Option<Rc<dyn Inspector>>,
+    records: RefCell<Vec<Record>>,
+    sources: RefCell<HashMap<String, String>>,
+}
+
+impl Provenance {
+    pub fn new(inner: Option<Rc<dyn Inspector>>) -> Self {
+        Self {
+            inner,
+            records: RefCell::default(),
+            sources: RefCell::default(),
+        }
+    }
+
+    /// The steps that `value` was derived from in the order they were evaluated, starting where it was created.
+    pub fn steps(&self, value: &Value) -> Vec<Step> {
+        let sources = self.sources.borrow();
+        let mut steps: Vec<Step> = Vec::new();
+
+        for record in self.records.borrow().iter() {
+            if record.value_span != value.span || record.type_name != value.type_of() {
+                continue;
+            }
+            if steps.last().is_some_and(|last| {
+                last.kind == record.kind && last.file == record.file && last.span == record.span
+            }) {
+                continue;
+            }
+
+            let text = sources.get(&record.file).map_or("", String::as_str);
+            let (line, column) = line_column(text, record.span.offset());
+            let snippet = text
+                .get(record.span.offset()..record.span.offset().saturating_add(record.span.len()))
+                .and_then(|snippet| snippet.lines().next())
+                .unwrap_or_default();
+
+            steps.push(Step {
+                kind: record.kind.clone(),
+                file: record.file.clone(),
+                span: record.span,
+                line,
+                column,
+                text: snippet.trim().to_string(),
+            });
+        }
+
+        steps
+    }
+
+    fn record(&self, kind: StepKind, value: &Value, frame: &Frame) {
+        let file = frame.source.name().to_string();
+        if let Entry::Vacant(entry) = self.sources.borrow_mut().entry(file.clone()) {
+            entry.insert(frame.source.inner().clone());
+        }
+
+        self.records.borrow_mut().push(Record {
+            kind,
+            file,
+            span: frame.expr.span,
+            value_span: value.span,
+            type_name: value.type_of(),
+        });
+    }
+}
+
+impl Inspector for Provenance {
+    fn enter(&self, frame: &Frame) {
+        if let Some(inner) = &self.inner {
+            inner.enter(frame);
+        }
+    }
+
+    fn exit(&self, frame: &Frame, result: &ValueResult) {
+        if let Some(inner) = &self.inner {
+            inner.exit(frame, result);
+        }
+
+        if let Ok(value) = result {
+            let kind = if value.span == frame.expr.span {
+                StepKind::Defined
+            } else {
+                StepKind::Read
+            };
+            self.record(kind, value, frame);
+        }
+    }
+
+    fn bind(&self, name: &str, value: &Value, frame: &Frame) {
+        if let Some(inner) = &self.inner {
+            inner.bind(name, value, frame);
+        }
+
+        self.record(
+            StepKind::Bound {
+                name: name.to_string(),
+            },
+            value,
+            frame,
+        );
+    }
+
+    fn import(&self, path: &Path) {
+        if let Some(inner) = &self.inner {
+            inner.import(path);
+        }
+    }
+}
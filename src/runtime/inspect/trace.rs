@@ -0,0 +1,60 @@
+use super::{Frame, Inspector};
+use crate::runtime::types::ValueResult;
+use std::{
+    cell::{Cell, RefCell},
+    fmt::{self, Debug},
+    io::Write,
+};
+
+/// An [`Inspector`] that writes every evaluated expression and its result, indented by depth.
+pub struct Tracer<W: Write> {
+    out: RefCell<W>,
+    depth: Cell<usize>,
+}
+
+impl<W: Write> Tracer<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out: RefCell::new(out),
+            depth: Cell::new(0),
+        }
+    }
+
+    fn write_line(&self, line: fmt::Arguments) {
+        let pad = "  ".repeat(self.depth.get());
+        // Tracing is best effort, a failed write shouldn't stop the evaluation
+        let _ = writeln!(self.out.borrow_mut(), "{pad}{line}");
+    }
+}
+
+impl<W: Write> Inspector for Tracer<W> {
+    fn enter(&self, frame: &Frame) {
+        let span = frame.expr.span;
+        let text = frame
+            .source
+            .inner()
+            .get(span.offset()..span.offset().saturating_add(span.len()))
+            .and_then(|text| text.lines().next())
+            .unwrap_or_default();
+
+        self.write_line(format_args!("> {}:{} {text}", frame.line(), span.offset()));
+        self.depth.set(self.depth.get().saturating_add(1));
+    }
+
+    fn exit(&self, _frame: &Frame, result: &ValueResult) {
+        self.depth.set(self.depth.get().saturating_sub(1));
+
+        match result {
+            Ok(value) => self.write_line(format_args!("< {value}")),
+            Err(err) => self.write_line(format_args!("< error: {}", err.kind)),
+        }
+    }
+}
+
+impl<W: Write> Debug for Tracer<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tracer")
+            .field("depth", &self.depth.get())
+            .finish_non_exhaustive()
+    }
+}
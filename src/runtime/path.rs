@@ -0,0 +1,100 @@
+use super::{
+    Scope, ValueKind, ValueResult,
+    types::{Builtin, Error, ErrorKind, NativeFnCtx, Value},
+};
+use crate::parser::ast::types::{Expr, ExprKind, Literal};
+use std::rc::Rc;
+
+impl Scope {
+    /// Evaluates the value at `path`, like `services.web.port`.
+    /// Keys are separated by dots, a leading dot is optional and numbers index arrays.
+    ///
+    /// Of an object literal only the field on the path is evaluated, along with the `let` bindings around it.
+    /// Any other expression on the path is evaluated fully before the rest of the path is selected from its value.
+    /// # Errors
+    /// This function will return an error if an evaluation error occurs or if a key of the path doesn't exist.
+    pub fn eval_path(&mut self, path: &str) -> ValueResult {
+        let keys: Vec<&str> = path
+            .trim_start_matches('.')
+            .split('.')
+            .filter(|key| !key.is_empty())
+            .collect();
+
+        let ast = Rc::clone(&self.ast);
+        self.eval_expr_at(&ast, &keys)
+    }
+
+    fn eval_expr_at(&mut self, expr: &Expr, keys: &[&str]) -> ValueResult {
+        let Some((key, rest)) = keys.split_first() else {
+            return self.eval_expr(expr);
+        };
+
+        // Contracts check the whole value
+        if expr.contract.is_none() {
+            match &expr.kind {
+                ExprKind::Literal(Literal::Object(fields)) => {
+                    return match fields.get(*key) {
+                        Some(field) => self.eval_expr_at(field, rest),
+                        None => Err(self.missing_field(key, "object", expr)),
+                    };
+                }
+                ExprKind::LetIn {
+                    bindings,
+                    expr: body,
+                } => return self.let_scope(bindings, body)?.eval_expr_at(body, keys),
+                _ => {}
+            }
+        }
+
+        let mut value = self.eval_expr(expr)?;
+        for key in keys {
+            value = self.select(value, key, expr)?;
+        }
+
+        Ok(value)
+    }
+
+    /// The field or item `key` of an evaluated value, lazy fields of an object created by `fix` are evaluated here.
+    fn select(&self, value: Value, key: &str, expr: &Expr) -> ValueResult {
+        match (&value.kind, key.parse::<usize>()) {
+            (ValueKind::Array(_), Ok(index)) => match value.try_index(index) {
+                Ok(item) => Ok(item.clone()),
+                Err(length) => Err(Error::new(
+                    ErrorKind::IndexOutOfBounds {
+                        length,
+                        index: expr.span,
+                    },
+                    self.source.clone(),
+                    expr.span,
+                )),
+            },
+            (ValueKind::Object(fields), _) => match fields.get(key) {
+                Some(Value {
+                    kind: ValueKind::Lazy(Builtin(thunk)),
+                    ..
+                }) => thunk(NativeFnCtx {
+                    expr: expr.clone(),
+                    variables: self.variables.clone(),
+                    source: self.source.clone(),
+                    options: self.options.clone(),
+                    origin: self.origin.clone(),
+                }),
+                Some(field) => Ok(field.clone()),
+                None => Err(self.missing_field(key, "object", expr)),
+            },
+            _ => Err(self.missing_field(key, value.type_of(), expr)),
+        }
+    }
+
+    fn missing_field(&self, field: &str, base_type: &'static str, expr: &Expr) -> Error {
+        Error::new(
+            ErrorKind::MissingField {
+                field: field.to_string(),
+                base_type: base_type.into(),
+                base: expr.span,
+            },
+            self.source.clone(),
+            expr.span,
+        )
+    }
+}
@@ -1,20 +1,50 @@
 use crate::runtime::ValueKind;
 
 use super::types::Value;
+use miette::{Diagnostic, SourceSpan};
 use serde::{
     Deserialize, Deserializer, Serialize, Serializer,
     de::{self, Expected, IntoDeserializer, MapAccess, SeqAccess, Visitor},
-    forward_to_deserialize_any,
+    forward_to_deserialize_any, ser,
 };
 use std::{collections::btree_map, fmt};
+use thiserror::Error;
+
+/// An error while deserializing a [`Value`], with the span of the innermost value that caused it.
+#[derive(Error, Diagnostic, Debug, Clone, PartialEq)]
+#[error("Could not deserialize value: {message}")]
+#[diagnostic(code(tl::runtime::deserialize))]
+pub struct DeError {
+    pub message: String,
+    pub span: Option<SourceSpan>,
+}
+
+impl DeError {
+    /// Sets the span if no inner value set it already.
+    fn or_span(mut self, span: SourceSpan) -> Self {
+        self.span.get_or_insert(span);
+        self
+    }
+}
+
+impl de::Error for DeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self {
+            message: msg.to_string(),
+            span: None,
+        }
+    }
+}
 
 impl<'de> Deserializer<'de> for Value {
-    type Error = de::value::Error;
+    type Error = DeError;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
+        let span = self.span;
+
         match self.kind {
             ValueKind::Null => visitor.visit_unit(),
             ValueKind::Boolean(val) => visitor.visit_bool(val),
@@ -22,6 +52,16 @@ impl<'de> Deserializer<'de> for Value {
                 val.try_into()
                     .map_err(|_| de::Error::custom("Integer overflowed"))?,
             ),
+            #[cfg(feature = "bigint")]
+            ValueKind::BigInt(val) => {
+                if let Ok(val) = i128::try_from(&val) {
+                    visitor.visit_i128(val)
+                } else if let Ok(val) = u128::try_from(&val) {
+                    visitor.visit_u128(val)
+                } else {
+                    visitor.visit_string(val.to_string())
+                }
+            }
             ValueKind::Float(val) => visitor.visit_f64(val),
             ValueKind::String(val) => visitor.visit_string(val),
             ValueKind::Path(val) => visitor.visit_string(val.display().to_string()),
@@ -42,7 +82,9 @@ impl<'de> Deserializer<'de> for Value {
                 Err(de::Error::custom("Functions cannot be deserialized"))
             }
             ValueKind::Builtin(..) => Err(de::Error::custom("Builtins cannot be deserialized")),
+            ValueKind::Lazy(..) => Err(de::Error::custom("Lazy values cannot be deserialized")),
         }
+        .map_err(|err| err.or_span(span))
     }
 
     fn deserialize_enum<V>(
@@ -54,10 +96,13 @@ impl<'de> Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
+        let span = self.span;
+
         match self.kind {
             ValueKind::String(s) => visitor.visit_enum(s.into_deserializer()),
             _ => Err(de::Error::invalid_type(de::Unexpected::Unit, &self)),
         }
+        .map_err(|err: DeError| err.or_span(span))
     }
 
     // Forward other methods to deserialize_any
@@ -79,7 +124,7 @@ struct ValueSeq {
 }
 
 impl<'de> SeqAccess<'de> for ValueSeq {
-    type Error = de::value::Error;
+    type Error = DeError;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
     where
@@ -98,7 +143,7 @@ struct ValueMap {
 }
 
 impl<'de> MapAccess<'de> for ValueMap {
-    type Error = de::value::Error;
+    type Error = DeError;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
     where
@@ -106,9 +151,10 @@ impl<'de> MapAccess<'de> for ValueMap {
     {
         match self.iter.next() {
             Some((key, value)) => {
+                // Keys have no span of their own, errors about them point at the value
+                let key = Value::new(ValueKind::String(key), value.span);
                 self.value = Some(value);
-                seed.deserialize(Value::new_builtin(ValueKind::String(key)))
-                    .map(Some)
+                seed.deserialize(key).map(Some)
             }
             None => Ok(None),
         }
@@ -130,6 +176,16 @@ impl Serialize for Value {
         match &self.kind {
             ValueKind::Boolean(v) => serializer.serialize_bool(*v),
             ValueKind::Int(v) => serializer.serialize_i64(*v as i64),
+            #[cfg(feature = "bigint")]
+            ValueKind::BigInt(v) => {
+                if let Ok(v) = i128::try_from(v) {
+                    serializer.serialize_i128(v)
+                } else if let Ok(v) = u128::try_from(v) {
+                    serializer.serialize_u128(v)
+                } else {
+                    serializer.serialize_str(&v.to_string())
+                }
+            }
             ValueKind::Float(v) => serializer.serialize_f64(*v),
             ValueKind::String(v) => serializer.serialize_str(v),
             ValueKind::Path(v) => serializer.serialize_str(&v.display().to_string()),
@@ -138,6 +194,7 @@ impl Serialize for Value {
             ValueKind::Null | ValueKind::Function { .. } | ValueKind::Builtin(..) => {
                 serializer.serialize_unit()
             }
+            ValueKind::Lazy(..) => Err(ser::Error::custom("Lazy values cannot be serialized")),
         }
     }
 }
@@ -193,10 +250,26 @@ impl<'de> Deserialize<'de> for Value {
                 Ok(Value::new_builtin(ValueKind::Int(v as isize)))
             }
 
+            #[cfg(not(feature = "bigint"))]
             fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
                 Ok(Value::new_builtin(ValueKind::Int(v as isize)))
             }
 
+            #[cfg(feature = "bigint")]
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(Value::new_builtin(ValueKind::from_big_int(v.into())))
+            }
+
+            #[cfg(feature = "bigint")]
+            fn visit_i128<E>(self, v: i128) -> Result<Self::Value, E> {
+                Ok(Value::new_builtin(ValueKind::from_big_int(v.into())))
+            }
+
+            #[cfg(feature = "bigint")]
+            fn visit_u128<E>(self, v: u128) -> Result<Self::Value, E> {
+                Ok(Value::new_builtin(ValueKind::from_big_int(v.into())))
+            }
+
             fn visit_f32<E>(self, v: f32) -> Result<Self::Value, E> {
                 Ok(Value::new_builtin(ValueKind::Float(f64::from(v))))
             }
@@ -1,12 +1,12 @@
 #![allow(clippy::arithmetic_side_effects, clippy::float_arithmetic)]
 
 use crate::{
-    merge_spans,
+    Options, merge_spans,
     parser::ast::{
         self,
-        types::{Expr, ExprKind},
+        types::{Expr, ExprKind, Type},
     },
-    runtime::Scope,
+    runtime::{BuiltinInfo, Scope},
 };
 use miette::{Diagnostic, NamedSource, SourceSpan};
 use std::{
@@ -15,7 +15,7 @@ use std::{
     fmt::{self, Debug, Display},
     io,
     ops::{Add, Div, Index, Mul, Rem, Sub},
-    path::PathBuf,
+    path::{Path, PathBuf},
     rc::Rc,
 };
 use thiserror::Error;
@@ -26,6 +26,8 @@ pub type ValueResult = Result<Value, Error>;
 pub struct Value {
     pub kind: ValueKind,
     pub span: SourceSpan,
+    /// The span of the `freeze` call that made this value read-only, overriding its fields is an error.
+    pub frozen: Option<SourceSpan>,
 }
 
 impl Default for Value {
@@ -36,7 +38,11 @@ impl Default for Value {
 
 impl Value {
     pub const fn new(kind: ValueKind, span: SourceSpan) -> Self {
-        Self { kind, span }
+        Self {
+            kind,
+            span,
+            frozen: None,
+        }
     }
 
     pub fn new_builtin(kind: ValueKind) -> Self {
@@ -50,6 +56,9 @@ pub enum ValueKind {
     Null,
     Boolean(bool),
     Int(isize),
+    /// An integer that doesn't fit into an `isize`.
+    #[cfg(feature = "bigint")]
+    BigInt(num_bigint::BigInt),
     Float(f64),
     String(String),
     Path(PathBuf),
@@ -57,9 +66,19 @@ pub enum ValueKind {
     Object(BTreeMap<String, Value>),
     Function {
         args: Vec<String>,
+        /// Expressions for arguments that can be omitted.
+        defaults: BTreeMap<String, Expr>,
+        /// Binds the arguments after `args` as an array.
+        rest: Option<String>,
         expr: Expr,
+        /// The source the function is defined in, its span is only meaningful in this source.
+        source: Box<NamedSource<String>>,
+        /// The directory of the file the function is defined in, see [`Scope::origin`].
+        origin: Option<Rc<Path>>,
     },
     Builtin(Builtin),
+    /// A field of an object that is evaluated when it is accessed, created by `fix`.
+    Lazy(Builtin),
 }
 
 #[derive(Debug, Clone)]
@@ -73,91 +92,180 @@ pub struct Builtin(pub NativeFn);
 
 pub type NativeFn = Rc<dyn Fn(NativeFnCtx) -> ValueResult>;
 
+#[derive(Clone)]
 pub struct NativeFnCtx {
     pub expr: Expr,
     pub variables: HashMap<String, Value>,
     pub source: NamedSource<String>,
+    pub options: Options,
+    /// The directory of the file the call is written in, see [`Scope::origin`].
+    pub origin: Option<Rc<Path>>,
 }
 
 impl NativeFnCtx {
     pub fn new_scope(&self) -> Scope {
-        Scope::new(
+        let mut scope = Scope::without_builtins(
             self.variables.clone(),
             self.source.clone(),
             self.expr.clone(),
-        )
+        );
+        scope.set_options(self.options.clone());
+        scope.origin.clone_from(&self.origin);
+
+        scope
     }
 
-    pub fn get_arg(&self, index: usize, expected_len: usize) -> Result<Expr, Error> {
-        let ExprKind::Call { args, .. } = &self.expr.kind else {
-            unreachable!()
-        };
+    fn call_args(&self) -> Result<&[Expr], Error> {
+        match &self.expr.kind {
+            ExprKind::Call { args, .. } => Ok(args),
+            _ => Err(self.not_a_call()),
+        }
+    }
 
-        let arg = args.get(index).ok_or(Error::new(
-            ErrorKind::ArgsMismatch {
-                len: expected_len,
-                args: self.call_args_span(),
+    /// Builtins read their arguments from `expr`, which hosts that construct a context could set to any expression.
+    fn not_a_call(&self) -> Error {
+        Error::new(
+            ErrorKind::Internal {
+                message: "the expression of a builtin call is not a call".to_string(),
             },
             self.source.clone(),
             self.expr.span,
-        ))?;
+        )
+    }
+
+    pub fn get_arg(&self, index: usize, expected_len: usize) -> Result<Expr, Error> {
+        let arg = self
+            .call_args()?
+            .get(index)
+            .ok_or_else(|| self.args_mismatch(expected_len))?;
 
         Ok(arg.clone())
     }
 
     pub fn get_arg_evaluated(&self, index: usize, expected_len: usize) -> ValueResult {
-        let ExprKind::Call { args, .. } = &self.expr.kind else {
-            unreachable!()
-        };
-
-        let arg = args.get(index).ok_or(Error::new(
-            ErrorKind::ArgsMismatch {
-                len: expected_len,
-                args: self.call_args_span(),
-            },
-            self.source.clone(),
-            self.expr.span,
-        ))?;
+        let arg = self
+            .call_args()?
+            .get(index)
+            .ok_or_else(|| self.args_mismatch(expected_len))?;
 
         self.eval_expr(arg.clone())
     }
 
     pub fn eval_expr(&self, expr: Expr) -> ValueResult {
-        let mut scope = Scope::new(
-            self.variables.clone(),
-            self.source.clone(),
-            self.expr.clone(),
-        );
+        self.new_scope().eval_expr(&expr)
+    }
 
-        scope.eval_expr(&expr)
+    /// # Errors
+    /// This function will return an [`ErrorKind::Internal`] if `expr` is not a call.
+    pub fn expr_args(&self) -> Result<Vec<Expr>, Error> {
+        Ok(self.call_args()?.to_vec())
     }
 
-    pub fn expr_args(&self) -> Vec<Expr> {
-        let ExprKind::Call { ref args, .. } = self.expr.kind else {
-            unreachable!()
-        };
+    /// # Errors
+    /// This function will return an [`ErrorKind::Internal`] if `expr` is not a call.
+    pub fn expr_args_evaluated(&self) -> Result<Vec<ValueResult>, Error> {
+        let mut scope = self.new_scope();
 
-        args.clone()
+        Ok(self
+            .call_args()?
+            .iter()
+            .map(|arg| scope.eval_expr(arg))
+            .collect())
     }
 
-    pub fn expr_args_evaluated(&self) -> Vec<ValueResult> {
-        let mut scope = Scope::new(
-            self.variables.clone(),
-            self.source.clone(),
-            self.expr.clone(),
-        );
+    /// Accesses the field `key` of `value` like `.` does, evaluating it if it is lazy.
+    /// # Errors
+    /// This function will return an error if evaluating a lazy field fails.
+    pub fn access(&self, value: &Value, key: &str) -> ValueResult {
+        match value.access(key) {
+            Value {
+                kind: ValueKind::Lazy(Builtin(thunk)),
+                ..
+            } => thunk(self.clone()),
+            field => Ok(field),
+        }
+    }
 
-        let args = self.expr_args();
+    /// Evaluates every lazy value inside of `value`, like the fields of the object that `fix` passes to its function.
+    /// # Errors
+    /// This function will return an error if evaluating a lazy value fails or if `value` contains itself.
+    pub fn force(&self, value: Value) -> ValueResult {
+        self.force_nested(value, "", &mut Vec::new())
+    }
 
-        args.iter().map(|arg| scope.eval_expr(arg)).collect()
+    /// `forcing` holds the lazy values that `value` is inside of and the key of each, `key` is the key of `value`.
+    fn force_nested(
+        &self,
+        value: Value,
+        key: &str,
+        forcing: &mut Vec<(*const (), String)>,
+    ) -> ValueResult {
+        let Value { kind, span, frozen } = value;
+        match kind {
+            ValueKind::Lazy(Builtin(thunk)) => {
+                let pointer = Rc::as_ptr(&thunk).cast::<()>();
+                if let Some(start) = forcing.iter().position(|(lazy, _)| *lazy == pointer) {
+                    let cycle = forcing
+                        .iter()
+                        .skip(start)
+                        .map(|(_, key)| key.as_str())
+                        .chain(std::iter::once(key))
+                        .collect::<Vec<_>>()
+                        .join(" -> ");
+
+                    return Err(Error::new(
+                        ErrorKind::InfiniteRecursion {
+                            field: key.to_string(),
+                            cycle,
+                            definition: span,
+                        },
+                        self.source.clone(),
+                        self.expr.span,
+                    ));
+                }
+
+                forcing.push((pointer, key.to_string()));
+                let result =
+                    thunk(self.clone()).and_then(|value| self.force_nested(value, key, forcing));
+                forcing.pop();
+
+                result
+            }
+            ValueKind::Array(items) => {
+                let items = items
+                    .into_iter()
+                    .map(|item| self.force_nested(item, key, forcing))
+                    .collect::<Result<_, _>>()?;
+
+                Ok(Value {
+                    kind: ValueKind::Array(items),
+                    span,
+                    frozen,
+                })
+            }
+            ValueKind::Object(fields) => {
+                let fields = fields
+                    .into_iter()
+                    .map(|(key, field)| Ok((key.clone(), self.force_nested(field, &key, forcing)?)))
+                    .collect::<Result<_, Error>>()?;
+
+                Ok(Value {
+                    kind: ValueKind::Object(fields),
+                    span,
+                    frozen,
+                })
+            }
+            kind => Ok(Value { kind, span, frozen }),
+        }
     }
 
+    /// The span of the arguments, or of the whole expression if there are none.
     pub fn call_args_span(&self) -> SourceSpan {
-        let ExprKind::Call { ref args, .. } = self.expr.kind else {
-            unreachable!()
-        };
-
-        let mut args_spans = args.iter().map(|arg| arg.span);
+        let mut args_spans = self
+            .call_args()
+            .unwrap_or_default()
+            .iter()
+            .map(|arg| arg.span);
 
         if let Some(start) = args_spans.next()
             && let Some(end) = args_spans.next_back()
@@ -168,6 +276,53 @@ impl NativeFnCtx {
         }
     }
 
+    /// Fails if [`Options::deterministic`] is on, for builtins named `name` whose result depends on more than
+    /// their arguments and the files tl reads.
+    pub fn ensure_deterministic(&self, name: &str) -> Result<(), Error> {
+        if !self.options.deterministic {
+            return Ok(());
+        }
+
+        Err(Error::new(
+            ErrorKind::ImpurityNotAllowed {
+                name: name.to_string(),
+                call: self.expr.span,
+            },
+            self.source.clone(),
+            self.expr.span,
+        ))
+    }
+
+    /// The error for calling this builtin with fewer than `expected_len` arguments.
+    /// The signature is looked up in the registry by the name the builtin is called by.
+    pub fn args_mismatch(&self, expected_len: usize) -> Error {
+        let ExprKind::Call {
+            ref base, ref args, ..
+        } = self.expr.kind
+        else {
+            return self.not_a_call();
+        };
+
+        let name = super::call::builtin_name(base);
+        let signature = Scope::builtins()
+            .into_iter()
+            .find(|builtin| builtin.name == name)
+            .map(BuiltinInfo::signature);
+
+        Error::new(
+            ErrorKind::ArgsMismatch {
+                len: expected_len,
+                variadic: false,
+                given: args.len(),
+                args: self.call_args_span(),
+                definition: None,
+                signature,
+            },
+            self.source.clone(),
+            self.expr.span,
+        )
+    }
+
     pub fn ensure_is_null(&self, value: Value) -> ValueResult {
         match value.kind {
             ValueKind::Null => Ok(value),
@@ -205,6 +360,12 @@ impl NativeFnCtx {
                 data: v,
                 span: value.span,
             }),
+            #[cfg(feature = "bigint")]
+            ValueKind::BigInt(_) => Err(Error::new(
+                ErrorKind::BigIntUnsupported { value: value.span },
+                self.source.clone(),
+                value.span,
+            )),
             _ => Err(Error::new(
                 ErrorKind::MismatchedTypes {
                     expected: ValueKind::Int(0).type_of().to_string(),
@@ -309,7 +470,7 @@ impl NativeFnCtx {
         value: Value,
     ) -> Result<ExtractedValue<(Vec<String>, Expr)>, Error> {
         match value.kind {
-            ValueKind::Function { args, expr } => Ok(ExtractedValue {
+            ValueKind::Function { args, expr, .. } => Ok(ExtractedValue {
                 data: (args, expr),
                 span: value.span,
             }),
@@ -317,7 +478,11 @@ impl NativeFnCtx {
                 ErrorKind::MismatchedTypes {
                     expected: ValueKind::Function {
                         args: Vec::new(),
+                        defaults: BTreeMap::new(),
+                        rest: None,
                         expr: Expr::default(),
+                        source: Box::new(self.source.clone()),
+                        origin: None,
                     }
                     .type_of()
                     .to_string(),
@@ -417,23 +582,52 @@ impl From<Builtin> for ValueKind {
     }
 }
 
+impl<T: Into<ValueKind>> From<T> for Value {
+    fn from(val: T) -> Self {
+        Self::new_builtin(val.into())
+    }
+}
+
+/// Creates an object [`Value`], keys can be identifiers, literals or expressions in brackets.
 #[macro_export]
 macro_rules! object {
-    ($($key:ident: $val:expr),* $(,)?) => {
-        Value::new_builtin($crate::runtime::ValueKind::Object(std::collections::BTreeMap::from([
+    (@key [$key:expr]) => {
+        ($key).to_string()
+    };
+    (@key $key:ident) => {
+        stringify!($key).to_owned()
+    };
+    (@key $key:literal) => {
+        $key.to_string()
+    };
+
+    ($($key:tt: $val:expr),* $(,)?) => {
+        $crate::runtime::Value::new_builtin($crate::runtime::ValueKind::Object(std::collections::BTreeMap::from([
             $(
-                (stringify!($key).to_owned(), Value::new_builtin($val.into())),
+                ($crate::object!(@key $key), $crate::runtime::Value::from($val)),
             )*
         ])))
     };
 }
 
+/// Creates an array [`Value`].
+#[macro_export]
+macro_rules! array {
+    ($($val:expr),* $(,)?) => {
+        $crate::runtime::Value::new_builtin($crate::runtime::ValueKind::Array(vec![
+            $($crate::runtime::Value::from($val)),*
+        ]))
+    };
+}
+
 impl ValueKind {
     pub fn type_of(&self) -> &'static str {
         match &self {
             ValueKind::Null => "null",
             ValueKind::Boolean(_) => "boolean",
             ValueKind::Int(_) => "number",
+            #[cfg(feature = "bigint")]
+            ValueKind::BigInt(_) => "number",
             ValueKind::Float(_) => "float",
             ValueKind::String(_) => "string",
             ValueKind::Path(_) => "path",
@@ -441,6 +635,29 @@ impl ValueKind {
             ValueKind::Object(_) => "object",
             ValueKind::Function { .. } => "function",
             ValueKind::Builtin(..) => "builtin",
+            ValueKind::Lazy(..) => "lazy",
+        }
+    }
+
+    /// Whether the value has the type `ty`, arrays are checked item by item.
+    pub fn satisfies(&self, ty: &Type) -> bool {
+        match (ty, self) {
+            (Type::Any, _)
+            | (Type::Null, ValueKind::Null)
+            | (Type::Boolean, ValueKind::Boolean(_))
+            | (Type::Int | Type::Number, ValueKind::Int(_))
+            | (Type::Float | Type::Number, ValueKind::Float(_))
+            | (Type::String, ValueKind::String(_))
+            | (Type::Path, ValueKind::Path(_))
+            | (Type::Object, ValueKind::Object(_))
+            | (Type::Function, ValueKind::Function { .. } | ValueKind::Builtin(..)) => true,
+            #[cfg(feature = "bigint")]
+            (Type::Int | Type::Number, ValueKind::BigInt(_)) => true,
+            (Type::Array(item), ValueKind::Array(items)) => {
+                items.iter().all(|value| value.kind.satisfies(item))
+            }
+            (Type::Optional(ty), value) => matches!(value, ValueKind::Null) || value.satisfies(ty),
+            _ => false,
         }
     }
 
@@ -448,12 +665,17 @@ impl ValueKind {
         match &self {
             ValueKind::Boolean(b) => *b,
             ValueKind::Int(n) => *n > 0,
+            #[cfg(feature = "bigint")]
+            ValueKind::BigInt(n) => n.sign() == num_bigint::Sign::Plus,
             ValueKind::Float(f) => *f > 0.0,
             ValueKind::String(s) => !s.is_empty(),
-            ValueKind::Path(p) => !p.exists(),
+            ValueKind::Path(_) => true,
             ValueKind::Array(arr) => !arr.is_empty(),
             ValueKind::Object(map) => !map.is_empty(),
-            ValueKind::Function { .. } | ValueKind::Builtin(..) | ValueKind::Null => false,
+            ValueKind::Function { .. }
+            | ValueKind::Builtin(..)
+            | ValueKind::Lazy(..)
+            | ValueKind::Null => false,
         }
     }
 
@@ -461,6 +683,87 @@ impl ValueKind {
         matches!(self, ValueKind::Function { .. } | ValueKind::Builtin(..))
     }
 
+    /// Whether both are functions with the same parameters and body, or the same builtin.
+    fn is_same_function(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                ValueKind::Function {
+                    args,
+                    defaults,
+                    rest,
+                    expr,
+                    ..
+                },
+                ValueKind::Function {
+                    args: other_args,
+                    defaults: other_defaults,
+                    rest: other_rest,
+                    expr: other_expr,
+                    ..
+                },
+            ) => {
+                args == other_args
+                    && rest == other_rest
+                    && defaults.len() == other_defaults.len()
+                    && defaults.iter().zip(other_defaults).all(
+                        |((name, default), (other_name, other_default))| {
+                            name == other_name && default.same_structure(other_default)
+                        },
+                    )
+                    && expr.same_structure(other_expr)
+            }
+            (ValueKind::Builtin(builtin), ValueKind::Builtin(other_builtin)) => {
+                Rc::ptr_eq(&builtin.0, &other_builtin.0)
+            }
+            _ => false,
+        }
+    }
+
+    /// The position of the type in the [ordering](Value::cmp) of values of different types.
+    const fn type_rank(&self) -> u8 {
+        match self {
+            ValueKind::Null => 0,
+            ValueKind::Boolean(_) => 1,
+            ValueKind::Int(_) | ValueKind::Float(_) => 2,
+            #[cfg(feature = "bigint")]
+            ValueKind::BigInt(_) => 2,
+            ValueKind::String(_) => 3,
+            ValueKind::Path(_) => 4,
+            ValueKind::Array(_) => 5,
+            ValueKind::Object(_) => 6,
+            ValueKind::Function { .. } => 7,
+            ValueKind::Builtin(_) => 8,
+            ValueKind::Lazy(_) => 9,
+        }
+    }
+
+    /// Whether `self` and `other` have a meaningful ordering between them.
+    pub fn is_comparable_with(&self, other: &Self) -> bool {
+        #[cfg(feature = "bigint")]
+        if (self.is_big_int() || other.is_big_int())
+            && self.as_f64().is_some()
+            && other.as_f64().is_some()
+        {
+            return true;
+        }
+
+        matches!(
+            (self, other),
+            (
+                ValueKind::Int(_) | ValueKind::Float(_),
+                ValueKind::Int(_) | ValueKind::Float(_)
+            ) | (ValueKind::String(_), ValueKind::String(_))
+        )
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match &self {
+            ValueKind::Int(n) => *n == 0,
+            ValueKind::Float(f) => *f == 0.0,
+            _ => false,
+        }
+    }
+
     pub fn and(&self, rhs: &Value) -> bool {
         self.is_truthy() && rhs.is_truthy()
     }
@@ -483,6 +786,10 @@ impl Value {
         self.kind.is_callable()
     }
 
+    pub fn is_zero(&self) -> bool {
+        self.kind.is_zero()
+    }
+
     pub fn and(&self, rhs: &Self) -> bool {
         self.kind.and(rhs)
     }
@@ -491,20 +798,44 @@ impl Value {
         self.kind.or(rhs)
     }
 
+    /// Marks the value and every value nested in it as frozen by the `freeze` call at `span`.
+    pub fn freeze(&mut self, span: SourceSpan) {
+        self.frozen = Some(span);
+
+        match &mut self.kind {
+            ValueKind::Array(values) => values.iter_mut().for_each(|value| value.freeze(span)),
+            ValueKind::Object(fields) => fields.values_mut().for_each(|value| value.freeze(span)),
+            _ => {}
+        }
+    }
+
+    /// Merges `other` into `self`, objects are merged by key and any other value of `other` replaces the one in `self`.
+    #[must_use]
+    pub fn deep_merge(self, other: Self) -> Self {
+        match (self.kind, other.kind) {
+            (ValueKind::Object(mut fields), ValueKind::Object(other_fields)) => {
+                for (key, value) in other_fields {
+                    let value = match fields.remove(&key) {
+                        Some(existing) => existing.deep_merge(value),
+                        None => value,
+                    };
+                    fields.insert(key, value);
+                }
+
+                Self::new(ValueKind::Object(fields), other.span)
+            }
+            (_, kind) => Self { kind, ..other },
+        }
+    }
+
     #[must_use]
     pub fn access(&self, rhs: impl Into<String>) -> Self {
         match &self.kind {
             ValueKind::Object(v) => v
                 .get(&rhs.into())
-                .unwrap_or(&Value {
-                    kind: ValueKind::Null,
-                    span: self.span,
-                })
-                .clone(),
-            _ => Value {
-                kind: ValueKind::Null,
-                span: self.span,
-            },
+                .cloned()
+                .unwrap_or_else(|| Value::new(ValueKind::Null, self.span)),
+            _ => Value::new(ValueKind::Null, self.span),
         }
     }
 
@@ -516,12 +847,52 @@ impl Value {
     }
 }
 
+impl Value {
+    /// Rounds every float in the value to `digits` decimal places, including the ones in arrays and objects.
+    /// Used before serializing a value so generated files don't contain numbers like `0.30000000000000004`.
+    #[must_use]
+    pub fn round_floats(self, digits: usize) -> Self {
+        let kind = match self.kind {
+            ValueKind::Float(value) => ValueKind::Float(round_float(value, digits)),
+            ValueKind::Array(items) => ValueKind::Array(
+                items
+                    .into_iter()
+                    .map(|item| item.round_floats(digits))
+                    .collect(),
+            ),
+            ValueKind::Object(fields) => ValueKind::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| (key, value.round_floats(digits)))
+                    .collect(),
+            ),
+            kind => kind,
+        };
+
+        Self::new(kind, self.span)
+    }
+}
+
+/// The most decimal places a float can have, the smallest subnormal `f64` is exactly `2^-1074`.
+pub const MAX_PRECISION: usize = 1074;
+
+/// Rounds `value` to `digits` decimal places, halfway cases are rounded to even.
+pub(crate) fn round_float(value: f64, digits: usize) -> f64 {
+    // Rounding to more places than a float has doesn't change it, and formatting panics above `u16::MAX`
+    let digits = digits.min(MAX_PRECISION);
+
+    // Formatting rounds based on the exact decimal value, which avoids the errors of scaling by a power of 10
+    format!("{value:.digits$}").parse().unwrap_or(value)
+}
+
 impl Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.kind {
             ValueKind::Null => f.write_str("null"),
             ValueKind::Boolean(v) => f.write_str(v.to_string().as_str()),
             ValueKind::Int(v) => f.write_str(v.to_string().as_str()),
+            #[cfg(feature = "bigint")]
+            ValueKind::BigInt(v) => f.write_str(v.to_string().as_str()),
             ValueKind::Float(v) => f.write_str(v.to_string().as_str()),
             ValueKind::String(v) => f.write_str(v),
             ValueKind::Path(v) => f.write_str(&v.display().to_string()),
@@ -538,6 +909,7 @@ impl Display for Value {
             }
             ValueKind::Function { .. } => f.write_str("function"),
             ValueKind::Builtin { .. } => f.write_str("builtin"),
+            ValueKind::Lazy(..) => f.write_str("lazy"),
         }
     }
 }
@@ -557,6 +929,17 @@ impl Add for ValueKind {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
+        #[cfg(feature = "bigint")]
+        if let Some(result) = super::bigint::promote(
+            &self,
+            &rhs,
+            isize::checked_add,
+            |lhs, rhs| Some(lhs + rhs),
+            |lhs, rhs| lhs + rhs,
+        ) {
+            return result;
+        }
+
         match (self, rhs) {
             // Numbers
             (Self::Int(lhs), Self::Int(rhs)) => Self::Int(lhs.saturating_add(rhs)),
@@ -602,6 +985,17 @@ impl Sub for ValueKind {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
+        #[cfg(feature = "bigint")]
+        if let Some(result) = super::bigint::promote(
+            &self,
+            &rhs,
+            isize::checked_sub,
+            |lhs, rhs| Some(lhs - rhs),
+            |lhs, rhs| lhs - rhs,
+        ) {
+            return result;
+        }
+
         match (self, rhs) {
             // Numbers
             (Self::Int(lhs), Self::Int(rhs)) => Self::Int(lhs.saturating_sub(rhs)),
@@ -627,6 +1021,17 @@ impl Mul for ValueKind {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self::Output {
+        #[cfg(feature = "bigint")]
+        if let Some(result) = super::bigint::promote(
+            &self,
+            &rhs,
+            isize::checked_mul,
+            |lhs, rhs| Some(lhs * rhs),
+            |lhs, rhs| lhs * rhs,
+        ) {
+            return result;
+        }
+
         match (self, rhs) {
             // Numbers
             (Self::Int(lhs), Self::Int(rhs)) => Self::Int(lhs.saturating_mul(rhs)),
@@ -662,6 +1067,17 @@ impl Div for ValueKind {
     type Output = Self;
 
     fn div(self, rhs: Self) -> Self::Output {
+        #[cfg(feature = "bigint")]
+        if (self.is_big_int() || rhs.is_big_int())
+            && let (Some(lhs), Some(rhs)) = (self.as_f64(), rhs.as_f64())
+        {
+            return if rhs == 0.0 {
+                Self::Null
+            } else {
+                Self::Float(lhs / rhs)
+            };
+        }
+
         match (self, rhs) {
             // Any combination of Int or Float
             (Self::Int(lhs), Self::Int(rhs)) => {
@@ -711,13 +1127,27 @@ impl Rem for ValueKind {
     type Output = Self;
 
     fn rem(self, rhs: Self) -> Self::Output {
+        #[cfg(feature = "bigint")]
+        if (self.is_big_int() || rhs.is_big_int())
+            && let Some(result) = super::bigint::promote(
+                &self,
+                &rhs,
+                isize::checked_rem,
+                |lhs, rhs| (rhs != num_bigint::BigInt::ZERO).then(|| lhs % rhs),
+                |lhs, rhs| lhs % rhs,
+            )
+        {
+            return result;
+        }
+
         match (self, rhs) {
             // Numbers
             (Self::Int(lhs), Self::Int(rhs)) => {
                 if rhs == 0 {
                     Self::Null
                 } else {
-                    Self::Int(lhs % rhs)
+                    // `isize::MIN % -1` overflows even though the result is always 0
+                    Self::Int(lhs.wrapping_rem(rhs))
                 }
             }
             (Self::Float(lhs), Self::Float(rhs)) => {
@@ -756,26 +1186,139 @@ impl Rem for Value {
     }
 }
 
+impl ValueKind {
+    /// Raises `self` to the power of `rhs`.
+    /// Negative integer exponents produce a float, integer overflow saturates like the other operators.
+    #[must_use]
+    pub fn pow(self, rhs: Self) -> Self {
+        #[cfg(feature = "bigint")]
+        if let Some(result) = super::bigint::pow(&self, &rhs) {
+            return result;
+        }
+
+        match (self, rhs) {
+            // Numbers
+            (Self::Int(lhs), Self::Int(rhs)) => match u32::try_from(rhs) {
+                Ok(exponent) => Self::Int(lhs.saturating_pow(exponent)),
+                Err(_) if rhs < 0 => Self::Float((lhs as f64).powf(rhs as f64)),
+                // Only the parity of a huge exponent matters for the saturated result
+                Err(_) if rhs % 2 == 0 => Self::Int(lhs.saturating_pow(u32::MAX - 1)),
+                Err(_) => Self::Int(lhs.saturating_pow(u32::MAX)),
+            },
+            (Self::Float(lhs), Self::Float(rhs)) => Self::Float(lhs.powf(rhs)),
+            (Self::Int(lhs), Self::Float(rhs)) => Self::Float((lhs as f64).powf(rhs)),
+            (Self::Float(lhs), Self::Int(rhs)) => Self::Float(lhs.powf(rhs as f64)),
+
+            _ => Self::Null,
+        }
+    }
+}
+
+impl Value {
+    #[must_use]
+    pub fn pow(self, rhs: Self) -> Self {
+        let span = crate::utils::merge_spans(self.span, rhs.span);
+        Value::new(self.kind.pow(rhs.kind), span)
+    }
+}
+
 impl PartialOrd for Value {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
+/// A total order of values, used for sorting.
+///
+/// Values of different types are ordered by their type:
+/// null, booleans, numbers, strings, paths, arrays, objects, functions and builtins.
+/// Numbers are ordered by value, floats with the IEEE 754 total order so `NaN` is bigger than every other number.
+/// Integers are compared with floats exactly, so the order stays transitive for integers that have no exact float.
+/// Arrays and objects are ordered lexicographically by their items and their fields, functions and builtins are all equal.
+///
+/// The comparison operators only order numbers and strings, see [`ValueKind::is_comparable_with`].
 impl Ord for Value {
     fn cmp(&self, other: &Self) -> Ordering {
+        #[cfg(feature = "bigint")]
+        if self.kind.is_big_int() || other.kind.is_big_int() {
+            if let (Some(lhs), Some(rhs)) = (self.kind.as_big_int(), other.kind.as_big_int()) {
+                return lhs.cmp(&rhs);
+            }
+            match (&self.kind, &other.kind) {
+                (ValueKind::BigInt(lhs), ValueKind::Float(rhs)) => {
+                    return super::bigint::cmp_with_float(lhs, *rhs);
+                }
+                (ValueKind::Float(lhs), ValueKind::BigInt(rhs)) => {
+                    return super::bigint::cmp_with_float(rhs, *lhs).reverse();
+                }
+                _ => {}
+            }
+        }
+
         match (&self.kind, &other.kind) {
             (ValueKind::Int(lhs), ValueKind::Int(rhs)) => lhs.cmp(rhs),
             (ValueKind::Float(lhs), ValueKind::Float(rhs)) => (*lhs).total_cmp(rhs),
-            (ValueKind::Int(lhs), ValueKind::Float(rhs)) => (*lhs as f64).total_cmp(rhs),
-            (ValueKind::Float(lhs), ValueKind::Int(rhs)) => lhs.total_cmp(&(*rhs as f64)),
+            (ValueKind::Int(lhs), ValueKind::Float(rhs)) => cmp_int_with_float(*lhs, *rhs),
+            (ValueKind::Float(lhs), ValueKind::Int(rhs)) => {
+                cmp_int_with_float(*rhs, *lhs).reverse()
+            }
+            (ValueKind::Boolean(lhs), ValueKind::Boolean(rhs)) => lhs.cmp(rhs),
             (ValueKind::String(lhs), ValueKind::String(rhs)) => lhs.cmp(rhs),
+            (ValueKind::Path(lhs), ValueKind::Path(rhs)) => lhs.cmp(rhs),
+            (ValueKind::Array(lhs), ValueKind::Array(rhs)) => lhs.cmp(rhs),
+            (ValueKind::Object(lhs), ValueKind::Object(rhs)) => lhs.cmp(rhs),
 
-            _ => Ordering::Equal,
+            _ => self.kind.type_rank().cmp(&other.kind.type_rank()),
         }
     }
 }
 
+/// Agrees with [`Ord`], so values that sort as equal are equal.
+/// The `==` operator converts between some types instead, see [`Value::loose_eq`].
+/// Compares an integer with a float exactly, in the total order of floats.
+///
+/// Casting the integer to a float would round integers above 2^53, making them equal to floats that other integers
+/// are equal to as well. `-0.0` is below every non-negative integer, like it is below `0.0`.
+pub(super) fn cmp_int_with_float(int: isize, float: f64) -> Ordering {
+    // 2^63, the first float above every isize
+    const BOUND: f64 = 9_223_372_036_854_775_808.0;
+
+    if float.is_nan() {
+        return if float.is_sign_negative() {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        };
+    }
+    if float >= BOUND {
+        return Ordering::Less;
+    }
+    if float < -BOUND {
+        return Ordering::Greater;
+    }
+
+    // Exact, the float is an integer inside of the range of an i128
+    let whole = float.trunc() as i128;
+    (int as i128).cmp(&whole).then_with(|| cmp_fraction(float))
+}
+
+/// How an integer compares with `float` if it equals the integer part of `float`.
+#[allow(
+    clippy::float_arithmetic,
+    reason = "The fractional part is only compared, not computed further"
+)]
+pub(super) fn cmp_fraction(float: f64) -> Ordering {
+    let fraction = float - float.trunc();
+
+    if fraction > 0.0 {
+        Ordering::Less
+    } else if fraction < 0.0 || (float.is_sign_negative() && float == 0.0) {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         #[cfg(test)]
@@ -783,23 +1326,90 @@ impl PartialEq for Value {
             return false;
         }
 
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Value {}
+
+impl Value {
+    /// Equality of the `==` operator.
+    ///
+    /// Numbers are compared by value and `true` is equal to any truthy value, whichever side it is on.
+    /// Arrays and objects are equal if all of their items and fields are,
+    /// functions if they have the same parameters and body and builtins if they are the same builtin.
+    pub fn loose_eq(&self, other: &Self) -> bool {
+        #[cfg(feature = "bigint")]
+        if self.kind.is_big_int() || other.kind.is_big_int() {
+            return self.cmp(other) == Ordering::Equal
+                && self.kind.as_f64().is_some()
+                && other.kind.as_f64().is_some();
+        }
+
         match (&self.kind, &other.kind) {
             (ValueKind::Null, ValueKind::Null) => true,
             (ValueKind::Boolean(lhs), ValueKind::Boolean(rhs)) => lhs == rhs,
-            (ValueKind::Boolean(bool), other) => *bool && other.is_truthy(),
-            (ValueKind::Int(lhs), ValueKind::Int(rhs)) => lhs == rhs,
-            (ValueKind::Int(lhs), ValueKind::Float(rhs)) => *lhs == (*rhs as isize),
-            (ValueKind::Float(lhs), ValueKind::Float(rhs)) => lhs == rhs,
-            (ValueKind::Float(lhs), ValueKind::Int(rhs)) => *lhs == (*rhs as f64),
+            (ValueKind::Boolean(bool), _) => *bool && other.is_truthy(),
+            (_, ValueKind::Boolean(bool)) => *bool && self.is_truthy(),
+            (ValueKind::Int(_) | ValueKind::Float(_), ValueKind::Int(_) | ValueKind::Float(_)) => {
+                self.cmp(other) == Ordering::Equal && !self.is_nan() && !other.is_nan()
+            }
             (ValueKind::String(lhs), ValueKind::String(rhs)) => lhs == rhs,
-            (ValueKind::Array(lhs), ValueKind::Array(rhs)) => lhs == rhs,
-            (ValueKind::Object(lhs), ValueKind::Object(rhs)) => lhs == rhs,
+            (ValueKind::Path(lhs), ValueKind::Path(rhs)) => lhs == rhs,
+            (ValueKind::Array(lhs), ValueKind::Array(rhs)) => {
+                lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(lhs, rhs)| lhs.loose_eq(rhs))
+            }
+            (ValueKind::Object(lhs), ValueKind::Object(rhs)) => {
+                lhs.len() == rhs.len()
+                    && lhs.iter().zip(rhs).all(|((lhs_key, lhs), (rhs_key, rhs))| {
+                        lhs_key == rhs_key && lhs.loose_eq(rhs)
+                    })
+            }
+            (ValueKind::Function { .. } | ValueKind::Builtin(_), other_kind) => {
+                self.kind.is_same_function(other_kind)
+            }
             _ => false,
         }
     }
+
+    const fn is_nan(&self) -> bool {
+        matches!(self.kind, ValueKind::Float(float) if float.is_nan())
+    }
+
+    /// Structural equality without the conversions of `==`, used by `deepEq`.
+    ///
+    /// Values have to be of the same type, except for integers and floats which are compared by value.
+    /// Arrays and objects are equal if all of their items and fields are,
+    /// functions if they have the same parameters and body and builtins if they are the same builtin.
+    pub fn deep_eq(&self, other: &Self) -> bool {
+        match (&self.kind, &other.kind) {
+            (ValueKind::Array(lhs), ValueKind::Array(rhs)) => {
+                lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(lhs, rhs)| lhs.deep_eq(rhs))
+            }
+            (ValueKind::Object(lhs), ValueKind::Object(rhs)) => {
+                lhs.len() == rhs.len()
+                    && lhs.iter().zip(rhs).all(|((lhs_key, lhs), (rhs_key, rhs))| {
+                        lhs_key == rhs_key && lhs.deep_eq(rhs)
+                    })
+            }
+            (ValueKind::Function { .. } | ValueKind::Builtin(_), other_kind) => {
+                self.kind.is_same_function(other_kind)
+            }
+            _ => {
+                self.kind.type_rank() == other.kind.type_rank()
+                    && self.cmp(other) == Ordering::Equal
+            }
+        }
+    }
 }
 
-impl Eq for Value {}
+/// The most items that a range can create.
+pub const MAX_ARRAY_LENGTH: usize = 1 << 20;
+/// The longest string in bytes that repeating a string can create.
+pub const MAX_STRING_LENGTH: usize = 1 << 26;
+/// The most bits of a big integer that `**` can create.
+#[cfg(feature = "bigint")]
+pub const MAX_BIG_INT_BITS: u64 = 1 << 26;
 
 pub type Error = crate::Error<ErrorKind>;
 
@@ -813,13 +1423,42 @@ pub enum ErrorKind {
         variable: SourceSpan,
     },
 
+    #[error("Can not call a {type_name}")]
+    #[diagnostic(
+        code(tl::runtime::not_callable),
+        help("Only functions and builtins can be called")
+    )]
+    NotCallable {
+        type_name: String,
+        /// The parentheses of the call.
+        #[label("Called here")]
+        call: SourceSpan,
+        /// Where the value was created, which is the called expression for literals.
+        #[label("This is a {type_name}")]
+        value: SourceSpan,
+    },
+
     #[error("Mismatch in number of function arguments")]
     #[diagnostic(code(tl::runtime::call))]
     ArgsMismatch {
         len: usize,
-
-        #[label("Supposed to have {len} argument{s}", s = if *len == 1 { "" } else { "s" })]
+        /// Whether the function takes any number of arguments after the first `len`.
+        variadic: bool,
+        /// The number of arguments that were passed.
+        given: usize,
+
+        #[label(
+            "Supposed to have {at_least}{len} argument{s}, found {given}",
+            at_least = if *variadic { "at least " } else { "" },
+            s = if *len == 1 { "" } else { "s" }
+        )]
         args: SourceSpan,
+        /// Where the called function is defined, builtins don't have a definition.
+        #[label("Defined here")]
+        definition: Option<SourceSpan>,
+        /// The parameters of the function, like `greet(name, greeting?)`.
+        #[help]
+        signature: Option<String>,
     },
 
     #[error("Index out of bounds")]
@@ -831,18 +1470,447 @@ pub enum ErrorKind {
         index: SourceSpan,
     },
 
+    #[error("Unknown argument")]
+    #[diagnostic(code(tl::runtime::call))]
+    UnknownArgument {
+        #[label("This function has no parameter with this name")]
+        argument: SourceSpan,
+    },
+
+    #[error("Argument passed more than once")]
+    #[diagnostic(code(tl::runtime::call))]
+    DuplicateArgument {
+        #[label("This parameter already has a value")]
+        argument: SourceSpan,
+    },
+
+    #[error("Character index out of bounds")]
+    #[diagnostic(
+        code(tl::runtime::builtin),
+        help("Strings are indexed by characters as they are displayed, not by UTF-8 bytes")
+    )]
+    CharIndexOutOfBounds {
+        length: usize,
+
+        #[label("Length is {length} character{s}", s = if *length == 1 { "" } else { "s" })]
+        index: SourceSpan,
+    },
+
+    #[error("Invalid codepoint")]
+    #[diagnostic(
+        code(tl::runtime::builtin),
+        help(
+            "Codepoints must be between 0 and 0x10FFFF and can not be a UTF-16 surrogate (0xD800 to 0xDFFF)"
+        )
+    )]
+    InvalidCodepoint {
+        #[label("This is not a unicode scalar value")]
+        codepoint: SourceSpan,
+    },
+
+    #[error("Invalid byte")]
+    #[diagnostic(code(tl::runtime::builtin))]
+    InvalidByte {
+        #[label("This is not between 0 and 255")]
+        byte: SourceSpan,
+    },
+
+    #[error("Invalid UTF-8")]
+    #[diagnostic(
+        code(tl::runtime::builtin),
+        help("The bytes have to be a string encoded as UTF-8, like the result of `toBytes`")
+    )]
+    InvalidUtf8 {
+        /// The number of bytes before the first invalid one.
+        valid_up_to: usize,
+        #[label("Not valid after the first {valid_up_to} bytes")]
+        bytes: SourceSpan,
+    },
+
     #[error("Mismatched types, expected {expected}, got {got}")]
     #[diagnostic(code(tl::runtime::expr))]
     MismatchedTypes { expected: String, got: String },
 
+    #[error("Integer overflow")]
+    #[diagnostic(
+        code(tl::runtime::integer_overflow),
+        help("The result does not fit into a {}-bit integer", isize::BITS)
+    )]
+    IntegerOverflow {
+        #[label("This operation overflowed")]
+        operation: SourceSpan,
+    },
+
+    #[cfg(feature = "bigint")]
+    #[error("This builtin doesn't take big integers")]
+    #[diagnostic(
+        code(tl::runtime::builtin),
+        help(
+            "Only operators and builtins like `clamp` or `toFixed` take integers that don't fit into {} bits",
+            isize::BITS
+        )
+    )]
+    BigIntUnsupported {
+        #[label("This is a big integer")]
+        value: SourceSpan,
+    },
+
+    #[error("Mismatched types, can not use '{operator}' on {lhs_type} and {rhs_type}")]
+    #[diagnostic(code(tl::runtime::binary_op))]
+    MismatchedOperandTypes {
+        operator: String,
+        lhs_type: String,
+        rhs_type: String,
+
+        #[label("This is a {lhs_type}")]
+        lhs: SourceSpan,
+        #[label("This is a {rhs_type}")]
+        rhs: SourceSpan,
+    },
+
+    #[error("{base_type} has no field `{field}`")]
+    #[diagnostic(
+        code(tl::runtime::missing_field),
+        help("Use `or` after the field access to provide a fallback")
+    )]
+    MissingField {
+        field: String,
+        base_type: String,
+        #[label("This is a {base_type}")]
+        base: SourceSpan,
+    },
+
+    #[error("Expected {expected}, found {found}")]
+    #[diagnostic(
+        code(tl::runtime::contract),
+        help("Change the value, or the type annotation if the value is right")
+    )]
+    ContractViolation {
+        expected: String,
+        found: String,
+        #[label("Annotated as {expected} here")]
+        annotation: SourceSpan,
+        #[label("This is a {found}")]
+        value: SourceSpan,
+    },
+
+    #[error("`fix` expects a function of one parameter whose body is an object literal")]
+    #[diagnostic(
+        code(tl::runtime::fix),
+        help(
+            "The fields have to be known before the body is evaluated, so it can't compute the object. \
+             Move `let` bindings around the call, like `let x = 1 in fix((self) {{ {{ a = x b = self.a + 1 }} }})`"
+        )
+    )]
+    InvalidFixpoint {
+        #[label("This is a {type_name}")]
+        function: SourceSpan,
+        type_name: String,
+    },
+
+    #[error("`{field}` depends on itself")]
+    #[diagnostic(
+        code(tl::runtime::infinite_recursion),
+        help("The fields depend on each other in a cycle: {cycle}")
+    )]
+    InfiniteRecursion {
+        field: String,
+        cycle: String,
+        #[label("Defined here")]
+        definition: SourceSpan,
+    },
+
+    #[error("Unknown type `{name}`")]
+    #[diagnostic(
+        code(tl::runtime::option),
+        help("The types are the ones of type annotations, like `int`, `[string]` or `path?`")
+    )]
+    UnknownType {
+        name: String,
+        #[label("Used here")]
+        ty: SourceSpan,
+    },
+
+    #[error("Unknown option `{option}`")]
+    #[diagnostic(code(tl::runtime::option))]
+    UnknownOption {
+        option: String,
+        #[label("Not declared")]
+        definition: SourceSpan,
+        #[help]
+        known: Option<String>,
+    },
+
+    #[error("Option `{option}` expects {expected}, found {found}")]
+    #[diagnostic(code(tl::runtime::option))]
+    InvalidOption {
+        option: String,
+        expected: String,
+        found: String,
+        #[label("This is a {found}")]
+        definition: SourceSpan,
+        #[label("Declared as {expected} here")]
+        declaration: SourceSpan,
+    },
+
+    #[error("Option `{option}` is not defined")]
+    #[diagnostic(
+        code(tl::runtime::option),
+        help("Define the option, or give it a `default`")
+    )]
+    MissingOption {
+        option: String,
+        #[label("Declared here without a default")]
+        declaration: SourceSpan,
+    },
+
+    #[error("Division by zero")]
+    #[diagnostic(code(tl::runtime::division_by_zero))]
+    DivisionByZero {
+        #[label("This is zero")]
+        divisor: SourceSpan,
+    },
+
+    #[error("Can not shift by a negative amount")]
+    #[diagnostic(code(tl::runtime::builtin))]
+    NegativeShift {
+        #[label("This is negative")]
+        amount: SourceSpan,
+    },
+
+    #[error("Can not round to a negative number of decimal places")]
+    #[diagnostic(code(tl::runtime::builtin))]
+    NegativePrecision {
+        #[label("This is negative")]
+        digits: SourceSpan,
+    },
+
+    #[error("Can not round to more than {limit} decimal places")]
+    #[diagnostic(
+        code(tl::runtime::builtin),
+        help("Floats have at most {limit} decimal places, rounding to more doesn't change them")
+    )]
+    PrecisionTooLarge {
+        limit: usize,
+        #[label("This is more than {limit}")]
+        digits: SourceSpan,
+    },
+
+    #[error("Condition is not a boolean")]
+    #[diagnostic(
+        code(tl::runtime::strict_conditions),
+        help("Compare the value explicitly, `Options::strict_conditions` only allows booleans")
+    )]
+    NonBooleanCondition {
+        type_name: String,
+        #[label("This is a {type_name}")]
+        condition: SourceSpan,
+    },
+
+    #[error("Invalid range, the lower bound is bigger than the upper bound")]
+    #[diagnostic(code(tl::runtime::builtin))]
+    InvalidRange {
+        #[label("This range is empty")]
+        range: SourceSpan,
+    },
+
+    #[error("Function calls are nested too deep")]
+    #[diagnostic(
+        code(tl::runtime::too_deep),
+        help(
+            "Functions can call each other at most {limit} levels deep, make sure the recursion ends \
+             or raise `Options::max_call_depth`"
+        )
+    )]
+    CallTooDeep {
+        limit: usize,
+        #[label("This call exceeded the limit")]
+        call: SourceSpan,
+    },
+
+    #[error("Result is too large")]
+    #[diagnostic(
+        code(tl::runtime::too_large),
+        help("Results are limited to {limit} {unit} so that evaluating can't exhaust the memory")
+    )]
+    TooLarge {
+        limit: usize,
+        unit: String,
+        #[label("This would create more than {limit} {unit}")]
+        operation: SourceSpan,
+    },
+
+    #[error("`{name}` of an empty array")]
+    #[diagnostic(
+        code(tl::runtime::builtin),
+        help("Only `sum` and `product` have a result for an empty array, 0 and 1")
+    )]
+    EmptyAggregate {
+        name: String,
+        #[label("This array is empty")]
+        array: SourceSpan,
+    },
+
+    #[error("File is larger than {limit} bytes")]
+    #[diagnostic(
+        code(tl::runtime::file_too_large),
+        help("Raise `Options::max_file_size` if the file is expected to be this large")
+    )]
+    FileTooLarge {
+        limit: u64,
+        #[label("This file")]
+        path: SourceSpan,
+    },
+
+    #[error("File is not valid UTF-8")]
+    #[diagnostic(code(tl::runtime::invalid_utf8_file))]
+    InvalidUtf8File {
+        /// The number of bytes before the first invalid one.
+        valid_up_to: usize,
+        #[label("Not valid after the first {valid_up_to} bytes")]
+        path: SourceSpan,
+        /// What the file probably is instead, like UTF-16 or binary data.
+        #[help]
+        hint: String,
+    },
+
+    #[error("File is imported while it is being evaluated")]
+    #[diagnostic(
+        code(tl::runtime::import_cycle),
+        help("The files import each other in a cycle: {cycle}")
+    )]
+    ImportCycle {
+        cycle: String,
+        #[label("This file is already being evaluated")]
+        path: SourceSpan,
+    },
+
+    #[error("Internal error: {message}")]
+    #[diagnostic(
+        code(tl::runtime::internal),
+        help("This is a bug in tl, or a host passed a value that tl never creates itself")
+    )]
+    Internal { message: String },
+
     #[error(transparent)]
+    #[diagnostic(transparent)]
     ParseError(#[from] ast::types::Error),
 
     #[error(transparent)]
+    #[diagnostic(code(tl::runtime::io))]
     IOError(#[from] io::Error),
 
+    #[error("The environment variable `{name}` is not set")]
+    #[diagnostic(
+        code(tl::runtime::builtin),
+        help("Use `${{{name}:-default}}` to fall back to a default value")
+    )]
+    EnvVarNotSet {
+        name: String,
+        #[label("Referenced in this string")]
+        string: SourceSpan,
+    },
+
+    #[error("Paths can not contain the control character {character:?}")]
+    #[diagnostic(code(tl::runtime::invalid_path))]
+    InvalidPathCharacter {
+        character: char,
+        #[label("Inserted here")]
+        part: SourceSpan,
+    },
+
+    #[error("Can not join an absolute path")]
+    #[diagnostic(
+        code(tl::runtime::invalid_path),
+        help("Joining it would replace the whole path, join a relative path like `./etc` instead")
+    )]
+    AbsoluteSegment {
+        #[label("This path is absolute")]
+        segment: SourceSpan,
+    },
+
+    #[error("Path is outside of {root}")]
+    #[diagnostic(
+        code(tl::runtime::sandbox),
+        help("Only paths inside of `Options::path_root` are allowed")
+    )]
+    PathOutsideRoot {
+        root: String,
+        #[label("Resolves to {resolved}")]
+        path: SourceSpan,
+        resolved: String,
+    },
+
+    #[error("`{name}` is not allowed here")]
+    #[diagnostic(code(tl::runtime::builtin_denied))]
+    BuiltinDenied { name: String },
+
+    #[error("`{name}` depends on the environment, which is not allowed in deterministic mode")]
+    #[diagnostic(
+        code(tl::runtime::impurity),
+        help("Pass the value in as a variable instead, or turn off `Options::deterministic`")
+    )]
+    ImpurityNotAllowed {
+        name: String,
+        #[label("Called here")]
+        call: SourceSpan,
+    },
+
+    #[cfg(feature = "fs-write")]
+    #[error("Writing to this path is not allowed")]
+    #[diagnostic(
+        code(tl::runtime::sandbox),
+        help("Add one of its parent directories to `Options::write_allow_list`")
+    )]
+    WriteNotAllowed {
+        #[label("Outside of the write allow-list")]
+        path: SourceSpan,
+    },
+
+    #[error("Can not override `{key}` of a frozen object")]
+    #[diagnostic(
+        code(tl::runtime::frozen),
+        help(
+            "Copy the fields that should stay into a new object instead of merging into the frozen one"
+        )
+    )]
+    FrozenOverride {
+        key: String,
+        #[label("Frozen here")]
+        frozen: SourceSpan,
+        #[label("Overridden here")]
+        overridden: SourceSpan,
+    },
+
+    #[error("Invalid value: {reason}")]
+    #[diagnostic(code(tl::runtime::builtin))]
+    InvalidQuantity {
+        reason: String,
+        #[label("This value")]
+        value: SourceSpan,
+    },
+
+    #[cfg(feature = "url")]
+    #[error("Invalid URL: {reason}")]
+    #[diagnostic(code(tl::runtime::builtin))]
+    InvalidUrl {
+        reason: String,
+        #[label("This URL")]
+        url: SourceSpan,
+    },
+
+    #[cfg(feature = "semver")]
+    #[error("Invalid semantic version: {reason}")]
+    #[diagnostic(code(tl::runtime::builtin))]
+    InvalidSemver {
+        reason: String,
+        #[label("This version")]
+        version: SourceSpan,
+    },
+
     #[cfg(feature = "toml")]
     #[error(transparent)]
+    #[diagnostic(code(tl::runtime::toml))]
     TomlParsingError(#[from] toml::de::Error),
 }
 
@@ -0,0 +1,15 @@
+//! Expressions embedded in text from the host, like a `${user.name}` placeholder in a message.
+
+use crate::{parser::parse_template, runtime::Scope};
+use miette::{NamedSource, Report};
+
+/// Evaluates every `${...}` region of `template` with the variables of `scope` and splices in the results,
+/// the rest of the text is kept as is. Like in a string literal, `\${` is a literal `${`.
+/// # Errors
+/// This function will return an error if an interpolation can not be parsed or if an evaluation error occurs.
+pub fn render(template: &str, scope: &mut Scope) -> Result<String, Report> {
+    let source = NamedSource::new("template", template.to_string());
+    let ast = parse_template(&source, scope.options())?;
+
+    Ok(scope.fork(source, ast).eval()?.to_string())
+}
@@ -0,0 +1,60 @@
+#![allow(clippy::unwrap_used, reason = "Panics automatically invalidate tests")]
+
+use crate::{Options, cache::EvalCache};
+use pretty_assertions::assert_eq;
+use std::fs;
+
+#[test]
+fn cached_evaluation() {
+    let dir = std::env::temp_dir().join("tl-cache");
+    let cache = EvalCache::new(dir.join("cache"));
+    cache.clear().unwrap();
+    fs::create_dir_all(&dir).unwrap();
+
+    let main = dir.join("main.tl");
+    let lib = dir.join("lib.tl");
+    fs::write(
+        &main,
+        format!(
+            r#"let lib = import({}) in {{ port = lib.port "a key" = "q\"uote\n\${{x}}" xs = [ 1 -2 0.5 null true ] dir = /tmp/x }}"#,
+            lib.display()
+        ),
+    )
+    .unwrap();
+    fs::write(&lib, "{ port = 80 }").unwrap();
+
+    let expected = r#"{ a key = q"uote
+${x}; dir = /tmp/x; port = 80; xs = [ 1 -2 0.5 null true ] }"#;
+    let eval = || {
+        cache
+            .eval_file(&main, Options::default())
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!(eval(), expected);
+    assert_eq!(eval(), expected);
+
+    // The second evaluation read the entry, which is only used while the files are unchanged
+    let entries: Vec<_> = fs::read_dir(dir.join("cache")).unwrap().collect();
+    assert_eq!(entries.len(), 1);
+    let entry = entries.into_iter().next().unwrap().unwrap().path();
+    let text = fs::read_to_string(&entry).unwrap();
+    fs::write(&entry, text.replace("80", "81")).unwrap();
+    assert_eq!(eval(), expected.replace("80", "81"));
+
+    fs::write(&lib, "{ port = 90 }").unwrap();
+    assert_eq!(eval(), expected.replace("80", "90"));
+
+    // Functions can't be cached
+    let function = dir.join("function.tl");
+    fs::write(&function, "(x) { x }").unwrap();
+    cache.clear().unwrap();
+    assert_eq!(
+        cache
+            .eval_file(&function, Options::default())
+            .unwrap()
+            .to_string(),
+        "function"
+    );
+    assert!(!dir.join("cache").exists());
+}
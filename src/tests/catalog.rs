@@ -0,0 +1,60 @@
+#![allow(clippy::unwrap_used, reason = "Panics automatically invalidate tests")]
+
+use crate::{Options, error_catalog, error_info, parser::parse_with_options, runtime::Scope};
+use miette::{NamedSource, Report};
+use std::collections::HashMap;
+
+fn eval(text: &str, options: Options) -> Result<(), Report> {
+    let source = NamedSource::new("example", text.to_string());
+    let ast = parse_with_options(&source, &options)?;
+
+    let mut scope = Scope::new(HashMap::new(), source, ast);
+    scope.set_options(options);
+    scope.eval()?;
+    Ok(())
+}
+
+#[test]
+fn examples_report_their_code() {
+    for info in error_catalog() {
+        let Some(example) = info.example else {
+            continue;
+        };
+
+        #[allow(
+            clippy::needless_update,
+            reason = "Other fields depend on enabled features"
+        )]
+        let options = Options {
+            checked_arithmetic: true,
+            strict_types: true,
+            strict_conditions: true,
+            strict_fields: true,
+            allow_env: info.code != "tl::runtime::builtin_denied",
+            deterministic: info.code == "tl::runtime::impurity",
+            path_root: (info.code == "tl::runtime::sandbox").then(std::env::temp_dir),
+            ..Default::default()
+        };
+
+        let result = eval(example, options);
+        assert!(result.is_err(), "{example}");
+        let err = result.unwrap_err();
+        assert_eq!(
+            err.code().map(|code| code.to_string()).as_deref(),
+            Some(info.code),
+            "{example}: {err:?}"
+        );
+    }
+}
+
+#[test]
+fn lookup() {
+    let infos = error_info("tl::runtime::call");
+    assert_eq!(infos.len(), 3);
+
+    let text = infos.first().unwrap().to_string();
+    assert!(text.starts_with("tl::runtime::call: Mismatch in number of function arguments\n\n"));
+    assert!(text.ends_with("Example:\n    let add = (a, b) { a + b } in add(1)"));
+
+    assert!(error_info("tl::runtime::unknown").is_empty());
+}
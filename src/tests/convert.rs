@@ -0,0 +1,114 @@
+#![allow(clippy::unwrap_used, reason = "Panics automatically invalidate tests")]
+
+use crate::{
+    convert::{ConvertError, Format, convert, parse_json, to_source},
+    eval_str,
+    parser::ast::DEFAULT_MAX_DEPTH,
+    runtime::{Value, ValueKind},
+};
+use miette::NamedSource;
+use pretty_assertions::assert_eq;
+
+fn source(text: &str) -> NamedSource<String> {
+    NamedSource::new("test", text.to_string())
+}
+
+#[test]
+fn json() {
+    let input = r#"{
+        "name": "web \"${x}\"",
+        "port": 8080,
+        "ratio": 1e2,
+        "server.host": "\u00e9\ud83d\ude00",
+        "in": null,
+        "tags": ["a", -1, true],
+        "routes": [{ "path": "/" }, []],
+        "empty": {}
+    }"#;
+    let expected = r#"{
+    empty = {}
+    "in" = null
+    name = "web \"\${x}\""
+    port = 8080
+    ratio = 100.0
+    routes = [
+        {
+            path = "/"
+        }
+        []
+    ]
+    "server.host" = "é😀"
+    tags = [ "a" -1 true ]
+}"#;
+
+    let output = convert(source(input), Format::Json).unwrap();
+    assert_eq!(output, expected);
+    assert_eq!(
+        eval_str(&output).unwrap().to_string(),
+        parse_json(source(input)).unwrap().to_string()
+    );
+}
+
+#[test]
+fn invalid_json() {
+    for (input, offset) in [
+        (r#"{ "a": 1, }"#, 10),
+        ("[ 1 2 ]", 4),
+        (r#""\x""#, 1),
+        ("nul", 0),
+        ("{} {}", 3),
+        ("1e999", 0),
+        ("[ 01 ]", 2),
+        ("-01", 0),
+        ("1.", 0),
+        ("1.e5", 0),
+        ("1e", 0),
+        ("+1", 0),
+        ("1-2", 0),
+    ] {
+        let err = convert(source(input), Format::Json).unwrap_err();
+        assert!(
+            matches!(err, ConvertError::InvalidJson { at, .. } if at.offset() == offset),
+            "{input}"
+        );
+    }
+
+    let nested = format!("{}1{}", "[".repeat(3000), "]".repeat(3000));
+    let err = convert(source(&nested), Format::Json).unwrap_err();
+    assert!(
+        matches!(err, ConvertError::InvalidJson { at, .. } if at.offset() == DEFAULT_MAX_DEPTH)
+    );
+
+    let depth = DEFAULT_MAX_DEPTH - 1;
+    let nested = format!("{}1{}", "[".repeat(depth), "]".repeat(depth));
+    let value = parse_json(source(&nested)).unwrap();
+    eval_str(&to_source(&value).unwrap()).unwrap();
+    let value = Value::new_builtin(ValueKind::Array(vec![value]));
+    assert!(to_source(&value).is_none());
+}
+
+#[cfg(feature = "bigint")]
+#[test]
+fn json_big_int() {
+    let input = "[ 18446744073709551616, -18446744073709551617, 9223372036854775807, -0, 1E2 ]";
+    let value = parse_json(source(input)).unwrap();
+    assert!(matches!(
+        &value.kind,
+        ValueKind::Array(items) if matches!(items.first().unwrap().kind, ValueKind::BigInt(_))
+    ));
+    assert_eq!(
+        to_source(&value).unwrap(),
+        "[ 18446744073709551616 -18446744073709551617 9223372036854775807 0 100.0 ]"
+    );
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn toml() {
+    let input = "title = \"tl\"\n\n[server]\nport = 80\nhosts = [\"a\", \"b\"]\n";
+    let expected = "{\n    server = {\n        hosts = [ \"a\" \"b\" ]\n        port = 80\n    }\n    title = \"tl\"\n}";
+    assert_eq!(convert(source(input), Format::Toml).unwrap(), expected);
+
+    let err = convert(source("x = inf"), Format::Toml).unwrap_err();
+    assert!(matches!(err, ConvertError::NoLiteral));
+}
@@ -0,0 +1,82 @@
+#![allow(clippy::unwrap_used, reason = "Panics automatically invalidate tests")]
+
+use crate::{
+    Options,
+    deps::{Dependency, DependencyKind, dependency_graph},
+};
+use pretty_assertions::assert_eq;
+use std::fs;
+
+#[test]
+fn static_dependencies() {
+    let dir = std::env::temp_dir().join("tl-deps");
+    fs::create_dir_all(dir.join("hosts")).unwrap();
+
+    let main = dir.join("main.tl");
+    let lib = dir.join("lib.tl");
+    let hosts = dir.join("hosts");
+    let template = dir.join("nginx.conf");
+    fs::write(
+        &main,
+        format!(
+            "let\n lib = import({lib})\n hosts = builtins.import({hosts})\n host = import(./hosts/${{lib.name}}.tl)\nin renderTemplate({template}, {{ }})",
+            lib = lib.display(),
+            hosts = hosts.display(),
+            template = template.display()
+        ),
+    )
+    .unwrap();
+    fs::write(
+        &lib,
+        format!("{{ name = \"a\" again = import({}) }}", main.display()),
+    )
+    .unwrap();
+    fs::write(hosts.join("default.tl"), "[ ]").unwrap();
+    fs::write(&template, format!("${{import({})}}", lib.display())).unwrap();
+
+    let graph = dependency_graph(&main, &Options::default()).unwrap();
+    let edge = |from: &std::path::Path, to: std::path::PathBuf, kind| Dependency {
+        from: from.to_path_buf(),
+        to,
+        kind,
+    };
+
+    assert_eq!(
+        graph.dependencies,
+        [
+            edge(&main, lib.clone(), DependencyKind::Import),
+            edge(&main, hosts.join("default.tl"), DependencyKind::Import),
+            edge(&main, template.clone(), DependencyKind::Template),
+            edge(&lib, main.clone(), DependencyKind::Import),
+            edge(&template, lib.clone(), DependencyKind::Import),
+        ]
+    );
+    assert_eq!(graph.files.len(), 4);
+    assert_eq!(graph.dynamic.len(), 1);
+    assert_eq!(graph.dynamic.first().unwrap().line, 4);
+
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph dependencies {\n"));
+    assert!(dot.contains(&format!(
+        "{:?} -> {:?} [style=dashed];",
+        main.display().to_string(),
+        template.display().to_string()
+    )));
+}
+
+#[test]
+fn relative_dependencies() {
+    let dir = std::env::temp_dir().join("tl-deps-relative");
+    fs::create_dir_all(dir.join("lib")).unwrap();
+
+    let main = dir.join("main.tl");
+    fs::write(&main, "import(./lib/mod.tl)").unwrap();
+    fs::write(dir.join("lib/mod.tl"), "import(../shared.tl)").unwrap();
+    fs::write(dir.join("shared.tl"), "{ }").unwrap();
+
+    let graph = dependency_graph(&main, &Options::default()).unwrap();
+    assert_eq!(
+        graph.files,
+        [main, dir.join("lib/mod.tl"), dir.join("shared.tl")]
+    );
+}
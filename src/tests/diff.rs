@@ -0,0 +1,54 @@
+#![allow(clippy::unwrap_used, reason = "Panics automatically invalidate tests")]
+
+use crate::{
+    diff::{ChangeKind, diff, render},
+    eval_str,
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn value_changes() {
+    let old =
+        eval_str(r#"{ server = { host = "a" port = 80 } hosts = [ "x" "y" "z" ] debug = true }"#)
+            .unwrap();
+    let new = eval_str(r#"{ server = { host = "a" port = 8080 } hosts = [ "x" "w" ] tls = true }"#)
+        .unwrap();
+
+    let changes = diff(&old, &new);
+    assert_eq!(
+        changes
+            .iter()
+            .map(|change| change.kind())
+            .collect::<Vec<_>>(),
+        [
+            ChangeKind::Removed,
+            ChangeKind::Changed,
+            ChangeKind::Removed,
+            ChangeKind::Changed,
+            ChangeKind::Added,
+        ]
+    );
+    assert_eq!(
+        render(&changes),
+        "- debug = true\n~ hosts[1]: y -> w\n- hosts[2] = z\n~ server.port: 80 -> 8080\n+ tls = true\n"
+    );
+
+    assert!(diff(&old, &old).is_empty());
+    assert_eq!(
+        render(&diff(&old, &eval_str("1").unwrap())).lines().count(),
+        1
+    );
+}
+
+#[test]
+fn diff_builtin() {
+    let value = eval_str(
+        "let\n old = { a = 1 b = [ 1 ] }\n new = { a = 2 b = [ 1 2 ] }\nin diff(old, new)",
+    )
+    .unwrap();
+
+    assert_eq!(
+        value.to_string(),
+        "{ added = [ { path = b[1]; value = 2 } ]; changed = [ { new = 2; old = 1; path = a } ]; removed = [  ] }"
+    );
+}
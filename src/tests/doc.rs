@@ -0,0 +1,86 @@
+#![allow(clippy::unwrap_used, reason = "Panics automatically invalidate tests")]
+
+use crate::{
+    doc::{DocEntry, module_docs},
+    parser::parse,
+};
+use miette::NamedSource;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn module_exports() {
+    let input = r#"
+(port ? 8080, host) {
+    let
+        ## The address the server listens on.
+        address = "${host}:${port}"
+    in
+    {
+        address = address
+        ## Whether TLS is used.
+        ## Defaults to off.
+        tls = false
+        ports = [ port ]
+    }
+}
+"#;
+    let source = NamedSource::new("server.tl", input.to_string());
+    let docs = module_docs(&source, &parse(&source).unwrap());
+
+    assert_eq!(
+        docs.params,
+        [
+            DocEntry {
+                name: "port".into(),
+                doc: None,
+                type_name: "number",
+                default: Some("8080".into()),
+            },
+            DocEntry {
+                name: "host".into(),
+                doc: None,
+                type_name: "any",
+                default: None,
+            },
+        ]
+    );
+    assert_eq!(
+        docs.exports,
+        [
+            DocEntry {
+                name: "address".into(),
+                doc: Some("The address the server listens on.".into()),
+                type_name: "string",
+                default: None,
+            },
+            DocEntry {
+                name: "tls".into(),
+                doc: Some("Whether TLS is used.\nDefaults to off.".into()),
+                type_name: "boolean",
+                default: Some("false".into()),
+            },
+            DocEntry {
+                name: "ports".into(),
+                doc: None,
+                type_name: "array",
+                default: None,
+            },
+        ]
+    );
+
+    assert!(docs.to_markdown("server").contains(
+        "### `tls`: `boolean`\nDefault: `false`\nWhether TLS is used.\nDefaults to off.\n"
+    ));
+}
+
+#[test]
+fn stray_doc_comments() {
+    let input = r"
+## Not attached to anything
+[ 1 ## Also not attached
+]
+";
+    let source = NamedSource::new("test", input.to_string());
+
+    assert!(parse(&source).is_ok());
+}
@@ -0,0 +1,45 @@
+#![allow(clippy::unwrap_used, reason = "Panics automatically invalidate tests")]
+
+use crate::{Options, explain::explain};
+use pretty_assertions::assert_eq;
+use std::fs;
+
+#[test]
+fn value_provenance() {
+    let dir = std::env::temp_dir().join("tl-explain");
+    fs::create_dir_all(&dir).unwrap();
+
+    let main = dir.join("main.tl");
+    let lib = dir.join("lib.tl");
+    fs::write(
+        &main,
+        format!(
+            "let lib = import({}) in\napplyOverrides(lib, [ {{ server = {{ host = \"a\" }} }} ])",
+            lib.display()
+        ),
+    )
+    .unwrap();
+    fs::write(&lib, "let port = 80 in\n{ server = { port = port } }").unwrap();
+
+    let explanation = explain(&main, ".server.port", Options::default()).unwrap();
+    let lib = lib.display();
+    assert_eq!(
+        explanation.to_string(),
+        format!(
+            "server.port = 80\n  defined at {lib}:1:12: 80\n  bound to `port` at {lib}:1:12: 80\n  read at {lib}:2:21: port\n"
+        )
+    );
+
+    let explanation = explain(&main, "server.host", Options::default()).unwrap();
+    assert_eq!(explanation.steps.len(), 1);
+    assert_eq!(
+        explanation.steps.first().unwrap().file,
+        main.display().to_string()
+    );
+
+    let err = explain(&main, ".server.missing", Options::default()).unwrap_err();
+    assert!(
+        err.to_string()
+            .ends_with("has no value at `server.missing`")
+    );
+}
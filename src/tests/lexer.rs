@@ -0,0 +1,179 @@
+#![allow(clippy::unwrap_used, reason = "Panics automatically invalidate tests")]
+
+use crate::parser::lexer::{
+    Lexer, lex,
+    types::{ErrorKind, TokenKind},
+};
+use miette::NamedSource;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn lossless_round_trip() {
+    let input = r#"
+// A comment
+let
+    ## Documented
+    name = "wörld ${1 + 2}" // Trailing
+    path = ./dir/${name}
+in
+    [ name	path ]
+"#;
+    let source = NamedSource::new("test", input.to_string());
+    let tokens = lex(&source).unwrap();
+
+    let text: String = tokens.iter().map(|token| token.text(input)).collect();
+    assert_eq!(text, input);
+
+    let comments: Vec<_> = tokens
+        .iter()
+        .filter_map(|token| match &token.kind {
+            TokenKind::Comment(comment) => Some(comment.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(comments, [" A comment", " Trailing"]);
+
+    let significant: Vec<_> = tokens.iter().filter(|token| !token.is_trivia()).collect();
+    assert_eq!(significant.first().unwrap().kind, TokenKind::Let);
+    // Whitespace between two tokens is a single trivia token
+    assert!(tokens.windows(2).all(|pair| {
+        !pair
+            .iter()
+            .all(|token| matches!(token.kind, TokenKind::Whitespace(_)))
+    }));
+}
+
+#[test]
+fn lazy_tokens() {
+    let lexer = Lexer::new(NamedSource::new(
+        "test",
+        "let a = 1 in \"unclosed".to_string(),
+    ));
+
+    // Tokens before an error can be consumed without scanning the rest
+    let kinds: Vec<_> = lexer
+        .tokens()
+        .take(2)
+        .map(|token| token.unwrap().kind)
+        .collect();
+    assert_eq!(kinds, [TokenKind::Let, TokenKind::Identifier("a".into())]);
+
+    let mut tokens = lexer.tokens().skip(5);
+    assert!(tokens.next().unwrap().is_err());
+    assert!(tokens.next().is_none());
+}
+
+#[test]
+fn interpolation() {
+    let tokenize = |input: &str| Lexer::new(NamedSource::new("test", input.to_string())).tokenize();
+
+    // Strings and paths scan interpolations the same way
+    for (input, first) in [
+        (r#""a${1 + { b = 2 }.b}c""#, "a"),
+        ("./a${1 + { b = 2 }.b}c", "./a"),
+    ] {
+        let parts = match tokenize(input).unwrap().first().unwrap().kind.clone() {
+            TokenKind::InterpolatedString(parts) | TokenKind::InterpolatedPath(parts) => parts,
+            _ => Vec::new(),
+        };
+
+        let texts: Vec<_> = parts.iter().map(|part| part.text(input)).collect();
+        assert_eq!(texts, [first, "1 + { b = 2 }.b", "c"]);
+    }
+
+    for input in [r#""a${b"#, "./a${b"] {
+        let error = tokenize(input).unwrap_err();
+        assert!(matches!(error.kind, ErrorKind::UnclosedInterpolation));
+        assert_eq!(error.span, (input.find('$').unwrap(), 3).into());
+    }
+}
+
+#[test]
+fn escaped_template_interpolation() {
+    let input = r"\${literal} ${value}";
+    let token = Lexer::new(NamedSource::new("test", input.to_string()))
+        .tokenize_template()
+        .unwrap();
+
+    let TokenKind::InterpolatedString(parts) = token.kind else {
+        unreachable!("a template with an interpolation is always interpolated");
+    };
+    let kinds: Vec<_> = parts.into_iter().map(|part| part.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            TokenKind::String("${literal} ".into()),
+            TokenKind::Identifier("value".into())
+        ]
+    );
+}
+
+#[test]
+fn spans() {
+    let input = "let\r\n    // ünïcode\r\n    a = \"wörld\r\n${\r\n  b + 1\r\n}!\"\r\nin ./ä/${a}";
+    let tokens = Lexer::new(NamedSource::new("test", input.to_string()))
+        .tokenize()
+        .unwrap();
+
+    let mut texts = Vec::new();
+    for token in &tokens {
+        texts.push(token.text(input));
+        if let TokenKind::InterpolatedString(parts) | TokenKind::InterpolatedPath(parts) =
+            &token.kind
+        {
+            texts.extend(parts.iter().map(|part| part.text(input)));
+        }
+    }
+    assert_eq!(
+        texts,
+        [
+            "let",
+            "a",
+            "=",
+            "\"wörld\r\n${\r\n  b + 1\r\n}!\"",
+            "wörld\r\n",
+            "b + 1",
+            "!",
+            "in",
+            "./ä/${a}",
+            "./ä/",
+            "a",
+        ]
+    );
+
+    // Errors inside an interpolation point into the whole source
+    let input = "\"ö ${ 1 ~ 2 }\"";
+    let error = Lexer::new(NamedSource::new("test", input.to_string()))
+        .tokenize()
+        .unwrap_err();
+    assert_eq!(error.source.inner(), input);
+    assert_eq!(error.span, (input.find('~').unwrap(), 1).into());
+}
+
+#[test]
+fn interpolation_depth() {
+    let nested =
+        |levels: usize| format!("{}1{}", r#""at ${"#.repeat(levels), r#"}""#.repeat(levels));
+
+    let tokenize_with_depth = |input: &str, max_depth| {
+        Lexer::new(NamedSource::new("test", input.to_string()))
+            .with_max_depth(max_depth)
+            .tokenize()
+    };
+
+    assert!(tokenize_with_depth(&nested(2), 2).is_ok());
+
+    let input = nested(3);
+    let error = tokenize_with_depth(&input, 2).unwrap_err();
+    assert!(matches!(
+        error.kind,
+        ErrorKind::InterpolationTooDeep { max: 2 }
+    ));
+    assert_eq!(error.span, (16, 2).into());
+
+    // Pathological nesting is rejected instead of overflowing the stack
+    let error = Lexer::new(NamedSource::new("test", nested(10_000)))
+        .tokenize()
+        .unwrap_err();
+    assert!(matches!(error.kind, ErrorKind::InterpolationTooDeep { .. }));
+}
@@ -0,0 +1,191 @@
+#![allow(clippy::unwrap_used, reason = "Panics automatically invalidate tests")]
+
+use crate::{
+    Options,
+    lint::{Level, Lint, LintConfig, LintKind, Rule, lint},
+    parser::parse_with_duplicate_keys,
+    span,
+};
+use miette::NamedSource;
+use pretty_assertions::assert_eq;
+
+fn lints(text: &str, config: &LintConfig) -> Vec<Lint> {
+    let source = NamedSource::new("test", text.to_string());
+    let (ast, duplicate_keys) = parse_with_duplicate_keys(&source, &Options::default()).unwrap();
+
+    lint(&source, &ast, &duplicate_keys, config)
+}
+
+fn lint_kinds(text: &str, config: &LintConfig) -> Vec<(LintKind, Level)> {
+    lints(text, config)
+        .into_iter()
+        .map(|lint| (lint.kind, lint.level))
+        .collect()
+}
+
+#[test]
+fn unused_and_shadowed_bindings() {
+    let input = r"
+let
+    a = 1
+    unused = 2
+    _ignored = 3
+    f = () { late }
+    late = 4
+in
+    let
+        a = 5
+    in
+        [ a f() ]
+";
+    let lints = lints(input, &LintConfig::default());
+
+    assert_eq!(
+        lints
+            .iter()
+            .map(|lint| lint.kind.clone())
+            .collect::<Vec<_>>(),
+        [
+            LintKind::UnusedBinding {
+                name: "unused".into()
+            },
+            LintKind::Shadowing {
+                name: "a".into(),
+                original: span(9, 1),
+            },
+        ]
+    );
+    assert_eq!(lints.first().unwrap().span, span(19, 6));
+}
+
+#[test]
+fn null_comparison_and_nested_interpolation() {
+    let input = r#"
+let
+    x = null
+in
+    [ x == null "a${ strLen("b${ strLen("c${x}") }") }" ]
+"#;
+
+    assert_eq!(
+        lint_kinds(input, &LintConfig::default()),
+        [
+            (LintKind::NullComparison, Level::Warn),
+            (LintKind::NestedInterpolation { max: 2 }, Level::Warn),
+        ]
+    );
+
+    let mut config = LintConfig::default();
+    config
+        .set(Rule::NullComparison, Level::Deny)
+        .set(Rule::NestedInterpolation, Level::Allow);
+    assert_eq!(
+        lint_kinds(input, &config),
+        [(LintKind::NullComparison, Level::Deny)]
+    );
+
+    assert_eq!("shadowing".parse(), Ok(Rule::Shadowing));
+    assert!("unknown".parse::<Rule>().is_err());
+}
+
+#[test]
+fn builtin_shadowing() {
+    let input = r"
+let
+    sort = (x) { strLen(x) }
+    builtins = 1
+in
+    [ sort(1) builtins [ 1 for strLen in [ 1 ] ] ]
+";
+
+    assert_eq!(
+        lint_kinds(input, &LintConfig::default()),
+        [
+            (
+                LintKind::BuiltinShadowing {
+                    name: "sort".into()
+                },
+                Level::Warn
+            ),
+            (
+                LintKind::BuiltinShadowing {
+                    name: "builtins".into()
+                },
+                Level::Warn
+            ),
+            (
+                LintKind::BuiltinShadowing {
+                    name: "strLen".into()
+                },
+                Level::Warn
+            ),
+        ]
+    );
+
+    let mut config = LintConfig::default();
+    config.set(Rule::BuiltinShadowing, Level::Deny);
+    assert!(lint_kinds("let div = 1 in div", &config).contains(&(
+        LintKind::BuiltinShadowing { name: "div".into() },
+        Level::Deny
+    )));
+}
+
+#[test]
+fn strict_config() {
+    let config = LintConfig::strict();
+    assert_eq!(config.level(Rule::Shadowing), Level::Deny);
+    assert_eq!(config.level(Rule::BuiltinShadowing), Level::Deny);
+    assert_eq!(config.level(Rule::DuplicateKey), Level::Deny);
+    assert_eq!(config.level(Rule::UnusedBinding), Level::Warn);
+    assert!(lint_kinds("let div = 1 in div", &config).contains(&(
+        LintKind::BuiltinShadowing { name: "div".into() },
+        Level::Deny
+    )));
+}
+
+#[test]
+fn duplicate_keys() {
+    let input = r"
+{
+    port = 80
+    server.host = localhost
+    server.port = 80
+    server.host = example
+    merged = { a = 1 }
+    merged.b = 2
+    port = 8080
+}
+";
+    let lints = lints(input, &LintConfig::default());
+
+    assert_eq!(
+        lints
+            .iter()
+            .map(|lint| (lint.kind.clone(), lint.span))
+            .collect::<Vec<_>>(),
+        [
+            (
+                LintKind::DuplicateKey {
+                    key: "server.host".into(),
+                    original: span(21, 11),
+                },
+                span(70, 11)
+            ),
+            (
+                LintKind::DuplicateKey {
+                    key: "port".into(),
+                    original: span(7, 4),
+                },
+                span(136, 4)
+            ),
+        ]
+    );
+
+    assert!(
+        lint_kinds(
+            "{ a.b = 1 a.c = 2 b = { x = 1 } b.y = 2 }",
+            &LintConfig::default()
+        )
+        .is_empty()
+    );
+}
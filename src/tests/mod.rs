@@ -1,2 +1,15 @@
+pub mod cache;
+pub mod catalog;
+pub mod convert;
+pub mod deps;
+pub mod diff;
+pub mod doc;
+pub mod explain;
+pub mod lexer;
+pub mod lint;
+pub mod nix;
 pub mod parser;
+pub mod rename;
+pub mod report;
 pub mod runtime;
+pub mod xref;
@@ -0,0 +1,184 @@
+#![allow(clippy::unwrap_used, reason = "Panics automatically invalidate tests")]
+
+use crate::{
+    eval_str,
+    nix::{NixError, from_nix, to_nix},
+    parser::{ast::DEFAULT_MAX_DEPTH, parse},
+};
+use miette::NamedSource;
+use pretty_assertions::assert_eq;
+
+fn source(text: &str) -> NamedSource<String> {
+    NamedSource::new("test", text.to_string())
+}
+
+fn tl_to_nix(text: &str) -> Result<String, NixError> {
+    let source = source(text);
+    to_nix(&source, &parse(&source).unwrap())
+}
+
+#[test]
+fn to_nix_expressions() {
+    let input = r#"let
+    add = (a, b) { a + b }
+    names = [ "a" "b" ]
+    { port } = { port = 80 }
+in
+    {
+        sum = add(1, 2 * 3)
+        "server.host" = "web ${names.first or "x"}"
+        all = names + extra
+        check = if(port >= 80 && enabled, 1, -1)
+        off = !enabled
+    }"#;
+    let expected = r#"let
+  add = a: b: a + b;
+  names = [ "a" "b" ];
+  inherit ({
+    port = 80;
+  }) port;
+in
+{
+  all = names + extra;
+  check = if port >= 80 && enabled then 1 else -1;
+  off = !enabled;
+  "server.host" = "web ${names.first or "x"}";
+  sum = add 1 (2 * 3);
+}"#;
+    assert_eq!(tl_to_nix(input).unwrap(), expected);
+}
+
+#[test]
+fn to_nix_unsupported() {
+    for (input, offset) in [
+        ("f(x = 1)", 2),
+        ("5 % 2", 0),
+        ("(a ? 1) { a }", 0),
+        ("let [a] = [1] in a", 10),
+    ] {
+        let err = tl_to_nix(input).unwrap_err();
+        assert!(
+            matches!(err, NixError::Unsupported { at, .. } if at.offset() == offset),
+            "{input}: {err:?}"
+        );
+    }
+}
+
+#[test]
+fn from_nix_expressions() {
+    let input = r#"
+        # A comment
+        let
+          inherit (builtins) map;
+          double = x: x * 2;
+          add = a: b: a + b;
+          text = ''
+            hello
+              ${toString (add 1 2)}
+          '';
+        in {
+          server.port = add 40 (double 1);
+          /* Merged */
+          names = names ++ extra // overrides;
+          flag = if !false then -1 else 2 - 1;
+          inherit text;
+          name = text.missing or "none";
+        }
+    "#;
+    let expected = r#"let
+    map = builtins.map
+    double = (x) { x * 2 }
+    add = (a, b) { a + b }
+    text = "hello\n  ${ toString(add(1, 2)) }\n"
+in
+    {
+        server.port = add(40, double(1))
+        names = names + extra + overrides
+        flag = if(!false, -1, 2 - 1)
+        text = text
+        name = text.missing or "none"
+    }"#;
+    assert_eq!(from_nix(&source(input)).unwrap(), expected);
+
+    let output = from_nix(&source(
+        "let add = a: b: a + b; a = [ 1 ]; in { sum = add 1 2 * 3; list = a ++ a; }",
+    ))
+    .unwrap();
+    assert_eq!(
+        eval_str(&output).unwrap().to_string(),
+        eval_str("{ sum = 9 list = [ 1 1 ] }").unwrap().to_string()
+    );
+}
+
+#[test]
+fn paths_and_division() {
+    let path = "let name = \"b\" in ./a/${name}.nix";
+    let nix = tl_to_nix(path).unwrap();
+    assert_eq!(nix, "let\n  name = \"b\";\nin\n./a/${name}.nix");
+    assert_eq!(
+        from_nix(&source(&nix)).unwrap(),
+        "let\n    name = \"b\"\nin\n    ./a/${name}.nix"
+    );
+    assert_eq!(
+        eval_str(&from_nix(&source(&nix)).unwrap())
+            .unwrap()
+            .to_string(),
+        eval_str(path).unwrap().to_string()
+    );
+
+    // tl divides as floats, Nix drops the remainder of integers
+    assert_eq!(tl_to_nix("a / 2").unwrap(), "a * 1.0 / 2");
+    assert_eq!(tl_to_nix("a / 2.5").unwrap(), "a / 2.5");
+    let nix = tl_to_nix("1 / 2").unwrap();
+    assert_eq!(
+        eval_str(&from_nix(&source(&nix)).unwrap())
+            .unwrap()
+            .to_string(),
+        "0.5"
+    );
+    assert_eq!(from_nix(&source("x * 1.5 / y")).unwrap(), "x * 1.5 / y");
+    let err = from_nix(&source("a / 2")).unwrap_err();
+    assert!(matches!(err, NixError::Unsupported { at, .. } if at.offset() == 0));
+}
+
+#[test]
+fn from_nix_errors() {
+    for (input, offset) in [
+        ("with pkgs; hello", 0),
+        ("{ a, b }: a", 0),
+        ("rec { a = 1; }", 0),
+        ("(a: a) 1", 1),
+        ("2 * (1 + 2)", 5),
+        ("{ ${x} = 1; }", 2),
+        ("[ 1 ] ++ [ 2 ]", 0),
+    ] {
+        let err = from_nix(&source(input)).unwrap_err();
+        assert!(
+            matches!(err, NixError::Unsupported { at, .. } if at.offset() == offset),
+            "{input}: {err:?}"
+        );
+    }
+
+    for (input, offset) in [("[ 1 2", 5), ("{ a = 1 }", 8), ("1 2 )", 4)] {
+        let err = from_nix(&source(input)).unwrap_err();
+        assert!(
+            matches!(err, NixError::Syntax { at, .. } if at.offset() == offset),
+            "{input}: {err:?}"
+        );
+    }
+
+    for input in [
+        format!("{}1{}", "[ ".repeat(1000), " ]".repeat(1000)),
+        format!("{}1{}", "(".repeat(1000), ")".repeat(1000)),
+        format!("{}true", "!".repeat(1000)),
+        format!("{}{{ }}", "{ } // ".repeat(1000)),
+        format!("{}1{}", "{ a = ".repeat(1000), "; }".repeat(1000)),
+    ] {
+        let err = from_nix(&source(&input)).unwrap_err();
+        assert!(
+            matches!(err, NixError::NestingTooDeep { max, .. } if max == DEFAULT_MAX_DEPTH),
+            "{err:?}"
+        );
+    }
+    assert!(from_nix(&source(&format!("{}1{}", "[ ".repeat(30), " ]".repeat(30)))).is_ok());
+}
@@ -1,9 +1,17 @@
 #![allow(clippy::unwrap_used, reason = "Panics automatically invalidate tests")]
 
 use crate::{
+    Options, line_column,
     parser::{
         self,
-        ast::types::{BinaryOperator, Expr, ExprKind, Literal},
+        ast::{
+            DEFAULT_MAX_DEPTH, Parser,
+            types::{
+                BinaryOperator, Contract, ErrorKind, Expr, ExprKind, Literal, NamedArg, Pattern,
+                Type,
+            },
+        },
+        lexer::Lexer,
     },
     span,
 };
@@ -166,12 +174,38 @@ fn field_access() {
         ExprKind::ObjectAccess {
             base: Expr::boxed(ExprKind::Identifier("package".into()), span(0, 7)),
             field: "dependencies".into(),
+            fallback: None,
         },
         span(0, 20),
     );
     assert_eq!(parse(input).unwrap(), expected);
 }
 
+#[test]
+fn field_access_fallback() {
+    let input = "a.b or c.d";
+    let expected = Expr::new(
+        ExprKind::ObjectAccess {
+            base: Expr::boxed_ident("a", span(0, 1)),
+            field: "b".into(),
+            fallback: Some(Box::new(Expr::new(
+                ExprKind::ObjectAccess {
+                    base: Expr::boxed_ident("c", span(7, 1)),
+                    field: "d".into(),
+                    fallback: None,
+                },
+                span(7, 3),
+            ))),
+        },
+        span(0, 10),
+    );
+    assert_eq!(parse(input).unwrap(), expected);
+
+    // `or` is a regular identifier everywhere else
+    let input = "or";
+    assert_eq!(parse(input).unwrap(), Expr::ident("or", span(0, 2)));
+}
+
 #[test]
 fn array() {
     let input = "[ 1 2 3 ]";
@@ -203,10 +237,13 @@ fn function_declaration() {
     let expected = Expr::new(
         ExprKind::FnDecl {
             args: vec![],
+            defaults: BTreeMap::new(),
+            rest: None,
             expr: Expr::boxed(
                 ExprKind::Call {
                     base: Expr::boxed_ident("println", span(5, 7)),
                     args: vec![literal!(String("Hello!".to_string()), span(13, 8))],
+                    named_args: Vec::new(),
                 },
                 span(5, 17),
             ),
@@ -220,6 +257,8 @@ fn function_declaration() {
     let expected = Expr::new(
         ExprKind::FnDecl {
             args: vec!["name".to_string()],
+            defaults: BTreeMap::new(),
+            rest: None,
             expr: box_literal!(
                 InterpolatedString(vec![
                     literal!(String("Hello, ".to_string()), span(10, 7)),
@@ -238,11 +277,13 @@ fn function_declaration() {
     let expected = Expr::new(
         ExprKind::FnDecl {
             args: vec!["name".to_string(), "age".to_string()],
+            defaults: BTreeMap::new(),
+            rest: None,
             expr: box_literal!(
                 InterpolatedString(vec![
                     literal!(String("Hello, "), span(15, 7)),
                     Expr::ident("name", span(24, 4)),
-                    literal!(String("! You are "), span(30, 10)),
+                    literal!(String("! You are "), span(29, 10)),
                     Expr::ident("age", span(41, 3)),
                     literal!(String(" years old."), span(45, 11)),
                 ]),
@@ -267,10 +308,12 @@ in
     let expected = Expr::new(
         ExprKind::LetIn {
             bindings: vec![(
-                "pow".to_string(),
+                Pattern::Identifier("pow".to_string()),
                 Expr::new(
                     ExprKind::FnDecl {
                         args: vec!["base".to_string(), "exponent".to_string()],
+                        defaults: BTreeMap::new(),
+                        rest: None,
                         expr: Expr::boxed(
                             ExprKind::Call {
                                 base: Expr::boxed_ident("if", span(41, 2)),
@@ -308,6 +351,7 @@ in
                                                             span(116, 12),
                                                         ),
                                                     ],
+                                                    named_args: Vec::new(),
                                                 },
                                                 span(106, 23),
                                             ),
@@ -315,6 +359,7 @@ in
                                         span(99, 30),
                                     ),
                                 ],
+                                named_args: Vec::new(),
                             },
                             span(41, 98),
                         ),
@@ -329,6 +374,7 @@ in
                         literal!(Int(2), span(157, 1)),
                         literal!(Int(10), span(160, 2)),
                     ],
+                    named_args: Vec::new(),
                 },
                 span(153, 10),
             ),
@@ -336,6 +382,35 @@ in
         span(0, 163),
     );
     assert_eq!(parse(input).unwrap(), expected);
+
+    // Rest parameter
+    let input = "(first, ...rest) { rest }";
+    let expected = Expr::new(
+        ExprKind::FnDecl {
+            args: vec!["first".to_string()],
+            defaults: BTreeMap::new(),
+            rest: Some("rest".to_string()),
+            expr: Expr::boxed_ident("rest", span(19, 4)),
+        },
+        span(0, 25),
+    );
+    assert_eq!(parse(input).unwrap(), expected);
+
+    // Default values
+    let input = r#"(name, greeting ? "Hello") { greeting }"#;
+    let expected = Expr::new(
+        ExprKind::FnDecl {
+            args: vec!["name".to_string(), "greeting".to_string()],
+            defaults: BTreeMap::from([(
+                "greeting".to_string(),
+                literal!(String("Hello"), span(18, 7)),
+            )]),
+            rest: None,
+            expr: Expr::boxed_ident("greeting", span(29, 8)),
+        },
+        span(0, 39),
+    );
+    assert_eq!(parse(input).unwrap(), expected);
 }
 
 #[test]
@@ -346,6 +421,7 @@ fn call() {
         ExprKind::Call {
             base: Expr::boxed_ident("exit", span(0, 4)),
             args: vec![],
+            named_args: Vec::new(),
         },
         span(0, 6),
     );
@@ -357,6 +433,7 @@ fn call() {
         ExprKind::Call {
             base: Expr::boxed_ident("println", span(0, 7)),
             args: vec![literal!(String("Hello, world!"), span(8, 15))],
+            named_args: Vec::new(),
         },
         span(0, 24),
     );
@@ -383,10 +460,27 @@ fn call() {
                 literal!(String("Password is correct"), span(44, 21)),
                 literal!(String("Password is incorrect"), span(70, 23)),
             ],
+            named_args: Vec::new(),
         },
         span(0, 95),
     );
     assert_eq!(parse(input).unwrap(), expected);
+
+    // Named arguments
+    let input = r#"connect("db", port = 5432)"#;
+    let expected = Expr::new(
+        ExprKind::Call {
+            base: Expr::boxed_ident("connect", span(0, 7)),
+            args: vec![literal!(String("db"), span(8, 4))],
+            named_args: vec![NamedArg {
+                name: "port".to_string(),
+                span: span(14, 4),
+                value: literal!(Int(5432), span(21, 4)),
+            }],
+        },
+        span(0, 26),
+    );
+    assert_eq!(parse(input).unwrap(), expected);
 }
 
 #[test]
@@ -410,6 +504,121 @@ fn binary_op() {
     assert_eq!(parse(input).unwrap(), expected);
 }
 
+#[test]
+fn operator_precedence() {
+    let input = "2 * 3 ** 2";
+    let expected = Expr::new(
+        ExprKind::BinaryOp {
+            left: box_literal!(Int(2), span(0, 1)),
+            operator: BinaryOperator::Multiply,
+            right: Expr::boxed(
+                ExprKind::BinaryOp {
+                    left: box_literal!(Int(3), span(4, 1)),
+                    operator: BinaryOperator::Power,
+                    right: box_literal!(Int(2), span(9, 1)),
+                },
+                span(4, 6),
+            ),
+        },
+        span(0, 10),
+    );
+    assert_eq!(parse(input).unwrap(), expected);
+
+    let input = "-2 ** 2";
+    let expected = Expr::new(
+        ExprKind::BinaryOp {
+            left: box_literal!(Int(-1), span(0, 1)),
+            operator: BinaryOperator::Multiply,
+            right: Expr::boxed(
+                ExprKind::BinaryOp {
+                    left: box_literal!(Int(2), span(1, 1)),
+                    operator: BinaryOperator::Power,
+                    right: box_literal!(Int(2), span(6, 1)),
+                },
+                span(0, 7),
+            ),
+        },
+        span(0, 7),
+    );
+    assert_eq!(parse(input).unwrap(), expected);
+
+    let input = "1 - 2 - 3";
+    let expected = Expr::new(
+        ExprKind::BinaryOp {
+            left: Expr::boxed(
+                ExprKind::BinaryOp {
+                    left: box_literal!(Int(1), span(0, 1)),
+                    operator: BinaryOperator::Minus,
+                    right: box_literal!(Int(2), span(4, 1)),
+                },
+                span(0, 5),
+            ),
+            operator: BinaryOperator::Minus,
+            right: box_literal!(Int(3), span(8, 1)),
+        },
+        span(0, 9),
+    );
+    assert_eq!(parse(input).unwrap(), expected);
+}
+
+#[test]
+fn range() {
+    let input = "1..=n + 1";
+    let expected = Expr::new(
+        ExprKind::BinaryOp {
+            left: box_literal!(Int(1), span(0, 1)),
+            operator: BinaryOperator::RangeInclusive,
+            right: Expr::boxed(
+                ExprKind::BinaryOp {
+                    left: Expr::boxed(ExprKind::Identifier("n".to_string()), span(4, 1)),
+                    operator: BinaryOperator::Plus,
+                    right: box_literal!(Int(1), span(8, 1)),
+                },
+                span(4, 5),
+            ),
+        },
+        span(0, 9),
+    );
+    assert_eq!(parse(input).unwrap(), expected);
+
+    // `..` followed by a slash is still a path
+    let input = "../config";
+    let expected = Expr::new(
+        ExprKind::Literal(Literal::Path(PathBuf::from("../config"))),
+        span(0, 9),
+    );
+    assert_eq!(parse(input).unwrap(), expected);
+}
+
+#[test]
+fn comprehension() {
+    let input = "[ x * 2 for x in xs if x > 0 ]";
+    let expected = Expr::new(
+        ExprKind::Comprehension {
+            expr: Expr::boxed(
+                ExprKind::BinaryOp {
+                    left: Expr::boxed(ExprKind::Identifier("x".to_string()), span(2, 1)),
+                    operator: BinaryOperator::Multiply,
+                    right: box_literal!(Int(2), span(6, 1)),
+                },
+                span(2, 5),
+            ),
+            binding: "x".to_string(),
+            iterable: Expr::boxed(ExprKind::Identifier("xs".to_string()), span(17, 2)),
+            condition: Some(Expr::boxed(
+                ExprKind::BinaryOp {
+                    left: Expr::boxed(ExprKind::Identifier("x".to_string()), span(23, 1)),
+                    operator: BinaryOperator::Gt,
+                    right: box_literal!(Int(0), span(27, 1)),
+                },
+                span(23, 5),
+            )),
+        },
+        span(0, 30),
+    );
+    assert_eq!(parse(input).unwrap(), expected);
+}
+
 #[test]
 fn bindings() {
     let input = r#"let
@@ -420,7 +629,7 @@ in
     let expected = Expr::new(
         ExprKind::LetIn {
             bindings: vec![(
-                "name".to_string(),
+                Pattern::Identifier("name".to_string()),
                 literal!(String("John Doe".to_string()), span(15, 10)),
             )],
             expr: box_literal!(Null, span(33, 4)),
@@ -429,3 +638,180 @@ in
     );
     assert_eq!(parse(input).unwrap(), expected);
 }
+
+#[test]
+fn destructuring() {
+    let input = "let { host, port } = server [ first ...rest ] = xs in null";
+    let expected = Expr::new(
+        ExprKind::LetIn {
+            bindings: vec![
+                (
+                    Pattern::Object(vec!["host".to_string(), "port".to_string()]),
+                    Expr::new(ExprKind::Identifier("server".to_string()), span(21, 6)),
+                ),
+                (
+                    Pattern::Array {
+                        items: vec!["first".to_string()],
+                        rest: Some("rest".to_string()),
+                    },
+                    Expr::new(ExprKind::Identifier("xs".to_string()), span(48, 2)),
+                ),
+            ],
+            expr: box_literal!(Null, span(54, 4)),
+        },
+        span(0, 58),
+    );
+    assert_eq!(parse(input).unwrap(), expected);
+
+    for (input, duplicate) in [
+        ("let [ a, a ] = [ 1 2 ] in a", span(9, 1)),
+        ("let { a b a } = x in a", span(10, 1)),
+        ("let [ a, ...a ] = [ 1 2 ] in a", span(12, 1)),
+    ] {
+        let err = parser::parse(&NamedSource::new("test", input.into())).unwrap_err();
+        assert!(
+            matches!(&err.kind, ErrorKind::DuplicateBinding { name, first } if name == "a" && *first == span(6, 1)),
+            "{input}"
+        );
+        assert_eq!(err.span, duplicate);
+    }
+}
+
+#[test]
+fn columns() {
+    let text = "a\r\n\tb\n日本 c\ne\u{301}d 🦀x";
+
+    let column = |needle: &str| line_column(text, text.find(needle).unwrap());
+    assert_eq!(column("a"), (1, 1));
+    assert_eq!(column("b"), (2, 5));
+    assert_eq!(column("c"), (3, 6));
+    assert_eq!(column("d"), (4, 2));
+    assert_eq!(column("x"), (4, 6));
+    assert_eq!(line_column(text, text.len() + 10), (4, 7));
+}
+
+#[test]
+fn nesting_depth() {
+    const OPENERS: [(&str, &str); 6] = [
+        ("[ ", " ]"),
+        ("{ a = ", " }"),
+        ("!", ""),
+        ("f(", ")"),
+        ("let a = 1 in ", ""),
+        ("\"a${ f(", ") }\""),
+    ];
+
+    let is_too_deep = |text: &str, max_depth: usize| {
+        let source = NamedSource::new("test", text.to_string());
+        let tokens = Lexer::new(source.clone()).tokenize().unwrap();
+
+        match Parser::new(tokens, source)
+            .with_max_depth(max_depth)
+            .parse()
+        {
+            Ok(_) => false,
+            Err(err) => matches!(err.kind, ErrorKind::NestingTooDeep { max } if max == max_depth),
+        }
+    };
+
+    // Random nestings from a fixed seed, so failures are reproducible
+    let mut seed = 0x2545_f491_u32;
+    for _ in 0..50 {
+        let mut open = String::new();
+        let mut close = Vec::new();
+
+        for _ in 0..DEFAULT_MAX_DEPTH.saturating_mul(4) {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+
+            // Interpolations are nested by the lexer as well, keep them shallow
+            let limit = if close.len() < 8 {
+                OPENERS.len()
+            } else {
+                OPENERS.len() - 1
+            };
+            let (opener, closer) = OPENERS.get(seed as usize % limit).unwrap();
+            open.push_str(opener);
+            close.push(*closer);
+        }
+
+        let text = format!("{open}1{}", close.into_iter().rev().collect::<String>());
+        assert!(is_too_deep(&text, DEFAULT_MAX_DEPTH), "{text}");
+    }
+
+    let nested = |depth: usize| format!("{}1{}", "[ ".repeat(depth), " ]".repeat(depth));
+    assert!(parse(nested(DEFAULT_MAX_DEPTH.saturating_sub(1))).is_ok());
+    assert!(is_too_deep(&nested(DEFAULT_MAX_DEPTH), DEFAULT_MAX_DEPTH));
+    assert!(is_too_deep(&nested(10_000), DEFAULT_MAX_DEPTH));
+    assert!(is_too_deep(&"2 ** ".repeat(10_000), DEFAULT_MAX_DEPTH));
+    assert!(is_too_deep(&"!".repeat(10_000), DEFAULT_MAX_DEPTH));
+    // Chains are built in a loop, but evaluating them recurses once per link
+    assert!(is_too_deep(
+        &format!("{{ {}b = 1 }}", "a.".repeat(20_000)),
+        DEFAULT_MAX_DEPTH
+    ));
+    assert!(is_too_deep(
+        &format!("x{}", ".a".repeat(50_000)),
+        DEFAULT_MAX_DEPTH
+    ));
+    assert!(is_too_deep(
+        &format!("f{}", "()".repeat(50_000)),
+        DEFAULT_MAX_DEPTH
+    ));
+    assert!(is_too_deep(
+        &format!("{}1", "1 + ".repeat(200_000)),
+        DEFAULT_MAX_DEPTH
+    ));
+    assert!(!is_too_deep("{ a.b.c = x.a.b.c(1)[0] + 1 + 2 }", 8));
+    assert!(!is_too_deep(&nested(20), 21));
+    assert!(is_too_deep(&nested(20), 20));
+
+    let options = Options {
+        max_depth: 3,
+        ..Default::default()
+    };
+    let source = NamedSource::new("test", nested(3));
+    assert!(matches!(
+        parser::parse_with_options(&source, &options)
+            .unwrap_err()
+            .kind,
+        ErrorKind::NestingTooDeep { max: 3 }
+    ));
+}
+
+#[test]
+fn type_annotation() {
+    let ExprKind::LetIn { bindings, .. } = parse("let hosts :: [string]? = null in hosts")
+        .unwrap()
+        .kind
+    else {
+        unreachable!("the input is a let expression");
+    };
+    assert_eq!(
+        bindings.first().unwrap().1.contract,
+        Some(Box::new(Contract {
+            ty: Type::Optional(Box::new(Type::Array(Box::new(Type::String)))),
+            span: span(13, 9),
+        }))
+    );
+
+    let ExprKind::Literal(Literal::Object(fields)) = parse("{ port :: int = 80 }").unwrap().kind
+    else {
+        unreachable!("the input is an object");
+    };
+    assert_eq!(
+        fields.get("port").unwrap().contract.as_ref().unwrap().ty,
+        Type::Int
+    );
+
+    let err = parser::parse(&NamedSource::new(
+        "test",
+        "let x :: integer = 1 in x".into(),
+    ))
+    .unwrap_err();
+    assert!(matches!(
+        err.kind,
+        ErrorKind::UnknownType { name } if name == "integer"
+    ));
+}
@@ -0,0 +1,146 @@
+#![allow(clippy::unwrap_used, reason = "Panics automatically invalidate tests")]
+
+use crate::{
+    parser::parse,
+    rename::{RenameError, rename, rename_at},
+    span,
+};
+use miette::NamedSource;
+use pretty_assertions::assert_eq;
+
+fn rename_in(input: &str, old: &str, new: &str) -> Result<String, RenameError> {
+    let source = NamedSource::new("test.tl", input.to_string());
+    rename(&source, &parse(&source).unwrap(), old, new)
+}
+
+#[test]
+fn renames_references() {
+    let input = "let
+    // The server
+    server   =   { port = 80 }
+    fib = (n) { if(n < 2, n, fib(n - 1) + fib(n - 2)) }
+in
+    [ fib(server.port) \"at ${server.port}\" server ]";
+
+    assert_eq!(
+        rename_in(input, "server", "backend").unwrap(),
+        "let
+    // The server
+    backend   =   { port = 80 }
+    fib = (n) { if(n < 2, n, fib(n - 1) + fib(n - 2)) }
+in
+    [ fib(backend.port) \"at ${backend.port}\" backend ]"
+    );
+    assert_eq!(
+        rename_in(input, "fib", "fibonacci").unwrap(),
+        input.replace("fib", "fibonacci")
+    );
+
+    // Only the binding at the offset is renamed
+    let input = "let x = 1 in let x = x + 1 in x";
+    let source = NamedSource::new("test.tl", input.to_string());
+    let ast = parse(&source).unwrap();
+    assert_eq!(
+        rename_at(&source, &ast, 30, "y").unwrap(),
+        "let x = 1 in let y = x + 1 in y"
+    );
+    assert_eq!(
+        rename_at(&source, &ast, 4, "y").unwrap(),
+        "let y = 1 in let x = y + 1 in x"
+    );
+}
+
+#[test]
+fn refused_renames() {
+    let input = "let
+    a = 1
+    { host } = { host = \"b\" }
+in
+    [ a host port strLen(\"\") ]";
+
+    let cases = [
+        ("a", "1a", RenameError::InvalidName { name: "1a".into() }),
+        ("a", "let", RenameError::InvalidName { name: "let".into() }),
+        (
+            "missing",
+            "b",
+            RenameError::NotFound {
+                name: "missing".into(),
+            },
+        ),
+        (
+            "a",
+            "strLen",
+            RenameError::Builtin {
+                name: "strLen".into(),
+            },
+        ),
+        (
+            "a",
+            "port",
+            RenameError::Collision {
+                name: "port".into(),
+                existing: span(60, 4),
+            },
+        ),
+        (
+            "host",
+            "server",
+            RenameError::ObjectPattern {
+                name: "host".into(),
+                pattern: span(20, 4),
+            },
+        ),
+    ];
+
+    for (old, new, expected) in cases {
+        assert_eq!(rename_in(input, old, new), Err(expected), "{old} -> {new}");
+    }
+
+    assert_eq!(
+        rename_in("let x = 1 in let x = 2 in x", "x", "y"),
+        Err(RenameError::Ambiguous {
+            name: "x".into(),
+            count: 2
+        })
+    );
+}
+
+#[test]
+fn renames_named_arguments() {
+    let input = "let
+    connect = (host, port ? 80) { \"${host}:${port}\" }
+    other = (port) { port }
+in
+    [ connect(\"a\", port = 8080) connect(port = 1, host = \"b\") other(port = 2) ]";
+
+    assert_eq!(
+        rename_in(input, "connect", "open").unwrap(),
+        input.replace("connect", "open")
+    );
+    let source = NamedSource::new("test.tl", input.to_string());
+    let ast = parse(&source).unwrap();
+    let offset = input.find("port ?").unwrap();
+    assert_eq!(
+        rename_at(&source, &ast, offset, "number").unwrap(),
+        "let
+    connect = (host, number ? 80) { \"${host}:${number}\" }
+    other = (port) { port }
+in
+    [ connect(\"a\", number = 8080) connect(number = 1, host = \"b\") other(port = 2) ]"
+    );
+
+    // A named argument that might reach the function through another name refuses the rename
+    let input = "let
+    f = (x) { x }
+    g = f
+in
+    [ f(x = 1) g(x = 2) ]";
+    assert_eq!(
+        rename_in(input, "x", "y"),
+        Err(RenameError::NamedArgument {
+            name: "x".into(),
+            argument: span(input.find("x = 2").unwrap(), 1),
+        })
+    );
+}
@@ -0,0 +1,36 @@
+#![allow(clippy::unwrap_used, reason = "Panics automatically invalidate tests")]
+
+use crate::{
+    parser::parse,
+    report::{Color, ReportStyle},
+};
+use miette::NamedSource;
+
+#[test]
+fn plain_report() {
+    let source = NamedSource::new("input", "let a = in a".to_string());
+    let err = parse(&source).unwrap_err();
+
+    let style = ReportStyle {
+        docs_url: Some("https://example.org/errors/".to_string()),
+        ..ReportStyle::plain()
+    };
+    let report = style.handler().render(&err);
+
+    assert!(report.is_ascii(), "{report}");
+    assert!(!report.contains('\u{1b}'), "{report}");
+    assert!(report.contains("let a = in a"), "{report}");
+    assert!(
+        report.contains("docs: https://example.org/errors/tl::parser::"),
+        "{report}"
+    );
+
+    let report = ReportStyle {
+        color: Color::Ansi,
+        ..ReportStyle::default()
+    }
+    .handler()
+    .render(&err);
+    assert!(report.contains('\u{1b}') && !report.is_ascii(), "{report}");
+    assert!(!report.contains("docs:"), "{report}");
+}
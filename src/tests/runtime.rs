@@ -1,16 +1,26 @@
 #![allow(clippy::unwrap_used, reason = "Panics automatically invalidate tests")]
 
 use crate::{
-    parser::parse,
+    Options, ScriptResult, eval_file, eval_files_merged, eval_str, eval_with_vars,
+    parser::{parse, parse_with_options},
     runtime::{
-        Scope, ValueKind,
-        types::{Error as RuntimeError, ErrorKind as RuntimeErrorKind, Value},
+        DEFAULT_MAX_CALL_DEPTH, Scope, ValueKind,
+        inspect::{Breakpoint, Coverage, Debugger, Profiler, Resume, Tracer},
+        types::{
+            Error as RuntimeError, ErrorKind as RuntimeErrorKind, MAX_ARRAY_LENGTH, MAX_PRECISION,
+            MAX_STRING_LENGTH, NativeFnCtx, Value,
+        },
     },
     span,
+    template::render,
 };
-use miette::NamedSource;
+use miette::{Diagnostic, NamedSource};
 use pretty_assertions::assert_eq;
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
 
 fn run(text: impl Into<String>) -> miette::Result<Value> {
     let source = NamedSource::new("test", text.into());
@@ -27,6 +37,16 @@ fn run_err(text: impl Into<String>) -> RuntimeError {
     Scope::new(HashMap::new(), source, ast).eval().unwrap_err()
 }
 
+/// Evaluate something with custom runtime options.
+fn run_with_options(text: impl Into<String>, options: Options) -> Result<Value, RuntimeError> {
+    let source = NamedSource::new("test", text.into());
+    let ast = parse(&source).unwrap();
+
+    let mut scope = Scope::new(HashMap::new(), source, ast);
+    scope.set_options(options);
+    scope.eval()
+}
+
 #[test]
 fn boolean() {
     let input = "true";
@@ -81,6 +101,19 @@ in
     assert_eq!(run(input).unwrap(), expected);
 }
 
+#[test]
+fn escaped_interpolation() {
+    let input = r#"let
+    name = "John"
+in
+    [ "\${name} is ${ let person = { name = name } in person.name }" ./\${name}/${name} ]
+"#;
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[ ${name} is John ./${name}/John ]"
+    );
+}
+
 #[test]
 fn array() {
     let input = "[ 1 2 3 ]";
@@ -203,3 +236,2589 @@ in
     let expected = Value::new(ValueKind::Int(1024), span(99, 99));
     assert_eq!(run(input).unwrap(), expected);
 }
+
+#[test]
+fn deep_recursion() {
+    // `<` instead of `==`, since values also compare their spans in tests
+    let value = run("let sum = (n) { if(n < 1, 0, n + sum(n - 1)) } in sum(500)").unwrap();
+    assert!(matches!(value.kind, ValueKind::Int(125_250)));
+
+    // Every operator of a chain counts toward the nesting depth
+    assert!(run(vec!["1"; 2000].join(" + ")).is_err());
+    let options = Options {
+        max_depth: 2048,
+        ..Default::default()
+    };
+    let source = NamedSource::new("test", vec!["1"; 2000].join(" + "));
+    let ast = parse_with_options(&source, &options).unwrap();
+    let value = Scope::new(HashMap::new(), source, ast).eval().unwrap();
+    assert!(matches!(value.kind, ValueKind::Int(2000)));
+}
+
+#[test]
+fn call_too_deep() {
+    let err = run_err("let loop = (n) { loop(n) } in loop(1)");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::CallTooDeep { limit, call } if limit == DEFAULT_MAX_CALL_DEPTH && call == span(17, 7)
+    ));
+
+    let options = Options {
+        max_call_depth: 3,
+        ..Default::default()
+    };
+    let input = "let count = (n) { if(n < 1, 0, 1 + count(n - 1)) } in count";
+    let value = run_with_options(format!("{input}(2)"), options.clone()).unwrap();
+    assert!(matches!(value.kind, ValueKind::Int(2)));
+    let err = run_with_options(format!("{input}(3)"), options).unwrap_err();
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::CallTooDeep { limit: 3, .. }
+    ));
+}
+
+#[cfg(feature = "fs-write")]
+#[test]
+fn write_file() {
+    let dir = std::env::temp_dir().join("tl-write-file");
+    let input = format!(
+        r#"let
+    dir = mkdirAll({dir})
+in
+    writeFile({dir}/out.txt, "generated")"#,
+        dir = dir.display()
+    );
+
+    let options = Options {
+        write_allow_list: vec![dir.clone()],
+        ..Default::default()
+    };
+    run_with_options(&input, options).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(dir.join("out.txt")).unwrap(),
+        "generated"
+    );
+
+    // Writing outside of the allow-list
+    let input = format!(
+        r#"writeFile({}/../escaped.txt, "generated")"#,
+        dir.display()
+    );
+    let options = Options {
+        write_allow_list: vec![dir.clone()],
+        ..Default::default()
+    };
+    let err = run_with_options(&input, options).unwrap_err();
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::WriteNotAllowed { path: span(0, 0) }
+    );
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn render_template() {
+    let path = std::env::temp_dir().join("tl-render-template.conf");
+    std::fs::write(&path, "host = \"${host}\"\nport = ${port + 1}\n").unwrap();
+
+    let input = format!(
+        r#"renderTemplate({}, {{ host = "localhost" port = 8079 }})"#,
+        path.display()
+    );
+    let expected = Value::new(
+        ValueKind::String("host = \"localhost\"\nport = 8080\n".into()),
+        span(0, input.len()),
+    );
+    assert_eq!(run(input).unwrap(), expected);
+}
+
+#[test]
+fn render_host_template() {
+    let source = NamedSource::new("input", "null".to_string());
+    let mut scope = Scope::new(HashMap::new(), source.clone(), parse(&source).unwrap());
+    scope.define("user", eval_str("{ name = \"ada\" unread = 2 }").unwrap());
+
+    assert_eq!(
+        render(
+            "Hi ${user.name}, you have ${user.unread + 1} messages \\${kept}",
+            &mut scope
+        )
+        .unwrap(),
+        "Hi ada, you have 3 messages ${kept}"
+    );
+    assert_eq!(
+        render("No placeholders", &mut scope).unwrap(),
+        "No placeholders"
+    );
+    assert!(render("${missing}", &mut scope).is_err());
+    assert!(render("${user.}", &mut scope).is_err());
+}
+
+#[test]
+fn native_context_without_call() {
+    let source = NamedSource::new("input", "1".to_string());
+    let ctx = NativeFnCtx {
+        expr: parse(&source).unwrap(),
+        variables: HashMap::new(),
+        source,
+        options: Options::default(),
+        origin: None,
+    };
+
+    let err = ctx.get_arg_evaluated(0, 1).unwrap_err();
+    assert!(matches!(err.kind, RuntimeErrorKind::Internal { .. }));
+    assert!(ctx.expr_args().is_err());
+    assert_eq!(ctx.call_args_span(), span(0, 1));
+
+    // Builtins report the context instead of panicking
+    for builtin in Scope::builtins() {
+        let _ = (builtin.func)(ctx.clone());
+    }
+    let str_len = Scope::builtins()
+        .into_iter()
+        .find(|builtin| builtin.name == "strLen")
+        .unwrap();
+    let err = (str_len.func)(ctx).unwrap_err();
+    assert!(matches!(err.kind, RuntimeErrorKind::Internal { .. }));
+}
+
+#[test]
+fn deep_equality() {
+    let input = r#"let
+    f = (x) { x + 1 }
+    g = (x) { x + 1 }
+    h = (y) { y + 1 }
+in
+    [
+        deepEq(f, g) deepEq(f, h) deepEq(div, builtins.div) deepEq(div, strLen)
+        deepEq(true, 1) deepEq(1, 1.0) deepEq(./a, ./a)
+        deepEq({ a = [ 1 "b" ] }, { a = [ 1 "b" ] }) deepEq({ a = 1 }, { a = 1 b = 2 })
+    ]
+"#;
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[ true false true false false true true true false ]"
+    );
+}
+
+#[test]
+fn equality() {
+    let input = r#"let
+    f = (x) { x + 1 }
+    fraction = 1 == 1.5
+    whole = 1 == 1.0
+    bool_lhs = true == 1
+    bool_rhs = 1 == true
+    falsy = 0 == true
+    ints = [ 1 true ]
+    floats = [ 1.0 1 ]
+    numbers = { a = 1 }
+    bools = { a = true }
+    arrays = ints == floats
+    objects = numbers == bools
+    same = f == f
+    builtin = f == strLen
+    not_eq = 1 != 1.5
+in
+    [ fraction whole bool_lhs bool_rhs falsy arrays objects same builtin not_eq ]
+"#;
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[ false true true true false true true true false true ]"
+    );
+
+    // `PartialEq` agrees with the order used for sorting
+    let value = |kind| Value::new(kind, span(0, 1));
+    assert_ne!(value(ValueKind::Int(1)), value(ValueKind::Float(1.5)));
+    assert_ne!(value(ValueKind::Boolean(true)), value(ValueKind::Int(1)));
+    assert_ne!(value(ValueKind::Int(1)), value(ValueKind::Boolean(true)));
+    assert_eq!(value(ValueKind::Int(1)), value(ValueKind::Float(1.0)));
+}
+
+#[test]
+fn set_operations() {
+    let input = r#"let
+    tags = [ "web" "db" "web" 1 1.0 { a = 1 } { a = 1 } ]
+    a = [ "git" "vim" "curl" "vim" ]
+    b = [ "curl" "htop" "git" ]
+in
+    [ toSet(tags) union(a, b) intersection(a, b) difference(a, b) symmetricDifference(a, b) ]
+"#;
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[ [ web db 1 { a = 1 } ] [ git vim curl htop ] [ git curl ] [ vim ] [ vim htop ] ]"
+    );
+
+    let err = run_err("union([ 1 ], 2)");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::MismatchedTypes { ref got, .. } if got == "number"
+    ));
+}
+
+#[test]
+fn sort() {
+    let input = r#"sort([ "b" 2 null [ 1 ] true 1.5 "a" ./p [ 0 5 ] -1 ])"#;
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[ null true -1 1.5 2 a b ./p [ 0 5 ] [ 1 ] ]"
+    );
+
+    // Integers above 2^53 have no exact float, they are still compared with floats exactly
+    let input = "sort([ 9007199254740993 9007199254740992.0 -0.0 9007199254740992 0 0.5 9007199254740994 ])";
+    let sorted = run(input).unwrap();
+    let ValueKind::Array(items) = &sorted.kind else {
+        unreachable!("sort returns an array");
+    };
+    assert!(items.is_sorted());
+    assert!(matches!(
+        items.as_slice(),
+        [
+            Value { kind: ValueKind::Float(zero), .. },
+            Value { kind: ValueKind::Int(0), .. },
+            Value { kind: ValueKind::Float(half), .. },
+            Value { kind: ValueKind::Float(_), .. },
+            Value { kind: ValueKind::Int(9_007_199_254_740_992), .. },
+            Value { kind: ValueKind::Int(9_007_199_254_740_993), .. },
+            Value { kind: ValueKind::Int(9_007_199_254_740_994), .. },
+        ] if zero.is_sign_negative() && *half == 0.5
+    ));
+
+    // Comparison operators still only order numbers and strings
+    let input = r#"let
+    lt = 1 < "a"
+    gt = 1 > "a"
+    le = 1 <= "a"
+in
+    [ lt gt le ]
+"#;
+    assert_eq!(run(input).unwrap().to_string(), "[ false false true ]");
+}
+
+#[test]
+fn float_formatting() {
+    let input = "[ round(0.1 + 0.2, 2) round(2.675, 2) round(2.5, 0) round(3, 2) toFixed(2, 0.1 + 0.2) toFixed(3, 7) toFixed(0, 2) ]";
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[ 0.3 2.67 2 3 0.30 7.000 2 ]"
+    );
+
+    let err = run_err("round(1.5, -1)");
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::NegativePrecision { digits: span(0, 0) }
+    );
+    assert_eq!(err.span, span(11, 2));
+
+    for input in [
+        "round(1.5, 70000)",
+        "toFixed(70000, 1.5)",
+        "toFixed(9223372036854775807, 1)",
+    ] {
+        let err = run_err(input);
+        assert!(matches!(
+            err.kind,
+            RuntimeErrorKind::PrecisionTooLarge { limit, .. } if limit == MAX_PRECISION
+        ));
+    }
+    assert_eq!(run("round(1.5, 1074)").unwrap().to_string(), "1.5");
+
+    let value = run("{ a = [ 0.1 + 0.2 1 ] b = 1 / 3 }").unwrap();
+    assert_eq!(
+        value.round_floats(3).to_string(),
+        "{ a = [ 0.3 1 ]; b = 0.333 }"
+    );
+}
+
+#[test]
+fn or_default() {
+    let input = r#"let
+    config = { port = 0 name = null }
+    sum = config.missing or 1 + 1
+in
+    [
+        config.port or 80 config.name or "web" config.missing.deep or [ 1 ] sum
+        orDefault(config.port, 80) orDefault(null, 1) maybe(config.port, 80)
+    ]
+"#;
+    assert_eq!(run(input).unwrap().to_string(), "[ 0 web [ 1 ] 2 0 1 80 ]");
+}
+
+#[test]
+fn empty_and_coalesce() {
+    let input = r#"[
+    isEmpty(null) isEmpty("") isEmpty([ ]) isEmpty({ }) isEmpty(" ") isEmpty([ null ])
+    default("", "web") default([ ], [ 1 ]) default(0, 80) default(false, true) default("api", missing)
+    coalesce(null, null, 2, missing) coalesce(null) coalesce()
+]"#;
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[ true true true true false false web [ 1 ] 0 false api 2 null null ]"
+    );
+
+    let err = run_err("isEmpty(0)");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::MismatchedTypes { ref got, .. } if got == "number"
+    ));
+    assert_eq!(err.span, span(8, 1));
+}
+
+#[test]
+fn seeded_random() {
+    let input = r"let
+    gen = random(42)
+in
+    [ randInt(gen, 1, 6) randInt(gen.next(), 1, 6) randInt(random(42), 1, 6) ]";
+
+    let value = run(input).unwrap();
+    let roll = |index| match value.kind[index] {
+        ValueKind::Int(v) => v,
+        _ => 0,
+    };
+
+    assert!((0..3).all(|index| (1..=6).contains(&roll(index))));
+    // Same seed, same result
+    assert_eq!(roll(0), roll(2));
+
+    // Every integer of a range that doesn't divide 2^64 is as likely, the first third isn't picked half of the time
+    let third = isize::MAX / 2;
+    let (lo, hi) = (-isize::MAX, third);
+    let first_third = (0..300)
+        .filter(|seed| {
+            let input = format!("randInt(random({seed}), {lo}, {hi})");
+            matches!(run(input).unwrap().kind, ValueKind::Int(v) if v < lo + third)
+        })
+        .count();
+    assert!((75..125).contains(&first_third), "{first_third}");
+
+    // Empty range
+    let err = run_err("randInt(random(1), 6, 1)");
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::InvalidRange { range: span(0, 0) }
+    );
+}
+
+#[test]
+fn power() {
+    let input = "2 ** 3 ** 2";
+    let expected = Value::new(ValueKind::Int(512), span(0, 11));
+    assert_eq!(run(input).unwrap(), expected);
+
+    let input = "2 * 3 ** 2";
+    let expected = Value::new(ValueKind::Int(18), span(0, 10));
+    assert_eq!(run(input).unwrap(), expected);
+
+    let input = "2 ** -1";
+    let expected = Value::new(ValueKind::Float(0.5), span(0, 7));
+    assert_eq!(run(input).unwrap(), expected);
+
+    // `**` binds tighter than the minus of a negative number
+    let input = "[ -2 ** 2 -2.0 ** 2 -2 ** 3 ** 2 2 ** -2 ** 2 ]";
+    assert_eq!(run(input).unwrap().to_string(), "[ -4 -4 -512 0.0625 ]");
+}
+
+#[test]
+fn integer_division() {
+    let input = "div(7, 2)";
+    let expected = Value::new(ValueKind::Int(3), span(0, 9));
+    assert_eq!(run(input).unwrap(), expected);
+
+    let input = "div(-7, 2)";
+    let expected = Value::new(ValueKind::Int(-4), span(0, 10));
+    assert_eq!(run(input).unwrap(), expected);
+}
+
+#[test]
+fn numeric_builtins() {
+    let input =
+        "[ clamp(15, 1, 10) clamp(-3, 1, 10) clamp(5.5, 1, 10) sign(-3) sign(0) sign(-2.5) ]";
+    assert_eq!(run(input).unwrap().to_string(), "[ 10 1 5.5 -1 0 -1 ]");
+
+    let input = "[ gcd(12, 18) gcd(-4, 6) gcd(0, 0) lcm(4, 6) lcm(0, 3) lcm(-3, 5) ]";
+    assert_eq!(run(input).unwrap().to_string(), "[ 6 2 0 12 0 15 ]");
+
+    let input = "[ sum([ 1 2 3 ]) sum([ ]) sum([ 1 2.5 ]) product([ 2 3 4 ]) product([ ]) ]";
+    assert_eq!(run(input).unwrap().to_string(), "[ 6 0 3.5 24 1 ]");
+
+    let input = "[ mean([ 1 2 3 4 ]) minOf([ 3 1.5 2 ]) maxOf([ 3 1.5 7 ]) ]";
+    assert_eq!(run(input).unwrap().to_string(), "[ 2.5 1.5 7 ]");
+
+    let err = run_err("clamp(5, 10, 1)");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::InvalidRange { range } if range == span(9, 5)
+    ));
+
+    let err = run_err("maxOf([ ])");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::EmptyAggregate { ref name, array } if name == "maxOf" && array == span(6, 3)
+    ));
+
+    let err = run_err("sum([ 1 \"a\" ])");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::MismatchedTypes { ref got, .. } if got == "string"
+    ));
+    assert_eq!(err.span, span(8, 3));
+}
+
+#[test]
+fn left_associativity() {
+    let input = "10 - 2 - 3";
+    let expected = Value::new(ValueKind::Int(5), span(0, 10));
+    assert_eq!(run(input).unwrap(), expected);
+}
+
+#[cfg(not(feature = "bigint"))]
+#[test]
+fn checked_arithmetic() {
+    let input = "9223372036854775807 + 1";
+    let expected = Value::new(ValueKind::Int(isize::MAX), span(0, 23));
+    assert_eq!(run(input).unwrap(), expected);
+
+    #[allow(
+        clippy::needless_update,
+        reason = "Other fields depend on enabled features"
+    )]
+    let options = Options {
+        checked_arithmetic: true,
+        ..Default::default()
+    };
+    let err = run_with_options(input, options.clone()).unwrap_err();
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::IntegerOverflow {
+            operation: span(0, 0)
+        }
+    );
+    assert_eq!(err.span, span(20, 1));
+
+    for input in [
+        "sum([ 9223372036854775807 1 ])",
+        "lcm(9223372036854775807, 2)",
+    ] {
+        assert_eq!(run(input).unwrap().to_string(), isize::MAX.to_string());
+        let err = run_with_options(input, options.clone()).unwrap_err();
+        assert!(matches!(err.kind, RuntimeErrorKind::IntegerOverflow { .. }));
+    }
+}
+
+#[test]
+fn division_by_zero() {
+    let input = "1 / 0";
+    let err = run_err(input);
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::DivisionByZero {
+            divisor: span(0, 0)
+        }
+    );
+    assert_eq!(err.span, span(4, 1));
+
+    #[allow(
+        clippy::needless_update,
+        reason = "Other fields depend on enabled features"
+    )]
+    let options = Options {
+        lenient_division: true,
+        ..Default::default()
+    };
+    let expected = Value::new(ValueKind::Null, span(0, 5));
+    assert_eq!(run_with_options(input, options).unwrap(), expected);
+}
+
+#[test]
+fn strict_types() {
+    let input = r#""abc" - 5"#;
+    let expected = Value::new(ValueKind::Null, span(0, 9));
+    assert_eq!(run(input).unwrap(), expected);
+
+    #[allow(
+        clippy::needless_update,
+        reason = "Other fields depend on enabled features"
+    )]
+    let options = Options {
+        strict_types: true,
+        ..Default::default()
+    };
+    let err = run_with_options(input, options).unwrap_err();
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::MismatchedOperandTypes {
+            operator: String::new(),
+            lhs_type: String::new(),
+            rhs_type: String::new(),
+            lhs: span(0, 0),
+            rhs: span(0, 0),
+        }
+    );
+    assert_eq!(err.span, span(6, 1));
+}
+
+#[test]
+fn strict_conditions() {
+    let input = r#"[ if(1, "yes", "no") [ x for x in [ 0 1 2 ] if x ] ]"#;
+    assert_eq!(run(input).unwrap().to_string(), "[ yes [ 1 2 ] ]");
+
+    #[allow(
+        clippy::needless_update,
+        reason = "Other fields depend on enabled features"
+    )]
+    let options = Options {
+        strict_conditions: true,
+        ..Default::default()
+    };
+    let err = run_with_options("if(1, 2, 3)", options.clone()).unwrap_err();
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::NonBooleanCondition {
+            type_name: String::new(),
+            condition: span(0, 0),
+        }
+    );
+    assert_eq!(err.span, span(3, 1));
+
+    let err = run_with_options("[ x for x in [ 1 ] if x ]", options.clone()).unwrap_err();
+    assert_eq!(err.span, span(22, 1));
+
+    let input = "[ x for x in [ 1 2 3 ] if x > 1 ]";
+    assert_eq!(
+        run_with_options(input, options).unwrap().to_string(),
+        "[ 2 3 ]"
+    );
+}
+
+#[test]
+fn interpolated_paths() {
+    let input = r#"let dir = "a/./b" in [ ./${dir}/../c ./x/${"../.."}/y /${"../etc"} ]"#;
+    assert_eq!(run(input).unwrap().to_string(), "[ ./a/c ../y /etc ]");
+
+    let err = run_err(r#"let name = "a\0b" in ./hosts/${name}.tl"#);
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::InvalidPathCharacter {
+            character: '\0',
+            part: span(0, 0),
+        }
+    );
+    assert_eq!(err.span, span(21, 18));
+
+    let cwd = std::env::current_dir().unwrap();
+    #[allow(
+        clippy::needless_update,
+        reason = "Other fields depend on enabled features"
+    )]
+    let options = Options {
+        path_root: Some(cwd.join("sandbox")),
+        ..Default::default()
+    };
+    let input = r#"let name = "../secret" in ./sandbox/${name}"#;
+    let err = run_with_options(input, options.clone()).unwrap_err();
+    assert!(matches!(err.kind, RuntimeErrorKind::PathOutsideRoot { .. }));
+    assert!(run_with_options("/etc/passwd", options.clone()).is_err());
+
+    let input = r#"let name = "a/../b" in ./sandbox/${name}"#;
+    assert_eq!(
+        run_with_options(input, options).unwrap().to_string(),
+        "./sandbox/b"
+    );
+}
+
+#[test]
+fn strict_fields() {
+    let input = "let config = { port = 80 } in config.host";
+    assert!(matches!(run(input).unwrap().kind, ValueKind::Null));
+
+    #[allow(
+        clippy::needless_update,
+        reason = "Other fields depend on enabled features"
+    )]
+    let options = Options {
+        strict_fields: true,
+        ..Default::default()
+    };
+    let err = run_with_options(input, options.clone()).unwrap_err();
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::MissingField {
+            field: String::new(),
+            base_type: String::new(),
+            base: span(0, 0),
+        }
+    );
+    assert_eq!(err.span, span(30, 11));
+
+    let input = r#"let config = { port = 80 } in [ config.port config.host or "localhost" ]"#;
+    assert_eq!(
+        run_with_options(input, options).unwrap().to_string(),
+        "[ 80 localhost ]"
+    );
+}
+
+#[test]
+fn contracts() {
+    let input = r#"
+        let
+            port :: int = 8080
+            hosts :: [string] = [ "a" "b" ]
+            timeout :: number? = null
+        in
+            { port :: int = port hosts :: array = hosts timeout :: any = timeout }
+    "#;
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "{ hosts = [ a b ]; port = 8080; timeout = null }"
+    );
+
+    let err = run_err(r#"let port :: int = "8080" in port"#);
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::ContractViolation {
+            expected: String::new(),
+            found: String::new(),
+            annotation: span(0, 0),
+            value: span(0, 0),
+        }
+    );
+    assert_eq!(err.span, span(18, 6));
+
+    let err = run_err(r#"{ hosts :: [string] = [ "a" 1 ] }"#);
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::ContractViolation { expected, found, annotation, .. }
+            if expected == "[string]" && found == "array" && annotation == span(11, 8)
+    ));
+}
+
+#[test]
+fn strict() {
+    let options = Options::strict();
+    assert!(run_with_options("if(1, 2, 3)", options.clone()).is_err());
+    assert!(run_with_options(r#""abc" - 5"#, options.clone()).is_err());
+    assert!(run_with_options("1 / 0", options.clone()).is_err());
+    assert!(run_with_options("let a = { b = 1 } in a.c", options.clone()).is_err());
+    assert_eq!(
+        run_with_options("if(true, 1, 2)", options)
+            .unwrap()
+            .to_string(),
+        "1"
+    );
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn path_exists() {
+    let input = format!(
+        "[ pathExists({}) pathExists(./does/not/exist) if(./does/not/exist, 1, 2) ]",
+        std::env::temp_dir().display()
+    );
+    assert_eq!(run(input).unwrap().to_string(), "[ true false 1 ]");
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn path_operations() {
+    assert_eq!(
+        run("[ parent(./a/b.tl) parent(./a/..) parent(../..) parent(/) join(./a, \"b/../c\") join(/etc, ./hosts) ]")
+            .unwrap()
+            .to_string(),
+        "[ ./a .. ../../.. / ./a/c /etc/hosts ]"
+    );
+    assert_eq!(
+        run("[ relativeTo(./a/b, ./a/c/d.tl) relativeTo(./a, ./a/b) relativeTo(/a, /a) ]")
+            .unwrap()
+            .to_string(),
+        "[ ../c/d.tl ./b . ]"
+    );
+
+    let err = run_err("join(./etc, /hosts)");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::AbsoluteSegment { segment } if segment == span(12, 6)
+    ));
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn canonicalize_path() {
+    let dir = std::env::temp_dir().join("tl-canonicalize");
+    std::fs::create_dir_all(&dir).unwrap();
+    let canonical = dir.canonicalize().unwrap();
+
+    assert_eq!(
+        run(format!(
+            "canonicalize({}/./../tl-canonicalize)",
+            dir.display()
+        ))
+        .unwrap()
+        .to_string(),
+        canonical.display().to_string()
+    );
+
+    let err = run_err("canonicalize(./does/not/exist)");
+    assert!(matches!(err.kind, RuntimeErrorKind::IOError(_)));
+    assert_eq!(err.span, span(13, 16));
+
+    #[allow(
+        clippy::needless_update,
+        reason = "Other fields depend on enabled features"
+    )]
+    let options = Options {
+        path_root: Some(dir.clone()),
+        ..Default::default()
+    };
+    #[cfg(unix)]
+    {
+        let link = dir.join("outside");
+        let _ = std::fs::remove_file(&link);
+        std::os::unix::fs::symlink(std::env::temp_dir(), &link).unwrap();
+
+        let err =
+            run_with_options(format!("canonicalize({})", link.display()), options).unwrap_err();
+        assert!(matches!(err.kind, RuntimeErrorKind::PathOutsideRoot { .. }));
+    }
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn non_utf8_and_large_files() {
+    let dir = std::env::temp_dir().join("tl-non-utf8-files");
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = |name: &str, bytes: &[u8]| {
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path.display().to_string()
+    };
+
+    let binary = file("binary.bin", &[0, 255, 104]);
+    let latin1 = file("latin1.tl", b"\"caf\xe9\"");
+    let utf16 = file("utf16.tl", &[0xff, 0xfe, b'1', 0]);
+    let bom = file("bom.tl", "\u{feff}\"café\"".as_bytes());
+
+    assert_eq!(
+        run(format!("readFileBytes({binary})")).unwrap().to_string(),
+        "[ 0 255 104 ]"
+    );
+    assert_eq!(run(format!("import({bom})")).unwrap().to_string(), "café");
+
+    for (path, hint) in [(&latin1, "WINDOWS-1252"), (&utf16, "UTF-16")] {
+        let err = run_err(format!("import({path})"));
+        assert!(
+            matches!(err.kind, RuntimeErrorKind::InvalidUtf8File { hint: ref found, .. } if found.contains(hint)),
+            "{err:?}"
+        );
+    }
+
+    #[allow(
+        clippy::needless_update,
+        reason = "Other fields depend on enabled features"
+    )]
+    let options = Options {
+        max_file_size: Some(2),
+        ..Default::default()
+    };
+    let err = run_with_options(format!("readFileBytes({binary})"), options.clone()).unwrap_err();
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::FileTooLarge { limit: 2, .. }
+    ));
+    assert!(run_with_options(format!("import({utf16})"), options).is_err());
+}
+
+#[cfg(feature = "bigint")]
+#[test]
+fn big_int() {
+    let input = "9223372036854775807 + 1";
+    let expected = Value::new(
+        ValueKind::BigInt("9223372036854775808".parse().unwrap()),
+        span(0, 23),
+    );
+    assert_eq!(run(input).unwrap(), expected);
+
+    // Demoted back to a regular integer when it fits again
+    let input = "18446744073709551616 - 18446744073709551615";
+    let expected = Value::new(ValueKind::Int(1), span(0, 43));
+    assert_eq!(run(input).unwrap(), expected);
+
+    let input = "sort([ 18446744073709551617 18446744073709551616.0 0.5 18446744073709551616 ])";
+    let sorted = run(input).unwrap();
+    assert!(matches!(&sorted.kind, ValueKind::Array(items) if items.is_sorted()));
+    assert_eq!(
+        sorted.to_string(),
+        "[ 0.5 18446744073709552000 18446744073709551616 18446744073709551617 ]"
+    );
+
+    // Big exponents only have a result for -1, 0 and 1, anything else is too large
+    let input = "let big = 9223372036854775808 in let odd = big + 1 in let minusOne = -1 in [ 2 ** 64 big ** 2 minusOne ** big minusOne ** odd 1 ** big 0 ** big ]";
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[ 18446744073709551616 85070591730234615865843651857942052864 1 -1 1 0 ]"
+    );
+    let input = "let big = 9223372036854775808 in let minusBig = 0 - big in let minusOne = -1 in [ big ** minusOne 2 ** minusBig ]";
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[ 0.00000000000000000010842021724855044 0 ]"
+    );
+    for input in [
+        "9223372036854775808 ** 9223372036854775808",
+        "2 ** 9223372036854775807",
+        "3 ** 100000000",
+    ] {
+        let err = run_err(input);
+        assert!(
+            matches!(err.kind, RuntimeErrorKind::TooLarge { ref unit, .. } if unit == "bits"),
+            "{input}"
+        );
+    }
+
+    let input = "toFixed(2, 9223372036854775808)";
+    assert_eq!(run(input).unwrap().to_string(), "9223372036854775808.00");
+
+    for input in [
+        "band(9223372036854775808, 1)",
+        "div(9223372036854775808, 2)",
+        "gcd(2, 9223372036854775808)",
+    ] {
+        let err = run_err(input);
+        assert!(
+            matches!(err.kind, RuntimeErrorKind::BigIntUnsupported { .. }),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn bitwise() {
+    let input = "bor(band(12, 10), bxor(1, 3))";
+    let expected = Value::new(ValueKind::Int(10), span(0, 29));
+    assert_eq!(run(input).unwrap(), expected);
+
+    let input = "shl(1, 4)";
+    let expected = Value::new(ValueKind::Int(16), span(0, 9));
+    assert_eq!(run(input).unwrap(), expected);
+
+    let input = "shr(-16, 2)";
+    let expected = Value::new(ValueKind::Int(-4), span(0, 11));
+    assert_eq!(run(input).unwrap(), expected);
+
+    let input = "bnot(0)";
+    let expected = Value::new(ValueKind::Int(-1), span(0, 7));
+    assert_eq!(run(input).unwrap(), expected);
+
+    let err = run_err("shl(1, -1)");
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::NegativeShift { amount: span(0, 0) }
+    );
+}
+
+#[test]
+fn string_builtins() {
+    let input = r#"strLen("héllo")"#;
+    let expected = Value::new(ValueKind::Int(5), span(0, 16));
+    assert_eq!(run(input).unwrap(), expected);
+
+    let input = r#"charAt("héllo", 1)"#;
+    let expected = Value::new(ValueKind::String("é".to_string()), span(0, 19));
+    assert_eq!(run(input).unwrap(), expected);
+
+    let input = r#"chars("hé")"#;
+    let expected = Value::new(
+        ValueKind::Array(vec![
+            Value::new(ValueKind::String("h".to_string()), span(0, 12)),
+            Value::new(ValueKind::String("é".to_string()), span(0, 12)),
+        ]),
+        span(0, 12),
+    );
+    assert_eq!(run(input).unwrap(), expected);
+
+    let input = r#"codepoints("hé")"#;
+    let expected = Value::new(
+        ValueKind::Array(vec![
+            Value::new(ValueKind::Int(104), span(0, 17)),
+            Value::new(ValueKind::Int(233), span(0, 17)),
+        ]),
+        span(0, 17),
+    );
+    assert_eq!(run(input).unwrap(), expected);
+
+    let input = "fromCodepoint(233)";
+    let expected = Value::new(ValueKind::String("é".to_string()), span(0, 18));
+    assert_eq!(run(input).unwrap(), expected);
+
+    // Characters are counted as they are displayed, not by codepoints
+    let input =
+        "[ strLen(\"e\u{301}\") strLen(\"👍🏽\") chars(\"a👍🏽e\u{301}\") charAt(\"👍🏽e\u{301}\", 1) ]";
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[ 1 1 [ a 👍🏽 e\u{301} ] e\u{301} ]"
+    );
+    let err = run_err("charAt(\"e\u{301}\", 1)");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::CharIndexOutOfBounds { length: 1, .. }
+    ));
+
+    let err = run_err(r#"charAt("hé", 2)"#);
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::CharIndexOutOfBounds {
+            length: 2,
+            index: span(14, 1)
+        }
+    );
+    assert_eq!(err.span, span(14, 1));
+
+    let err = run_err("fromCodepoint(55296)");
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::InvalidCodepoint {
+            codepoint: span(14, 5)
+        }
+    );
+    assert_eq!(err.span, span(14, 5));
+}
+
+#[test]
+fn byte_builtins() {
+    let input =
+        r#"[ strLen("héllo") byteLen("héllo") isAscii("hello") isAscii("héllo") toBytes("hé") ]"#;
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[ 5 6 true false [ 104 195 169 ] ]"
+    );
+
+    let input = r#"fromBytes(toBytes("wörld"))"#;
+    assert_eq!(run(input).unwrap().to_string(), "wörld");
+
+    let err = run_err("fromBytes([ 104 256 ])");
+    assert_eq!(err.kind, RuntimeErrorKind::InvalidByte { byte: span(0, 0) });
+    assert_eq!(err.span, span(16, 3));
+
+    let err = run_err("fromBytes([ 104 195 ])");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::InvalidUtf8 { valid_up_to: 1, .. }
+    ));
+}
+
+#[test]
+fn field_paths() {
+    let input = r#"
+let
+    config = { server = { host = "localhost" port = 80 } }
+in
+    [
+        getPath(config, "server.port")
+        getPath(config, [ "server" "host" ])
+        getPath(config, "server.missing.key")
+        setPath(config, "server.port", 8080)
+        setPath(config, [ "log" "level" ], "debug")
+        updatePath(config, "server.port", (port) { port + 1 })
+    ]
+"#;
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[ 80 localhost null { server = { host = localhost; port = 8080 } } { log = { level = debug }; server = { host = localhost; port = 80 } } { server = { host = localhost; port = 81 } } ]"
+    );
+
+    let err = run_err("setPath({ a = 1 }, \"a.b\", 2)");
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::MismatchedTypes {
+            expected: String::new(),
+            got: String::new()
+        }
+    );
+
+    let err = run_err("setPath(freeze({ a = 1 }), \"a\", 2)");
+    assert!(matches!(err.kind, RuntimeErrorKind::FrozenOverride { ref key, .. } if key == "a"));
+}
+
+#[test]
+fn overrides() {
+    let input = r#"
+let
+    base = {
+        port = mkDefault(80)
+        host = "localhost"
+        log = { level = "info" format = "text" }
+        users = mkForce([ "root" ])
+    }
+    machine = { port = 8080 log.level = "debug" users = [ "guest" ] }
+    local = { port = 9090 log = mkForce({ level = "trace" }) }
+in
+    applyOverrides(base, [ machine local ])
+"#;
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "{ host = localhost; log = { level = trace }; port = 9090; users = [ root ] }"
+    );
+
+    let input = "applyOverrides({ a = mkOverride(10, 1) }, [ { a = mkOverride(5, 2) } { a = 3 } ])";
+    assert_eq!(run(input).unwrap().to_string(), "{ a = 2 }");
+
+    let err = run_err("applyOverrides({ a = 1 }, [ 2 ])");
+    assert!(matches!(err.kind, RuntimeErrorKind::MismatchedTypes { .. }));
+    let err = run_err("let f = freeze({ a = 1 }) in applyOverrides(f, [ { a = 2 } ])");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::FrozenOverride { ref key, overridden, .. } if key == "a" && overridden == span(55, 1)
+    ));
+    let input =
+        "applyOverrides({ server = freeze({ port = 80 }) }, [ { server.port = mkForce(8080) } ])";
+    assert!(matches!(
+        run_err(input).kind,
+        RuntimeErrorKind::FrozenOverride { ref key, .. } if key == "port"
+    ));
+    let input = "applyOverrides(freeze({ a = 1 }), [ { b = 2 } ])";
+    assert_eq!(run(input).unwrap().to_string(), "{ a = 1; b = 2 }");
+}
+
+#[test]
+fn fixpoint() {
+    let input = r#"
+fix((self) {
+    {
+        port = self.base + 1
+        base = 8000
+        url = "http://localhost:${self.port}"
+        server.address = "${self.url}/api"
+    }
+})
+"#;
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "{ base = 8000; port = 8001; server = { address = http://localhost:8001/api }; url = http://localhost:8001 }"
+    );
+
+    let err = run_err("fix((self) { { a = self.b b = self.a } })");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::InfiniteRecursion { ref cycle, .. } if cycle == "a -> b -> a"
+    ));
+    assert_eq!(err.span, span(30, 6));
+
+    let err = run_err("fix((self) { 1 })");
+    assert!(matches!(err.kind, RuntimeErrorKind::InvalidFixpoint { .. }));
+    let err = run_err("fix((self) { let x = 1 in { a = x } })");
+    assert!(matches!(err.kind, RuntimeErrorKind::InvalidFixpoint { .. }));
+
+    // The object passed to the function never escapes with lazy fields
+    let input = r#"fix((self) { { a = 1 b = getPath(self, "a") c = deepEq(self.a, 1) d = { e = self.a } } })"#;
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "{ a = 1; b = 1; c = true; d = { e = 1 } }"
+    );
+    let input = r#"let o = fix((self) { { a = 1 b = { c = self.a } } }) in [ getPath(o, "b.c") deepEq(o.b, { c = 1 }) ]"#;
+    assert_eq!(run(input).unwrap().to_string(), "[ 1 true ]");
+    let err = run_err("fix((self) { { a = 1 me = self } })");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::InfiniteRecursion { ref cycle, .. } if cycle == "me -> me"
+    ));
+}
+
+#[test]
+fn option_declarations() {
+    let declarations = r#"
+    declarations = {
+        port = mkOption({ type = "int" default = 80 description = "The port to listen on" })
+        hosts = mkOption({ type = "[string]" })
+        log.level = mkOption({ type = "string?" default = null })
+    }
+"#;
+    let eval = |definitions: &str| {
+        format!("let {declarations} in evalOptions(declarations, {definitions})")
+    };
+
+    assert_eq!(
+        run(eval(r#"{ hosts = [ "a" ] log.level = "debug" }"#))
+            .unwrap()
+            .to_string(),
+        "{ hosts = [ a ]; log = { level = debug }; port = 80 }"
+    );
+
+    let err = run_err(eval(r#"{ hosts = [ "a" ] prot = 8080 }"#));
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::UnknownOption { ref option, known: Some(_), .. } if option == "prot"
+    ));
+
+    let err = run_err(eval(r#"{ hosts = [ "a" ] log.level = 1 }"#));
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::InvalidOption { ref option, ref expected, .. } if option == "log.level" && expected == "string?"
+    ));
+
+    let err = run_err(eval("{ }"));
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::MissingOption { ref option, .. } if option == "hosts"
+    ));
+
+    let err = run_err(r#"mkOption({ type = "integer" })"#);
+    assert!(matches!(err.kind, RuntimeErrorKind::UnknownType { .. }));
+}
+
+#[test]
+fn range() {
+    let input = "1..4";
+    let expected = Value::new(
+        ValueKind::Array(vec![
+            Value::new(ValueKind::Int(1), span(0, 4)),
+            Value::new(ValueKind::Int(2), span(0, 4)),
+            Value::new(ValueKind::Int(3), span(0, 4)),
+        ]),
+        span(0, 4),
+    );
+    assert_eq!(run(input).unwrap(), expected);
+
+    let input = "-1..=1";
+    let expected = Value::new(
+        ValueKind::Array(vec![
+            Value::new(ValueKind::Int(-1), span(0, 6)),
+            Value::new(ValueKind::Int(0), span(0, 6)),
+            Value::new(ValueKind::Int(1), span(0, 6)),
+        ]),
+        span(0, 6),
+    );
+    assert_eq!(run(input).unwrap(), expected);
+
+    let input = "3..1";
+    let expected = Value::new(ValueKind::Array(Vec::new()), span(0, 4));
+    assert_eq!(run(input).unwrap(), expected);
+
+    let err = run_err("1..true");
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::MismatchedOperandTypes {
+            operator: String::new(),
+            lhs_type: String::new(),
+            rhs_type: String::new(),
+            lhs: span(0, 0),
+            rhs: span(0, 0),
+        }
+    );
+    assert_eq!(err.span, span(1, 2));
+
+    let err = run_err("0..9223372036854775807");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::TooLarge { limit, operation, .. } if limit == MAX_ARRAY_LENGTH && operation == span(0, 22)
+    ));
+    let err = run_err("-9223372036854775807 ..= 9223372036854775807");
+    assert!(matches!(err.kind, RuntimeErrorKind::TooLarge { .. }));
+
+    // Repeating a string is limited the same way
+    let err = run_err(r#""ab" * 9223372036854775807"#);
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::TooLarge { limit, operation, .. } if limit == MAX_STRING_LENGTH && operation == span(0, 26)
+    ));
+    assert_eq!(run(r#""ab" * 3"#).unwrap().to_string(), "ababab");
+}
+
+#[test]
+fn comprehension() {
+    let input = "let xs = [ -1 2 3 ] in [ x * 2 for x in xs if x > 0 ]";
+    let expected = Value::new(
+        ValueKind::Array(vec![
+            Value::new(ValueKind::Int(4), span(14, 16)),
+            Value::new(ValueKind::Int(6), span(16, 14)),
+        ]),
+        span(23, 30),
+    );
+    assert_eq!(run(input).unwrap(), expected);
+
+    let err = run_err("[ x for x in 1 ]");
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::MismatchedTypes {
+            expected: String::new(),
+            got: String::new(),
+        }
+    );
+    assert_eq!(err.span, span(13, 1));
+}
+
+#[test]
+fn destructuring() {
+    let input = r#"
+let
+    { host, port } = { host = "localhost" port = 8080 }
+    [ first, ...rest ] = [ 1 2 3 ]
+in
+    [ host port first rest ]
+"#;
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[ localhost 8080 1 [ 2 3 ] ]"
+    );
+
+    let err = run_err("let [ a, b ] = [ 1 ] in a");
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::IndexOutOfBounds {
+            length: 1,
+            index: span(15, 5),
+        }
+    );
+    assert_eq!(err.span, span(15, 5));
+}
+
+#[test]
+fn named_args() {
+    let input = r#"
+let
+    connect = (host, port, user) { "${user}@${host}:${port}" }
+in
+    connect(port = 5432, "db", user = "admin")
+"#;
+    assert_eq!(run(input).unwrap().to_string(), "admin@db:5432");
+
+    let err = run_err("let f = (a) { a } in f(b = 1)");
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::UnknownArgument {
+            argument: span(0, 0)
+        }
+    );
+    assert_eq!(err.span, span(23, 1));
+
+    let err = run_err("let f = (a, b) { a } in f(a = 1, a = 2)");
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::DuplicateArgument {
+            argument: span(0, 0)
+        }
+    );
+    assert_eq!(err.span, span(33, 1));
+}
+
+#[test]
+fn variadic_function() {
+    let input = r#"
+let
+    join = (separator, ...parts) { [ "${separator}${part}" for part in parts ] }
+in
+    join("/", "a", "b")
+"#;
+    assert_eq!(run(input).unwrap().to_string(), "[ /a /b ]");
+
+    let err = run_err("let f = (a, ...rest) { a } in f()");
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::ArgsMismatch {
+            len: 1,
+            variadic: true,
+            given: 0,
+            args: span(0, 0),
+            definition: None,
+            signature: None,
+        }
+    );
+    assert_eq!(err.span, span(30, 3));
+}
+
+#[test]
+fn args_mismatch_details() {
+    let err = run_err("let greet = (name, greeting ? 1, ...rest) { name } in greet()");
+    let RuntimeErrorKind::ArgsMismatch {
+        given,
+        definition,
+        signature,
+        ..
+    } = err.kind
+    else {
+        unreachable!("calling a function without arguments is an arity error");
+    };
+    assert_eq!(given, 0);
+    assert_eq!(definition, Some(span(12, 38)));
+    assert_eq!(
+        signature.as_deref(),
+        Some("greet(name, greeting?, ...rest)")
+    );
+
+    let err = run_err("div(1)");
+    let RuntimeErrorKind::ArgsMismatch {
+        given,
+        definition,
+        signature,
+        ..
+    } = err.kind
+    else {
+        unreachable!("`div` takes two arguments");
+    };
+    assert_eq!(given, 1);
+    assert_eq!(definition, None);
+    assert_eq!(signature.as_deref(), Some("div(lhs: int, rhs: int)"));
+
+    // A function from another source isn't labeled, even where its span covers a function in this one
+    let function = run("let h = (ab) { ab } in h").unwrap();
+    let source = NamedSource::new("main", "let g = (b) { f() } in g(1)".to_string());
+    let ast = parse(&source).unwrap();
+    let variables = HashMap::from([("f".to_string(), function)]);
+    let err = Scope::new(variables, source, ast).eval().unwrap_err();
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::ArgsMismatch {
+            definition: None,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn not_callable() {
+    let err = run_err("5(1, 2)");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::NotCallable { ref type_name, call, value }
+            if type_name == "number" && call == span(1, 6) && value == span(0, 1)
+    ));
+    assert_eq!(err.span, span(0, 7));
+    // The literal that is called is labeled with its type
+    let labels = err
+        .labels()
+        .into_iter()
+        .flatten()
+        .map(|label| (label.label().map(str::to_string), label.inner().offset()))
+        .collect::<Vec<_>>();
+    assert!(labels.contains(&(Some("This is a number".to_string()), 0)));
+
+    let err = run_err("let port = 8080 in port(1)");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::NotCallable { type_name, call, value }
+            if type_name == "number" && call == span(23, 3) && value == span(11, 4)
+    ));
+
+    // A `(` after whitespace starts a function instead of calling the number
+    assert!(matches!(
+        run("[ 5 (x) { x } ]").unwrap().kind,
+        ValueKind::Array(items) if items.len() == 2
+    ));
+}
+
+#[test]
+fn default_args() {
+    let input = r#"
+let
+    greet = (name, greeting ? "Hello", line ? "${greeting}, ${name}") { line }
+in
+    [ greet("John") greet("Jane", "Hey") greet("Joe", line = "Hi") ]
+"#;
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[ Hello, John Hey, Jane Hi ]"
+    );
+
+    let err = run_err(r#"let f = (a, b ? 1) { a } in f(b = 2)"#);
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::ArgsMismatch {
+            len: 1,
+            variadic: true,
+            given: 0,
+            args: span(0, 0),
+            definition: None,
+            signature: None,
+        }
+    );
+    assert_eq!(err.span, span(28, 8));
+
+    // A default before a parameter without one is only used when the later arguments are passed by name
+    let input = "let f = (a, b ? 2, c) { [ a b c ] } in [ f(1, c = 3) f(1, 5, 3) ]";
+    assert_eq!(run(input).unwrap().to_string(), "[ [ 1 2 3 ] [ 1 5 3 ] ]");
+    let err = run_err("let f = (a, b ? 2, c) { [ a b c ] } in f(1, 3)");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::ArgsMismatch {
+            len: 3,
+            given: 2,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn modules() {
+    let dir = std::env::temp_dir();
+
+    let lib = dir.join("tl-modules-lib.tl");
+    std::fs::write(
+        &lib,
+        r#"{ helpers = { double = (x) { x * 2 } } version = "1.0" }"#,
+    )
+    .unwrap();
+
+    let input = format!("import({}).helpers.double(21)", lib.display());
+    assert_eq!(run(input).unwrap().to_string(), "42");
+
+    let server = dir.join("tl-modules-server.tl");
+    std::fs::write(
+        &server,
+        r#"(name, port ? 8080) { { address = "${name}:${port}" } }"#,
+    )
+    .unwrap();
+
+    let input = format!(
+        r#"importWith({}, {{ name = "web" }}).address"#,
+        server.display()
+    );
+    assert_eq!(run(input).unwrap().to_string(), "web:8080");
+
+    let input = format!(
+        r#"importWith({}, {{ name = "web" host = "localhost" }})"#,
+        server.display()
+    );
+    let err = run_err(input);
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::UnknownArgument {
+            argument: span(0, 0)
+        }
+    );
+}
+
+#[test]
+fn directory_import() {
+    let dir = std::env::temp_dir().join("tl-directory-import");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("default.tl"), "{ name = \"default\" }").unwrap();
+    std::fs::write(dir.join("lib.tl"), "{ name = \"lib\" }").unwrap();
+
+    let input = format!("import({}).name", dir.display());
+    assert_eq!(run(input.clone()).unwrap().to_string(), "default");
+
+    let options = Options {
+        directory_import_file: Some("lib.tl".into()),
+        ..Default::default()
+    };
+    assert_eq!(run_with_options(input, options).unwrap().to_string(), "lib");
+}
+
+#[test]
+fn file_location() {
+    let dir = std::env::temp_dir().join("tl-file-location");
+    std::fs::create_dir_all(dir.join("lib")).unwrap();
+    std::fs::write(
+        dir.join("lib/default.tl"),
+        "{ file = __file__ dir = __dir__ }",
+    )
+    .unwrap();
+
+    let main = dir.join("main.tl");
+    std::fs::write(
+        &main,
+        format!("[ __file__ __dir__ import({}/lib) ]", dir.display()),
+    )
+    .unwrap();
+
+    assert_eq!(
+        eval_file(&main).unwrap().to_string(),
+        format!(
+            "[ {main} {dir} {{ dir = {dir}/lib; file = {dir}/lib/default.tl }} ]",
+            main = main.display(),
+            dir = dir.display()
+        )
+    );
+
+    assert!(run("__file__").is_err());
+}
+
+#[test]
+fn relative_imports() {
+    let dir = std::env::temp_dir().join("tl-relative-imports");
+    for sub in ["a/lib", "b/nested"] {
+        std::fs::create_dir_all(dir.join(sub)).unwrap();
+    }
+
+    let files = [
+        (
+            "a/main.tl",
+            "{ lib = import(./lib/mod.tl) other = import(../b/other.tl) data = ./data.txt }",
+        ),
+        (
+            "a/lib/mod.tl",
+            "let name = \"shared\" in import(../../${name}.tl)",
+        ),
+        ("shared.tl", "{ path = ./x here = __dir__ }"),
+        ("b/other.tl", "import(./nested)"),
+        ("b/nested/default.tl", "[ ./. ../other.tl ]"),
+    ];
+    for (file, text) in files {
+        std::fs::write(dir.join(file), text).unwrap();
+    }
+
+    let expected = format!(
+        "{{ data = {dir}/a/data.txt; lib = {{ here = {dir}; path = {dir}/x }}; other = [ {dir}/b/nested {dir}/b/other.tl ] }}",
+        dir = dir.display()
+    );
+    // The result doesn't depend on how the entry file is reached
+    for main in [dir.join("a/main.tl"), dir.join("b/../a/./main.tl")] {
+        assert_eq!(eval_file(&main).unwrap().to_string(), expected);
+    }
+
+    // Without a file, paths stay relative to the working directory
+    assert_eq!(run("./lib/../a.tl").unwrap().to_string(), "./lib/../a.tl");
+}
+
+#[test]
+fn library_paths() {
+    let dir = std::env::temp_dir().join("tl-library-paths");
+    for sub in ["app", "lib"] {
+        std::fs::create_dir_all(dir.join(sub)).unwrap();
+    }
+
+    let files = [
+        (
+            "app/main.tl",
+            "let lib = import(../lib) shadowed = let __dir__ = ./.. in import(./data.tl) in [ lib.load() lib.data lib.fixed.value shadowed ]",
+        ),
+        (
+            "lib/default.tl",
+            "let data = () { ./data.tl } in { load = () { import(./data.tl) } data = data() fixed = fix((self) { { value = import(./data.tl) } }) }",
+        ),
+        ("app/data.tl", "\"app\""),
+        ("lib/data.tl", "\"lib\""),
+    ];
+    for (file, text) in files {
+        std::fs::write(dir.join(file), text).unwrap();
+    }
+
+    // Functions resolve paths against the file they are defined in, not the one calling them
+    assert_eq!(
+        eval_file(dir.join("app/main.tl")).unwrap().to_string(),
+        format!("[ lib {}/lib/data.tl lib app ]", dir.display())
+    );
+}
+
+#[test]
+fn import_cycle() {
+    let dir = std::env::temp_dir().join("tl-import-cycle");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let files = [
+        ("self.tl", "import(./self.tl)"),
+        ("a.tl", "{ b = import(./b.tl) }"),
+        ("b.tl", "[ import(./a.tl) ]"),
+        ("template.tl", "renderTemplate(./template.txt, {})"),
+        ("template.txt", "${renderTemplate(./template.txt, {})}"),
+        ("twice.tl", "[ import(./leaf.tl) import(./leaf.tl) ]"),
+        ("leaf.tl", "1"),
+    ];
+    for (file, text) in files {
+        std::fs::write(dir.join(file), text).unwrap();
+    }
+
+    let cycle = |file: &str| {
+        let err = eval_file(dir.join(file)).unwrap_err();
+        assert_eq!(
+            err.code().map(|code| code.to_string()).as_deref(),
+            Some("tl::runtime::import_cycle"),
+            "{file}: {err:?}"
+        );
+        err.help().map(|help| help.to_string()).unwrap_or_default()
+    };
+
+    let files_of = |help: String| {
+        help.rsplit(": ")
+            .next()
+            .unwrap_or_default()
+            .split(" -> ")
+            .map(|path| path.rsplit('/').next().unwrap_or_default().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(files_of(cycle("self.tl")), ["self.tl", "self.tl"]);
+    assert_eq!(files_of(cycle("a.tl")), ["b.tl", "a.tl", "b.tl"]);
+    #[cfg(feature = "fs")]
+    cycle("template.tl");
+
+    // Importing the same file twice is not a cycle
+    assert_eq!(
+        eval_file(dir.join("twice.tl")).unwrap().to_string(),
+        "[ 1 1 ]"
+    );
+}
+
+#[test]
+fn script_args() {
+    assert_eq!(run("args").unwrap().to_string(), "[  ]");
+
+    #[allow(
+        clippy::needless_update,
+        reason = "Other fields depend on enabled features"
+    )]
+    let options = Options {
+        args: vec!["--port".into(), "8080".into()],
+        ..Default::default()
+    };
+    assert_eq!(
+        run_with_options("let f = (args) { args } in [ args f(1) ]", options)
+            .unwrap()
+            .to_string(),
+        "[ [ --port 8080 ] 1 ]"
+    );
+}
+
+#[test]
+fn builtins_object() {
+    let input = r"
+let
+    div = (a, b) { 0 }
+    f = () { div(7, 2) }
+in
+    [ div(7, 2) f() builtins.div(7, 2) ]
+";
+    assert_eq!(run(input).unwrap().to_string(), "[ 0 0 3 ]");
+
+    let keys = run("objectKeys(builtins)").unwrap().to_string();
+    assert!(keys.contains(" div ") && keys.contains(" import "));
+}
+
+#[test]
+fn builtin_registry() {
+    let builtins = Scope::builtins();
+
+    let div = builtins
+        .iter()
+        .find(|builtin| builtin.name == "div")
+        .unwrap();
+    assert_eq!(div.arity(), 2);
+    assert_eq!(div.feature, None);
+
+    let keys = run("objectKeys(builtins)").unwrap().to_string();
+    for builtin in builtins {
+        assert!(
+            keys.contains(&format!(" {} ", builtin.name)),
+            "{}",
+            builtin.name
+        );
+    }
+}
+
+#[test]
+fn completions() {
+    let mut scope = Scope::new(
+        HashMap::new(),
+        NamedSource::new("test", String::new()),
+        parse(&NamedSource::new("test", "null".to_string())).unwrap(),
+    );
+    scope.define(
+        "server",
+        Value::new_builtin(ValueKind::Object(BTreeMap::from([
+            ("host".to_string(), Value::new_builtin(ValueKind::Null)),
+            ("port".to_string(), Value::new_builtin(ValueKind::Int(8080))),
+        ]))),
+    );
+
+    assert_eq!(scope.completions("ser"), ["server"]);
+    assert_eq!(scope.completions("str"), ["strLen"]);
+    assert_eq!(scope.completions("1 + server.p"), ["port"]);
+    assert_eq!(scope.completions("server."), ["host", "port"]);
+    assert_eq!(scope.completions("server.port."), Vec::<String>::new());
+    assert_eq!(scope.completions("\"unclosed"), Vec::<String>::new());
+}
+
+#[test]
+fn debugger() {
+    let input = r"
+let
+    a = 1
+    b = a + 1
+in
+    [ a b ]
+";
+    let pauses = Rc::new(RefCell::new(Vec::new()));
+    let debugger = Debugger::new(
+        vec![
+            Breakpoint::Line {
+                file: "test".into(),
+                line: 6,
+            },
+            Breakpoint::Binding("b".into()),
+        ],
+        {
+            let pauses = Rc::clone(&pauses);
+            move |frame, breakpoint| {
+                pauses.borrow_mut().push((
+                    frame.line(),
+                    breakpoint.cloned(),
+                    frame.variables.contains_key("b"),
+                ));
+
+                // Step once after the line breakpoint
+                if matches!(breakpoint, Some(Breakpoint::Line { .. })) {
+                    Resume::Step
+                } else {
+                    Resume::Continue
+                }
+            }
+        },
+    );
+
+    let result = run_with_options(
+        input,
+        Options {
+            inspector: Some(Rc::new(debugger)),
+            ..Default::default()
+        },
+    );
+    assert_eq!(result.unwrap().to_string(), "[ 1 2 ]");
+
+    assert_eq!(
+        *pauses.borrow(),
+        [
+            (4, Some(Breakpoint::Binding("b".into())), true),
+            (
+                6,
+                Some(Breakpoint::Line {
+                    file: "test".into(),
+                    line: 6
+                }),
+                true
+            ),
+            (6, None, true),
+        ]
+    );
+}
+
+#[test]
+fn tracer() {
+    #[derive(Debug)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+    impl std::io::Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let out = Rc::new(RefCell::new(Vec::new()));
+    run_with_options(
+        "1 + 2",
+        Options {
+            inspector: Some(Rc::new(Tracer::new(Shared(Rc::clone(&out))))),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        String::from_utf8(out.borrow().clone()).unwrap(),
+        "> 1:0 1 + 2\n  > 1:0 1\n  < 1\n  > 1:4 2\n  < 2\n< 3\n"
+    );
+}
+
+#[test]
+fn profiler() {
+    let input = r#"
+let
+    double = (x) { x * 2 }
+in
+    [ double(1) double(2) strLen("abc") ]
+"#;
+    let profiler = Rc::new(Profiler::new());
+
+    run_with_options(
+        input,
+        Options {
+            inspector: Some(Rc::clone(&profiler) as _),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let report = profiler.report();
+    let calls = |name: &str| {
+        report
+            .iter()
+            .find(|(function, _)| function == name)
+            .map(|(_, stats)| stats.count)
+    };
+    assert_eq!(calls("double"), Some(2));
+    assert_eq!(calls("strLen"), Some(1));
+    assert_eq!(report.len(), 2);
+}
+
+#[test]
+fn coverage() {
+    let input = r#"
+let
+    x = 1
+in
+    if(x > 5,
+        "big",
+        "small")
+"#;
+    let source = NamedSource::new("test", input.to_string());
+    let ast = parse(&source).unwrap();
+    let coverage = Rc::new(Coverage::new());
+
+    run_with_options(
+        input,
+        Options {
+            inspector: Some(Rc::clone(&coverage) as _),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(coverage.unreached("test", &ast), [span(40, 5)]);
+    assert_eq!(coverage.hits("test", span(55, 7)), 1);
+    assert_eq!(
+        coverage.lcov(&source, &ast),
+        "SF:test\nDA:2,1\nDA:3,1\nDA:5,1\nDA:6,0\nDA:7,1\nLF:5\nLH:4\nend_of_record\n"
+    );
+    assert!(
+        coverage
+            .annotate(&source, &ast)
+            .contains("     0 |         \"big\",\n       |         ^^^^^\n")
+    );
+}
+
+#[test]
+fn convenience_api() {
+    assert_eq!(eval_str("1 + 2").unwrap().to_string(), "3");
+    assert!(eval_str("1 +").is_err());
+
+    let path = std::env::temp_dir().join("tl-eval-file.tl");
+    std::fs::write(&path, "let a = 20 in a + 1").unwrap();
+    assert_eq!(eval_file(&path).unwrap().to_string(), "21");
+    assert!(eval_file(std::env::temp_dir().join("tl-missing.tl")).is_err());
+
+    let variables = HashMap::from([("name".to_string(), Value::new_builtin("tl".into()))]);
+    let source = NamedSource::new("test", r#""hello ${name}""#.to_string());
+    assert_eq!(
+        eval_with_vars(source, variables).unwrap().to_string(),
+        "hello tl"
+    );
+}
+
+#[test]
+fn merged_files() {
+    let dir = std::env::temp_dir().join("tl-merged-files");
+    std::fs::create_dir_all(&dir).unwrap();
+    let base = dir.join("base.tl");
+    let production = dir.join("production.tl");
+    std::fs::write(
+        &base,
+        r#"{ server = { host = "localhost" port = 80 } hosts = [ "a" ] debug = true }"#,
+    )
+    .unwrap();
+    std::fs::write(
+        &production,
+        r#"{ server.host = "example.com" hosts = [ "b" ] debug = false }"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        eval_files_merged(&[&base, &production])
+            .unwrap()
+            .to_string(),
+        "{ debug = false; hosts = [ b ]; server = { host = example.com; port = 80 } }"
+    );
+    assert!(eval_files_merged(&[base, dir.join("missing.tl")]).is_err());
+}
+
+#[test]
+fn script_results() {
+    let result = |text: &str| ScriptResult::from_value(&eval_str(text).unwrap());
+
+    assert_eq!(
+        result("3"),
+        ScriptResult {
+            exit_code: 3,
+            output: None
+        }
+    );
+    assert_eq!(
+        result(r#"{ exitCode = 2 output = "not found" }"#),
+        ScriptResult {
+            exit_code: 2,
+            output: Some("not found".into())
+        }
+    );
+    assert_eq!(
+        result("{ exitCode = 0 }"),
+        ScriptResult {
+            exit_code: 0,
+            output: None
+        }
+    );
+    assert_eq!(
+        result("{ output = 1 }"),
+        ScriptResult {
+            exit_code: 0,
+            output: Some("{ output = 1 }".into())
+        }
+    );
+    assert_eq!(
+        result("[ 1 2 ]"),
+        ScriptResult {
+            exit_code: 0,
+            output: Some("[ 1 2 ]".into())
+        }
+    );
+    assert_eq!(result("9999999999999").exit_code, 1);
+    assert_eq!(result("255").exit_code, 255);
+    assert_eq!(result("256").exit_code, 1);
+    assert_eq!(result("-1").exit_code, 1);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn typed_extraction() {
+    use crate::{from_file, from_str, runtime::serde::DeError};
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Server {
+        host: String,
+        ports: Vec<u16>,
+    }
+
+    let input = r#"{ host = "localhost" ports = [ 80 443 ] }"#;
+    assert_eq!(
+        from_str::<Server>(input).unwrap(),
+        Server {
+            host: "localhost".into(),
+            ports: vec![80, 443],
+        }
+    );
+
+    let path = std::env::temp_dir().join("tl-from-file.tl");
+    std::fs::write(&path, input).unwrap();
+    assert_eq!(from_file::<Server>(&path).unwrap().ports, vec![80, 443]);
+
+    let input = r#"{ host = "localhost" ports = [ 80 "443" ] }"#;
+    let report = from_str::<Server>(input).unwrap_err();
+    let err = report.downcast_ref::<crate::Error<DeError>>().unwrap();
+    assert_eq!(err.span, span(34, 5));
+}
+
+#[test]
+fn value_builders() {
+    use crate::{array, object, runtime::ValueBuilder};
+
+    let name = "port";
+    let value = object! {
+        host: "localhost",
+        "max-connections": 64,
+        [name]: 8080,
+        nested: object! { list: array![1, "two", ()] },
+    };
+    assert_eq!(
+        value.to_string(),
+        "{ host = localhost; max-connections = 64; nested = { list = [ 1 two null ] }; port = 8080 }"
+    );
+
+    let system = ValueBuilder::new()
+        .field("arch", "aarch64")
+        .array("cores", [0, 1])
+        .object("kernel", |kernel| kernel.field("version", "6.6"))
+        .build();
+    assert_eq!(
+        system.to_string(),
+        "{ arch = aarch64; cores = [ 0 1 ]; kernel = { version = 6.6 } }"
+    );
+}
+
+#[test]
+fn engine_and_fork() {
+    use crate::runtime::Engine;
+
+    let mut engine = Engine::new();
+    engine.define("base", 40);
+
+    let eval = |text: &str| engine.eval(NamedSource::new("test", text.to_string()));
+    assert_eq!(eval("base + 2").unwrap().to_string(), "42");
+    assert_eq!(eval("let base = 1 in base").unwrap().to_string(), "1");
+    assert_eq!(eval("strLen(\"abc\") + base").unwrap().to_string(), "43");
+    assert!(matches!(
+        eval("missing").unwrap_err().kind,
+        RuntimeErrorKind::VariableNotInScope { .. }
+    ));
+
+    let source = NamedSource::new("test", "x * 2".to_string());
+    let mut scope = Scope::new(HashMap::new(), source.clone(), parse(&source).unwrap());
+    scope.define("x", 21);
+    assert!(scope.snapshot().contains_key("x"));
+
+    let mut fork = scope.fork(source.clone(), parse(&source).unwrap());
+    assert_eq!(fork.eval().unwrap().to_string(), "42");
+    assert_eq!(scope.eval().unwrap().to_string(), "42");
+}
+
+#[test]
+fn builtin_setup() {
+    let source = NamedSource::new("test", "[ if(true, 1, 2) strLen(\"ab\") ]".to_string());
+
+    let mut scope = Scope::new(HashMap::new(), source.clone(), parse(&source).unwrap());
+    scope.define_builtin("if", |_| Ok(Value::new_builtin(ValueKind::Int(7))));
+    assert_eq!(scope.eval().unwrap().to_string(), "[ 7 2 ]");
+
+    let mut scope =
+        Scope::without_builtins(HashMap::new(), source.clone(), parse(&source).unwrap());
+    assert!(scope.fetch_var(&"strLen").is_none());
+    assert!(matches!(
+        scope.eval().unwrap_err().kind,
+        RuntimeErrorKind::VariableNotInScope { .. }
+    ));
+
+    let source = NamedSource::new(
+        "test",
+        "[ import builtins.import builtins.strLen ]".to_string(),
+    );
+    let mut scope = Scope::new(HashMap::new(), source.clone(), parse(&source).unwrap());
+    scope.remove_builtin("import");
+    assert!(scope.fetch_var(&"import").is_none());
+    assert!(matches!(
+        scope.eval().unwrap_err().kind,
+        RuntimeErrorKind::VariableNotInScope { .. }
+    ));
+}
+
+#[test]
+fn builtin_hook() {
+    use crate::BuiltinHook;
+    use std::ops::ControlFlow;
+
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let recorded = Rc::clone(&calls);
+    let options = Options {
+        on_builtin_call: Some(BuiltinHook(Rc::new(move |name, ctx| {
+            recorded.borrow_mut().push(name.to_string());
+
+            match name {
+                "random" => ControlFlow::Break(Ok(Value::new_builtin(ValueKind::Int(4)))),
+                "import" => ControlFlow::Break(Err(RuntimeError::new(
+                    RuntimeErrorKind::BuiltinDenied { name: name.into() },
+                    ctx.source.clone(),
+                    ctx.expr.span,
+                ))),
+                _ => ControlFlow::Continue(()),
+            }
+        }))),
+        ..Default::default()
+    };
+
+    let value = run_with_options("[ strLen(\"abc\") builtins.random() ]", options.clone());
+    assert_eq!(value.unwrap().to_string(), "[ 3 4 ]");
+    assert_eq!(*calls.borrow(), ["strLen", "random"]);
+
+    let err = run_with_options("import(./lib.tl)", options).unwrap_err();
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::BuiltinDenied {
+            name: "import".into()
+        }
+    );
+}
+
+#[test]
+fn memoize() {
+    use crate::BuiltinHook;
+    use std::ops::ControlFlow;
+
+    // Counts the calls of `if`, one per evaluated call of `fib`
+    let count_calls = |input: &str| {
+        let calls = Rc::new(RefCell::new(0));
+        let counter = Rc::clone(&calls);
+        let options = Options {
+            on_builtin_call: Some(BuiltinHook(Rc::new(move |name, _| {
+                if name == "if" {
+                    *counter.borrow_mut() += 1;
+                }
+                ControlFlow::Continue(())
+            }))),
+            ..Default::default()
+        };
+
+        let value = run_with_options(input, options).unwrap().to_string();
+        (value, *calls.borrow())
+    };
+
+    assert_eq!(
+        count_calls("let fib = (n) { if(n < 2, n, fib(n - 1) + fib(n - 2)) } in fib(15)"),
+        ("610".into(), 1973)
+    );
+    assert_eq!(
+        count_calls("let fib = memoize((n) { if(n < 2, n, fib(n - 1) + fib(n - 2)) }) in fib(15)"),
+        ("610".into(), 16)
+    );
+
+    let input =
+        "let apply = memoize((f) { f(1) }) in [ apply((x) { x + 1 }) apply((x) { x * 10 }) ]";
+    assert_eq!(run(input).unwrap().to_string(), "[ 2 10 ]");
+    let input = "let id = memoize((x) { x }) in [ id(1) id(1.0) ]";
+    assert!(matches!(
+        run(input).unwrap().kind,
+        ValueKind::Array(items) if matches!(
+            items.as_slice(),
+            [Value { kind: ValueKind::Int(1), .. }, Value { kind: ValueKind::Float(_), .. }]
+        )
+    ));
+
+    let err = run_err("memoize(1)");
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::MismatchedTypes {
+            expected: String::new(),
+            got: String::new()
+        }
+    );
+}
+
+#[test]
+fn sequences() {
+    assert_eq!(
+        run("take(3, iterate((port) { port + 1 }, 8000))")
+            .unwrap()
+            .to_string(),
+        "[ 8000 8001 8002 ]"
+    );
+    assert_eq!(
+        run("take(4, generate((i) { i * i }))").unwrap().to_string(),
+        "[ 0 1 4 9 ]"
+    );
+    assert_eq!(
+        run("let s = iterate((x) { x * 2 }, 1) in take(2, s.next())")
+            .unwrap()
+            .to_string(),
+        "[ 2 4 ]"
+    );
+    assert_eq!(run("take(5, [1 2])").unwrap().to_string(), "[ 1 2 ]");
+    assert_eq!(
+        run("take(0, generate((i) { i }))").unwrap().to_string(),
+        "[  ]"
+    );
+
+    let err = run_err("take(2, 1)");
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::MismatchedTypes {
+            expected: String::new(),
+            got: String::new()
+        }
+    );
+
+    let err = run_err("take(1000000000, generate((i) { i }))");
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::TooLarge { limit, operation, .. } if limit == MAX_ARRAY_LENGTH && operation == span(5, 10)
+    ));
+}
+
+#[test]
+fn escape_helpers() {
+    let escape = |input: &str| run(input).unwrap().to_string();
+
+    assert_eq!(
+        escape(r#"escapeShell("it's; rm -rf /")"#),
+        r"'it'\''s; rm -rf /'"
+    );
+    assert_eq!(
+        escape(r#"escapeJson("a \"b\"\n\\</script>")"#),
+        r#"a \"b\"\n\\\u003c/script>"#
+    );
+    assert_eq!(
+        escape(r#"escapeXml("<a href=\"x\">Tom & Jerry's</a>")"#),
+        "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+    );
+    assert_eq!(escape(r#"escapeHtml("'<b>'")"#), "&#39;&lt;b&gt;&#39;");
+    assert_eq!(escape(r#"escapeRegex("1.5 (a|b)*")"#), r"1\.5 \(a\|b\)\*");
+
+    let err = run_err("escapeHtml(1)");
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::MismatchedTypes {
+            expected: String::new(),
+            got: String::new()
+        }
+    );
+}
+
+#[test]
+#[cfg(feature = "url")]
+fn parse_url() {
+    assert_eq!(
+        run(r#"parseUrl("HTTPS://admin@Example.com:8443/api/v1?debug=1#top")"#)
+            .unwrap()
+            .to_string(),
+        "{ fragment = top; host = example.com; path = /api/v1; port = 8443; query = debug=1; scheme = https; user = admin }"
+    );
+    assert_eq!(
+        run(r#"let url = parseUrl("http://[::1]") in [url.host url.port url.path url.query]"#)
+            .unwrap()
+            .to_string(),
+        "[ ::1 80 / null ]"
+    );
+    assert_eq!(
+        run(r#"let url = parseUrl("mailto:someone@example.com") in [url.host url.path]"#)
+            .unwrap()
+            .to_string(),
+        "[ null someone@example.com ]"
+    );
+
+    for url in [
+        "example.com",
+        "http://:80",
+        "http://example.com:99999",
+        "1http://a",
+    ] {
+        let err = run_err(format!("parseUrl({url:?})"));
+        assert_eq!(
+            err.kind,
+            RuntimeErrorKind::InvalidUrl {
+                reason: String::new(),
+                url: (0, 0).into()
+            }
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "semver")]
+fn semver() {
+    assert_eq!(
+        run(r#"parseSemver("v1.2.3-beta.2+build.5")"#)
+            .unwrap()
+            .to_string(),
+        "{ build = build.5; major = 1; minor = 2; patch = 3; pre = beta.2 }"
+    );
+
+    let satisfies = |version: &str, range: &str| {
+        run(format!("semverSatisfies({version:?}, {range:?})"))
+            .unwrap()
+            .to_string()
+            == "true"
+    };
+    let cases = [
+        ("1.2.3", "^1.2", true),
+        ("2.0.0", "^1.2", false),
+        ("0.2.9", "^0.2.3", true),
+        ("0.3.0", "^0.2.3", false),
+        ("0.0.4", "^0.0.3", false),
+        ("1.2.9", "~1.2.3", true),
+        ("1.3.0", "~1.2.3", false),
+        ("1.5.0", ">=1.0.0 <2.0.0", true),
+        ("1.5.0", ">= 1.0.0, < 1.5.0", false),
+        ("3.1.0", "1.x || >=3", true),
+        ("2.1.0", "1.x || >=3", false),
+        ("1.0.0-alpha", "<1.0.0", true),
+        ("1.0.0-alpha.2", ">1.0.0-alpha.10", false),
+        ("1.0.0+build", "=1.0.0", true),
+        ("1.2.0", "<=1.2", true),
+        ("1.3.0", ">1.2", true),
+        ("5.0.0", "*", true),
+    ];
+    for (version, range, expected) in cases {
+        assert_eq!(satisfies(version, range), expected, "{version} in {range}");
+    }
+
+    for input in [
+        r#"parseSemver("1.2")"#,
+        r#"parseSemver("01.2.3")"#,
+        r#"parseSemver("1.2.3-")"#,
+        r#"semverSatisfies("1.2.3", "!1.2")"#,
+    ] {
+        let err = run_err(input);
+        assert_eq!(
+            err.kind,
+            RuntimeErrorKind::InvalidSemver {
+                reason: String::new(),
+                version: (0, 0).into()
+            }
+        );
+    }
+}
+
+#[test]
+fn units() {
+    let eval = |input: &str| run(input).unwrap().to_string();
+
+    assert_eq!(eval(r#"parseDuration("1h30m")"#), "5400");
+    assert_eq!(eval(r#"parseDuration("1.5d 10s")"#), "129610");
+    assert_eq!(eval(r#"parseDuration("2w")"#), "1209600");
+    assert_eq!(eval(r#"parseDuration("0")"#), "0");
+    assert_eq!(eval("formatDuration(5400)"), "1h30m");
+    assert_eq!(eval("formatDuration(93784)"), "1d2h3m4s");
+    assert_eq!(eval("formatDuration(0)"), "0s");
+    assert_eq!(eval("formatDuration(-90)"), "-1m30s");
+
+    assert_eq!(eval(r#"parseBytes("512MiB")"#), "536870912");
+    assert_eq!(eval(r#"parseBytes("1.5 GB")"#), "1500000000");
+    assert_eq!(eval(r#"parseBytes("4kib")"#), "4096");
+    assert_eq!(eval(r#"parseBytes("100")"#), "100");
+    assert_eq!(eval("formatBytes(536870912)"), "512MiB");
+    assert_eq!(eval("formatBytes(1536)"), "1.5KiB");
+    assert_eq!(eval("formatBytes(1000)"), "1000B");
+    assert_eq!(eval(r#"formatBytes(parseBytes("2.25GiB"))"#), "2.25GiB");
+    assert_eq!(eval(r#"formatBytes(parseBytes("1.5EiB"))"#), "1.5EiB");
+    assert_eq!(eval("formatBytes(9223372036854775807)"), "7.99EiB");
+    assert_eq!(eval("formatBytes(-9223372036854775808)"), "-8EiB");
+
+    for input in [
+        r#"parseDuration("90")"#,
+        r#"parseDuration("1y")"#,
+        r#"parseDuration("h")"#,
+        r#"parseBytes("1MiB 2KiB")"#,
+        r#"parseBytes("1.MB")"#,
+        r#"parseBytes("99999999PiB")"#,
+        r#"parseBytes("8EiB")"#,
+    ] {
+        let err = run_err(input);
+        assert_eq!(
+            err.kind,
+            RuntimeErrorKind::InvalidQuantity {
+                reason: String::new(),
+                value: (0, 0).into()
+            },
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn freeze() {
+    let merge = |defaults: &str, overrides: &str, expr: &str| {
+        format!("let\n defaults = {defaults}\n overrides = {overrides}\nin {expr}")
+    };
+
+    assert_eq!(
+        run(merge(
+            "freeze({ port = 80 })",
+            "{ host = \"a\" }",
+            "defaults + overrides"
+        ))
+        .unwrap()
+        .to_string(),
+        "{ host = a; port = 80 }"
+    );
+    // The merged object is a new value that can be overridden again
+    assert_eq!(
+        run(merge(
+            "freeze({ port = 80 })",
+            "{ host = \"a\" }",
+            "let merged = defaults + overrides in merged + overrides"
+        ))
+        .unwrap()
+        .to_string(),
+        "{ host = a; port = 80 }"
+    );
+    // Frozen values can still override the fields of other values
+    assert_eq!(
+        run(merge(
+            "{ port = 80 }",
+            "freeze({ port = 8080 })",
+            "defaults + overrides"
+        ))
+        .unwrap()
+        .to_string(),
+        "{ port = 8080 }"
+    );
+
+    for (defaults, expr) in [
+        ("freeze({ port = 80 })", "defaults + overrides"),
+        (
+            "freeze({ server = { port = 80 } })",
+            "defaults.server + overrides",
+        ),
+        ("freeze([ { port = 80 } ])", "defaults[0] + overrides"),
+    ] {
+        let err = run_err(merge(defaults, "{ port = 8080 }", expr));
+        assert_eq!(
+            err.kind,
+            RuntimeErrorKind::FrozenOverride {
+                key: String::new(),
+                frozen: (0, 0).into(),
+                overridden: (0, 0).into()
+            },
+            "{expr}"
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "fs")]
+fn evaluation_metadata() {
+    use crate::{
+        eval_with_metadata,
+        lint::{Level, LintConfig, Rule},
+        parser::ast::DEFAULT_MAX_DEPTH,
+    };
+
+    let lib = std::env::temp_dir().join("tl-metadata-lib.tl");
+    std::fs::write(&lib, "{ port = 80 }").unwrap();
+
+    let input = format!(
+        "let\n lib = import({0})\n again = import({0})\n unused = 1\nin lib.port + again.port",
+        lib.display()
+    );
+    let source = NamedSource::new("test", input);
+
+    let profiler = Rc::new(Profiler::new());
+    let options = Options {
+        inspector: Some(profiler.clone()),
+        ..Default::default()
+    };
+    let evaluation = eval_with_metadata(source.clone(), options, &LintConfig::default()).unwrap();
+
+    assert_eq!(evaluation.value.to_string(), "160");
+    assert_eq!(evaluation.imports, [lib]);
+    assert_eq!(evaluation.warnings.len(), 1);
+    // The inspector of the options still gets notified
+    assert!(!profiler.report().is_empty());
+
+    let mut lints = LintConfig::default();
+    lints.set(Rule::UnusedBinding, Level::Deny);
+    assert!(eval_with_metadata(source, Options::default(), &lints).is_err());
+
+    // Duplicate keys are found with the depth of the options, not the default one
+    let depth = DEFAULT_MAX_DEPTH + 10;
+    let input = format!(
+        "{}{{ a = 1 a = 2 }}{}",
+        "{ b = ".repeat(depth),
+        " }".repeat(depth)
+    );
+    let options = Options {
+        max_depth: depth * 2,
+        ..Default::default()
+    };
+    let evaluation = eval_with_metadata(
+        NamedSource::new("test", input),
+        options,
+        &LintConfig::default(),
+    )
+    .unwrap();
+    assert_eq!(evaluation.warnings.len(), 1);
+}
+
+#[test]
+fn interpolate_env() {
+    let allowed = || Options {
+        allow_env: true,
+        ..Default::default()
+    };
+    let path = std::env::var("PATH").unwrap();
+
+    assert_eq!(
+        run_with_options(r#"interpolateEnv("PATH=$PATH;")"#, allowed())
+            .unwrap()
+            .to_string(),
+        format!("PATH={path};")
+    );
+    assert_eq!(
+        run_with_options(
+            r#"interpolateEnv("\${PATH} \${TL_TEST_UNSET:-fallback} costs $5")"#,
+            allowed()
+        )
+        .unwrap()
+        .to_string(),
+        format!("{path} fallback costs $5")
+    );
+
+    let err = run_with_options(r#"interpolateEnv("$TL_TEST_UNSET")"#, allowed()).unwrap_err();
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::EnvVarNotSet {
+            name: String::new(),
+            string: span(0, 0)
+        }
+    );
+
+    let err = run_err(r#"interpolateEnv("$PATH")"#);
+    assert_eq!(
+        err.kind,
+        RuntimeErrorKind::BuiltinDenied {
+            name: String::new()
+        }
+    );
+}
+
+#[test]
+fn deterministic() {
+    let deterministic = || Options {
+        deterministic: true,
+        allow_env: true,
+        ..Default::default()
+    };
+
+    let err = run_with_options(r#"interpolateEnv("$PATH")"#, deterministic()).unwrap_err();
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::ImpurityNotAllowed { ref name, call } if name == "interpolateEnv" && call == span(0, 23)
+    ));
+
+    #[cfg(feature = "fs")]
+    {
+        for input in ["pathExists(/tmp)", "canonicalize(/tmp)"] {
+            let err = run_with_options(input, deterministic()).unwrap_err();
+            assert!(matches!(
+                err.kind,
+                RuntimeErrorKind::ImpurityNotAllowed { .. }
+            ));
+        }
+    }
+
+    // Seeded generators and path truthiness don't depend on the environment
+    let input = "[ randInt(random(1), 1, 6) if(/missing, 1, 2) ]";
+    assert_eq!(
+        run_with_options(input, deterministic())
+            .unwrap()
+            .to_string(),
+        run(input).unwrap().to_string()
+    );
+}
+
+#[test]
+fn eval_path() {
+    let eval_path = |path: &str| {
+        let source = NamedSource::new(
+            "test",
+            r#"let port = 80 in { services = { web = { port = port hosts = [ "a" "b" ] } db = broken } }"#
+                .to_string(),
+        );
+        let ast = parse(&source).unwrap();
+        Scope::new(HashMap::new(), source, ast).eval_path(path)
+    };
+
+    // Only the fields on the path are evaluated, so the broken field doesn't matter
+    assert_eq!(eval_path("services.web.port").unwrap().to_string(), "80");
+    assert_eq!(eval_path(".services.web.hosts.1").unwrap().to_string(), "b");
+    assert_eq!(
+        eval_path("services.web").unwrap().to_string(),
+        "{ hosts = [ a b ]; port = 80 }"
+    );
+
+    let err = eval_path("services.db").unwrap_err();
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::VariableNotInScope { .. }
+    ));
+    let err = eval_path("").unwrap_err();
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::VariableNotInScope { .. }
+    ));
+
+    let err = eval_path("services.cache").unwrap_err();
+    assert!(matches!(&err.kind, RuntimeErrorKind::MissingField { field, .. } if field == "cache"));
+    let err = eval_path("services.web.port.number").unwrap_err();
+    assert!(
+        matches!(&err.kind, RuntimeErrorKind::MissingField { base_type, .. } if base_type == "number")
+    );
+    let err = eval_path("services.web.hosts.2").unwrap_err();
+    assert!(matches!(
+        err.kind,
+        RuntimeErrorKind::IndexOutOfBounds { length: 2, .. }
+    ));
+}
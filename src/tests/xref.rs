@@ -0,0 +1,86 @@
+#![allow(clippy::unwrap_used, reason = "Panics automatically invalidate tests")]
+
+use crate::{
+    parser::parse,
+    xref::{BindingKind, cross_reference},
+};
+use miette::NamedSource;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn bindings_and_references() {
+    let input = "let
+    fib = (n) { if(n < 2, n, fib(n - 1) + fib(n - 2)) }
+    server = { port = 80 }
+    unused = 1
+in
+    [ fib(server.port) + host for x in [ 1 ] if x > 0 ]";
+    let source = NamedSource::new("test.tl", input.to_string());
+    let xref = cross_reference(&source, &parse(&source).unwrap());
+
+    let summary: Vec<_> = xref
+        .bindings
+        .iter()
+        .map(|binding| {
+            (
+                binding.name.as_str(),
+                binding.kind,
+                binding.definition.line,
+                binding.references.len(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("fib", BindingKind::Let, 2, 3),
+            ("n", BindingKind::Parameter, 2, 4),
+            ("server", BindingKind::Let, 3, 1),
+            ("unused", BindingKind::Let, 4, 0),
+            ("x", BindingKind::Comprehension, 6, 1),
+        ]
+    );
+
+    let unresolved: Vec<_> = xref
+        .unresolved
+        .iter()
+        .map(|reference| (reference.name.as_str(), reference.builtin))
+        .collect();
+    assert_eq!(unresolved, [("if", true), ("host", false)]);
+
+    assert_eq!(
+        xref.unused()
+            .map(|binding| binding.name.as_str())
+            .collect::<Vec<_>>(),
+        ["unused"]
+    );
+
+    // Go to definition from the last reference of `server`
+    let offset = input.find("server.port").unwrap();
+    let binding = xref.binding_at(offset).unwrap();
+    assert_eq!(
+        (binding.name.as_str(), binding.definition.offset),
+        ("server", input.find("server").unwrap())
+    );
+}
+
+#[test]
+fn sequential_let() {
+    let input = "let x = 1 in let x = x + 1 in x";
+    let source = NamedSource::new("test.tl", input.to_string());
+    let xref = cross_reference(&source, &parse(&source).unwrap());
+
+    let references: Vec<_> = xref
+        .bindings
+        .iter()
+        .map(|binding| {
+            binding
+                .references
+                .iter()
+                .map(|reference| reference.offset)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    // The value of the inner `x` refers to the outer one
+    assert_eq!(references, [vec![21], vec![30]]);
+}
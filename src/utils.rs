@@ -1,9 +1,25 @@
 use crate::{
-    parser::parse,
-    runtime::{Scope, types::Value},
+    Options,
+    lint::{Level, Lint, LintConfig, lint},
+    parser::{parse, parse_with_duplicate_keys},
+    runtime::{
+        Scope, charset_hint,
+        inspect::{ImportLog, Inspector},
+        types::{Value, ValueKind},
+    },
 };
 use miette::{NamedSource, Report, SourceSpan};
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+use unicode_width::UnicodeWidthChar;
+
+/// The width of a tab when computing columns, the same as in miette's reports.
+const TAB_WIDTH: usize = 4;
 
 /// Evaluate a source script and return the result as a deserialized value.
 /// # Errors
@@ -20,12 +36,40 @@ where
 
     scope_setup(&mut scope);
 
-    match scope.eval() {
-        Ok(value) => Ok(Deserialize::deserialize(value).map_err(|err| {
-            Report::msg(format!("Could not deserialize value: {err}")).with_source_code(source)
-        })?),
-        Err(err) => Err(err.into()),
-    }
+    let value = scope.eval()?;
+    let span = value.span;
+
+    Ok(
+        Deserialize::deserialize(value).map_err(|err: crate::runtime::serde::DeError| {
+            let span = err.span.unwrap_or(span);
+            crate::Error::new(err, source, span)
+        })?,
+    )
+}
+
+/// Evaluate a string of tl code and deserialize the result.
+/// # Errors
+/// This function will return an error if either a parsing, evaluation or deserialization error occurs.
+/// Deserialization errors point at the value that could not be deserialized.
+#[cfg(feature = "serde")]
+pub fn from_str<T>(text: &str) -> Result<T, Report>
+where
+    T: for<'de> serde::Deserialize<'de>,
+{
+    eval(NamedSource::new("input", text.to_string()), |_| ())
+}
+
+/// Evaluate the tl file at `path` and deserialize the result.
+/// # Errors
+/// This function will return an error if the file can not be read, or if a parsing, evaluation or deserialization error occurs.
+/// Deserialization errors point at the value that could not be deserialized.
+#[cfg(feature = "serde")]
+pub fn from_file<T>(path: impl AsRef<Path>) -> Result<T, Report>
+where
+    T: for<'de> serde::Deserialize<'de>,
+{
+    let path = path.as_ref();
+    eval(read_source(path)?, |scope| scope.define_location(path))
 }
 
 /// Evaluate a source script.
@@ -60,6 +104,206 @@ pub fn eval(
     Ok(runtime.eval()?)
 }
 
+/// Evaluate a string of tl code.
+/// # Errors
+/// This function will return an error if either a parsing error or an evaluation error occurs.
+pub fn eval_str(text: &str) -> Result<Value, Report> {
+    eval_with_vars(NamedSource::new("input", text.to_string()), HashMap::new())
+}
+
+/// Evaluate the tl file at `path`.
+/// # Errors
+/// This function will return an error if the file can not be read, or if a parsing or evaluation error occurs.
+pub fn eval_file(path: impl AsRef<Path>) -> Result<Value, Report> {
+    let source = read_source(path.as_ref())?;
+    let ast = parse(&source)?;
+
+    let mut scope = Scope::new(HashMap::new(), source, ast);
+    scope.define_location(path.as_ref());
+
+    Ok(scope.eval()?)
+}
+
+/// Evaluate the tl files at `paths` in order and deep-merge their values, later files override earlier ones.
+/// Objects are merged by key, any other value replaces the value of the files before it.
+/// # Errors
+/// This function will return an error if a file can not be read, or if a parsing or evaluation error occurs.
+pub fn eval_files_merged(paths: &[impl AsRef<Path>]) -> Result<Value, Report> {
+    let mut merged: Option<Value> = None;
+
+    for path in paths {
+        let value = eval_file(path)?;
+        merged = Some(match merged {
+            Some(merged) => merged.deep_merge(value),
+            None => value,
+        });
+    }
+
+    Ok(merged.unwrap_or_default())
+}
+
+pub(crate) fn read_source(path: &Path) -> Result<NamedSource<String>, Report> {
+    let bytes = fs::read(path)
+        .map_err(|err| Report::msg(format!("Could not read {}: {err}", path.display())))?;
+    let text = String::from_utf8(bytes).map_err(|err| {
+        Report::msg(format!(
+            "{} is not valid UTF-8 after the first {} bytes. {}",
+            path.display(),
+            err.utf8_error().valid_up_to(),
+            charset_hint(err.as_bytes())
+        ))
+    })?;
+    let text = match text.strip_prefix('\u{feff}') {
+        Some(text) => text.to_string(),
+        None => text,
+    };
+
+    Ok(NamedSource::new(path.display().to_string(), text))
+}
+
+/// Evaluate a source script with `variables` defined.
+/// # Errors
+/// This function will return an error if either a parsing error or an evaluation error occurs.
+pub fn eval_with_vars(
+    source: NamedSource<String>,
+    variables: HashMap<String, Value>,
+) -> Result<Value, Report> {
+    let ast = parse(&source)?;
+
+    Ok(Scope::new(variables, source, ast).eval()?)
+}
+
+/// The result of [`eval_with_metadata`], with where it came from.
+///
+/// With the `serde` feature it serializes to `{ value, warnings, duration_ms, imports }`,
+/// where every warning is `{ rule, message, line, column }`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Evaluation {
+    pub value: Value,
+    /// The lints of the evaluated source that are not denied or allowed.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_lints"))]
+    pub warnings: Vec<Lint>,
+    /// How long parsing, linting and evaluating took.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "duration_ms", serialize_with = "serialize_millis")
+    )]
+    pub duration: Duration,
+    /// Every imported, rendered or read file, in the order they were first read.
+    pub imports: Vec<PathBuf>,
+}
+
+/// Evaluate a source script with `options`, collecting the lints of the source and the imported files.
+/// # Errors
+/// This function will return an error if a parsing error, a lint that is denied in `lints` or an evaluation error occurs.
+pub fn eval_with_metadata(
+    source: NamedSource<String>,
+    options: Options,
+    lints: &LintConfig,
+) -> Result<Evaluation, Report> {
+    let start = Instant::now();
+    let (ast, duplicate_keys) = parse_with_duplicate_keys(&source, &options)?;
+
+    let (denied, warnings): (Vec<_>, Vec<_>) = lint(&source, &ast, &duplicate_keys, lints)
+        .into_iter()
+        .partition(|lint| lint.level == Level::Deny);
+    if let Some(denied) = denied.into_iter().next() {
+        return Err(denied.into());
+    }
+
+    let log = Rc::new(ImportLog::new(options.inspector.clone()));
+    let mut scope = Scope::new(HashMap::new(), source, ast);
+    scope.set_options(Options {
+        inspector: Some(Rc::clone(&log) as Rc<dyn Inspector>),
+        ..options
+    });
+    let value = scope.eval()?;
+
+    Ok(Evaluation {
+        value,
+        warnings,
+        duration: start.elapsed(),
+        imports: log.imports(),
+    })
+}
+
+#[cfg(feature = "serde")]
+fn serialize_lints<S: serde::Serializer>(lints: &[Lint], serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::ser::{SerializeMap, SerializeSeq};
+
+    struct Warning<'a>(&'a Lint);
+
+    impl serde::Serialize for Warning<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let (line, column) = line_column(self.0.source.inner(), self.0.span.offset());
+
+            let mut map = serializer.serialize_map(Some(4))?;
+            map.serialize_entry("rule", self.0.kind.rule().name())?;
+            map.serialize_entry("message", &self.0.to_string())?;
+            map.serialize_entry("line", &line)?;
+            map.serialize_entry("column", &column)?;
+            map.end()
+        }
+    }
+
+    let mut seq = serializer.serialize_seq(Some(lints.len()))?;
+    for lint in lints {
+        seq.serialize_element(&Warning(lint))?;
+    }
+    seq.end()
+}
+
+#[cfg(feature = "serde")]
+fn serialize_millis<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+}
+
+/// What a script should print and exit with, see [`ScriptResult::from_value`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptResult {
+    pub exit_code: i32,
+    pub output: Option<String>,
+}
+
+impl ScriptResult {
+    /// Maps the top-level value of a script to a process result:
+    /// - an int exits with that code and prints nothing
+    /// - an object with an int `exitCode` exits with it and prints its `output` if there is one
+    /// - anything else is printed and exits with 0
+    ///
+    /// Ints outside of `0..=255` don't fit into an exit code and exit with 1.
+    pub fn from_value(value: &Value) -> Self {
+        let exit_code = |code: isize| u8::try_from(code).map_or(1, i32::from);
+
+        match &value.kind {
+            ValueKind::Int(code) => Self {
+                exit_code: exit_code(*code),
+                output: None,
+            },
+            ValueKind::Object(fields)
+                if let Some(ValueKind::Int(code)) =
+                    fields.get("exitCode").map(|code| &code.kind) =>
+            {
+                Self {
+                    exit_code: exit_code(*code),
+                    output: fields
+                        .get("output")
+                        .filter(|output| !matches!(output.kind, ValueKind::Null))
+                        .map(ToString::to_string),
+                }
+            }
+            _ => Self {
+                exit_code: 0,
+                output: Some(value.to_string()),
+            },
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub(crate) fn span(start: usize, len: usize) -> SourceSpan {
     SourceSpan::new(start.into(), len)
@@ -70,3 +314,88 @@ pub(crate) fn merge_spans(start: SourceSpan, end: SourceSpan) -> SourceSpan {
     let len = (end.offset().saturating_add(end.len())).saturating_sub(start.offset());
     SourceSpan::new(offset, len)
 }
+
+/// Resolves `.` and `..` components of a path without touching the filesystem.
+/// Relative paths are made absolute using the current working directory.
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+    let path = if path.is_relative()
+        && let Ok(cwd) = std::env::current_dir()
+    {
+        cwd.join(path)
+    } else {
+        path.to_path_buf()
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// Resolves `.` and `..` components of a path like [`normalize_path`], but keeps relative paths relative.
+/// A leading `.` and `..` components that would leave a relative path are kept.
+pub(crate) fn clean_path(path: &Path) -> PathBuf {
+    let mut components: Vec<Component> = Vec::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir if components.is_empty() => components.push(component),
+            Component::CurDir => (),
+            Component::ParentDir => match components.last() {
+                Some(Component::Normal(_)) => {
+                    components.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => (),
+                Some(Component::CurDir) => {
+                    components.pop();
+                    components.push(component);
+                }
+                Some(Component::ParentDir) | None => components.push(component),
+            },
+            component => components.push(component),
+        }
+    }
+
+    if components.is_empty() {
+        return PathBuf::from(".");
+    }
+
+    components.into_iter().collect()
+}
+
+/// Returns the line and column of a byte offset into `text`, both starting at 1.
+/// Columns count terminal cells, so wide characters like CJK and emoji take two, combining marks none
+/// and tabs advance to the next multiple of 4.
+pub fn line_column(text: &str, mut offset: usize) -> (usize, usize) {
+    offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset = offset.saturating_sub(1);
+    }
+
+    let before = text.get(..offset).unwrap_or_default();
+    let line_start = before
+        .rfind('\n')
+        .map_or(0, |index| index.saturating_add(1));
+    let line = before.matches('\n').count().saturating_add(1);
+
+    let column = before
+        .get(line_start..)
+        .unwrap_or_default()
+        .chars()
+        .fold(0usize, |column, ch| match ch {
+            '\t' => column
+                .saturating_add(TAB_WIDTH)
+                .saturating_sub(column.checked_rem(TAB_WIDTH).unwrap_or_default()),
+            _ => column.saturating_add(ch.width().unwrap_or_default()),
+        });
+
+    (line, column.saturating_add(1))
+}
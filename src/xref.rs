@@ -0,0 +1,244 @@
+//! Where every binding is defined and referenced, for go-to-definition, renaming and finding dead code.
+
+use crate::{
+    line_column,
+    lint::binding_span,
+    parser::ast::types::{Expr, ExprKind, Pattern},
+    runtime::{self, ARGS_VARIABLE, BUILTINS_OBJECT, DIR_VARIABLE, FILE_VARIABLE},
+};
+use miette::{NamedSource, SourceSpan};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum BindingKind {
+    Let,
+    /// A field of an object pattern like `{ host, port } = server`, its name is also the field it reads.
+    ObjectPattern,
+    Parameter,
+    /// The variable of a comprehension, like `x` in `[x * 2 for x in xs]`.
+    Comprehension,
+}
+
+/// A span with its line and column, both starting at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Location {
+    pub offset: usize,
+    pub length: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    pub(crate) fn new(source: &NamedSource<String>, span: SourceSpan) -> Self {
+        let (line, column) = line_column(source.inner(), span.offset());
+
+        Self {
+            offset: span.offset(),
+            length: span.len(),
+            line,
+            column,
+        }
+    }
+
+    pub const fn contains(&self, offset: usize) -> bool {
+        self.offset <= offset && offset <= self.offset.saturating_add(self.length)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Binding {
+    pub name: String,
+    pub kind: BindingKind,
+    pub definition: Location,
+    pub references: Vec<Location>,
+}
+
+/// An identifier that does not refer to a binding of the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Reference {
+    pub name: String,
+    pub location: Location,
+    /// Whether the identifier refers to a builtin, otherwise it has to be defined by the host or the importing file.
+    pub builtin: bool,
+}
+
+/// Every binding of a file with its references, with the `serde` feature it serializes to `{ bindings, unresolved }`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CrossReference {
+    /// The bindings in the order they are defined in.
+    pub bindings: Vec<Binding>,
+    pub unresolved: Vec<Reference>,
+}
+
+impl CrossReference {
+    /// The binding that is defined or referenced at a byte offset.
+    pub fn binding_at(&self, offset: usize) -> Option<&Binding> {
+        self.bindings.iter().find(|binding| {
+            binding.definition.contains(offset)
+                || binding
+                    .references
+                    .iter()
+                    .any(|reference| reference.contains(offset))
+        })
+    }
+
+    /// The bindings that are never referenced, except for the ones whose name starts with `_`.
+    pub fn unused(&self) -> impl Iterator<Item = &Binding> {
+        self.bindings
+            .iter()
+            .filter(|binding| binding.references.is_empty() && !binding.name.starts_with('_'))
+    }
+}
+
+/// Resolves every identifier of `ast` to the binding it refers to.
+///
+/// Bindings are resolved lexically. A `let` binding whose value is a function is visible inside of
+/// its own value, so recursive functions refer to themselves.
+pub fn cross_reference(source: &NamedSource<String>, ast: &Expr) -> CrossReference {
+    let mut resolver = Resolver {
+        source,
+        builtins: runtime::Scope::builtins()
+            .into_iter()
+            .map(|builtin| builtin.name)
+            .chain([BUILTINS_OBJECT, ARGS_VARIABLE, FILE_VARIABLE, DIR_VARIABLE])
+            .collect(),
+        scopes: Vec::new(),
+        xref: CrossReference::default(),
+    };
+
+    resolver.walk(ast);
+    resolver.xref
+}
+
+struct Resolver<'a> {
+    source: &'a NamedSource<String>,
+    builtins: HashSet<&'static str>,
+    /// The names and indices into `xref.bindings` of the bindings of every enclosing scope.
+    scopes: Vec<Vec<(String, usize)>>,
+    xref: CrossReference,
+}
+
+impl Resolver<'_> {
+    fn walk(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Identifier(name) => self.reference(name, expr.span),
+            ExprKind::LetIn {
+                bindings,
+                expr: body,
+            } => {
+                self.scopes.push(Vec::new());
+
+                for (pattern, value) in bindings {
+                    let span = |name| binding_span(self.source.inner(), name, value.span);
+                    let names: Vec<_> = pattern
+                        .names()
+                        .into_iter()
+                        .map(|name| (name, span(name)))
+                        .collect();
+
+                    let kind = match pattern {
+                        Pattern::Object(_) => BindingKind::ObjectPattern,
+                        Pattern::Identifier(_) | Pattern::Array { .. } => BindingKind::Let,
+                    };
+
+                    if matches!(value.kind, ExprKind::FnDecl { .. }) {
+                        self.declare_all(&names, kind);
+                        self.walk(value);
+                    } else {
+                        self.walk(value);
+                        self.declare_all(&names, kind);
+                    }
+                }
+
+                self.walk(body);
+                self.scopes.pop();
+            }
+            ExprKind::FnDecl {
+                args,
+                defaults,
+                rest,
+                expr: body,
+            } => {
+                self.scopes.push(Vec::new());
+                for name in args.iter().chain(rest) {
+                    let span = binding_span(self.source.inner(), name, body.span);
+                    self.declare(name, span, BindingKind::Parameter);
+                }
+
+                for default in defaults.values() {
+                    self.walk(default);
+                }
+                self.walk(body);
+                self.scopes.pop();
+            }
+            ExprKind::Comprehension {
+                expr: body,
+                binding,
+                iterable,
+                condition,
+            } => {
+                self.walk(iterable);
+
+                self.scopes.push(Vec::new());
+                let span = binding_span(self.source.inner(), binding, iterable.span);
+                self.declare(binding, span, BindingKind::Comprehension);
+
+                if let Some(condition) = condition {
+                    self.walk(condition);
+                }
+                self.walk(body);
+                self.scopes.pop();
+            }
+            _ => {
+                for child in expr.children() {
+                    self.walk(child);
+                }
+            }
+        }
+    }
+
+    fn declare_all(&mut self, names: &[(&str, SourceSpan)], kind: BindingKind) {
+        for (name, span) in names {
+            self.declare(name, *span, kind);
+        }
+    }
+
+    fn declare(&mut self, name: &str, span: SourceSpan, kind: BindingKind) {
+        let index = self.xref.bindings.len();
+        self.xref.bindings.push(Binding {
+            name: name.to_string(),
+            kind,
+            definition: Location::new(self.source, span),
+            references: Vec::new(),
+        });
+
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push((name.to_string(), index));
+        }
+    }
+
+    fn reference(&mut self, name: &str, span: SourceSpan) {
+        let location = Location::new(self.source, span);
+        let binding = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.iter().rev().find(|(binding, _)| binding == name))
+            .and_then(|(_, index)| self.xref.bindings.get_mut(*index));
+
+        match binding {
+            Some(binding) => binding.references.push(location),
+            None => self.xref.unresolved.push(Reference {
+                name: name.to_string(),
+                location,
+                builtin: self.builtins.contains(name),
+            }),
+        }
+    }
+}